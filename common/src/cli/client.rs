@@ -1,4 +1,13 @@
-use crate::fetcher::{Fetcher, FetcherOptions};
+use crate::{
+    cli::parser::{parse_basic_auth, parse_bearer_token},
+    fetcher::{
+        CircuitBreakerMode, CircuitBreakerOptions, ClientCertificateOptions, Fetcher,
+        FetcherOptions, ProxyOptions,
+    },
+    sender::provider::{Credentials, LazyOpenIdTokenProvider, OpenIdTokenProviderConfig},
+};
+use std::path::PathBuf;
+use url::Url;
 
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Client")]
@@ -14,14 +23,148 @@ pub struct ClientArguments {
     /// Per-request minimum delay after rate limit (429).
     #[arg(long, default_value = "10s")]
     pub default_retry_after: humantime::Duration,
+
+    /// Number of consecutive failures against a host, before the circuit breaker opens. Disabled if not set.
+    #[arg(long)]
+    pub circuit_breaker_threshold: Option<usize>,
+
+    /// Window in which the consecutive failures must occur.
+    #[arg(long, default_value = "1m")]
+    pub circuit_breaker_window: humantime::Duration,
+
+    /// Time the circuit breaker stays open.
+    #[arg(long, default_value = "30s")]
+    pub circuit_breaker_cooldown: humantime::Duration,
+
+    /// Behavior while the circuit breaker is open.
+    #[arg(long, value_enum, default_value_t = CircuitBreakerMode::Abort)]
+    pub circuit_breaker_mode: CircuitBreakerMode,
+
+    /// HTTP basic auth credentials (`username:password`) for all source requests.
+    #[arg(
+        long,
+        env = "SOURCE_BASIC_AUTH",
+        hide_env_values = true,
+        value_parser = parse_basic_auth,
+        conflicts_with = "source_bearer_token"
+    )]
+    pub source_basic_auth: Option<Credentials>,
+
+    /// Static bearer token for all source requests.
+    #[arg(
+        long,
+        env = "SOURCE_BEARER_TOKEN",
+        hide_env_values = true,
+        value_parser = parse_bearer_token
+    )]
+    pub source_bearer_token: Option<Credentials>,
+
+    /// OIDC client ID, for requesting access tokens (client credentials flow) for all source requests.
+    #[arg(
+        long,
+        env = "SOURCE_OIDC_CLIENT_ID",
+        requires_all = ["source_oidc_client_secret", "source_oidc_issuer_url"],
+        conflicts_with_all = ["source_basic_auth", "source_bearer_token"]
+    )]
+    pub source_oidc_client_id: Option<String>,
+
+    /// OIDC client secret, for requesting access tokens for all source requests.
+    #[arg(
+        long,
+        env = "SOURCE_OIDC_CLIENT_SECRET",
+        hide_env_values = true,
+        requires = "source_oidc_client_id"
+    )]
+    pub source_oidc_client_secret: Option<String>,
+
+    /// OIDC issuer URL, for requesting access tokens for all source requests.
+    #[arg(
+        long,
+        env = "SOURCE_OIDC_ISSUER_URL",
+        requires = "source_oidc_client_id"
+    )]
+    pub source_oidc_issuer_url: Option<String>,
+
+    /// The time an access token for source requests must still be valid, before refreshing it.
+    #[arg(long, env = "SOURCE_OIDC_REFRESH_BEFORE", default_value = "30s")]
+    pub source_oidc_refresh_before: humantime::Duration,
+
+    /// Client certificate (PEM) for mutual TLS with sources. May contain the full chain.
+    #[arg(long, requires = "client_key")]
+    pub client_certificate: Option<PathBuf>,
+
+    /// Private key (PEM) of the client certificate.
+    #[arg(long, requires = "client_certificate")]
+    pub client_key: Option<PathBuf>,
+
+    /// Refuse to follow redirects to a different origin than the one requested.
+    #[arg(long)]
+    pub strict_redirects: bool,
+
+    /// Proxy for all requests. Disables proxies from the environment (like `HTTPS_PROXY`).
+    #[arg(long)]
+    pub proxy: Option<Url>,
+
+    /// Proxy for `http` URLs, overriding `--proxy`.
+    #[arg(long)]
+    pub http_proxy: Option<Url>,
+
+    /// Proxy for `https` URLs, overriding `--proxy`.
+    #[arg(long)]
+    pub https_proxy: Option<Url>,
+
+    /// Hosts, domains, or IP ranges to access without the proxy (e.g. `localhost,.example.com,10.0.0.0/8`).
+    #[arg(long, value_delimiter = ',')]
+    pub no_proxy: Vec<String>,
+
+    /// HTTP basic auth credentials (`username:password`) for the proxy.
+    #[arg(
+        long,
+        env = "PROXY_BASIC_AUTH",
+        hide_env_values = true,
+        value_parser = parse_basic_auth
+    )]
+    pub proxy_basic_auth: Option<Credentials>,
+
+    /// Maximum number of idle connections kept per host, for re-using them. Unlimited if not set.
+    #[arg(long)]
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Time after which idle connections are closed.
+    #[arg(long, default_value = "90s")]
+    pub pool_idle_timeout: humantime::Duration,
 }
 
 impl From<ClientArguments> for FetcherOptions {
     fn from(value: ClientArguments) -> Self {
-        FetcherOptions::new()
+        let proxy = value.proxy_options();
+        let oidc = value.oidc_config();
+        let client_certificate = value
+            .client_certificate
+            .zip(value.client_key)
+            .map(|(certificate, key)| ClientCertificateOptions::new(certificate, key));
+
+        let options = FetcherOptions::new()
             .timeout(value.timeout)
             .retries(value.retries)
             .retry_after(value.default_retry_after.into())
+            .circuit_breaker(value.circuit_breaker_threshold.map(|threshold| {
+                CircuitBreakerOptions::new(threshold)
+                    .window(value.circuit_breaker_window)
+                    .cooldown(value.circuit_breaker_cooldown)
+                    .mode(value.circuit_breaker_mode)
+            }))
+            .credentials(value.source_basic_auth.or(value.source_bearer_token))
+            .strict_redirects(value.strict_redirects)
+            .proxy(proxy)
+            .client_certificate(client_certificate)
+            .pool_max_idle_per_host(value.pool_max_idle_per_host)
+            .pool_idle_timeout(Some(value.pool_idle_timeout.into()));
+
+        match oidc {
+            Some(config) => options.token_provider(LazyOpenIdTokenProvider::new(config)),
+            None => options,
+        }
     }
 }
 
@@ -30,4 +173,100 @@ impl ClientArguments {
     pub async fn new_fetcher(self) -> Result<Fetcher, anyhow::Error> {
         Fetcher::new(self.into()).await
     }
+
+    /// The OIDC configuration for source requests, if a client was configured.
+    fn oidc_config(&self) -> Option<OpenIdTokenProviderConfig> {
+        match (
+            &self.source_oidc_client_id,
+            &self.source_oidc_client_secret,
+            &self.source_oidc_issuer_url,
+        ) {
+            (Some(client_id), Some(client_secret), Some(issuer_url)) => {
+                Some(OpenIdTokenProviderConfig {
+                    client_id: client_id.clone(),
+                    client_secret: client_secret.clone(),
+                    issuer_url: issuer_url.clone(),
+                    refresh_before: self.source_oidc_refresh_before,
+                    tls_insecure: false,
+                    tls_ca_certificates: vec![],
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The proxy configuration, if any proxy was requested.
+    fn proxy_options(&self) -> Option<ProxyOptions> {
+        let http = self.http_proxy.clone().or_else(|| self.proxy.clone());
+        let https = self.https_proxy.clone().or_else(|| self.proxy.clone());
+
+        if http.is_none() && https.is_none() {
+            return None;
+        }
+
+        Some(
+            ProxyOptions::new(None)
+                .http(http)
+                .https(https)
+                .no_proxy(self.no_proxy.clone())
+                .credentials(self.proxy_basic_auth.clone()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Parser;
+
+    fn parse(args: &[&str]) -> ClientArguments {
+        ClientArguments::parse_from(std::iter::once("test").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn pool_is_unlimited_by_default() {
+        assert_eq!(parse(&[]).pool_max_idle_per_host, None);
+        assert_eq!(
+            parse(&["--pool-max-idle-per-host", "2"]).pool_max_idle_per_host,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn source_oidc() {
+        assert!(parse(&[]).oidc_config().is_none());
+
+        let config = parse(&[
+            "--source-oidc-client-id",
+            "walker",
+            "--source-oidc-client-secret",
+            "secret",
+            "--source-oidc-issuer-url",
+            "https://sso.example.com/realms/walker",
+        ])
+        .oidc_config()
+        .unwrap();
+        assert_eq!(config.client_id, "walker");
+        assert_eq!(config.issuer_url, "https://sso.example.com/realms/walker");
+
+        // all of client ID, secret, and issuer are required
+        assert!(
+            ClientArguments::try_parse_from(["test", "--source-oidc-client-id", "walker"]).is_err()
+        );
+        // and can't be combined with static credentials
+        assert!(
+            ClientArguments::try_parse_from([
+                "test",
+                "--source-oidc-client-id",
+                "walker",
+                "--source-oidc-client-secret",
+                "secret",
+                "--source-oidc-issuer-url",
+                "https://sso.example.com/realms/walker",
+                "--source-bearer-token",
+                "token",
+            ])
+            .is_err()
+        );
+    }
 }
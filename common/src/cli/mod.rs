@@ -2,6 +2,7 @@
 pub mod client;
 pub mod parser;
 pub mod runner;
+pub mod shutdown;
 
 #[cfg(feature = "openpgp")]
 pub mod validation;
@@ -1,4 +1,5 @@
-use anyhow::Context;
+use crate::sender::provider::Credentials;
+use anyhow::{Context, bail};
 use reqwest::StatusCode;
 use std::{collections::HashSet, fmt::Display};
 
@@ -35,3 +36,112 @@ pub fn parse_allow_client_errors(
 
     Ok(allow_client_errors)
 }
+
+/// Parses HTTP basic auth credentials in the format of `username:password`.
+pub fn parse_basic_auth(value: &str) -> anyhow::Result<Credentials> {
+    let Some((username, password)) = value.split_once(':') else {
+        bail!("Basic auth credentials must be in the format of 'username:password'");
+    };
+
+    if username.is_empty() {
+        bail!("Basic auth username must not be empty");
+    }
+
+    let password = (!password.is_empty()).then(|| password.to_string());
+
+    Ok(Credentials::Basic(username.to_string(), password))
+}
+
+/// Parses a static bearer token.
+pub fn parse_bearer_token(value: &str) -> anyhow::Result<Credentials> {
+    if value.is_empty() {
+        bail!("Bearer token must not be empty");
+    }
+
+    if value
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || !c.is_ascii())
+    {
+        bail!("Bearer token must only contain visible ASCII characters");
+    }
+
+    Ok(Credentials::Bearer(value.to_string()))
+}
+
+/// Parses a size in bytes, with an optional binary (e.g. `64MiB`) or decimal (e.g. `64MB`) unit.
+pub fn parse_byte_size(value: &str) -> anyhow::Result<usize> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: usize = number
+        .parse()
+        .with_context(|| format!("Failed to parse '{value}' as byte size"))?;
+
+    let factor: usize = match unit.trim() {
+        "" | "B" => 1,
+        "KB" | "kB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        unit => bail!("Unknown byte size unit: '{unit}'"),
+    };
+
+    number
+        .checked_mul(factor)
+        .with_context(|| format!("Byte size is too large: '{value}'"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn basic_auth() {
+        assert_eq!(
+            parse_basic_auth("user:pass").expect("must parse"),
+            Credentials::Basic("user".into(), Some("pass".into()))
+        );
+        assert_eq!(
+            parse_basic_auth("user:").expect("must parse"),
+            Credentials::Basic("user".into(), None)
+        );
+        assert!(parse_basic_auth("user").is_err());
+        assert!(parse_basic_auth(":pass").is_err());
+    }
+
+    #[test]
+    fn bearer_token() {
+        assert_eq!(
+            parse_bearer_token("abc.def").expect("must parse"),
+            Credentials::Bearer("abc.def".into())
+        );
+        assert!(parse_bearer_token("").is_err());
+        assert!(parse_bearer_token("abc def").is_err());
+    }
+
+    #[test]
+    fn byte_size() {
+        assert_eq!(parse_byte_size("1024").expect("must parse"), 1024);
+        assert_eq!(parse_byte_size("2KB").expect("must parse"), 2_000);
+        assert_eq!(parse_byte_size("64MiB").expect("must parse"), 64 << 20);
+        assert_eq!(parse_byte_size("1 GiB").expect("must parse"), 1 << 30);
+        assert!(parse_byte_size("MiB").is_err());
+        assert!(parse_byte_size("1XB").is_err());
+    }
+
+    #[test]
+    fn no_secrets_in_debug() {
+        let debug = format!(
+            "{:?}",
+            Credentials::Basic("user".into(), Some("pass".into()))
+        );
+        assert!(!debug.contains("pass"));
+        let debug = format!("{:?}", Credentials::Bearer("secret".into()));
+        assert!(!debug.contains("secret"));
+    }
+}
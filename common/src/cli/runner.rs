@@ -1,7 +1,24 @@
+use crate::{cli::parser::parse_byte_size, utils::budget::MemoryBudget};
+
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Runner")]
 pub struct RunnerArguments {
     /// Number of workers, too many parallel requests might make you violate request rates. NOTE: A number of zero will spawn an unlimited amount of workers.
     #[arg(short, long, default_value = "1")]
     pub workers: usize,
+
+    /// Maximum number of bytes of retrieved, but not yet processed, documents (e.g. `64MiB`). When exceeded, no new documents will be fetched until enough data was processed. Only used with multiple workers.
+    #[arg(long, value_parser = parse_byte_size)]
+    pub max_in_flight_bytes: Option<usize>,
+
+    /// Continue with the remaining documents when processing a document fails, reporting all failures at the end.
+    #[arg(long)]
+    pub continue_on_error: bool,
+}
+
+impl RunnerArguments {
+    /// Create the memory budget, if configured.
+    pub fn memory_budget(&self) -> Option<MemoryBudget> {
+        self.max_in_flight_bytes.map(MemoryBudget::new)
+    }
 }
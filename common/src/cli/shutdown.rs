@@ -0,0 +1,45 @@
+//! Graceful shutdown on signals
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Cancel the token on the first Ctrl-C (or SIGTERM), and exit the process on the second one.
+///
+/// Returns the handle of the listening task, which should be aborted once the work is done.
+pub fn cancel_on_signal(cancellation: CancellationToken) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        log::warn!(
+            "Shutting down, finishing in-flight documents. Press Ctrl-C again to exit immediately."
+        );
+        cancellation.cancel();
+
+        shutdown_signal().await;
+        log::warn!("Exiting immediately");
+        std::process::exit(130);
+    })
+}
+
+/// Wait for Ctrl-C, or SIGTERM on Unix systems.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = terminate.recv() => {},
+                }
+                return;
+            }
+            Err(err) => log::warn!("Unable to listen for SIGTERM: {err}"),
+        }
+    }
+
+    if let Err(err) = tokio::signal::ctrl_c().await {
+        log::warn!("Unable to listen for Ctrl-C: {err}");
+        std::future::pending::<()>().await;
+    }
+}
@@ -1,4 +1,4 @@
-use crate::validate::ValidationOptions;
+use crate::validate::{MissingSignature, SignatureVersion, ValidationOptions};
 use flexible_time::timestamp::StartTimestamp;
 use std::time::SystemTime;
 use time::{Date, Month, UtcOffset};
@@ -10,9 +10,27 @@ pub struct ValidationArguments {
     #[arg(long)]
     policy_date: Option<StartTimestamp>,
 
-    /// Enable OpenPGP v3 signatures. Conflicts with 'policy_date'.
-    #[arg(short = '3', long = "v3-signatures", conflicts_with = "policy_date")]
+    /// Enable OpenPGP v3 signatures, using a legacy policy date. Shorthand for
+    /// '--signature-version any'. Conflicts with 'policy_date' and 'signature_version'.
+    #[arg(
+        short = '3',
+        long = "v3-signatures",
+        conflicts_with_all = ["policy_date", "signature_version"]
+    )]
     v3_signatures: bool,
+
+    /// OpenPGP versions of signatures to accept: policy (v3 and v4, as far as the policy allows),
+    /// any (v3 and v4, regardless of the policy date), v4, or v3.
+    #[arg(long, default_value_t)]
+    signature_version: SignatureVersion,
+
+    /// Fail the validation of documents without a signature, instead of accepting them.
+    #[arg(long)]
+    require_signatures: bool,
+
+    /// Log a warning for documents without a signature, but still accept them.
+    #[arg(long, conflicts_with = "require_signatures")]
+    warn_missing_signatures: bool,
 }
 
 impl From<ValidationArguments> for ValidationOptions {
@@ -30,6 +48,20 @@ impl From<ValidationArguments> for ValidationOptions {
 
         log::debug!("Policy date: {validation_date:?}");
 
-        Self { validation_date }
+        let missing_signature = match (value.require_signatures, value.warn_missing_signatures) {
+            (true, _) => MissingSignature::Reject,
+            (false, true) => MissingSignature::Warn,
+            (false, false) => MissingSignature::Allow,
+        };
+
+        let signature_version = match value.v3_signatures {
+            true => SignatureVersion::Any,
+            false => value.signature_version,
+        };
+
+        Self::new()
+            .validation_date(validation_date)
+            .missing_signature(missing_signature)
+            .signature_version(signature_version)
     }
 }
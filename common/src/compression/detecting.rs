@@ -87,6 +87,41 @@ impl Compression {
             Compression::None => Ok(None),
         }
     }
+
+    /// The file name extension of the compression, without the leading dot.
+    ///
+    /// Returns `None` for [`Compression::None`].
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(any(feature = "bzip2", feature = "bzip2-rs"))]
+            Compression::Bzip2 => Some("bz2"),
+            #[cfg(feature = "lzma")]
+            Compression::Xz => Some("xz"),
+            #[cfg(feature = "flate2")]
+            Compression::Gzip => Some("gz"),
+            Compression::None => None,
+        }
+    }
+
+    /// Perform compression.
+    ///
+    /// Returns `None` for [`Compression::None`]. Currently, only gzip is supported for
+    /// compressing, other formats fail with [`std::io::ErrorKind::Unsupported`].
+    pub fn compress_opt(
+        &self,
+        #[allow(unused_variables)] data: &[u8],
+    ) -> Result<Option<Bytes>, std::io::Error> {
+        match self {
+            #[cfg(feature = "flate2")]
+            Compression::Gzip => super::compress_gzip(data).map(Some),
+            Compression::None => Ok(None),
+            #[allow(unreachable_patterns)]
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("compressing with {self:?} is not supported"),
+            )),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
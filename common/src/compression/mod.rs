@@ -73,6 +73,14 @@ fn decompress_gzip_with(data: &[u8], opts: &DecompressionOptions) -> Result<Byte
     decompress_limit(decoder, opts.limit)
 }
 
+/// Compress gzip using `zlib-rs` and `flate2`.
+#[cfg(feature = "flate2")]
+fn compress_gzip(data: &[u8]) -> Result<Bytes, std::io::Error> {
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?.into())
+}
+
 /// Decompress with an uncompressed payload limit.
 #[allow(unused)]
 fn decompress_limit(mut reader: impl std::io::Read, limit: usize) -> Result<Bytes, std::io::Error> {
@@ -0,0 +1,181 @@
+use super::Error;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Options for the circuit breaker of a [`super::Fetcher`].
+///
+/// The circuit breaker tracks consecutive failures per host. Once the threshold is reached within
+/// the configured window, the circuit "opens" for the duration of the cooldown. While open,
+/// requests to that host will either fail immediately, or wait for the cooldown to pass. This
+/// prevents each document from individually burning through its retry budget against a host which
+/// is down anyway.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerOptions {
+    /// Number of consecutive failures before the circuit opens.
+    pub threshold: usize,
+    /// The window in which the consecutive failures must occur.
+    pub window: Duration,
+    /// The time the circuit stays open.
+    pub cooldown: Duration,
+    /// The behavior while the circuit is open.
+    pub mode: CircuitBreakerMode,
+}
+
+impl CircuitBreakerOptions {
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    pub fn window(mut self, window: impl Into<Duration>) -> Self {
+        self.window = window.into();
+        self
+    }
+
+    pub fn cooldown(mut self, cooldown: impl Into<Duration>) -> Self {
+        self.cooldown = cooldown.into();
+        self
+    }
+
+    pub fn mode(mut self, mode: CircuitBreakerMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+impl Default for CircuitBreakerOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 10,
+            window: Duration::from_mins(1),
+            cooldown: Duration::from_secs(30),
+            mode: CircuitBreakerMode::default(),
+        }
+    }
+}
+
+/// Behavior of an open circuit.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum CircuitBreakerMode {
+    /// Fail requests immediately, without retrying.
+    #[default]
+    Abort,
+    /// Wait for the cooldown to pass, then try again.
+    Pause,
+}
+
+#[derive(Debug, Default)]
+struct HostState {
+    failures: usize,
+    first_failure: Option<Instant>,
+    open_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    options: CircuitBreakerOptions,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+fn host(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    }
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(options: CircuitBreakerOptions) -> Self {
+        Self {
+            options,
+            hosts: Default::default(),
+        }
+    }
+
+    /// Check if a request to the host of the URL may proceed.
+    pub(crate) async fn acquire(&self, url: &Url) -> Result<(), Error> {
+        let host = host(url);
+
+        loop {
+            let open_until = self
+                .hosts
+                .lock()
+                .get(&host)
+                .and_then(|state| state.open_until)
+                .filter(|open_until| *open_until > Instant::now());
+
+            let Some(open_until) = open_until else {
+                return Ok(());
+            };
+
+            match self.options.mode {
+                CircuitBreakerMode::Abort => return Err(Error::CircuitOpen(host)),
+                CircuitBreakerMode::Pause => {
+                    log::debug!("Circuit open for host {host}, waiting for cooldown");
+                    tokio::time::sleep_until(open_until.into()).await;
+                }
+            }
+        }
+    }
+
+    /// Record the outcome of a request.
+    pub(crate) fn record<T>(&self, url: &Url, result: &Result<T, Error>) {
+        match result {
+            // the host answered, even if it was a client error or a refused redirect
+            Ok(_) | Err(Error::ClientError(_) | Error::CrossOriginRedirect(_)) => self.success(url),
+            Err(Error::Request(_) | Error::ServiceUnavailable(_)) => self.failure(url),
+            // the host wasn't reached, or didn't fail
+            Err(Error::RateLimited(_) | Error::CircuitOpen(_) | Error::Credentials(_)) => {}
+        }
+    }
+
+    fn success(&self, url: &Url) {
+        self.hosts.lock().remove(&host(url));
+    }
+
+    fn failure(&self, url: &Url) {
+        let host = host(url);
+        let now = Instant::now();
+
+        let mut hosts = self.hosts.lock();
+        let state = hosts.entry(host.clone()).or_default();
+
+        if let Some(open_until) = state.open_until {
+            // the circuit was open before, so a single failure is enough to re-open it
+            if open_until <= now {
+                log::warn!(
+                    "Circuit open for host {host}, still failing. Cooling down for {}.",
+                    humantime::Duration::from(self.options.cooldown)
+                );
+            }
+            state.open_until = Some(now + self.options.cooldown);
+            return;
+        }
+
+        match state.first_failure {
+            Some(first) if now.duration_since(first) <= self.options.window => {}
+            _ => {
+                state.failures = 0;
+                state.first_failure = Some(now);
+            }
+        }
+
+        state.failures += 1;
+
+        if state.failures >= self.options.threshold {
+            log::warn!(
+                "Circuit open for host {host}, after {} consecutive failures. Cooling down for {}.",
+                state.failures,
+                humantime::Duration::from(self.options.cooldown)
+            );
+            state.open_until = Some(now + self.options.cooldown);
+        }
+    }
+}
@@ -1,15 +1,34 @@
 //! Fetching remote resources
 
+mod circuit;
 mod data;
+mod redirect;
+use anyhow::Context;
 use backon::{ExponentialBuilder, Retryable};
+pub use circuit::{CircuitBreakerMode, CircuitBreakerOptions};
 pub use data::*;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+pub use redirect::CrossOriginRedirect;
 
-use crate::http::{calculate_retry_after_from_response_header, get_client_error};
-use reqwest::{Client, ClientBuilder, IntoUrl, Method, Response, StatusCode};
+use crate::fetcher::circuit::CircuitBreaker;
+use crate::http::{
+    calculate_retry_after_from_response_header, get_client_error, retry_after_from_response_header,
+};
+use crate::metrics::Metrics;
+use crate::sender::provider::{Credentials, TokenProvider};
+use crate::utils::url::ensure_slash;
+use reqwest::{
+    Client, ClientBuilder, Identity, IntoUrl, Method, NoProxy, Proxy, Response, StatusCode,
+    header::{HeaderMap, HeaderValue},
+};
 use std::fmt::Debug;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Semaphore;
 use url::Url;
 
 /// Fetch data using HTTP.
@@ -22,6 +41,22 @@ pub struct Fetcher {
     retries: usize,
     /// *default_retry_after* is used when a 429 response does not include a Retry-After header
     default_retry_after: Duration,
+    /// Shared between clones, so that all requests of a walk contribute to it
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    /// Static credentials, applied to all requests
+    credentials: Option<Credentials>,
+    /// Provides credentials per request, taking precedence over the static ones
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    /// Static headers, applied to all requests
+    headers: Arc<HeaderMap>,
+    /// Equivalent base URLs, tried in order when a host is unavailable
+    mirrors: Arc<Vec<Url>>,
+    /// Shared between clones, so that all requests of a walk are limited together
+    rate_limiter: Option<Arc<DefaultDirectRateLimiter>>,
+    /// Shared between clones, bounding the requests in flight of a walk
+    request_budget: Option<Arc<Semaphore>>,
+    /// Receives events of requests, if set
+    metrics: Option<Arc<dyn Metrics>>,
 }
 
 /// Error when retrieving
@@ -31,8 +66,177 @@ pub enum Error {
     Request(#[from] reqwest::Error),
     #[error("Rate limited (HTTP 429), retry after {0:?}")]
     RateLimited(Duration),
+    #[error("Service unavailable (HTTP 503), retry after {0:?}")]
+    ServiceUnavailable(Duration),
     #[error("Client error: {0}")]
     ClientError(StatusCode),
+    #[error("Circuit open for host {0}")]
+    CircuitOpen(String),
+    #[error(transparent)]
+    CrossOriginRedirect(CrossOriginRedirect),
+    #[error("Failed to provide credentials: {0}")]
+    Credentials(crate::sender::Error),
+}
+
+impl Error {
+    /// The HTTP status code of the response causing the error, if any.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Request(err) => err.status(),
+            Self::RateLimited(_) => Some(StatusCode::TOO_MANY_REQUESTS),
+            Self::ServiceUnavailable(_) => Some(StatusCode::SERVICE_UNAVAILABLE),
+            Self::ClientError(status) => Some(*status),
+            Self::CircuitOpen(_) | Self::CrossOriginRedirect(_) | Self::Credentials(_) => None,
+        }
+    }
+
+    /// Check if the error indicates that the host is unavailable, rather than the resource.
+    fn is_unavailable(&self) -> bool {
+        match self {
+            Self::Request(err) => {
+                err.is_connect()
+                    || err.is_timeout()
+                    || err.status().is_some_and(|status| status.is_server_error())
+            }
+            Self::ServiceUnavailable(_) | Self::CircuitOpen(_) => true,
+            Self::RateLimited(_)
+            | Self::ClientError(_)
+            | Self::CrossOriginRedirect(_)
+            | Self::Credentials(_) => false,
+        }
+    }
+}
+
+/// Proxy configuration of the [`Fetcher`]
+///
+/// Once a proxy is configured, proxies from the environment (like `HTTPS_PROXY`) are no longer
+/// considered.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default)]
+pub struct ProxyOptions {
+    /// The proxy for `http` URLs
+    pub http: Option<Url>,
+    /// The proxy for `https` URLs
+    pub https: Option<Url>,
+    /// Hosts, domains, or IP ranges which are accessed directly, e.g. `localhost`, `.example.com`,
+    /// or `10.0.0.0/8`
+    pub no_proxy: Vec<String>,
+    /// Credentials for authenticating with the proxy
+    pub credentials: Option<Credentials>,
+}
+
+impl ProxyOptions {
+    /// Create a new instance, using the same proxy for `http` and `https` URLs.
+    pub fn new(proxy: impl Into<Option<Url>>) -> Self {
+        let proxy = proxy.into();
+        Self {
+            http: proxy.clone(),
+            https: proxy,
+            ..Default::default()
+        }
+    }
+
+    /// Set the proxy for `http` URLs.
+    pub fn http(mut self, http: impl Into<Option<Url>>) -> Self {
+        self.http = http.into();
+        self
+    }
+
+    /// Set the proxy for `https` URLs.
+    pub fn https(mut self, https: impl Into<Option<Url>>) -> Self {
+        self.https = https.into();
+        self
+    }
+
+    /// Set the destinations which are accessed directly, bypassing the proxy.
+    pub fn no_proxy<I>(mut self, no_proxy: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.no_proxy = no_proxy.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set credentials (basic or bearer), which will be sent to the proxy.
+    pub fn credentials(mut self, credentials: impl Into<Option<Credentials>>) -> Self {
+        self.credentials = credentials.into();
+        self
+    }
+
+    /// Apply the configuration to a client.
+    fn apply(self, mut client: ClientBuilder) -> anyhow::Result<ClientBuilder> {
+        let no_proxy = NoProxy::from_string(&self.no_proxy.join(","));
+
+        for proxy in [self.http.map(Proxy::http), self.https.map(Proxy::https)]
+            .into_iter()
+            .flatten()
+        {
+            let mut proxy = proxy?.no_proxy(no_proxy.clone());
+            proxy = match &self.credentials {
+                None => proxy,
+                Some(Credentials::Basic(username, password)) => {
+                    proxy.basic_auth(username, password.as_deref().unwrap_or_default())
+                }
+                Some(Credentials::Bearer(token)) => {
+                    let mut value = HeaderValue::try_from(format!("Bearer {token}"))?;
+                    value.set_sensitive(true);
+                    proxy.custom_http_auth(value)
+                }
+            };
+            client = client.proxy(proxy);
+        }
+
+        Ok(client)
+    }
+}
+
+/// Client certificate configuration of the [`Fetcher`], for mutual TLS
+///
+/// Both files are PEM encoded. The certificate file may contain the full chain, starting with the
+/// client certificate.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct ClientCertificateOptions {
+    /// The client certificate
+    pub certificate: PathBuf,
+    /// The private key of the client certificate
+    pub key: PathBuf,
+}
+
+impl ClientCertificateOptions {
+    /// Create a new instance.
+    pub fn new(certificate: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        Self {
+            certificate: certificate.into(),
+            key: key.into(),
+        }
+    }
+
+    /// Load the identity from the files.
+    fn identity(&self) -> anyhow::Result<Identity> {
+        let mut pem = std::fs::read(&self.certificate).with_context(|| {
+            format!(
+                "Failed to read client certificate: {}",
+                self.certificate.display()
+            )
+        })?;
+        pem.push(b'\n');
+        pem.extend(std::fs::read(&self.key).with_context(|| {
+            format!(
+                "Failed to read client certificate key: {}",
+                self.key.display()
+            )
+        })?);
+
+        Identity::from_pem(&pem).with_context(|| {
+            format!(
+                "Failed to load client certificate: {} / {}",
+                self.certificate.display(),
+                self.key.display()
+            )
+        })
+    }
 }
 
 /// Options for the [`Fetcher`]
@@ -43,6 +247,14 @@ pub struct FetcherOptions {
     retries: usize,
     default_retry_after: Duration,
     max_retry_after: Duration,
+    circuit_breaker: Option<CircuitBreakerOptions>,
+    credentials: Option<Credentials>,
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    strict_redirects: bool,
+    proxy: Option<ProxyOptions>,
+    client_certificate: Option<ClientCertificateOptions>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
 }
 
 impl FetcherOptions {
@@ -82,6 +294,68 @@ impl FetcherOptions {
         self.max_retry_after = max;
         self
     }
+
+    /// Set the circuit breaker options. If `None`, no circuit breaker will be used.
+    pub fn circuit_breaker(
+        mut self,
+        circuit_breaker: impl Into<Option<CircuitBreakerOptions>>,
+    ) -> Self {
+        self.circuit_breaker = circuit_breaker.into();
+        self
+    }
+
+    /// Set static credentials (basic or bearer), which will be sent with every request.
+    pub fn credentials(mut self, credentials: impl Into<Option<Credentials>>) -> Self {
+        self.credentials = credentials.into();
+        self
+    }
+
+    /// Set a provider for credentials (like an OIDC client), which will be asked before every
+    /// request. Credentials provided by it take precedence over static ones.
+    pub fn token_provider(mut self, token_provider: impl TokenProvider + 'static) -> Self {
+        self.token_provider = Some(Arc::new(token_provider));
+        self
+    }
+
+    /// Refuse to follow redirects to a different origin than the one originally requested.
+    pub fn strict_redirects(mut self, strict_redirects: bool) -> Self {
+        self.strict_redirects = strict_redirects;
+        self
+    }
+
+    /// Set the proxy configuration. If `None`, proxies from the environment will be used.
+    pub fn proxy(mut self, proxy: impl Into<Option<ProxyOptions>>) -> Self {
+        self.proxy = proxy.into();
+        self
+    }
+
+    /// Set the client certificate, used for mutual TLS. If `None`, no client certificate will be
+    /// presented.
+    pub fn client_certificate(
+        mut self,
+        client_certificate: impl Into<Option<ClientCertificateOptions>>,
+    ) -> Self {
+        self.client_certificate = client_certificate.into();
+        self
+    }
+
+    /// Set the maximum number of idle connections kept per host. If `None`, there is no limit.
+    ///
+    /// Idle connections are re-used by later requests, instead of opening new ones. When walking
+    /// in parallel, this should be at least the number of workers.
+    pub fn pool_max_idle_per_host(
+        mut self,
+        pool_max_idle_per_host: impl Into<Option<usize>>,
+    ) -> Self {
+        self.pool_max_idle_per_host = pool_max_idle_per_host.into();
+        self
+    }
+
+    /// Set the time after which idle connections are closed. If `None`, they are kept open.
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: impl Into<Option<Duration>>) -> Self {
+        self.pool_idle_timeout = pool_idle_timeout.into();
+        self
+    }
 }
 
 impl Default for FetcherOptions {
@@ -91,6 +365,14 @@ impl Default for FetcherOptions {
             retries: 5,
             default_retry_after: Duration::from_secs(10),
             max_retry_after: Duration::from_mins(5),
+            circuit_breaker: None,
+            credentials: None,
+            token_provider: None,
+            strict_redirects: false,
+            proxy: None,
+            client_certificate: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
         }
     }
 }
@@ -104,9 +386,41 @@ impl From<Client> for Fetcher {
 impl Fetcher {
     /// Create a new downloader from options
     pub async fn new(options: FetcherOptions) -> anyhow::Result<Self> {
-        let client = ClientBuilder::new().timeout(options.timeout);
+        let mut client = ClientBuilder::new()
+            // negotiate and transparently decode the transport encoding (`Content-Encoding`)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
+            .timeout(options.timeout)
+            .redirect(redirect::policy(options.strict_redirects))
+            .pool_idle_timeout(options.pool_idle_timeout);
+
+        if let Some(pool_max_idle_per_host) = options.pool_max_idle_per_host {
+            client = client.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+
+        if let Some(proxy) = options.proxy.clone() {
+            client = proxy.apply(client)?;
+        }
 
-        Ok(Self::with_client(client.build()?, options))
+        let client = match &options.client_certificate {
+            Some(client_certificate) => client
+                // PEM identities are only supported by rustls, which might not be the default
+                .tls_backend_rustls()
+                .identity(client_certificate.identity()?)
+                // the key is only checked against the certificate when building the client
+                .build()
+                .with_context(|| {
+                    format!(
+                        "Failed to use client certificate, does the key match it? {} / {}",
+                        client_certificate.certificate.display(),
+                        client_certificate.key.display()
+                    )
+                })?,
+            None => client.build()?,
+        };
+
+        Ok(Self::with_client(client, options))
     }
 
     /// Create a fetcher providing an existing client.
@@ -115,15 +429,114 @@ impl Fetcher {
             client,
             retries: options.retries,
             default_retry_after: options.default_retry_after,
+            circuit_breaker: options
+                .circuit_breaker
+                .map(|options| Arc::new(CircuitBreaker::new(options))),
+            credentials: options.credentials,
+            token_provider: options.token_provider,
+            headers: Default::default(),
+            mirrors: Default::default(),
+            rate_limiter: None,
+            request_budget: None,
+            metrics: None,
         }
     }
 
+    /// Add static headers, which will be sent with every request of this instance.
+    ///
+    /// Headers carrying secrets should be marked using [`reqwest::header::HeaderValue::set_sensitive`],
+    /// so that they don't end up in debug output.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        Arc::make_mut(&mut self.headers).extend(headers);
+        self
+    }
+
+    /// Set equivalent base URLs, serving the same tree.
+    ///
+    /// When a request to a URL below one of the bases fails, due to a connection error or a server
+    /// error, the same relative path is requested from the following bases, in order, before
+    /// giving up.
+    pub fn with_mirrors(mut self, mirrors: impl IntoIterator<Item = Url>) -> Self {
+        self.mirrors = Arc::new(mirrors.into_iter().map(ensure_slash).collect());
+        self
+    }
+
+    /// Limit the number of requests per second.
+    ///
+    /// The limit is shared with all clones of this instance, and applies to every attempt of a
+    /// request, including retries and requests to mirrors.
+    pub fn with_rate_limit(mut self, requests_per_second: NonZeroU32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::direct(Quota::per_second(
+            requests_per_second,
+        ))));
+        self
+    }
+
+    /// Limit the number of requests in flight.
+    ///
+    /// The limit is shared with all clones of this instance. A request holds its slot until its
+    /// response was processed, but not while waiting for a retry.
+    pub fn with_max_requests(mut self, max_requests: NonZeroUsize) -> Self {
+        self.request_budget = Some(Arc::new(Semaphore::new(max_requests.get())));
+        self
+    }
+
+    /// Report events of requests to a [`Metrics`] implementation.
+    ///
+    /// The implementation is shared with all clones of this instance, including the ones used by
+    /// sources for retrieving documents.
+    pub fn with_metrics(mut self, metrics: impl Metrics + 'static) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+
+    /// The [`Metrics`] implementation, if one was set.
+    pub fn metrics(&self) -> Option<&dyn Metrics> {
+        self.metrics.as_deref()
+    }
+
+    /// The mirrors of a URL, in the order they should be tried.
+    fn mirrors(&self, url: &Url) -> Vec<Url> {
+        let Some((index, relative)) = self
+            .mirrors
+            .iter()
+            .enumerate()
+            .find_map(|(index, base)| Some((index, url.as_str().strip_prefix(base.as_str())?)))
+        else {
+            return vec![];
+        };
+
+        self.mirrors[index + 1..]
+            .iter()
+            .filter_map(|base| base.join(relative).ok())
+            .collect()
+    }
+
     async fn new_request(
         &self,
         method: Method,
         url: Url,
-    ) -> Result<reqwest::RequestBuilder, reqwest::Error> {
-        Ok(self.client.request(method, url))
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let mut request = self.client.request(method, url);
+        if !self.headers.is_empty() {
+            request = request.headers(HeaderMap::clone(&self.headers));
+        }
+
+        let provided = match &self.token_provider {
+            Some(token_provider) => token_provider
+                .provide_access_token()
+                .await
+                .map_err(Error::Credentials)?,
+            None => None,
+        };
+
+        Ok(match provided.as_ref().or(self.credentials.as_ref()) {
+            None => request,
+            Some(Credentials::Bearer(token)) => request.bearer_auth(token),
+            Some(Credentials::Basic(username, password)) => {
+                request.basic_auth(username, password.as_ref())
+            }
+        })
     }
 
     /// fetch data, using a GET request.
@@ -141,22 +554,58 @@ impl Fetcher {
         // if the URL building fails, there is no need to re-try, abort now.
         let url = url.into_url()?;
 
+        let mut result = self.fetch_retrying(url.clone(), &processor).await;
+
+        for mirror in self.mirrors(&url) {
+            match &result {
+                Err(err) if err.is_unavailable() => {
+                    log::info!("Failed to fetch, trying mirror {mirror}: {err}");
+                    result = self.fetch_retrying(mirror, &processor).await;
+                }
+                _ => break,
+            }
+        }
+
+        result
+    }
+
+    /// fetch data from a single URL, retrying on errors.
+    async fn fetch_retrying<D: DataProcessor>(
+        &self,
+        url: Url,
+        processor: &D,
+    ) -> Result<D::Type, Error> {
         let retries = self.retries;
         let retry = ExponentialBuilder::default().with_max_times(retries);
 
-        (|| async { self.fetch_once(url.clone(), &processor).await })
+        (|| async { self.fetch_once(url.clone(), processor).await })
             .retry(retry)
-            .when(|e| !matches!(e, Error::ClientError(_)))
+            .when(|e| {
+                !matches!(
+                    e,
+                    Error::ClientError(_) | Error::CircuitOpen(_) | Error::CrossOriginRedirect(_)
+                )
+            })
             .adjust(|e, dur| {
-                if let Error::RateLimited(retry_after) = e {
-                    if let Some(dur_value) = dur
-                        && dur_value > *retry_after
-                    {
-                        return dur;
+                let retry_after = match e {
+                    Error::RateLimited(retry_after) | Error::ServiceUnavailable(retry_after) => {
+                        *retry_after
                     }
-                    Some(*retry_after) // only use server-provided delay if it's longer
-                } else {
-                    dur // minimum delay as per backoff strategy
+                    _ => return dur, // minimum delay as per backoff strategy
+                };
+
+                if let Some(dur_value) = dur
+                    && dur_value > retry_after
+                {
+                    return dur;
+                }
+                // only use server-provided delay if it's longer
+                log::debug!("Server requested delay of {retry_after:?}, overriding {dur:?}");
+                Some(retry_after)
+            })
+            .notify(|_, delay| {
+                if let Some(metrics) = &self.metrics {
+                    metrics.retry_attempted(&url, delay);
                 }
             })
             .await
@@ -167,7 +616,43 @@ impl Fetcher {
         url: Url,
         processor: &D,
     ) -> Result<D::Type, Error> {
-        let response = self.new_request(Method::GET, url).await?.send().await?;
+        // the semaphore never gets closed, so acquiring only fails if it's absent
+        let _permit = match &self.request_budget {
+            Some(request_budget) => request_budget.acquire().await.ok(),
+            None => None,
+        };
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.until_ready().await;
+        }
+
+        let Some(circuit_breaker) = &self.circuit_breaker else {
+            return self.request_once(url, processor).await;
+        };
+
+        circuit_breaker.acquire(&url).await?;
+        let result = self.request_once(url.clone(), processor).await;
+        circuit_breaker.record(&url, &result);
+
+        result
+    }
+
+    async fn request_once<D: DataProcessor>(
+        &self,
+        url: Url,
+        processor: &D,
+    ) -> Result<D::Type, Error> {
+        if let Some(metrics) = &self.metrics {
+            metrics.request_started(&url);
+        }
+
+        let request = self.new_request(Method::GET, url).await?;
+        let response = processor.prepare(request).send().await.map_err(|err| {
+            match redirect::refused(&err) {
+                Some(refused) => Error::CrossOriginRedirect(refused),
+                None => Error::Request(err),
+            }
+        })?;
 
         log::debug!("Response Status: {}", response.status());
 
@@ -179,6 +664,13 @@ impl Fetcher {
             return Err(Error::RateLimited(retry_after));
         }
 
+        if response.status() == StatusCode::SERVICE_UNAVAILABLE
+            && let Some(retry_after) = retry_after_from_response_header(&response)
+        {
+            log::info!("Service unavailable (503), retry after: {retry_after:?}");
+            return Err(Error::ServiceUnavailable(retry_after));
+        }
+
         // Now test if we can convert the (possibly failed) response to result data.
         // This includes allowed for 404 becoming `None`.
         match processor.process(response).await {
@@ -201,6 +693,12 @@ impl Fetcher {
 /// Processing data returned by a request.
 pub trait DataProcessor {
     type Type: Sized;
+
+    /// Prepare the request, e.g. adding headers. By default, the request is used as is.
+    fn prepare(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request
+    }
+
     fn process(
         &self,
         response: reqwest::Response,
@@ -0,0 +1,71 @@
+use reqwest::redirect::Policy;
+use url::Url;
+
+/// Maximum number of redirects to follow, the same as the default policy of `reqwest`.
+const MAX_REDIRECTS: usize = 10;
+
+/// A redirect which was refused, as it would have crossed to a different origin.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Refusing cross-origin redirect to {to} (chain: {})", chain_to_string(.chain))]
+pub struct CrossOriginRedirect {
+    /// The URLs requested so far, starting with the original one
+    pub chain: Vec<Url>,
+    /// The URL the redirect would have gone to
+    pub to: Url,
+}
+
+fn chain_to_string(chain: &[Url]) -> String {
+    chain
+        .iter()
+        .map(Url::as_str)
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// Create the redirect policy for a fetcher.
+///
+/// All redirects get logged. In strict mode, redirects to a different origin than the one of the
+/// original request will be refused.
+pub(crate) fn policy(strict: bool) -> Policy {
+    Policy::custom(move |attempt| {
+        let previous = attempt.previous();
+
+        if let Some(from) = previous.last() {
+            log::debug!("Redirect: {from} -> {}", attempt.url());
+        }
+
+        if previous.len() > MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+
+        if strict
+            && let Some(original) = previous.first()
+            && original.origin() != attempt.url().origin()
+        {
+            let err = CrossOriginRedirect {
+                chain: previous.to_vec(),
+                to: attempt.url().clone(),
+            };
+            return attempt.error(err);
+        }
+
+        attempt.follow()
+    })
+}
+
+/// Extract a refused redirect from a request error.
+pub(crate) fn refused(err: &reqwest::Error) -> Option<CrossOriginRedirect> {
+    if !err.is_redirect() {
+        return None;
+    }
+
+    let mut source = std::error::Error::source(err);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<CrossOriginRedirect>() {
+            return Some(err.clone());
+        }
+        source = err.source();
+    }
+
+    None
+}
@@ -24,33 +24,36 @@ fn parse_retry_after(value: &str) -> Option<RetryAfter> {
     None
 }
 
+/// Get the delay requested by the `Retry-After` header of a response, if present and valid.
+pub fn retry_after_from_response_header(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_retry_after)
+        .and_then(|retry| match retry {
+            RetryAfter::Duration(d) => Some(d),
+            RetryAfter::After(after) => {
+                // Calculate duration from now until the specified time
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .ok()
+                    .and_then(|now| {
+                        after
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .ok()
+                            .and_then(|target| target.checked_sub(now))
+                    })
+            }
+        })
+}
+
 pub fn calculate_retry_after_from_response_header(
     response: &Response,
     default_duration: Duration,
 ) -> Option<Duration> {
     if response.status() == StatusCode::TOO_MANY_REQUESTS {
-        let retry_after = response
-            .headers()
-            .get(header::RETRY_AFTER)
-            .and_then(|v| v.to_str().ok())
-            .and_then(parse_retry_after)
-            .and_then(|retry| match retry {
-                RetryAfter::Duration(d) => Some(d),
-                RetryAfter::After(after) => {
-                    // Calculate duration from now until the specified time
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .ok()
-                        .and_then(|now| {
-                            after
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .ok()
-                                .and_then(|target| target.checked_sub(now))
-                        })
-                }
-            })
-            .unwrap_or(default_duration);
-        return Some(retry_after);
+        return Some(retry_after_from_response_header(response).unwrap_or(default_duration));
     }
     None
 }
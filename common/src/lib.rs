@@ -6,6 +6,8 @@ pub mod compression;
 pub mod fetcher;
 pub mod http;
 pub mod locale;
+pub mod lock;
+pub mod metrics;
 pub mod progress;
 pub mod report;
 pub mod retrieve;
@@ -15,6 +17,7 @@ pub mod since;
 pub mod source;
 pub mod store;
 pub mod utils;
+pub mod walker;
 
 #[cfg(feature = "openpgp")]
 pub mod validate;
@@ -0,0 +1,99 @@
+//! Advisory file locks, preventing concurrent runs from interfering with each other.
+
+use anyhow::{Context, bail};
+use std::{
+    ffi::OsString,
+    fs::{File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+};
+
+/// The name of the lock file of a store, located in its base directory.
+pub const FILE_LOCK: &str = ".lock";
+
+/// An exclusive, advisory lock on a file.
+///
+/// The lock is held until this is dropped, which also happens when a run fails.
+#[derive(Debug)]
+pub struct Lock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl Lock {
+    /// Acquire the lock, failing if it is already held by another run.
+    ///
+    /// The lock file is created, if it doesn't exist yet. It is not removed when the lock gets
+    /// released.
+    pub fn acquire(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file: {}", path.display()))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => bail!(
+                "Sync already in progress, the lock is held by another run: {}",
+                path.display()
+            ),
+            Err(TryLockError::Error(err)) => {
+                return Err(err)
+                    .with_context(|| format!("Failed to acquire lock: {}", path.display()));
+            }
+        }
+
+        log::debug!("Acquired lock: {}", path.display());
+
+        Ok(Self { path, _file: file })
+    }
+
+    /// Acquire the lock of a store, located in its base directory.
+    pub fn store(base: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::acquire(base.as_ref().join(FILE_LOCK))
+    }
+
+    /// Acquire the lock protecting a file, using a `.lock` file next to it.
+    pub fn file(file: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut path = OsString::from(file.as_ref().as_os_str());
+        path.push(".lock");
+        Self::acquire(path)
+    }
+
+    /// The path of the lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exclusive() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let lock = Lock::store(dir.path())?;
+        assert_eq!(lock.path(), dir.path().join(FILE_LOCK));
+
+        // held by the first lock
+        assert!(Lock::store(dir.path()).is_err());
+
+        // released on drop
+        drop(lock);
+        let _lock = Lock::store(dir.path())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+//! Hooks for collecting metrics
+
+use std::{fmt::Debug, sync::Arc, time::Duration};
+use url::Url;
+
+/// Receives events of retrieving remote content, e.g. to forward them to a metrics system.
+///
+/// All methods default to doing nothing, so that implementors only need to handle the events
+/// they are interested in. Without a registered implementation, no events are created at all.
+///
+/// Events may be reported concurrently, from different tasks of a parallel walk.
+pub trait Metrics: Debug + Send + Sync {
+    /// An HTTP request is about to be sent.
+    ///
+    /// This is reported for every attempt, including retries and requests to mirrors.
+    fn request_started(&self, url: &Url) {
+        let _ = url;
+    }
+
+    /// A failed request will be retried, after the provided delay.
+    fn retry_attempted(&self, url: &Url, delay: Duration) {
+        let _ = (url, delay);
+    }
+
+    /// The content of a document was received.
+    fn bytes_received(&self, url: &Url, bytes: u64) {
+        let _ = (url, bytes);
+    }
+
+    /// A document was not modified (HTTP 304), so the stored copy was used.
+    fn not_modified(&self, url: &Url) {
+        let _ = url;
+    }
+
+    /// The digest of a document was checked against the expected value.
+    ///
+    /// The algorithm is the file extension of the digest, e.g. `sha256`.
+    fn digest_verified(&self, url: &Url, algorithm: &str, valid: bool) {
+        let _ = (url, algorithm, valid);
+    }
+}
+
+/// Allows keeping a handle to an implementation, e.g. for reading collected values.
+impl<M: Metrics + ?Sized> Metrics for Arc<M> {
+    fn request_started(&self, url: &Url) {
+        (**self).request_started(url)
+    }
+
+    fn retry_attempted(&self, url: &Url, delay: Duration) {
+        (**self).retry_attempted(url, delay)
+    }
+
+    fn bytes_received(&self, url: &Url, bytes: u64) {
+        (**self).bytes_received(url, bytes)
+    }
+
+    fn not_modified(&self, url: &Url) {
+        (**self).not_modified(url)
+    }
+
+    fn digest_verified(&self, url: &Url, algorithm: &str, valid: bool) {
+        (**self).digest_verified(url, algorithm, valid)
+    }
+}
@@ -1,4 +1,6 @@
+use super::ProgressState;
 use indicatif::{MultiProgress, ProgressStyle};
+use std::{fmt::Write, time::Duration};
 
 impl super::Progress for MultiProgress {
     type Instance = indicatif::ProgressBar;
@@ -8,9 +10,27 @@ impl super::Progress for MultiProgress {
         let bar = indicatif::ProgressBar::new(work);
         bar.set_style(
             ProgressStyle::default_bar()
-                .template("{msg:<20} {wide_bar} {pos}/{len} ({eta})")
+                .template("{msg:<20} {wide_bar} {pos}/{len} ({rate}, ETA {eta})")
+                .expect("template must parse")
+                .with_key(
+                    "rate",
+                    |state: &indicatif::ProgressState, w: &mut dyn Write| {
+                        let _ = write!(w, "{:.0}/s", state.per_sec());
+                    },
+                ),
+        );
+
+        self.add(bar)
+    }
+
+    fn start_indeterminate(&self) -> Self::Instance {
+        let bar = indicatif::ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner} {msg:<20} {pos}")
                 .expect("template must parse"),
         );
+        bar.enable_steady_tick(Duration::from_millis(100));
 
         self.add(bar)
     }
@@ -21,6 +41,13 @@ impl super::Progress for MultiProgress {
 }
 
 impl super::ProgressBar for indicatif::ProgressBar {
+    fn state(&self) -> Option<ProgressState> {
+        Some(ProgressState {
+            position: self.position(),
+            length: self.length(),
+            elapsed: self.elapsed(),
+        })
+    }
     async fn increment(&mut self, work: usize) {
         indicatif::ProgressBar::inc(self, work as u64)
     }
@@ -1,6 +1,10 @@
 //! Progress reporting
 
-use std::future::Future;
+use std::{
+    fmt::{Display, Formatter},
+    future::Future,
+    time::Duration,
+};
 
 pub mod indicatif;
 
@@ -9,14 +13,89 @@ pub trait Progress {
 
     fn start(&self, work: usize) -> Self::Instance;
 
+    /// Start a progress for an unknown amount of work, like a spinner.
+    ///
+    /// By default, this starts a progress without any work.
+    fn start_indeterminate(&self) -> Self::Instance {
+        self.start(0)
+    }
+
     fn println(&self, #[allow(unused_variables)] message: &str) {}
 }
 
+/// A snapshot of the state of a progress bar.
+///
+/// Renders as `1234/50000 (42/s, ETA 18m)`, omitting the parts which are unknown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProgressState {
+    /// The amount of completed work
+    pub position: u64,
+    /// The total amount of work, if known
+    pub length: Option<u64>,
+    /// The time elapsed since the start of the work
+    pub elapsed: Duration,
+}
+
+impl ProgressState {
+    /// The amount of completed work per second, if any time has elapsed.
+    pub fn per_sec(&self) -> Option<f64> {
+        let elapsed = self.elapsed.as_secs_f64();
+        (elapsed > 0.0).then(|| self.position as f64 / elapsed)
+    }
+
+    /// The estimated time until all work is completed, if the total amount of work and the rate
+    /// are known.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.length?.saturating_sub(self.position);
+        match self.per_sec()? {
+            rate if rate > 0.0 => Some(Duration::from_secs_f64(remaining as f64 / rate)),
+            _ => None,
+        }
+    }
+}
+
+/// Format a duration, in a precision fitting its magnitude.
+fn format_eta(eta: Duration, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let secs = eta.as_secs();
+    match secs {
+        0..60 => write!(f, "{secs}s"),
+        60..3600 => write!(f, "{}m", secs / 60),
+        _ => write!(f, "{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+impl Display for ProgressState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.position)?;
+        if let Some(length) = self.length {
+            write!(f, "/{length}")?;
+        }
+
+        if let Some(per_sec) = self.per_sec() {
+            write!(f, " ({per_sec:.0}/s")?;
+            if let Some(eta) = self.eta() {
+                f.write_str(", ETA ")?;
+                format_eta(eta, f)?;
+            }
+            f.write_str(")")?;
+        }
+
+        Ok(())
+    }
+}
+
 pub trait ProgressBar {
     fn tick(&mut self) -> impl Future<Output = ()> {
         self.increment(1)
     }
 
+    /// The current state of the progress, if it is being tracked.
+    ///
+    /// By default, the state is not tracked.
+    fn state(&self) -> Option<ProgressState> {
+        None
+    }
+
     fn increment(&mut self, work: usize) -> impl Future<Output = ()>;
 
     fn finish(self) -> impl Future<Output = ()>;
@@ -64,6 +143,10 @@ impl<P: Progress> Progress for Option<P> {
         self.as_ref().map(|progress| progress.start(work))
     }
 
+    fn start_indeterminate(&self) -> Self::Instance {
+        self.as_ref().map(|progress| progress.start_indeterminate())
+    }
+
     fn println(&self, message: &str) {
         if let Some(progress) = self {
             progress.println(message)
@@ -74,6 +157,9 @@ impl<P: Progress> Progress for Option<P> {
 }
 
 impl<P: ProgressBar> ProgressBar for Option<P> {
+    fn state(&self) -> Option<ProgressState> {
+        self.as_ref().and_then(|bar| bar.state())
+    }
     async fn increment(&mut self, work: usize) {
         if let Some(bar) = self {
             bar.increment(work).await;
@@ -92,3 +178,32 @@ impl<P: ProgressBar> ProgressBar for Option<P> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state(position: u64, length: Option<u64>, elapsed: u64) -> ProgressState {
+        ProgressState {
+            position,
+            length,
+            elapsed: Duration::from_secs(elapsed),
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            state(1260, Some(50000), 30).to_string(),
+            "1260/50000 (42/s, ETA 19m)"
+        );
+        assert_eq!(state(42, Some(84), 1).to_string(), "42/84 (42/s, ETA 1s)");
+        assert_eq!(
+            state(42, Some(420_000), 1).to_string(),
+            "42/420000 (42/s, ETA 2h 46m)"
+        );
+        assert_eq!(state(42, None, 1).to_string(), "42 (42/s)");
+        assert_eq!(state(0, Some(10), 0).to_string(), "0/10");
+        assert_eq!(state(0, Some(10), 5).to_string(), "0/10 (0/s)");
+    }
+}
@@ -44,6 +44,50 @@ impl<D: Digest> Debug for RetrievedDigest<D> {
     }
 }
 
+/// An invalid digest file.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum DigestFormatError {
+    #[error("Empty digest file")]
+    Empty,
+    #[error("Invalid digest, expected {expected} hex characters: {value}")]
+    Invalid { expected: usize, value: String },
+}
+
+/// Parse the content of a digest file, like `{document}.sha256`.
+///
+/// Supported are files containing only the digest, the GNU coreutils format (`<digest>  <file>`),
+/// and the BSD format (`SHA256 (<file>) = <digest>`). Only the first line is considered. The
+/// digest must be a hex string, matching the output size of the algorithm. It is returned in lower
+/// case.
+pub fn parse_digest<D: Digest>(content: &str) -> Result<String, DigestFormatError> {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .ok_or(DigestFormatError::Empty)?;
+
+    let value = match line.rsplit_once(") = ") {
+        // BSD format
+        Some((_, value)) if line.contains(" (") => value.trim(),
+        // digest only, or GNU format, which prefixes the digest with a backslash for escaped names
+        _ => line
+            .split_whitespace()
+            .next()
+            .map(|value| value.strip_prefix('\\').unwrap_or(value))
+            .unwrap_or_default(),
+    };
+
+    let expected = <D as Digest>::output_size() * 2;
+    if value.len() != expected || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(DigestFormatError::Invalid {
+            expected,
+            value: value.to_string(),
+        });
+    }
+
+    Ok(value.to_ascii_lowercase())
+}
+
 /// Building a digest while retrieving.
 #[derive(Clone)]
 pub struct RetrievingDigest<D: Digest> {
@@ -84,10 +128,67 @@ where
 }
 
 /// Metadata of the retrieval process.
+///
+/// This can be used for implementing custom incremental logic, e.g. deciding if a document needs
+/// to be stored again, without reading the extended attributes of a stored copy.
 #[derive(Clone, Debug, Default)]
 pub struct RetrievalMetadata {
-    /// Last known modification time
+    /// Last known modification time, as reported by the server (`Last-Modified` header) or the
+    /// stored copy of the document
     pub last_modification: Option<OffsetDateTime>,
-    /// ETag
+    /// The entity tag of the document (`ETag` header), if provided by the server
     pub etag: Option<String>,
+    /// The document was not modified since it was last retrieved (HTTP 304), the data is the
+    /// previously stored content.
+    pub not_modified: bool,
+    /// The content was changed after retrieving it, so it is no longer the published document,
+    /// and its digests are calculated over the changed content.
+    pub transformed: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::{Sha256, Sha512};
+
+    const SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn digest_formats() {
+        for content in [
+            SHA256.to_string(),
+            format!("{SHA256}\n"),
+            format!("{SHA256}  file.json\n"),
+            format!("{SHA256} *file.json"),
+            format!("\\{SHA256}  file\\nname.json"),
+            format!("SHA256 (file.json) = {SHA256}\n"),
+            SHA256.to_uppercase(),
+        ] {
+            assert_eq!(
+                parse_digest::<Sha256>(&content).as_deref(),
+                Ok(SHA256),
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_digests() {
+        assert_eq!(parse_digest::<Sha256>(" \n"), Err(DigestFormatError::Empty));
+        assert_eq!(
+            parse_digest::<Sha512>(SHA256),
+            Err(DigestFormatError::Invalid {
+                expected: 128,
+                value: SHA256.to_string()
+            })
+        );
+        assert_eq!(
+            parse_digest::<Sha256>("<html>Not found</html>"),
+            Err(DigestFormatError::Invalid {
+                expected: 64,
+                value: "<html>Not".to_string()
+            })
+        );
+        assert!(parse_digest::<Sha256>(&format!("SHA256 (file.json) = {}", &SHA256[1..])).is_err());
+    }
 }
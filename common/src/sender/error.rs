@@ -2,4 +2,6 @@
 pub enum Error {
     #[error(transparent)]
     OpenId(#[from] openid::error::Error),
+    #[error("Failed to create token provider: {0:#}")]
+    Setup(anyhow::Error),
 }
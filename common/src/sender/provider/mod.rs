@@ -13,16 +13,30 @@ pub use self::openid::*;
 use crate::sender::Error;
 use async_trait::async_trait;
 use base64::{prelude::BASE64_STANDARD, write::EncoderStringWriter};
-use std::fmt::Debug;
+use std::fmt::{Debug, Formatter};
 use std::io::Write;
 use std::sync::Arc;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Eq)]
 pub enum Credentials {
     Bearer(String),
     Basic(String, Option<String>),
 }
 
+/// Don't leak secrets into logs.
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bearer(_) => f.debug_tuple("Bearer").field(&"***").finish(),
+            Self::Basic(username, password) => f
+                .debug_tuple("Basic")
+                .field(username)
+                .field(&password.as_ref().map(|_| "***"))
+                .finish(),
+        }
+    }
+}
+
 impl Credentials {
     /// Turn this into a value suitable for an `Authorization` header
     pub fn to_authorization_value(&self) -> String {
@@ -46,6 +60,13 @@ pub trait TokenProvider: Send + Sync {
     async fn provide_access_token(&self) -> Result<Option<Credentials>, Error>;
 }
 
+/// Providers may hold secrets, don't leak them into logs.
+impl Debug for dyn TokenProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TokenProvider")
+    }
+}
+
 #[async_trait]
 impl<T> TokenProvider for Arc<T>
 where
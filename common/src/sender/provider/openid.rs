@@ -114,6 +114,39 @@ impl From<OpenIdTokenProviderConfigArguments> for Option<OpenIdTokenProviderConf
     }
 }
 
+/// An [`OpenIdTokenProvider`], created from its configuration when the first token is requested.
+///
+/// Creating the provider requires discovering the issuer. Deferring this allows creating it
+/// without an async context, and without contacting the issuer unless a token is needed.
+#[cfg(feature = "clap")]
+pub struct LazyOpenIdTokenProvider {
+    config: OpenIdTokenProviderConfig,
+    provider: tokio::sync::OnceCell<OpenIdTokenProvider>,
+}
+
+#[cfg(feature = "clap")]
+impl LazyOpenIdTokenProvider {
+    pub fn new(config: OpenIdTokenProviderConfig) -> Self {
+        Self {
+            config,
+            provider: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "clap")]
+#[async_trait::async_trait]
+impl TokenProvider for LazyOpenIdTokenProvider {
+    async fn provide_access_token(&self) -> Result<Option<Credentials>, Error> {
+        self.provider
+            .get_or_try_init(|| OpenIdTokenProvider::with_config(self.config.clone()))
+            .await
+            .map_err(Error::Setup)?
+            .provide_access_token()
+            .await
+    }
+}
+
 /// A provider which provides access tokens for clients.
 #[derive(Clone)]
 pub struct OpenIdTokenProvider {
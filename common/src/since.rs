@@ -1,4 +1,6 @@
 //! Handling of detecting changes "since"
+use crate::lock::Lock;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
 use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
 use std::ops::Deref;
@@ -8,6 +10,11 @@ use std::time::{Duration, SystemTime};
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct SinceState {
     pub last_run: SystemTime,
+    /// The last successful run of distributions which failed since, keyed by their URL.
+    ///
+    /// Distributions not listed use `last_run`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub distributions: BTreeMap<String, SystemTime>,
 }
 
 impl SinceState {
@@ -41,10 +48,23 @@ impl SinceState {
 }
 
 /// Load and record since state
+///
+/// When using a since file, a lock next to it is held until the state is stored, or this gets
+/// dropped. So that concurrent runs don't overwrite each other's state.
+///
+/// Distributions can be recorded as failed using [`Self::failed`]. Storing the state then only
+/// advances the other distributions, so that the next run processes the failed ones again,
+/// starting from their last successful run.
 pub struct Since {
     pub since: Option<SystemTime>,
     pub last_run: SystemTime,
     pub since_file: Option<PathBuf>,
+    /// The since of distributions which failed in a previous run, keyed by their URL.
+    pub distributions: BTreeMap<String, SystemTime>,
+    previous: Option<SinceState>,
+    fallback: Option<SystemTime>,
+    failed: BTreeSet<String>,
+    _lock: Option<Lock>,
 }
 
 impl Deref for Since {
@@ -61,24 +81,28 @@ impl Since {
         since_file: Option<PathBuf>,
         since_file_offset: Duration,
     ) -> anyhow::Result<Self> {
-        let since = match (since, &since_file) {
+        let lock = since_file.as_ref().map(Lock::file).transpose()?;
+        let fallback = since.map(Into::into);
+        let previous = since_file
+            .as_ref()
+            .map(SinceState::load_from)
+            .transpose()?
+            .flatten();
+
+        let since = match (fallback, &previous) {
             // try file, then fall back to dedicated "since"
-            (skip, Some(file)) => match SinceState::load_from(file)? {
-                Some(since) => {
-                    let result = since.last_run + since_file_offset;
-                    log::info!(
-                        "Since state from file - last run: {}, offset: {} = {}",
-                        humantime::Timestamp::from(since.last_run),
-                        humantime::Duration::from(since_file_offset),
-                        humantime::Timestamp::from(result),
-                    );
-                    Some(result)
-                }
-                None => skip.map(|s| s.into()),
-            },
+            (_, Some(since)) => {
+                let result = since.last_run + since_file_offset;
+                log::info!(
+                    "Since state from file - last run: {}, offset: {} = {}",
+                    humantime::Timestamp::from(since.last_run),
+                    humantime::Duration::from(since_file_offset),
+                    humantime::Timestamp::from(result),
+                );
+                Some(result)
+            }
             // dedicated "since" value
-            (Some(skip), None) => {
-                let since = skip.into();
+            (Some(since), None) => {
                 log::info!("Using provided since {}", humantime::Timestamp::from(since));
                 Some(since)
             }
@@ -86,26 +110,156 @@ impl Since {
             (None, None) => None,
         };
 
+        let distributions = previous
+            .iter()
+            .flat_map(|previous| &previous.distributions)
+            .map(|(url, last_run)| (url.clone(), *last_run + since_file_offset))
+            .collect::<BTreeMap<_, _>>();
+
+        for (url, since) in &distributions {
+            log::info!(
+                "Since state of distribution {url}: {}",
+                humantime::Timestamp::from(*since)
+            );
+        }
+
         let last_run = SystemTime::now();
 
         Ok(Since {
             since,
             last_run,
             since_file,
+            distributions,
+            previous,
+            fallback,
+            failed: Default::default(),
+            _lock: lock,
         })
     }
 
+    /// The since of a distribution, falling back to the one of the whole run.
+    pub fn distribution(&self, url: &str) -> Option<SystemTime> {
+        self.distributions.get(url).copied().or(self.since)
+    }
+
+    /// The earliest since of all distributions.
+    ///
+    /// This is [`None`] if there is no since for the whole run, as new distributions need to be
+    /// processed completely.
+    pub fn earliest(&self) -> Option<SystemTime> {
+        self.distributions
+            .values()
+            .fold(self.since, |earliest, since| {
+                earliest.map(|earliest| earliest.min(*since))
+            })
+    }
+
+    /// Record distributions which failed, so that they don't get advanced when storing the state.
+    pub fn failed(&mut self, distributions: impl IntoIterator<Item = impl Into<String>>) {
+        self.failed
+            .extend(distributions.into_iter().map(Into::into));
+    }
+
+    /// Check if any distribution was recorded as failed.
+    pub fn has_failed(&self) -> bool {
+        !self.failed.is_empty()
+    }
+
+    /// The state to store, rewinding failed distributions to their last successful run.
+    fn state(&self) -> SinceState {
+        let distributions = self
+            .failed
+            .iter()
+            .map(|url| {
+                let last_run = match &self.previous {
+                    Some(previous) => previous
+                        .distributions
+                        .get(url)
+                        .copied()
+                        .unwrap_or(previous.last_run),
+                    // without a previous state, only the provided since was processed
+                    None => self.fallback.unwrap_or(SystemTime::UNIX_EPOCH),
+                };
+                (url.clone(), last_run)
+            })
+            .collect();
+
+        SinceState {
+            last_run: self.last_run,
+            distributions,
+        }
+    }
+
     pub fn store(self) -> anyhow::Result<()> {
         if let Some(path) = &self.since_file {
             log::info!(
                 "Storing last_run = {}",
                 humantime::Timestamp::from(self.last_run)
             );
-            SinceState {
-                last_run: self.last_run,
+            let state = self.state();
+            for (url, last_run) in &state.distributions {
+                log::info!(
+                    "Keeping last_run of failed distribution {url} = {}",
+                    humantime::Timestamp::from(*last_run)
+                );
             }
-            .store(BufWriter::new(File::create(path)?))?;
+            state.store(BufWriter::new(File::create(path)?))?;
         }
         Ok(())
     }
+
+    /// Store the state after a failed walk, if the failure was recorded using [`Self::failed`].
+    ///
+    /// Otherwise, the walk was aborted and the state is kept as it is.
+    pub fn store_failed(self) -> anyhow::Result<()> {
+        match self.has_failed() {
+            true => self.store(),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn failed_distributions_are_rewound() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("since.json");
+        let first = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+
+        let mut since = Since::new(Some(first), Some(file.clone()), Duration::ZERO).unwrap();
+        assert_eq!(since.distribution("https://example.com/a/"), Some(first));
+        since.failed(["https://example.com/a/"]);
+        let second = since.last_run;
+        since.store().unwrap();
+
+        let mut since = Since::new(None::<SystemTime>, Some(file.clone()), Duration::ZERO).unwrap();
+        assert_eq!(since.distribution("https://example.com/a/"), Some(first));
+        assert_eq!(since.distribution("https://example.com/b/"), Some(second));
+        assert_eq!(since.earliest(), Some(first));
+        since.failed(["https://example.com/b/"]);
+        since.store().unwrap();
+
+        let state = SinceState::load_from(&file).unwrap().unwrap();
+        // a succeeded distribution is advanced, a failed one keeps its last successful run
+        assert_eq!(
+            state.distributions,
+            BTreeMap::from([("https://example.com/b/".to_string(), second)])
+        );
+    }
+
+    #[test]
+    fn legacy_state() {
+        let state = SinceState::load(
+            r#"{"last_run":{"secs_since_epoch":1000,"nanos_since_epoch":0}}"#.as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            state.last_run,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_000)
+        );
+        assert!(state.distributions.is_empty());
+    }
 }
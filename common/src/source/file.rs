@@ -1,9 +1,10 @@
-use crate::retrieve::RetrievedDigest;
-use anyhow::anyhow;
+use crate::retrieve::{DigestFormatError, RetrievedDigest, parse_digest};
+use anyhow::{Context, anyhow};
 use bytes::Bytes;
 use digest::Digest;
 use futures_util::try_join;
-use sha2::{Sha256, Sha512};
+use sha2::{Sha256, Sha384, Sha512};
+use std::future::Future;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use url::Url;
@@ -35,35 +36,135 @@ pub async fn read_sig_and_digests(
     Option<RetrievedDigest<Sha256>>,
     Option<RetrievedDigest<Sha512>>,
 )> {
+    read_sig_and_digests_with(&path.display().to_string(), data, read_optional).await
+}
+
+/// Read the signature file and digests, using the provided function to read the sibling files.
+///
+/// This allows reading them from something other than the file system, like an archive. The
+/// function gets called with the name of the sibling (e.g. `{base}.asc`), and must return
+/// `None` if it doesn't exist.
+pub async fn read_sig_and_digests_with<F, Fut>(
+    base: &str,
+    data: &Bytes,
+    read: F,
+) -> anyhow::Result<(
+    Option<String>,
+    Option<RetrievedDigest<Sha256>>,
+    Option<RetrievedDigest<Sha512>>,
+)>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<Option<String>>>,
+{
     let (signature, sha256, sha512) = try_join!(
-        read_optional(format!("{}.asc", path.display())),
-        read_optional(format!("{}.sha256", path.display())),
-        read_optional(format!("{}.sha512", path.display())),
+        read(format!("{base}.asc")),
+        read(format!("{base}.sha256")),
+        read(format!("{base}.sha512")),
     )?;
 
-    let sha256 = sha256
-        // take the first "word" from the line
-        .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-        .map(|expected| {
-            let mut actual = Sha256::new();
-            actual.update(data);
-            RetrievedDigest::<Sha256> {
-                expected,
-                actual: actual.finalize(),
-            }
-        });
+    Ok((
+        signature,
+        retrieved_digest(sha256, data).with_context(|| format!("{base}.sha256"))?,
+        retrieved_digest(sha512, data).with_context(|| format!("{base}.sha512"))?,
+    ))
+}
+
+/// Read the SHA-384 digest, expected at `{base}.sha384`
+pub async fn read_sha384(
+    path: &Path,
+    data: &Bytes,
+) -> anyhow::Result<Option<RetrievedDigest<Sha384>>> {
+    read_sha384_with(&path.display().to_string(), data, read_optional).await
+}
+
+/// Read the SHA-384 digest, using the provided function to read the sibling file.
+///
+/// See [`read_sig_and_digests_with`].
+pub async fn read_sha384_with<F, Fut>(
+    base: &str,
+    data: &Bytes,
+    read: F,
+) -> anyhow::Result<Option<RetrievedDigest<Sha384>>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<Option<String>>>,
+{
+    retrieved_digest(read(format!("{base}.sha384")).await?, data)
+        .with_context(|| format!("{base}.sha384"))
+}
 
-    let sha512 = sha512
-        // take the first "word" from the line
-        .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
+/// Create a digest from the content of a digest file, and the actual data.
+fn retrieved_digest<D: Digest>(
+    expected: Option<String>,
+    data: &Bytes,
+) -> Result<Option<RetrievedDigest<D>>, DigestFormatError> {
+    expected
         .map(|expected| {
-            let mut actual = Sha512::new();
+            let expected = parse_digest::<D>(&expected)?;
+            let mut actual = D::new();
             actual.update(data);
-            RetrievedDigest::<Sha512> {
+            Ok(RetrievedDigest::<D> {
                 expected,
                 actual: actual.finalize(),
-            }
-        });
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::hex::Hex;
+    use rstest::rstest;
 
-    Ok((signature, sha256, sha512))
+    const DOCUMENT: &[u8] = br#"{"document":{}}"#;
+
+    #[rstest]
+    #[case::plain(|digest: &str| format!("{digest}\n"))]
+    #[case::gnu(|digest: &str| format!("{digest}  cve-x.json\n"))]
+    #[case::gnu_binary(|digest: &str| format!("{digest} *cve-x.json\n"))]
+    #[case::bsd(|digest: &str| format!("SHA256 (cve-x.json) = {digest}\n"))]
+    #[tokio::test]
+    async fn digest_formats(#[case] format: fn(&str) -> String) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cve-x.json");
+        let data = Bytes::from_static(DOCUMENT);
+
+        tokio::fs::write(&path, &data).await.unwrap();
+        tokio::fs::write(
+            dir.path().join("cve-x.json.sha256"),
+            format(&Hex(&Sha256::digest(DOCUMENT)).to_lower()),
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            dir.path().join("cve-x.json.sha512"),
+            format(&Hex(&Sha512::digest(DOCUMENT)).to_lower()).replace("SHA256", "SHA512"),
+        )
+        .await
+        .unwrap();
+
+        let (_, sha256, sha512) = read_sig_and_digests(&path, &data).await.unwrap();
+        assert!(sha256.unwrap().validate().is_ok());
+        assert!(sha512.unwrap().validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn invalid_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cve-x.json");
+
+        tokio::fs::write(
+            dir.path().join("cve-x.json.sha256"),
+            "<html>Not found</html>",
+        )
+        .await
+        .unwrap();
+
+        let err = read_sig_and_digests(&path, &Bytes::from_static(DOCUMENT))
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("cve-x.json.sha256"), "{err:#}");
+    }
 }
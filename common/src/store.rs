@@ -1,12 +1,27 @@
-use crate::retrieve::{RetrievalMetadata, RetrievedDigest};
+use crate::{
+    compression::Compression,
+    retrieve::{RetrievalMetadata, RetrievedDigest},
+};
 use anyhow::Context;
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Sha512};
-use std::{path::Path, time::SystemTime};
+use sha2::{Sha256, Sha384, Sha512};
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::SystemTime,
+};
 use tokio::fs;
 
 pub const ATTR_ETAG: &str = "etag";
 
+/// The extended attribute storing the uncompressed length of a compressed document.
+pub const ATTR_CONTENT_LENGTH: &str = "content-length";
+
+/// File name extensions of compressed documents.
+const COMPRESSED_EXTENSIONS: &[&str] = &["gz", "bz2", "xz"];
+
 #[derive(Debug, thiserror::Error)]
 pub enum StoreError {
     #[error("{0:#}")]
@@ -15,13 +30,63 @@ pub enum StoreError {
     Filename(String),
     #[error("Serialize key error: {0:#}")]
     SerializeKey(anyhow::Error),
+    #[error("File name {path} collides with {existing}, only differing by case")]
+    CaseCollision { path: String, existing: String },
 }
 
+/// Tracks stored file names, detecting names which only differ by case.
+///
+/// On case-insensitive filesystems, such files would silently overwrite each other.
+#[derive(Debug, Default)]
+pub struct CaseCollisions {
+    names: parking_lot::Mutex<HashMap<String, PathBuf>>,
+}
+
+impl CaseCollisions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a file name, failing if another file only differing by case was recorded before.
+    pub fn check(&self, file: &Path) -> Result<(), StoreError> {
+        let key = file.to_string_lossy().to_lowercase();
+
+        match self.names.lock().entry(key) {
+            Entry::Occupied(entry) if entry.get() != file => Err(StoreError::CaseCollision {
+                path: file.display().to_string(),
+                existing: entry.get().display().to_string(),
+            }),
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(file.to_path_buf());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Probe if the filesystem of the given directory is case-insensitive.
+///
+/// This creates (and removes) a temporary file in the directory, which must already exist.
+pub fn is_case_insensitive(dir: &Path) -> std::io::Result<bool> {
+    let name = format!(".case-probe-{}", std::process::id());
+    let probe = dir.join(name.to_uppercase());
+
+    std::fs::write(&probe, [])?;
+    let result = dir.join(&name).try_exists();
+    std::fs::remove_file(&probe)?;
+
+    result
+}
+
+#[derive(Clone, Copy)]
 pub struct Document<'a> {
     /// The data to store
     pub data: &'a [u8],
     /// An optional SHA256 digest
     pub sha256: &'a Option<RetrievedDigest<Sha256>>,
+    /// An optional SHA384 digest
+    pub sha384: &'a Option<RetrievedDigest<Sha384>>,
     /// An optional SHA512 digest
     pub sha512: &'a Option<RetrievedDigest<Sha512>>,
     /// An optional signature
@@ -35,6 +100,13 @@ pub struct Document<'a> {
 
     pub no_timestamps: bool,
     pub no_xattrs: bool,
+
+    /// The compression to apply to the stored document.
+    ///
+    /// A compressed document gets the extension of the compression appended to its file name.
+    /// Digests and signature still refer to the uncompressed data, and are stored next to it,
+    /// using the uncompressed file name.
+    pub compression: Compression,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,7 +139,25 @@ pub async fn store_errors(file: &Path, document: ErrorData) -> Result<(), StoreE
     Ok(())
 }
 
+/// The name of the temporary file a document is written to before being moved into place.
+///
+/// The file is located in the same directory, so that it can be renamed atomically. The name is
+/// unique for each call, so that concurrent writes of the same document don't interfere.
+pub fn temp_file(file: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    file.with_file_name(format!(".{name}.{}.{n}.tmp", std::process::id()))
+}
+
 /// Stores the document and associated files (checksums, signature, xattrs, timestamps).
+///
+/// The document itself is written to a temporary file first, and then renamed into place once
+/// all associated files have been written. So readers only ever see complete documents.
 pub async fn store_document(file: &Path, document: Document<'_>) -> Result<(), StoreError> {
     log::debug!("Writing {}", file.display());
 
@@ -78,9 +168,156 @@ pub async fn store_document(file: &Path, document: Document<'_>) -> Result<(), S
             .map_err(StoreError::Io)?;
     }
 
-    fs::write(&file, document.data)
+    let target = match document.compression.extension() {
+        Some(ext) => file.with_added_extension(ext),
+        None => file.to_path_buf(),
+    };
+
+    let temp = temp_file(&target);
+    let result = store_document_via(file, &target, &temp, document).await;
+
+    if result.is_err() {
+        // best effort, the original error is more relevant
+        let _ = fs::remove_file(&temp).await;
+        return result;
+    }
+
+    remove_stale_variants(file, &target).await
+}
+
+/// Stores a document by hard linking an identical, previously stored, one.
+///
+/// Associated files (checksums, signature) are linked as well if their content is identical to
+/// the ones of the existing document, and written otherwise. As the linked files share their
+/// inode, timestamps and extended attributes are those of the existing document.
+///
+/// Fails without modifying the document if the hard link cannot be created, e.g. because the
+/// filesystem doesn't support it. In this case, the document should be stored using
+/// [`store_document`] instead.
+pub async fn link_document(
+    existing: &Path,
+    file: &Path,
+    document: Document<'_>,
+) -> Result<(), StoreError> {
+    log::debug!("Linking {} to {}", file.display(), existing.display());
+
+    let (source, target) = match document.compression.extension() {
+        Some(ext) => (
+            existing.with_added_extension(ext),
+            file.with_added_extension(ext),
+        ),
+        None => (existing.to_path_buf(), file.to_path_buf()),
+    };
+
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create parent directory: {}", parent.display()))
+            .map_err(StoreError::Io)?;
+    }
+
+    let companions = [
+        ("sha256", document.sha256.as_ref().map(|d| &d.expected)),
+        ("sha384", document.sha384.as_ref().map(|d| &d.expected)),
+        ("sha512", document.sha512.as_ref().map(|d| &d.expected)),
+        ("asc", document.signature.as_ref()),
+    ];
+
+    for (ext, content) in companions {
+        let Some(content) = content else {
+            continue;
+        };
+
+        let source = existing.with_added_extension(ext);
+        let target = file.with_added_extension(ext);
+
+        let identical = fs::read(&source)
+            .await
+            .is_ok_and(|existing| existing == content.as_bytes());
+        if !(identical && link_into_place(&source, &target).await.is_ok()) {
+            fs::write(&target, content)
+                .await
+                .with_context(|| format!("Failed to write {ext} file: {}", target.display()))
+                .map_err(StoreError::Io)?;
+        }
+    }
+
+    // link the document last, so that readers only ever see complete documents
+    link_into_place(&source, &target)
         .await
-        .with_context(|| format!("Failed to write advisory: {}", file.display()))
+        .with_context(|| {
+            format!(
+                "Failed to link {} to {}",
+                target.display(),
+                source.display()
+            )
+        })
+        .map_err(StoreError::Io)?;
+
+    remove_stale_variants(file, &target).await
+}
+
+/// Create a hard link, replacing an existing target file.
+async fn link_into_place(source: &Path, target: &Path) -> std::io::Result<()> {
+    let temp = temp_file(target);
+    fs::hard_link(source, &temp).await?;
+
+    let result = fs::rename(&temp, target).await;
+    if result.is_err() {
+        // best effort, the original error is more relevant
+        let _ = fs::remove_file(&temp).await;
+    }
+
+    result
+}
+
+/// Remove other (un)compressed variants of a document, which would shadow the stored one.
+async fn remove_stale_variants(file: &Path, target: &Path) -> Result<(), StoreError> {
+    let variants = std::iter::once(file.to_path_buf()).chain(
+        COMPRESSED_EXTENSIONS
+            .iter()
+            .map(|ext| file.with_added_extension(ext)),
+    );
+
+    for variant in variants.filter(|variant| variant != target) {
+        match fs::remove_file(&variant).await {
+            Ok(()) => log::debug!("Removed stale variant: {}", variant.display()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(StoreError::Io(anyhow::Error::from(err).context(format!(
+                    "Failed to remove stale variant: {}",
+                    variant.display()
+                ))));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn store_document_via(
+    file: &Path,
+    target: &Path,
+    temp: &Path,
+    document: Document<'_>,
+) -> Result<(), StoreError> {
+    let compressed = match document.compression {
+        Compression::None => None,
+        compression => {
+            // compressing is CPU bound, so keep it off the async runtime
+            let data = Bytes::copy_from_slice(document.data);
+            tokio::task::spawn_blocking(move || compression.compress_opt(&data))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result.map_err(anyhow::Error::from))
+                .with_context(|| format!("Failed to compress advisory: {}", target.display()))
+                .map_err(StoreError::Io)?
+        }
+    };
+
+    fs::write(temp, compressed.as_deref().unwrap_or(document.data))
+        .await
+        .with_context(|| format!("Failed to write advisory: {}", temp.display()))
         .map_err(StoreError::Io)?;
 
     if let Some(sha256) = &document.sha256 {
@@ -90,6 +327,13 @@ pub async fn store_document(file: &Path, document: Document<'_>) -> Result<(), S
             .with_context(|| format!("Failed to write checksum: {file}"))
             .map_err(StoreError::Io)?;
     }
+    if let Some(sha384) = &document.sha384 {
+        let file = format!("{}.sha384", file.display());
+        fs::write(&file, &sha384.expected)
+            .await
+            .with_context(|| format!("Failed to write checksum: {file}"))
+            .map_err(StoreError::Io)?;
+    }
     if let Some(sha512) = &document.sha512 {
         let file = format!("{}.sha512", file.display());
         fs::write(&file, &sha512.expected)
@@ -114,7 +358,7 @@ pub async fn store_document(file: &Path, document: Document<'_>) -> Result<(), S
             .map(SystemTime::from)
             .unwrap_or_else(|| document.changed)
             .into();
-        filetime::set_file_mtime(file, mtime)
+        filetime::set_file_mtime(temp, mtime)
             .with_context(|| {
                 format!(
                     "Failed to set last modification timestamp: {}",
@@ -127,10 +371,31 @@ pub async fn store_document(file: &Path, document: Document<'_>) -> Result<(), S
     if !document.no_xattrs
         && let Some(etag) = &document.metadata.etag
     {
-        fsquirrel::set(file, ATTR_ETAG, etag.as_bytes())
+        fsquirrel::set(temp, ATTR_ETAG, etag.as_bytes())
             .with_context(|| format!("Failed to store {}: {}", ATTR_ETAG, file.display()))
             .map_err(StoreError::Io)?;
     }
 
+    if !document.no_xattrs && compressed.is_some() {
+        fsquirrel::set(
+            temp,
+            ATTR_CONTENT_LENGTH,
+            document.data.len().to_string().as_bytes(),
+        )
+        .with_context(|| {
+            format!(
+                "Failed to store {}: {}",
+                ATTR_CONTENT_LENGTH,
+                target.display()
+            )
+        })
+        .map_err(StoreError::Io)?;
+    }
+
+    fs::rename(temp, target)
+        .await
+        .with_context(|| format!("Failed to move advisory into place: {}", target.display()))
+        .map_err(StoreError::Io)?;
+
     Ok(())
 }
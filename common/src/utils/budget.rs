@@ -0,0 +1,122 @@
+//! Limiting the amount of in-flight data
+
+use parking_lot::Mutex;
+use std::{pin::pin, sync::Arc};
+use tokio::sync::Notify;
+
+/// A budget of bytes, limiting the amount of data being held in memory.
+///
+/// The budget doesn't block reservations, as the size of a document is only known once it was
+/// retrieved. Instead, it allows waiting for the budget to become available again before starting
+/// new work.
+#[derive(Clone, Debug)]
+pub struct MemoryBudget {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    limit: usize,
+    used: Mutex<usize>,
+    notify: Notify,
+}
+
+impl MemoryBudget {
+    /// Create a new budget with a limit, in bytes.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                limit,
+                used: Mutex::new(0),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// The limit, in bytes.
+    pub fn limit(&self) -> usize {
+        self.inner.limit
+    }
+
+    /// The number of bytes currently reserved.
+    pub fn used(&self) -> usize {
+        *self.inner.used.lock()
+    }
+
+    /// Wait until the used bytes drop below the limit.
+    pub async fn available(&self) {
+        loop {
+            let mut notified = pin!(self.inner.notify.notified());
+            // register before checking, so that we don't miss a release in between
+            notified.as_mut().enable();
+
+            if self.used() < self.inner.limit {
+                return;
+            }
+
+            log::debug!(
+                "Memory budget exhausted ({} of {} bytes), waiting",
+                self.used(),
+                self.inner.limit
+            );
+
+            notified.await;
+        }
+    }
+
+    /// Reserve bytes, until the returned guard is dropped.
+    pub fn reserve(&self, bytes: usize) -> BudgetGuard {
+        *self.inner.used.lock() += bytes;
+        BudgetGuard {
+            budget: self.clone(),
+            bytes,
+        }
+    }
+}
+
+/// Reserved bytes of a [`MemoryBudget`], released when dropped.
+#[derive(Debug)]
+pub struct BudgetGuard {
+    budget: MemoryBudget,
+    bytes: usize,
+}
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        let inner = &self.budget.inner;
+        *inner.used.lock() -= self.bytes;
+        inner.notify.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn wait_for_release() {
+        let budget = MemoryBudget::new(100);
+
+        // below the limit, no need to wait
+        budget.available().await;
+
+        let guard = budget.reserve(150);
+        assert_eq!(budget.used(), 150);
+
+        let waiting = tokio::spawn({
+            let budget = budget.clone();
+            async move { budget.available().await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        drop(guard);
+        tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .expect("must finish after release")
+            .expect("must not fail");
+        assert_eq!(budget.used(), 0);
+    }
+}
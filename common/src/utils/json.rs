@@ -0,0 +1,136 @@
+//! Parsing JSON with diagnostics
+
+use serde::de::DeserializeOwned;
+use serde_path_to_error::{Path, Segment};
+use std::fmt::{Display, Formatter};
+
+/// An error parsing JSON, pointing at the location of the failure.
+#[derive(Debug)]
+pub struct JsonParseError {
+    /// The JSON pointer (RFC 6901) to the failing node, empty for the root.
+    pub pointer: String,
+    /// The original error
+    pub error: serde_json::Error,
+}
+
+impl JsonParseError {
+    fn new(err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        Self {
+            pointer: to_pointer(err.path()),
+            error: err.into_inner(),
+        }
+    }
+
+    /// The line of the failure, starting at 1. Zero if unknown, e.g. when parsing from a value.
+    pub fn line(&self) -> usize {
+        self.error.line()
+    }
+
+    /// The column of the failure, starting at 1. Zero if unknown, e.g. when parsing from a value.
+    pub fn column(&self) -> usize {
+        self.error.column()
+    }
+}
+
+impl Display for JsonParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // the original error already reports line and column, if available
+        write!(f, "{}", self.error)?;
+        if !self.pointer.is_empty() {
+            write!(f, ", path {}", self.pointer)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for JsonParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Convert a path into a JSON pointer.
+fn to_pointer(path: &Path) -> String {
+    let mut result = String::new();
+
+    for segment in path {
+        let segment = match segment {
+            Segment::Seq { index } => index.to_string(),
+            Segment::Map { key } => key.replace('~', "~0").replace('/', "~1"),
+            // enum variants and unknown segments don't have a representation in the document
+            Segment::Enum { .. } | Segment::Unknown => continue,
+        };
+        result.push('/');
+        result.push_str(&segment);
+    }
+
+    result
+}
+
+/// Deserialize an instance of type `T` from bytes of JSON text, tracking the location of a failure.
+pub fn from_slice<T: DeserializeOwned>(data: &[u8]) -> Result<T, JsonParseError> {
+    let mut de = serde_json::Deserializer::from_slice(data);
+    let result = serde_path_to_error::deserialize(&mut de).map_err(JsonParseError::new)?;
+    de.end().map_err(|error| JsonParseError {
+        pointer: String::new(),
+        error,
+    })?;
+    Ok(result)
+}
+
+/// Deserialize an instance of type `T` from a JSON value, tracking the location of a failure.
+///
+/// As a value doesn't carry any position information, only the path will be reported.
+pub fn from_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, JsonParseError> {
+    serde_path_to_error::deserialize(value).map_err(JsonParseError::new)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(unused)]
+    struct Document {
+        components: Vec<Component>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(unused)]
+    struct Component {
+        name: String,
+        licenses: Vec<String>,
+    }
+
+    #[test]
+    fn location() {
+        let err = from_slice::<Document>(
+            br#"{
+  "components": [
+    { "name": "a", "licenses": [] },
+    { "name": "b/c", "licenses": "MIT" }
+  ]
+}"#,
+        )
+        .expect_err("must fail");
+
+        assert_eq!(err.pointer, "/components/1/licenses");
+        assert_eq!(err.line(), 4);
+        assert!(
+            err.to_string().ends_with(", path /components/1/licenses"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn escape_pointer() {
+        let err = from_value::<std::collections::HashMap<String, u32>>(
+            serde_json::json!({"a/b~c": "text"}),
+        )
+        .expect_err("must fail");
+
+        assert_eq!(err.pointer, "/a~1b~0c");
+        assert_eq!(err.line(), 0);
+    }
+}
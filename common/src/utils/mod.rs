@@ -1,5 +1,7 @@
 //! Common utilities
+pub mod budget;
 pub mod hex;
+pub mod json;
 pub mod measure;
 pub mod url;
 
@@ -21,10 +21,12 @@ where
         retrieved: S::Retrieved,
     },
     /// Invalid signature of the document
-    Signature {
+    SignatureInvalid {
         error: anyhow::Error,
         retrieved: S::Retrieved,
     },
+    /// The document has no signature, but one is required
+    SignatureMissing { retrieved: S::Retrieved },
 }
 
 impl<S> Urlify for ValidationError<S>
@@ -35,7 +37,8 @@ where
         match self {
             Self::Retrieval(err) => err.url(),
             Self::DigestMismatch { retrieved, .. } => retrieved.url(),
-            Self::Signature { retrieved, .. } => retrieved.url(),
+            Self::SignatureInvalid { retrieved, .. } => retrieved.url(),
+            Self::SignatureMissing { retrieved } => retrieved.url(),
         }
     }
 }
@@ -56,9 +59,12 @@ where
                 "Digest mismatch - expected: {expected}, actual: {actual} ({})",
                 retrieved.url()
             ),
-            Self::Signature { error, retrieved } => {
+            Self::SignatureInvalid { error, retrieved } => {
                 write!(f, "Invalid signature: {error} ({})", retrieved.url())
             }
+            Self::SignatureMissing { retrieved } => {
+                write!(f, "Missing signature ({})", retrieved.url())
+            }
         }
     }
 }
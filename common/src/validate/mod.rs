@@ -6,13 +6,105 @@ pub mod digest;
 pub mod openpgp;
 pub mod source;
 
-use std::time::SystemTime;
+use crate::utils::openpgp::PublicKey;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+    time::SystemTime,
+};
+
+/// How to handle documents without a signature.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MissingSignature {
+    /// Accept the document
+    ///
+    /// Signatures are optional, so this is only logged at debug level.
+    #[default]
+    Allow,
+    /// Accept the document, logging a warning
+    Warn,
+    /// Reject the document, failing its validation
+    Reject,
+}
+
+/// The OpenPGP versions of signatures to accept.
+///
+/// Providers are migrating from legacy v3 signatures to v4 signatures. This allows requiring
+/// either one of them, or accepting whichever is present.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SignatureVersion {
+    /// Accept v3 as well as v4 signatures, as far as the policy allows them
+    ///
+    /// The standard policy rejects v3 signatures, unless a policy date before 2007 is used.
+    #[default]
+    Policy,
+    /// Accept v3 as well as v4 signatures, accepting v3 signatures regardless of the policy date
+    Any,
+    /// Only accept v4 signatures
+    V4,
+    /// Only accept legacy v3 signatures, regardless of the policy date
+    V3,
+}
+
+impl SignatureVersion {
+    /// Check if signatures of the OpenPGP version are accepted.
+    pub fn accepts(&self, version: u8) -> bool {
+        match self {
+            Self::Policy | Self::Any => version == 3 || version == 4,
+            Self::V4 => version == 4,
+            Self::V3 => version == 3,
+        }
+    }
+
+    /// Check if v3 signatures are accepted, even if the policy would reject them.
+    pub fn overrides_policy(&self) -> bool {
+        matches!(self, Self::Any | Self::V3)
+    }
+}
+
+impl Display for SignatureVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Policy => f.write_str("policy"),
+            Self::Any => f.write_str("any"),
+            Self::V4 => f.write_str("v4"),
+            Self::V3 => f.write_str("v3"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown signature version '{0}', expected one of: policy, any, v4, v3")]
+pub struct UnknownSignatureVersion(String);
+
+impl FromStr for SignatureVersion {
+    type Err = UnknownSignatureVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "policy" => Ok(Self::Policy),
+            "any" => Ok(Self::Any),
+            "v4" | "4" => Ok(Self::V4),
+            "v3" | "3" => Ok(Self::V3),
+            _ => Err(UnknownSignatureVersion(s.to_string())),
+        }
+    }
+}
 
 #[non_exhaustive]
 #[derive(Clone, Debug, Default)]
 pub struct ValidationOptions {
     /// time for policy checks
     pub validation_date: Option<SystemTime>,
+
+    /// keys to accept signatures of, in addition to the ones of the provider
+    pub additional_keys: Vec<PublicKey>,
+
+    /// how to handle documents without a signature
+    pub missing_signature: MissingSignature,
+
+    /// the OpenPGP versions of signatures to accept
+    pub signature_version: SignatureVersion,
 }
 
 impl ValidationOptions {
@@ -24,4 +116,33 @@ impl ValidationOptions {
         self.validation_date = validation_date.into();
         self
     }
+
+    /// Set keys to accept signatures of, in addition to the ones of the provider.
+    ///
+    /// A signature is valid if it was created by any of the candidate keys. This allows
+    /// accepting advisories signed with a key not (or no longer) listed by the provider, e.g.
+    /// during a key rotation.
+    pub fn additional_keys(mut self, additional_keys: impl IntoIterator<Item = PublicKey>) -> Self {
+        self.additional_keys = additional_keys.into_iter().collect();
+        self
+    }
+
+    /// Set how to handle documents without a signature.
+    ///
+    /// Invalid signatures always fail the validation. Missing signatures only do when using
+    /// [`MissingSignature::Reject`].
+    pub fn missing_signature(mut self, missing_signature: MissingSignature) -> Self {
+        self.missing_signature = missing_signature;
+        self
+    }
+
+    /// Set the OpenPGP versions of signatures to accept.
+    ///
+    /// Signatures of other versions fail the validation, even if they are valid otherwise. The
+    /// version is checked before looking up the signing key, so it applies to the keys of the
+    /// provider as well as to the [additional keys](Self::additional_keys).
+    pub fn signature_version(mut self, signature_version: SignatureVersion) -> Self {
+        self.signature_version = signature_version;
+        self
+    }
 }
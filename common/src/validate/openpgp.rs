@@ -1,10 +1,11 @@
 //! OpenPGP validation
 use crate::utils::openpgp::PublicKey;
-use crate::validate::ValidationOptions;
+use crate::validate::{SignatureVersion, ValidationOptions};
 use anyhow::bail;
 use sequoia_openpgp::{
-    Cert, KeyHandle, Packet,
+    Cert, Fingerprint, KeyHandle, Packet, PacketPile,
     cert::prelude::ValidErasedKeyAmalgamation,
+    packet::Tag,
     packet::{Signature, key::PublicParts},
     parse::{
         Parse,
@@ -13,15 +14,34 @@ use sequoia_openpgp::{
     policy::{HashAlgoSecurity, Policy, StandardPolicy},
     types::{AEADAlgorithm, SymmetricAlgorithm},
 };
-use std::fmt::Debug;
+use std::{fmt::Debug, time::SystemTime};
+
+/// The creator of a verified signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signer {
+    /// The fingerprint of the certificate
+    pub fingerprint: Fingerprint,
+    /// The fingerprint of the (sub)key which created the signature
+    pub key_fingerprint: Fingerprint,
+    /// The creation time of the signature
+    pub created: Option<SystemTime>,
+}
 
 struct Helper<'a> {
     keys: &'a [PublicKey],
+    additional_keys: &'a [PublicKey],
+    /// The creator of the good signature
+    signer: Option<Signer>,
 }
 
 impl VerificationHelper for Helper<'_> {
     fn get_certs(&mut self, _ids: &[KeyHandle]) -> sequoia_openpgp::Result<Vec<Cert>> {
-        Ok(self.keys.iter().flat_map(|k| k.certs.clone()).collect())
+        Ok(self
+            .keys
+            .iter()
+            .chain(self.additional_keys)
+            .flat_map(|k| k.certs.clone())
+            .collect())
     }
 
     fn check(&mut self, structure: MessageStructure) -> sequoia_openpgp::Result<()> {
@@ -32,7 +52,14 @@ impl VerificationHelper for Helper<'_> {
 
             match (i, layer) {
                 (0, MessageLayer::SignatureGroup { results }) => match results.into_iter().next() {
-                    Some(Ok(_)) => good = true,
+                    Some(Ok(checksum)) => {
+                        self.signer = Some(Signer {
+                            fingerprint: checksum.ka.cert().fingerprint(),
+                            key_fingerprint: checksum.ka.key().fingerprint(),
+                            created: checksum.sig.signature_creation_time(),
+                        });
+                        good = true;
+                    }
                     Some(Err(err)) => {
                         return Err(sequoia_openpgp::Error::from(err).into());
                     }
@@ -91,19 +118,243 @@ pub fn validate_signature(
     signature: &str,
     data: impl AsRef<[u8]>,
 ) -> Result<(), anyhow::Error> {
+    verify_signature(options, keys, signature, data)?;
+    Ok(())
+}
+
+/// Ensure that all signatures have an accepted version.
+fn check_signature_version(
+    signature_version: SignatureVersion,
+    signature: &str,
+) -> Result<(), anyhow::Error> {
+    let pile = PacketPile::from_bytes(signature)?;
+
+    for packet in pile.descendants() {
+        if let Packet::Signature(sig) = packet
+            && !signature_version.accepts(sig.version())
+        {
+            bail!(
+                "Signature version {} is not accepted (accepting: {signature_version})",
+                sig.version()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate a detached signature, returning its creator.
+///
+/// The signature is accepted if it was created by any certificate of the keys, or the additional
+/// keys of the options, and its version is accepted by the options.
+pub fn verify_signature(
+    options: &ValidationOptions,
+    keys: &[PublicKey],
+    signature: &str,
+    data: impl AsRef<[u8]>,
+) -> Result<Signer, anyhow::Error> {
+    check_signature_version(options.signature_version, signature)?;
+
     // TODO: we could move this into the context and re-use
-    let policy = match options.validation_date {
+    let mut policy = match options.validation_date {
         Some(time) => StandardPolicy::at(time),
         None => StandardPolicy::new(),
     };
+    if options.signature_version.overrides_policy() {
+        // v3 signatures are explicitly accepted, so they must not be rejected by the policy
+        policy.accept_packet_tag_version(Tag::Signature, 3);
+    }
     let policy = LoggingPolicy(policy);
     let mut verifier = DetachedVerifierBuilder::from_bytes(&signature)?.with_policy(
         &policy,
         None,
-        Helper { keys },
+        Helper {
+            keys,
+            additional_keys: &options.additional_keys,
+            signer: None,
+        },
     )?;
 
     verifier.verify_bytes(data)?;
 
-    Ok(())
+    match verifier.into_helper().signer {
+        Some(signer) => Ok(signer),
+        None => bail!("No signature"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sequoia_openpgp::{
+        armor::{self, Kind},
+        cert::CertBuilder,
+        crypto::{KeyPair, Signer as _},
+        packet::signature::Signature3,
+        serialize::{
+            Serialize,
+            stream::{Armorer, Message, Signer},
+        },
+        types::{HashAlgorithm, SignatureType, Timestamp},
+    };
+    use std::io::Write;
+
+    const DATA: &[u8] = br#"{"document":{}}"#;
+
+    fn cert() -> Cert {
+        let (cert, _) = CertBuilder::general_purpose(Some("test@example.com"))
+            .generate()
+            .expect("must generate");
+        cert
+    }
+
+    fn public_key(cert: &Cert) -> PublicKey {
+        PublicKey {
+            certs: vec![cert.clone()],
+            raw: Default::default(),
+        }
+    }
+
+    fn keypair(cert: &Cert) -> KeyPair {
+        cert.keys()
+            .unencrypted_secret()
+            .with_policy(&StandardPolicy::new(), None)
+            .for_signing()
+            .next()
+            .expect("must have signing key")
+            .key()
+            .clone()
+            .into_keypair()
+            .expect("must create keypair")
+    }
+
+    fn sign(cert: &Cert) -> String {
+        let keypair = keypair(cert);
+
+        let mut sink = vec![];
+        let message = Armorer::new(Message::new(&mut sink))
+            .kind(Kind::Signature)
+            .build()
+            .expect("must build armorer");
+        let mut signer = Signer::new(message, keypair)
+            .expect("must create signer")
+            .detached()
+            .build()
+            .expect("must build signer");
+        signer.write_all(DATA).expect("must write");
+        signer.finalize().expect("must finalize");
+
+        String::from_utf8(sink).expect("must be UTF-8")
+    }
+
+    /// Create a detached v3 signature, which the streaming signer can't create.
+    fn sign_v3(cert: &Cert) -> String {
+        let mut keypair = keypair(cert);
+        let typ = SignatureType::Binary;
+        let creation_time = Timestamp::now();
+
+        // v3 signatures hash the data, followed by the signature type and creation time
+        let mut hash = HashAlgorithm::SHA256
+            .context()
+            .expect("must create hash context")
+            .for_signature(3);
+        hash.update(DATA);
+        hash.update(&[u8::from(typ)]);
+        hash.update(&u32::from(creation_time).to_be_bytes());
+        let digest = hash.into_digest().expect("must digest");
+
+        let mpis = keypair
+            .sign(HashAlgorithm::SHA256, &digest)
+            .expect("must sign");
+        let signature = Signature3::new(
+            typ,
+            creation_time,
+            keypair.public().keyid(),
+            keypair.public().pk_algo(),
+            HashAlgorithm::SHA256,
+            [digest[0], digest[1]],
+            mpis,
+        );
+
+        let mut sink = vec![];
+        let mut writer =
+            armor::Writer::new(&mut sink, Kind::Signature).expect("must create armor writer");
+        Packet::from(signature)
+            .serialize(&mut writer)
+            .expect("must serialize");
+        writer.finalize().expect("must finalize");
+
+        String::from_utf8(sink).expect("must be UTF-8")
+    }
+
+    #[test]
+    fn signer_of_candidates() {
+        let (old, new) = (cert(), cert());
+        let signature = sign(&new);
+        let keys = [public_key(&old), public_key(&new)];
+
+        let signer = verify_signature(&ValidationOptions::new(), &keys, &signature, DATA)
+            .expect("must verify");
+        assert_eq!(signer.fingerprint, new.fingerprint());
+        assert!(
+            new.keys()
+                .any(|key| key.key().fingerprint() == signer.key_fingerprint)
+        );
+        assert!(signer.created.is_some());
+    }
+
+    #[test]
+    fn additional_keys() {
+        let (provider, rotated) = (cert(), cert());
+        let signature = sign(&rotated);
+        let keys = [public_key(&provider)];
+
+        assert!(verify_signature(&ValidationOptions::new(), &keys, &signature, DATA).is_err());
+
+        let options = ValidationOptions::new().additional_keys([public_key(&rotated)]);
+        let signer = verify_signature(&options, &keys, &signature, DATA).expect("must verify");
+        assert_eq!(signer.fingerprint, rotated.fingerprint());
+    }
+
+    #[test]
+    fn signature_version() {
+        let cert = cert();
+        let signature = sign(&cert);
+        let keys = [public_key(&cert)];
+
+        for (version, valid) in [
+            (SignatureVersion::V4, true),
+            (SignatureVersion::Policy, true),
+            (SignatureVersion::Any, true),
+            (SignatureVersion::V3, false),
+        ] {
+            let options = ValidationOptions::new().signature_version(version);
+            assert_eq!(
+                verify_signature(&options, &keys, &signature, DATA).is_ok(),
+                valid,
+                "{version}"
+            );
+        }
+    }
+
+    #[test]
+    fn v3_signature() {
+        let cert = cert();
+        let signature = sign_v3(&cert);
+        let keys = [public_key(&cert)];
+
+        for (version, valid) in [
+            (SignatureVersion::V4, false),
+            (SignatureVersion::Policy, false),
+            (SignatureVersion::Any, true),
+            (SignatureVersion::V3, true),
+        ] {
+            let options = ValidationOptions::new().signature_version(version);
+            assert_eq!(
+                verify_signature(&options, &keys, &signature, DATA).is_ok(),
+                valid,
+                "{version}"
+            );
+        }
+    }
 }
@@ -0,0 +1,71 @@
+//! Shared types of the walkers
+
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+/// Statistics of a walk.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WalkStats {
+    /// The number of walked distributions, if the source has any.
+    pub distributions: usize,
+    /// The number of discovered documents, after filtering.
+    pub discovered: usize,
+    /// The number of successfully visited documents.
+    pub succeeded: usize,
+    /// The number of documents which failed to be visited.
+    pub failed: usize,
+    /// The number of downloaded bytes, when the visitor retrieves documents.
+    pub downloaded_bytes: u64,
+    /// The number of bytes of documents which were not modified (HTTP 304), and so skipped.
+    pub skipped_bytes: u64,
+    /// The wall time of the walk.
+    pub duration: Duration,
+}
+
+impl WalkStats {
+    /// The number of visited documents, successful or not.
+    pub fn visited(&self) -> usize {
+        self.succeeded + self.failed
+    }
+}
+
+impl Display for WalkStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.distributions > 0 {
+            write!(f, "{} distributions, ", self.distributions)?;
+        }
+        write!(
+            f,
+            "{} documents discovered, {} visited ({} failed), {} bytes downloaded, {} bytes skipped, took {:.2?}",
+            self.discovered,
+            self.visited(),
+            self.failed,
+            self.downloaded_bytes,
+            self.skipped_bytes,
+            self.duration,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display_without_distributions() {
+        let stats = WalkStats {
+            discovered: 3,
+            succeeded: 2,
+            failed: 1,
+            ..Default::default()
+        };
+
+        assert!(
+            stats
+                .to_string()
+                .starts_with("3 documents discovered, 3 visited (1 failed)")
+        );
+    }
+}
@@ -1,10 +1,19 @@
 use reqwest::StatusCode;
 use rstest::rstest;
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
-use walker_common::fetcher::{Error, Fetcher, FetcherOptions};
+use url::Url;
+use walker_common::{
+    fetcher::{
+        CircuitBreakerMode, CircuitBreakerOptions, ClientCertificateOptions, Error, Fetcher,
+        FetcherOptions, ProxyOptions,
+    },
+    metrics::Metrics,
+    sender::provider::Credentials,
+};
 
 /// Test helper to start a mock HTTP server
 async fn start_mock_server<F>(handler: F) -> String
@@ -304,3 +313,434 @@ async fn test_configurable_default_retry_after(#[case] custom_default_secs: u64)
         elapsed
     );
 }
+
+#[tokio::test]
+async fn test_circuit_breaker_aborts() {
+    let attempt_count = Arc::new(AtomicUsize::new(0));
+    let attempt_count_clone = attempt_count.clone();
+
+    let server = start_mock_server(move |_req| {
+        attempt_count_clone.fetch_add(1, Ordering::SeqCst);
+        hyper::Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body("Server error".to_string())
+            .unwrap()
+    })
+    .await;
+
+    let fetcher = Fetcher::new(
+        FetcherOptions::new().retries(5).circuit_breaker(
+            CircuitBreakerOptions::new(2)
+                .cooldown(Duration::from_mins(1))
+                .mode(CircuitBreakerMode::Abort),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let result: Result<String, _> = fetcher.fetch(&server).await;
+    match result {
+        Err(Error::CircuitOpen(_)) => {}
+        other => panic!("expected CircuitOpen, got {other:?}"),
+    }
+    assert_eq!(attempt_count.load(Ordering::SeqCst), 2);
+
+    // further requests to the same host must not reach the server
+    let result: Result<String, _> = fetcher.clone().fetch(format!("{server}/other")).await;
+    assert!(matches!(result, Err(Error::CircuitOpen(_))));
+    assert_eq!(attempt_count.load(Ordering::SeqCst), 2);
+}
+
+#[rstest]
+#[case::bearer(Credentials::Bearer("token".into()), "Bearer token")]
+#[case::basic(
+    Credentials::Basic("user".into(), Some("pass".into())),
+    "Basic dXNlcjpwYXNz"
+)]
+#[tokio::test]
+async fn test_static_credentials(#[case] credentials: Credentials, #[case] expected: &str) {
+    let server = start_mock_server(|req| {
+        let authorization = req
+            .headers()
+            .get(hyper::header::AUTHORIZATION)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        hyper::Response::builder()
+            .status(StatusCode::OK)
+            .body(authorization)
+            .unwrap()
+    })
+    .await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new().credentials(credentials))
+        .await
+        .unwrap();
+
+    let result: String = fetcher.fetch(&server).await.unwrap();
+    assert_eq!(result, expected);
+}
+
+#[tokio::test]
+async fn test_token_provider() {
+    let server = start_mock_server(|req| {
+        let authorization = req
+            .headers()
+            .get(hyper::header::AUTHORIZATION)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        hyper::Response::builder()
+            .status(StatusCode::OK)
+            .body(authorization)
+            .unwrap()
+    })
+    .await;
+
+    // provided credentials take precedence over static ones
+    let fetcher = Fetcher::new(
+        FetcherOptions::new()
+            .credentials(Credentials::Bearer("static".into()))
+            .token_provider("provided".to_string()),
+    )
+    .await
+    .unwrap();
+    let result: String = fetcher.fetch(&server).await.unwrap();
+    assert_eq!(result, "Bearer provided");
+
+    // unless the provider has none
+    let fetcher = Fetcher::new(
+        FetcherOptions::new()
+            .credentials(Credentials::Bearer("static".into()))
+            .token_provider(()),
+    )
+    .await
+    .unwrap();
+    let result: String = fetcher.fetch(&server).await.unwrap();
+    assert_eq!(result, "Bearer static");
+}
+
+#[rstest]
+#[case::bearer(Credentials::Bearer("token".into()), "Bearer token")]
+#[case::basic(
+    Credentials::Basic("user".into(), Some("pass".into())),
+    "Basic dXNlcjpwYXNz"
+)]
+#[tokio::test]
+async fn test_proxy(#[case] credentials: Credentials, #[case] expected: &str) {
+    let proxy = start_mock_server(|req| {
+        let authorization = req
+            .headers()
+            .get(hyper::header::PROXY_AUTHORIZATION)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        hyper::Response::builder()
+            .status(StatusCode::OK)
+            .body(format!("{} {authorization}", req.uri()))
+            .unwrap()
+    })
+    .await;
+    let direct = start_mock_server(|_req| {
+        hyper::Response::builder()
+            .status(StatusCode::OK)
+            .body("direct".to_string())
+            .unwrap()
+    })
+    .await;
+
+    let fetcher = Fetcher::new(
+        FetcherOptions::new().proxy(
+            ProxyOptions::new(Url::parse(&proxy).unwrap())
+                .no_proxy(["127.0.0.1"])
+                .credentials(credentials),
+        ),
+    )
+    .await
+    .unwrap();
+
+    // the proxy receives the request, in absolute form
+    let result: String = fetcher
+        .fetch("http://csaf.example.invalid/advisory.json")
+        .await
+        .unwrap();
+    assert_eq!(
+        result,
+        format!("http://csaf.example.invalid/advisory.json {expected}")
+    );
+
+    // excluded from the proxy
+    let result: String = fetcher.fetch(&direct).await.unwrap();
+    assert_eq!(result, "direct");
+}
+
+#[rstest]
+#[case::permissive(false)]
+#[case::strict(true)]
+#[tokio::test]
+async fn test_cross_origin_redirect(#[case] strict: bool) {
+    let target = start_mock_server(|_req| {
+        hyper::Response::builder()
+            .status(StatusCode::OK)
+            .body("Redirected".to_string())
+            .unwrap()
+    })
+    .await;
+
+    let location = format!("{target}/advisory.json");
+    let server = start_mock_server(move |_req| {
+        hyper::Response::builder()
+            .status(StatusCode::FOUND)
+            .header(hyper::header::LOCATION, &location)
+            .body(String::new())
+            .unwrap()
+    })
+    .await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new().strict_redirects(strict))
+        .await
+        .unwrap();
+
+    let result: Result<String, _> = fetcher.fetch(format!("{server}/advisory.json")).await;
+    match (strict, result) {
+        (false, Ok(result)) => assert_eq!(result, "Redirected"),
+        (true, Err(Error::CrossOriginRedirect(err))) => {
+            assert_eq!(err.to.as_str(), format!("{target}/advisory.json"));
+            assert_eq!(err.chain.len(), 1);
+        }
+        (_, other) => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_service_unavailable_retry_after() {
+    let attempt_count = Arc::new(AtomicUsize::new(0));
+    let attempt_count_clone = attempt_count.clone();
+
+    let server = start_mock_server(move |_req| {
+        let count = attempt_count_clone.fetch_add(1, Ordering::SeqCst);
+
+        // First request returns 503, asking to wait
+        if count == 0 {
+            hyper::Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .header("Retry-After", "2")
+                .body("Service unavailable".to_string())
+                .unwrap()
+        } else {
+            hyper::Response::builder()
+                .status(StatusCode::OK)
+                .body("Success after retry".to_string())
+                .unwrap()
+        }
+    })
+    .await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new().retries(3))
+        .await
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let result: String = fetcher.fetch(&server).await.unwrap();
+    let elapsed = start.elapsed();
+
+    assert_eq!(result, "Success after retry");
+    assert_eq!(attempt_count.load(Ordering::SeqCst), 2);
+    // the default backoff would only wait one second
+    assert!(
+        elapsed >= Duration::from_secs(2),
+        "Expected at least 2s wait, got {elapsed:?}"
+    );
+}
+
+#[rstest]
+#[case::server_error(StatusCode::INTERNAL_SERVER_ERROR, true)]
+#[case::not_found(StatusCode::NOT_FOUND, false)]
+#[tokio::test]
+async fn test_mirror_failover(#[case] primary_status: StatusCode, #[case] expect_failover: bool) {
+    let mirror_count = Arc::new(AtomicUsize::new(0));
+    let mirror_count_clone = mirror_count.clone();
+
+    let primary = start_mock_server(move |_req| {
+        hyper::Response::builder()
+            .status(primary_status)
+            .body("Primary".to_string())
+            .unwrap()
+    })
+    .await;
+    let mirror = start_mock_server(move |req| {
+        mirror_count_clone.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(req.uri().path(), "/dir/file.json");
+        hyper::Response::builder()
+            .status(StatusCode::OK)
+            .body("Mirror".to_string())
+            .unwrap()
+    })
+    .await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new().retries(0))
+        .await
+        .unwrap()
+        .with_mirrors([Url::parse(&primary).unwrap(), Url::parse(&mirror).unwrap()]);
+
+    let result = fetcher
+        .fetch::<Option<String>>(format!("{primary}/dir/file.json"))
+        .await
+        .unwrap();
+
+    if expect_failover {
+        assert_eq!(result.as_deref(), Some("Mirror"));
+        assert_eq!(mirror_count.load(Ordering::SeqCst), 1);
+    } else {
+        assert_eq!(result, None);
+        assert_eq!(mirror_count.load(Ordering::SeqCst), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limit_shared_between_clones() {
+    let server = start_mock_server(|_req| {
+        hyper::Response::builder()
+            .status(StatusCode::OK)
+            .body("Hello, World!".to_string())
+            .unwrap()
+    })
+    .await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new())
+        .await
+        .unwrap()
+        .with_rate_limit(NonZeroU32::new(2).unwrap());
+
+    let start = Instant::now();
+    // a burst of two, followed by one request every 500ms
+    futures_util::future::try_join_all((0..5).map(|_| {
+        let fetcher = fetcher.clone();
+        let server = server.clone();
+        async move { fetcher.fetch::<String>(&server).await }
+    }))
+    .await
+    .unwrap();
+
+    assert!(start.elapsed() >= Duration::from_millis(1400));
+}
+
+#[tokio::test]
+async fn test_max_requests_shared_between_clones() {
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use std::convert::Infallible;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // the current and the maximum number of requests in flight
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+    let (current, max) = (in_flight.clone(), max_in_flight.clone());
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (current, max) = (current.clone(), max.clone());
+            tokio::spawn(async move {
+                let service = service_fn(move |_req| {
+                    let (current, max) = (current.clone(), max.clone());
+                    async move {
+                        max.fetch_max(current.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        current.fetch_sub(1, Ordering::SeqCst);
+                        Ok::<_, Infallible>(hyper::Response::new("Hello, World!".to_string()))
+                    }
+                });
+
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+
+    let server = format!("http://{addr}");
+    let fetcher = Fetcher::new(FetcherOptions::new())
+        .await
+        .unwrap()
+        .with_max_requests(NonZeroUsize::new(2).unwrap());
+
+    futures_util::future::try_join_all((0..6).map(|_| {
+        let fetcher = fetcher.clone();
+        let server = server.clone();
+        async move { fetcher.fetch::<String>(&server).await }
+    }))
+    .await
+    .unwrap();
+
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[derive(Debug, Default)]
+struct CountingMetrics {
+    requests: AtomicUsize,
+    retries: AtomicUsize,
+}
+
+impl Metrics for CountingMetrics {
+    fn request_started(&self, _url: &Url) {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn retry_attempted(&self, _url: &Url, _delay: Duration) {
+        self.retries.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let attempt_count = Arc::new(AtomicUsize::new(0));
+    let attempt_count_clone = attempt_count.clone();
+
+    let server = start_mock_server(move |_req| {
+        let status = match attempt_count_clone.fetch_add(1, Ordering::SeqCst) {
+            0 => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::OK,
+        };
+        hyper::Response::builder()
+            .status(status)
+            .body(String::new())
+            .unwrap()
+    })
+    .await;
+
+    let metrics = Arc::new(CountingMetrics::default());
+    let fetcher = Fetcher::new(FetcherOptions::new().retries(2))
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone());
+
+    fetcher.fetch::<String>(&server).await.unwrap();
+
+    assert_eq!(metrics.requests.load(Ordering::SeqCst), 2);
+    assert_eq!(metrics.retries.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_client_certificate() {
+    let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/tls");
+
+    Fetcher::new(
+        FetcherOptions::new().client_certificate(ClientCertificateOptions::new(
+            data.join("client.crt"),
+            data.join("client.key"),
+        )),
+    )
+    .await
+    .unwrap();
+
+    // a key not belonging to the certificate must be rejected
+    let err = Fetcher::new(FetcherOptions::new().client_certificate(
+        ClientCertificateOptions::new(data.join("client.crt"), data.join("other.key")),
+    ))
+    .await
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("does the key match it?"),
+        "unexpected error: {err:#}"
+    );
+}
@@ -1,8 +1,8 @@
 use crate::{
     cmd::{DiscoverArguments, FilterArguments},
-    common::filter,
+    common::{Discovery, filter},
 };
-use csaf_walker::{discover::DiscoveredAdvisory, source::new_source, walker::Walker};
+use csaf_walker::{discover::DiscoveredAdvisory, visitors::filter::FilterConfig, walker::Walker};
 use std::convert::Infallible;
 use walker_common::{
     cli::{CommandDefaults, client::ClientArguments},
@@ -30,17 +30,24 @@ impl CommandDefaults for Discover {
 
 impl Discover {
     pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
-        Walker::new(new_source(self.discover, self.client).await?)
-            .with_progress(progress.clone())
-            .walk(filter(
-                self.filter,
-                async |discovered: DiscoveredAdvisory| {
-                    progress.println(&format!("{}", discovered.url));
-
-                    Ok::<_, Infallible>(())
-                },
-            ))
-            .await?;
+        let discovery = Discovery::from(self.discover);
+        let filter_config = FilterConfig::from(self.filter);
+
+        for source in discovery.sources(self.client).await? {
+            let walker = Walker::new(source).with_progress(progress.clone());
+
+            discovery
+                .walker(walker)
+                .walk(filter(
+                    filter_config.clone(),
+                    async |discovered: DiscoveredAdvisory| {
+                        progress.println(&format!("{}", discovered.url));
+
+                        Ok::<_, Infallible>(())
+                    },
+                ))
+                .await?;
+        }
 
         Ok(())
     }
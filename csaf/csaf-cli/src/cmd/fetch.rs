@@ -1,11 +1,10 @@
 use crate::{
-    cmd::{DiscoverArguments, FilterArguments, SkipArguments},
-    common::walk_visitor,
+    cmd::{ContentFilterArguments, DiscoverArguments, FilterArguments, SkipArguments},
+    common::{Discovery, walk_visitor},
 };
 use colored_json::write_colored_json;
 use csaf_walker::{
-    discover::DiscoverConfig, retrieve::RetrievingVisitor, validation::ValidatedAdvisory,
-    validation::ValidationVisitor,
+    retrieve::RetrievingVisitor, validation::ValidatedAdvisory, validation::ValidationVisitor,
 };
 use jsonpath_rust::JsonPath;
 use serde_json::Value;
@@ -32,6 +31,9 @@ pub struct Fetch {
     #[command(flatten)]
     filter: FilterArguments,
 
+    #[command(flatten)]
+    content_filter: ContentFilterArguments,
+
     #[command(flatten)]
     validation: ValidationArguments,
 
@@ -85,10 +87,10 @@ fn show(output: &str, pretty: bool, doc: ValidatedAdvisory) -> anyhow::Result<()
 }
 
 impl Fetch {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
 
-        let since = Since::new(
+        let mut since = Since::new(
             self.skip.since,
             self.skip.since_file,
             self.skip
@@ -103,18 +105,25 @@ impl Fetch {
             Ok::<_, anyhow::Error>(())
         };
 
-        walk_visitor(
+        let result = walk_visitor(
             progress,
             self.client,
-            DiscoverConfig::from(self.discover).with_since(since.since),
+            Discovery::from(self.discover).with_since(since.earliest()),
             self.filter,
             self.runner,
+            Some(&mut since),
             async |source| {
-                let validation = ValidationVisitor::new(show).with_options(options);
+                let validation =
+                    ValidationVisitor::new(self.content_filter.wrap(show)).with_options(options);
                 Ok(RetrievingVisitor::new(source.clone(), validation))
             },
         )
-        .await?;
+        .await;
+
+        if let Err(err) = result {
+            since.store_failed()?;
+            return Err(err);
+        }
 
         since.store()?;
 
@@ -62,14 +62,7 @@ impl Metadata {
     }
 
     async fn default(self) -> anyhow::Result<()> {
-        let source = new_source(
-            DiscoverConfig {
-                since: None,
-                source: self.source,
-            },
-            self.client,
-        )
-        .await?;
+        let source = new_source(DiscoverConfig::from(self.source.as_str()), self.client).await?;
 
         let metadata = source.load_metadata().await?;
         Self::show_metadata(&metadata)?;
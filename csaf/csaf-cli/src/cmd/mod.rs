@@ -1,7 +1,19 @@
-use anyhow::Context;
-use csaf_walker::visitors::{filter::FilterConfig, store::StoreVisitor};
+use anyhow::{Context, bail};
+use csaf_walker::{
+    model::{metadata::TlpLabel, tracking::TrackingStatus},
+    source::HttpOptions,
+    verification::check::{Check, CsafProfile, init_profile_verifying_visitor},
+    visitors::{
+        diff::DiffReport,
+        errors::{ErrorReport, ErrorReportVisitor},
+        filter::FilterConfig,
+        status::StatusFilteringVisitor,
+        store::StoreVisitor,
+        transform::{Transform, TransformVisitor},
+    },
+};
 use flexible_time::timestamp::StartTimestamp;
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
 use walker_common::cli::parser::parse_allow_client_errors;
 
 pub mod discover;
@@ -14,14 +26,90 @@ pub mod scan;
 pub mod scoop;
 pub mod send;
 pub mod sync;
+pub mod verify;
 
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Discovery")]
 pub struct DiscoverArguments {
     /// Source to scan from.
     ///
-    /// CSAF trusted provider base domain (e.g. `redhat.com`), the full URL to the provider metadata file, or a local `file:` source.
+    /// CSAF trusted provider base domain (e.g. `redhat.com`), the full URL to the provider metadata file, or a local source.
+    ///
+    /// A local source is either a `file:` URL, or a path starting with `/`, `./`, or `../`. It can point to the output of
+    /// `sync` or `download`, reading the stored advisories without fetching them again.
+    ///
+    /// Use `@path` to read a list of remote sources from a file, or `-` to read them from stdin. Sources are
+    /// separated by newlines, blank lines and lines starting with `#` are ignored. Each source is walked on its own,
+    /// one after the other.
     pub source: String,
+
+    /// Treat a local source as a flat directory of advisories, without provider metadata.
+    #[arg(long)]
+    pub rootless: bool,
+
+    /// Treat a remote source as a CSAF aggregator, walking all listed providers and publishers.
+    ///
+    /// A domain is looked up using the `/.well-known/csaf-aggregator/aggregator.json` location.
+    #[arg(long, conflicts_with = "rootless")]
+    pub aggregator: bool,
+
+    /// Fail if the provider metadata is invalid, instead of only logging a warning.
+    #[arg(long)]
+    pub strict_metadata: bool,
+
+    /// Process the discovered advisories ordered by their URL, for reproducible logs and reports.
+    #[arg(long)]
+    pub sorted: bool,
+
+    /// Walk one distribution at a time, visiting its advisories as soon as its index is loaded. Only used with multiple workers.
+    #[arg(long)]
+    pub per_distribution: bool,
+
+    /// Only walk ROLIE feeds with one of these TLP labels. Directory based distributions have no TLP label, and are always walked.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub tlp: Vec<Tlp>,
+
+    /// Fail retrieving advisories which aren't served as JSON (or gzip), instead of only logging a warning.
+    #[arg(long)]
+    pub strict_content_type: bool,
+
+    /// Maximum number of requests in flight against a remote source, shared by all workers. Unlimited if not set.
+    ///
+    /// Each advisory takes up to three requests (document, signature, and digests), so `n` workers may have up to
+    /// `3 * n` requests in flight. This limits them to `min(max_requests, 3 * n)`.
+    #[arg(long)]
+    pub max_requests: Option<NonZeroUsize>,
+}
+
+impl DiscoverArguments {
+    /// The options of remote sources.
+    pub fn http_options(&self) -> HttpOptions {
+        HttpOptions::new()
+            .strict_content_type(self.strict_content_type)
+            .max_requests(self.max_requests)
+    }
+}
+
+/// The TLP label of a ROLIE feed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Tlp {
+    Unlabeled,
+    White,
+    Green,
+    Amber,
+    Red,
+}
+
+impl From<Tlp> for TlpLabel {
+    fn from(value: Tlp) -> Self {
+        match value {
+            Tlp::Unlabeled => Self::Unlabeled,
+            Tlp::White => Self::White,
+            Tlp::Green => Self::Green,
+            Tlp::Amber => Self::Amber,
+            Tlp::Red => Self::Red,
+        }
+    }
 }
 
 #[derive(Debug, clap::Parser)]
@@ -49,6 +137,102 @@ impl From<FilterArguments> for FilterConfig {
     }
 }
 
+#[derive(Debug, clap::Parser)]
+#[command(next_help_heading = "Filters")]
+pub struct ContentFilterArguments {
+    /// Only process documents with this tracking status (e.g. `final`), evaluated after retrieval
+    #[arg(long)]
+    pub only_status: Vec<TrackingStatus>,
+}
+
+impl ContentFilterArguments {
+    /// Wrap a visitor, applying the content filters.
+    pub fn wrap<V>(self, visitor: V) -> StatusFilteringVisitor<V> {
+        StatusFilteringVisitor::new(visitor).only_status(self.only_status)
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+#[command(next_help_heading = "Transform")]
+pub struct TransformArguments {
+    /// A JSON patch (RFC 6902), applied to each document after validation. Transformed documents lose their signature and digests.
+    #[arg(long, conflicts_with = "transform_file")]
+    pub transform: Option<String>,
+
+    /// A file containing a JSON patch (RFC 6902), applied like `--transform`.
+    #[arg(long)]
+    pub transform_file: Option<PathBuf>,
+}
+
+impl TransformArguments {
+    /// Wrap a visitor, applying the transformation (if requested).
+    pub fn wrap<V>(self, visitor: V) -> anyhow::Result<TransformVisitor<V>> {
+        let transform = match (self.transform, self.transform_file) {
+            (Some(transform), _) => {
+                Some(Transform::from_json(transform.as_bytes()).context("Parse transform")?)
+            }
+            (None, Some(file)) => Some(
+                Transform::from_json(
+                    &std::fs::read(&file)
+                        .with_context(|| format!("Read transform file: {}", file.display()))?,
+                )
+                .with_context(|| format!("Parse transform file: {}", file.display()))?,
+            ),
+            (None, None) => None,
+        };
+
+        Ok(TransformVisitor::new(visitor, transform))
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+#[command(next_help_heading = "Error report")]
+pub struct ErrorReportArguments {
+    /// Write every failed document (URL, status, and reason) to this file, as newline delimited JSON.
+    #[arg(long)]
+    pub error_report: Option<PathBuf>,
+}
+
+impl ErrorReportArguments {
+    /// Wrap a visitor, recording failures to the error report (if requested).
+    pub fn wrap<V>(self, visitor: V) -> anyhow::Result<ErrorReportVisitor<V>> {
+        let report = self
+            .error_report
+            .map(|path| {
+                ErrorReport::create(&path)
+                    .with_context(|| format!("Create error report: {}", path.display()))
+            })
+            .transpose()?;
+
+        Ok(ErrorReportVisitor::new(visitor, report))
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+#[command(next_help_heading = "Diff report")]
+pub struct DiffArguments {
+    /// Write the added, modified, and (with `--prune`) removed advisories, compared to the previously stored ones, to this file (JSON).
+    #[arg(long)]
+    pub diff: Option<PathBuf>,
+}
+
+impl DiffArguments {
+    /// Create a report, if requested.
+    pub fn report(&self) -> Option<DiffReport> {
+        self.diff.as_ref().map(|_| DiffReport::new())
+    }
+
+    /// Write the report to the file, logging its summary.
+    pub fn write(self, report: Option<DiffReport>) -> anyhow::Result<()> {
+        if let (Some(path), Some(report)) = (self.diff, report) {
+            log::info!("Changes: {}", report.summary());
+            report.write(&path)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Storage")]
 pub struct StoreArguments {
@@ -60,6 +244,40 @@ pub struct StoreArguments {
     #[arg(long)]
     pub no_timestamps: bool,
 
+    /// Always fail on file names only differing by case, not only on case-insensitive filesystems.
+    #[arg(long)]
+    pub check_case_collisions: bool,
+
+    /// Request documents conditionally, using the ETag and timestamp of previously stored files.
+    #[arg(long)]
+    pub conditional: bool,
+
+    /// Fetch existing advisories again if their SHA-256 digest changed, even if their modification timestamp didn't.
+    #[arg(long)]
+    pub compare_digests: bool,
+
+    /// Remove files of previously stored advisories which are no longer part of the index.
+    ///
+    /// Advisories excluded by filters are considered stale as well. Cannot be combined with
+    /// `--since` or `--since-file`.
+    #[arg(long)]
+    pub prune: bool,
+
+    /// Write an index of all stored advisories to `metadata/index.json`, once the run completed.
+    #[arg(long)]
+    pub write_index: bool,
+
+    /// Store advisories identical to an already stored one as hard links.
+    #[arg(long)]
+    pub dedup: bool,
+
+    /// Store distributions in a separate directory per TLP label, e.g. `tlp-white`.
+    ///
+    /// Each directory gets its own provider metadata. Cannot be combined with `--conditional`
+    /// or `--prune`.
+    #[arg(long, conflicts_with_all = ["conditional", "prune"])]
+    pub partition_tlp: bool,
+
     /// Output path, defaults to the local directory.
     #[arg(short, long)]
     pub data: Option<PathBuf>,
@@ -87,6 +305,11 @@ impl TryFrom<StoreArguments> for StoreVisitor {
 
         let result = Self::new(base)
             .no_timestamps(value.no_timestamps)
+            .check_case_collisions(value.check_case_collisions)
+            .prune(value.prune)
+            .enable_indexer(value.write_index)
+            .dedup(value.dedup)
+            .partition_tlp(value.partition_tlp)
             .allow_client_errors(allow_client_errors);
 
         let result = result.no_xattrs(value.no_xattrs);
@@ -113,4 +336,134 @@ pub struct SkipArguments {
 
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Checks")]
-pub struct VerificationArguments {}
+pub struct VerificationArguments {
+    /// Run the checks of a document concurrently.
+    #[arg(long)]
+    pub parallel_checks: bool,
+
+    /// The profile to check documents against (base, security_incident_response,
+    /// informational_advisory, security_advisory, vex).
+    ///
+    /// By default, the profile of each document is detected from its category.
+    #[arg(long)]
+    pub profile: Option<CsafProfile>,
+
+    /// Skip a check, by name. May be repeated.
+    #[arg(long, conflicts_with = "only_check")]
+    pub skip_check: Vec<String>,
+
+    /// Only run a check, by name. May be repeated.
+    #[arg(long)]
+    pub only_check: Vec<String>,
+}
+
+impl VerificationArguments {
+    /// Create the checks of the selected profile, applying the skipped and selected checks.
+    pub fn checks(&self) -> anyhow::Result<Vec<(&'static str, Box<dyn Check>)>> {
+        let checks = init_profile_verifying_visitor(self.profile);
+
+        let unknown = self
+            .skip_check
+            .iter()
+            .chain(&self.only_check)
+            .filter(|name| !checks.iter().any(|(check, _)| check == name))
+            .collect::<Vec<_>>();
+
+        if !unknown.is_empty() {
+            let available = checks
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!(
+                "Unknown check(s): {}. Available checks: {available}",
+                unknown
+                    .iter()
+                    .map(|name| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        Ok(checks
+            .into_iter()
+            .filter(|(name, _)| {
+                !self.skip_check.iter().any(|skip| skip == name)
+                    && (self.only_check.is_empty()
+                        || self.only_check.iter().any(|only| only == name))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Parser;
+
+    fn names(args: &[&str]) -> anyhow::Result<Vec<&'static str>> {
+        let args = VerificationArguments::try_parse_from(
+            std::iter::once("verify").chain(args.iter().copied()),
+        )?;
+        Ok(args.checks()?.into_iter().map(|(name, _)| name).collect())
+    }
+
+    #[test]
+    fn skip_check() -> anyhow::Result<()> {
+        let all = names(&[])?;
+        let skipped = names(&[
+            "--skip-check",
+            "check_csaf_vex",
+            "--skip-check",
+            "check_history",
+        ])?;
+
+        assert_eq!(skipped.len(), all.len() - 2);
+        assert!(!skipped.contains(&"check_csaf_vex"));
+        assert!(!skipped.contains(&"check_history"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_check() -> anyhow::Result<()> {
+        assert_eq!(
+            names(&["--only-check", "check_csaf_vex"])?,
+            vec!["check_csaf_vex"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_check() {
+        let err = names(&["--skip-check", "check_foo"]).expect_err("must fail");
+        assert!(err.to_string().contains("check_foo"));
+        assert!(err.to_string().contains("check_csaf_vex"));
+    }
+
+    #[test]
+    fn max_requests() -> anyhow::Result<()> {
+        let args = DiscoverArguments::try_parse_from(["discover", "example.com"])?;
+        assert_eq!(args.http_options().max_requests, None);
+
+        let args =
+            DiscoverArguments::try_parse_from(["discover", "example.com", "--max-requests", "4"])?;
+        assert_eq!(args.http_options().max_requests, NonZeroUsize::new(4));
+
+        assert!(
+            DiscoverArguments::try_parse_from(["discover", "example.com", "--max-requests", "0"])
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tlp_labels() -> anyhow::Result<()> {
+        let args =
+            DiscoverArguments::try_parse_from(["discover", "example.com", "--tlp", "white,green"])?;
+        assert_eq!(args.tlp, [Tlp::White, Tlp::Green]);
+        assert_eq!(TlpLabel::from(args.tlp[0]), TlpLabel::White);
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    cmd::{DiscoverArguments, FilterArguments, VerificationArguments},
+    cmd::{ContentFilterArguments, DiscoverArguments, FilterArguments, VerificationArguments},
     common::walk_visitor,
 };
 use csaf_walker::{
@@ -8,10 +8,7 @@ use csaf_walker::{
     retrieve::RetrievingVisitor,
     source::DispatchSource,
     validation::{ValidatedAdvisory, ValidationError, ValidationVisitor},
-    verification::{
-        VerificationError, VerifiedAdvisory, VerifyingVisitor,
-        check::{CheckError, init_verifying_visitor},
-    },
+    verification::{VerificationError, VerifiedAdvisory, VerifyingVisitor, check::CheckError},
     visitors::duplicates::DetectDuplicatesVisitor,
 };
 use reqwest::Url;
@@ -50,6 +47,9 @@ pub struct Report {
     #[command(flatten)]
     filter: FilterArguments,
 
+    #[command(flatten)]
+    content_filter: ContentFilterArguments,
+
     #[command(flatten)]
     validation: ValidationArguments,
 
@@ -83,7 +83,7 @@ pub struct RenderOptions {
 }
 
 impl Report {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
 
         let total = Arc::new(AtomicUsize::default());
@@ -142,11 +142,13 @@ impl Report {
 
             // content checks
 
-            let visitor = VerifyingVisitor::with_checks(visitor, init_verifying_visitor());
+            let visitor = VerifyingVisitor::with_checks(visitor, self.verification.checks()?)
+                .parallel(self.verification.parallel_checks);
 
             // validation (can we work with this document?)
 
-            let visitor = ValidationVisitor::new(visitor).with_options(options);
+            let visitor =
+                ValidationVisitor::new(self.content_filter.wrap(visitor)).with_options(options);
 
             walk_visitor(
                 progress,
@@ -154,6 +156,7 @@ impl Report {
                 self.discover,
                 self.filter,
                 self.runner,
+                None,
                 async move |source| {
                     let visitor = RetrievingVisitor::new(source.clone(), visitor);
 
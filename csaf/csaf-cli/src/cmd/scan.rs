@@ -1,8 +1,7 @@
 use crate::{
-    cmd::{DiscoverArguments, FilterArguments},
+    cmd::{ContentFilterArguments, DiscoverArguments, FilterArguments},
     common::walk_standard,
 };
-use csaf::Csaf;
 use csaf_walker::{
     source::DispatchSource,
     validation::{ValidatedAdvisory, ValidationError},
@@ -30,6 +29,9 @@ pub struct Scan {
     #[command(flatten)]
     filter: FilterArguments,
 
+    #[command(flatten)]
+    content_filter: ContentFilterArguments,
+
     #[command(flatten)]
     validation: ValidationArguments,
 }
@@ -45,34 +47,37 @@ impl Scan {
             self.discover,
             self.filter,
             self.validation,
-            async |advisory: Result<ValidatedAdvisory, ValidationError<DispatchSource>>| {
-                match advisory {
-                    Ok(adv) => {
-                        progress.println(&format!("Advisory: {}", adv.url));
-                        log::debug!("  Metadata: {:?}", adv.sha256);
-                        log::debug!("    SHA256: {:?}", adv.sha256);
-                        log::debug!("    SHA512: {:?}", adv.sha512);
-                        match serde_json::from_slice::<Csaf>(&adv.data) {
-                            Ok(csaf) => {
-                                progress.println(&format!(
-                                    "  {} ({}): {}",
-                                    csaf.document.tracking.id,
-                                    csaf.document.tracking.initial_release_date,
-                                    csaf.document.title
-                                ));
-                            }
-                            Err(err) => {
-                                eprintln!("  Format error: {err}");
+            self.content_filter.wrap(
+                async |advisory: Result<ValidatedAdvisory, ValidationError<DispatchSource>>| {
+                    match advisory {
+                        Ok(adv) => {
+                            progress.println(&format!("Advisory: {}", adv.url));
+                            log::debug!("  Metadata: {:?}", adv.sha256);
+                            log::debug!("    SHA256: {:?}", adv.sha256);
+                            log::debug!("    SHA384: {:?}", adv.sha384);
+                            log::debug!("    SHA512: {:?}", adv.sha512);
+                            match adv.parse() {
+                                Ok(csaf) => {
+                                    progress.println(&format!(
+                                        "  {} ({}): {}",
+                                        csaf.document.tracking.id,
+                                        csaf.document.tracking.initial_release_date,
+                                        csaf.document.title
+                                    ));
+                                }
+                                Err(err) => {
+                                    eprintln!("  Format error: {err}");
+                                }
                             }
                         }
+                        Err(err) => {
+                            eprintln!("Advisory(ERR): {err}");
+                        }
                     }
-                    Err(err) => {
-                        eprintln!("Advisory(ERR): {err}");
-                    }
-                }
 
-                Ok::<_, anyhow::Error>(())
-            },
+                    Ok::<_, anyhow::Error>(())
+                },
+            ),
         )
         .await?;
 
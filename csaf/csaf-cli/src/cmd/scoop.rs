@@ -5,7 +5,7 @@ use walker_common::{
     progress::Progress,
     scoop::{ScooperBuilder, Source},
 };
-use walker_extras::visitors::{SendArguments, SendVisitor};
+use walker_extras::visitors::SendArguments;
 
 /// Walk a local directory (or single file) and send the files to a target without any validation.
 #[derive(clap::Args, Debug)]
@@ -46,6 +46,16 @@ impl Scoop {
     pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
         log::debug!("Start processing");
 
+        // files are considered processed once sent, which doesn't work with buffering them
+        if self.send.batch_size.is_some() {
+            anyhow::bail!("Sending in batches is not supported when scooping");
+        }
+
+        // files would be considered processed, without having been sent
+        if self.send.dry_run && (self.delete || self.processed.is_some()) {
+            anyhow::bail!("A dry run cannot be combined with deleting or moving processed files");
+        }
+
         let scooper = ScooperBuilder {
             sources: self
                 .source
@@ -60,7 +70,7 @@ impl Scoop {
         .build()
         .await?;
 
-        let send: SendVisitor = self.send.into_visitor().await?;
+        let send = self.send.into_visitor().await?;
 
         scooper
             .process(progress, async move |name: &str, data: Bytes| {
@@ -1,10 +1,12 @@
 use crate::{
-    cmd::{DiscoverArguments, FilterArguments, SkipArguments},
-    common::walk_visitor,
+    cmd::{
+        ContentFilterArguments, DiscoverArguments, ErrorReportArguments, FilterArguments,
+        SkipArguments, TransformArguments,
+    },
+    common::{Discovery, walk_visitor},
 };
 use csaf_walker::{
-    discover::DiscoverConfig, retrieve::RetrievingVisitor, validation::ValidationVisitor,
-    visitors::skip::SkipFailedVisitor,
+    retrieve::RetrievingVisitor, validation::ValidationVisitor, visitors::skip::SkipFailedVisitor,
 };
 use walker_common::{
     cli::{
@@ -15,7 +17,7 @@ use walker_common::{
     since::Since,
     validate::ValidationOptions,
 };
-use walker_extras::visitors::{SendArguments, SendVisitor};
+use walker_extras::visitors::SendArguments;
 
 /// Walk a source and send validated/retrieved documents to a sink.
 #[derive(clap::Args, Debug)]
@@ -36,6 +38,15 @@ pub struct Send {
     #[command(flatten)]
     filter: FilterArguments,
 
+    #[command(flatten)]
+    content_filter: ContentFilterArguments,
+
+    #[command(flatten)]
+    error_report: ErrorReportArguments,
+
+    #[command(flatten)]
+    transform: TransformArguments,
+
     #[command(flatten)]
     validation: ValidationArguments,
 
@@ -49,11 +60,15 @@ pub struct Send {
 impl CommandDefaults for Send {}
 
 impl Send {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
-        let send: SendVisitor = self.send.into_visitor().await?;
+        // nothing gets sent, so nothing must be recorded as processed
+        let dry_run = self.send.dry_run;
+        let skip_failures = self.skip_failures;
+        let send = self.send.into_visitor().await?;
+        let flush = send.clone();
 
-        let since = Since::new(
+        let mut since = Since::new(
             self.skip.since,
             self.skip.since_file,
             self.skip
@@ -62,27 +77,48 @@ impl Send {
                 .unwrap_or_default(),
         )?;
 
-        walk_visitor(
+        let result = walk_visitor(
             progress,
             self.client,
-            DiscoverConfig::from(self.discover).with_since(since.since),
+            Discovery::from(self.discover).with_since(since.earliest()),
             self.filter,
             self.runner,
+            Some(&mut since),
             async move |source| {
                 Ok({
                     RetrievingVisitor::new(source.clone(), {
-                        ValidationVisitor::new(SkipFailedVisitor {
-                            skip_failures: self.skip_failures,
-                            visitor: send,
-                        })
+                        ValidationVisitor::new(self.error_report.wrap(self.content_filter.wrap(
+                            self.transform.wrap(SkipFailedVisitor {
+                                skip_failures: self.skip_failures,
+                                visitor: send,
+                            })?,
+                        ))?)
                         .with_options(options)
                     })
                 })
             },
         )
-        .await?;
+        .await;
+
+        // send what's left of the last batch, reporting the documents of batches which failed
+        let result = result.and(match flush.flush().await {
+            Err(err) if skip_failures => {
+                log::warn!("Skipping failed documents: {err}");
+                Ok(())
+            }
+            flushed => flushed.map_err(Into::into),
+        });
+
+        if let Err(err) = result {
+            if !dry_run {
+                since.store_failed()?;
+            }
+            return Err(err);
+        }
 
-        since.store()?;
+        if !dry_run {
+            since.store()?;
+        }
 
         Ok(())
     }
@@ -1,18 +1,21 @@
 use crate::{
-    cmd::{DiscoverArguments, FilterArguments, SkipArguments, StoreArguments},
-    common::walk_visitor,
+    cmd::{
+        ContentFilterArguments, DiffArguments, DiscoverArguments, ErrorReportArguments,
+        FilterArguments, SkipArguments, StoreArguments, TransformArguments,
+    },
+    common::{Discovery, walk_visitor},
 };
 use csaf_walker::{
-    discover::DiscoverConfig,
     retrieve::RetrievingVisitor,
     validation::ValidationVisitor,
-    visitors::{skip::SkipExistingVisitor, store::StoreVisitor},
+    visitors::{diff::DiffVisitor, skip::SkipExistingVisitor, store::StoreVisitor},
 };
 use walker_common::{
     cli::{
         CommandDefaults, client::ClientArguments, runner::RunnerArguments,
         validation::ValidationArguments,
     },
+    lock::Lock,
     progress::Progress,
     since::Since,
     validate::ValidationOptions,
@@ -33,6 +36,18 @@ pub struct Sync {
     #[command(flatten)]
     filter: FilterArguments,
 
+    #[command(flatten)]
+    content_filter: ContentFilterArguments,
+
+    #[command(flatten)]
+    error_report: ErrorReportArguments,
+
+    #[command(flatten)]
+    diff: DiffArguments,
+
+    #[command(flatten)]
+    transform: TransformArguments,
+
     #[command(flatten)]
     validation: ValidationArguments,
 
@@ -46,12 +61,22 @@ pub struct Sync {
 impl CommandDefaults for Sync {}
 
 impl Sync {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
+        let conditional = self.store.conditional;
+        let compare_digests = self.store.compare_digests;
+        let prune = self.store.prune;
         let store: StoreVisitor = self.store.try_into()?;
+        let partition_tlp = store.partition_tlp;
         let base = store.base.clone();
+        let _lock = Lock::store(&base)?;
+        let pruner = store.pruner();
+        let indexer = store.indexer();
+        let diff = self.diff.report();
+        let store =
+            DiffVisitor::new(store, base.clone(), diff.clone()).partition_tlp(partition_tlp);
 
-        let since = Since::new(
+        let mut since = Since::new(
             self.skip.since,
             self.skip.since_file,
             self.skip
@@ -60,28 +85,63 @@ impl Sync {
                 .unwrap_or_default(),
         )?;
 
-        walk_visitor(
+        if prune && (since.since.is_some() || since.since_file.is_some()) {
+            anyhow::bail!("Pruning cannot be combined with skipping advisories by time");
+        }
+
+        let skip_since = since.earliest();
+        let tracker = pruner.clone();
+        let result = walk_visitor(
             progress,
             self.client,
-            DiscoverConfig::from(self.discover).with_since(since.since),
+            Discovery::from(self.discover)
+                .with_since(since.earliest())
+                .with_conditional(conditional.then(|| base.clone())),
             self.filter,
             self.runner,
+            Some(&mut since),
             async move |source| {
+                let digests = compare_digests.then(|| source.clone());
                 let visitor = {
                     RetrievingVisitor::new(
                         source,
-                        ValidationVisitor::new(store).with_options(options),
+                        ValidationVisitor::new(
+                            self.error_report
+                                .wrap(self.content_filter.wrap(self.transform.wrap(store)?))?,
+                        )
+                        .with_options(options),
                     )
                 };
 
-                Ok(SkipExistingVisitor {
-                    visitor,
-                    output: base,
-                    since: since.since,
-                })
+                Ok(tracker.track(
+                    base.clone(),
+                    SkipExistingVisitor {
+                        visitor,
+                        output: base,
+                        since: skip_since,
+                        partition_tlp,
+                    }
+                    .with_digests(digests),
+                ))
             },
         )
-        .await?;
+        .await;
+
+        if let Err(err) = result {
+            since.store_failed()?;
+            return Err(err);
+        }
+
+        if prune {
+            let removed = pruner.prune()?;
+            log::info!("Removed {} stale files", removed.len());
+            if let Some(diff) = &diff {
+                diff.removed(&removed);
+            }
+        }
+
+        indexer.write().await?;
+        self.diff.write(diff)?;
 
         since.store()?;
 
@@ -0,0 +1,205 @@
+use crate::{
+    cmd::{ContentFilterArguments, DiscoverArguments, FilterArguments, VerificationArguments},
+    common::walk_visitor,
+};
+use anyhow::bail;
+use csaf_walker::{
+    retrieve::RetrievingVisitor,
+    source::DispatchSource,
+    validation::{ValidatedAdvisory, ValidationError, ValidationVisitor},
+    verification::{
+        VerificationError, VerifiedAdvisory, VerifyingVisitor,
+        check::{CheckError, Severity},
+    },
+};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use walker_common::{
+    cli::{
+        CommandDefaults, client::ClientArguments, runner::RunnerArguments,
+        validation::ValidationArguments,
+    },
+    progress::Progress,
+    validate::ValidationOptions,
+};
+
+/// Verify the content of advisories, failing if any check reports an error.
+#[derive(clap::Args, Debug)]
+pub struct Verify {
+    #[command(flatten)]
+    client: ClientArguments,
+
+    #[command(flatten)]
+    runner: RunnerArguments,
+
+    #[command(flatten)]
+    discover: DiscoverArguments,
+
+    #[command(flatten)]
+    filter: FilterArguments,
+
+    #[command(flatten)]
+    content_filter: ContentFilterArguments,
+
+    #[command(flatten)]
+    validation: ValidationArguments,
+
+    #[command(flatten)]
+    verification: VerificationArguments,
+
+    /// The number of errors which are tolerated before the command fails.
+    ///
+    /// Every error reported by a check counts, as well as every document which could not be
+    /// retrieved, validated, or parsed.
+    #[arg(long, default_value_t = 0)]
+    max_errors: usize,
+
+    /// The minimum severity of a finding to count as an error (info, warning, error).
+    ///
+    /// Findings of a lower severity are still reported, but don't fail the command.
+    #[arg(long, default_value_t = Severity::Error)]
+    fail_on: Severity,
+}
+
+impl CommandDefaults for Verify {
+    fn progress(&self) -> bool {
+        false
+    }
+}
+
+impl Verify {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
+        let options: ValidationOptions = self.validation.into();
+
+        let documents = Arc::new(AtomicUsize::default());
+        let errors = Arc::new(AtomicUsize::default());
+        let ignored = Arc::new(AtomicUsize::default());
+
+        {
+            let documents = documents.clone();
+            let errors = errors.clone();
+            let ignored = ignored.clone();
+            let output = progress.clone();
+            let fail_on = self.fail_on;
+
+            let visitor = move |advisory: Result<
+                VerifiedAdvisory<ValidatedAdvisory, &'static str>,
+                VerificationError<ValidationError<DispatchSource>, ValidatedAdvisory>,
+            >| {
+                documents.fetch_add(1, Ordering::Relaxed);
+
+                match advisory {
+                    Ok(adv) => {
+                        let (failing, other) =
+                            count_findings(adv.failures.values().flatten(), fail_on);
+                        if failing > 0 || other > 0 {
+                            output.println(&format!(
+                                "{}: {failing} error(s), {other} ignored finding(s)",
+                                adv.url,
+                            ));
+                            for (check, failures) in &adv.failures {
+                                for failure in failures {
+                                    output.println(&format!(
+                                        "  {check} ({}): {failure}",
+                                        failure.severity
+                                    ));
+                                }
+                            }
+                            errors.fetch_add(failing, Ordering::Relaxed);
+                            ignored.fetch_add(other, Ordering::Relaxed);
+                        }
+                    }
+                    Err(err) => {
+                        output.println(&format!("Advisory(ERR): {err}"));
+                        errors.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+
+                async { Ok::<_, anyhow::Error>(()) }
+            };
+
+            let visitor = VerifyingVisitor::with_checks(visitor, self.verification.checks()?)
+                .parallel(self.verification.parallel_checks);
+
+            let visitor =
+                ValidationVisitor::new(self.content_filter.wrap(visitor)).with_options(options);
+
+            walk_visitor(
+                progress.clone(),
+                self.client,
+                self.discover,
+                self.filter,
+                self.runner,
+                None,
+                async move |source| Ok(RetrievingVisitor::new(source, visitor)),
+            )
+            .await?;
+        }
+
+        let documents = documents.load(Ordering::Relaxed);
+        let errors = errors.load(Ordering::Relaxed);
+        let ignored = ignored.load(Ordering::Relaxed);
+
+        progress.println(&format!(
+            "Verified {documents} document(s), found {errors} error(s), ignored {ignored} finding(s)"
+        ));
+
+        if errors > self.max_errors {
+            bail!(
+                "Found {errors} error(s), exceeding the maximum of {}",
+                self.max_errors
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Count the findings which fail the command, and the ones which are only reported.
+fn count_findings<'a>(
+    findings: impl IntoIterator<Item = &'a CheckError>,
+    fail_on: Severity,
+) -> (usize, usize) {
+    findings
+        .into_iter()
+        .fold((0, 0), |(failing, other), finding| {
+            match finding.severity >= fail_on {
+                true => (failing + 1, other),
+                false => (failing, other + 1),
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use csaf::Csaf;
+    use csaf_walker::verification::check::{CsafProfile, init_profile_verifying_visitor};
+
+    #[tokio::test]
+    async fn fail_on_splits_warnings_from_errors() {
+        let mut source: serde_json::Value =
+            serde_json::from_str(include_str!("../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        let document = &mut source["document"];
+        document["category"] = "csaf_security_incident_response".into();
+        // missing notes and external references are warnings
+        document["notes"] = serde_json::json!([]);
+        document["references"] = serde_json::json!([]);
+        // a tracking version not matching the revision history is an error
+        document["tracking"]["version"] = "42".into();
+        let csaf: Csaf = serde_json::from_value(source).expect("document must parse");
+
+        let mut findings = vec![];
+        for (_, check) in
+            init_profile_verifying_visitor(Some(CsafProfile::SecurityIncidentResponse))
+        {
+            findings.extend(check.check(&csaf).await.expect("check must run"));
+        }
+
+        assert_eq!(count_findings(&findings, Severity::Error), (1, 2));
+        assert_eq!(count_findings(&findings, Severity::Warning), (3, 0));
+    }
+}
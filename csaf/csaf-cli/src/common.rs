@@ -1,16 +1,22 @@
 use crate::cmd::DiscoverArguments;
 use csaf_walker::{
-    discover::{DiscoverConfig, DiscoveredVisitor},
+    discover::{DiscoverConfig, DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
+    model::metadata::TlpLabel,
     retrieve::RetrievingVisitor,
-    source::{DispatchSource, new_source},
+    source::{DispatchSource, HttpOptions, new_sources_with_options},
     validation::{ValidatedVisitor, ValidationVisitor},
     visitors::filter::{FilterConfig, FilteringVisitor},
-    walker::Walker,
+    walker::{self, Walker},
 };
-use std::future::Future;
+use std::{future::Future, path::PathBuf, time::SystemTime};
+use tokio_util::sync::CancellationToken;
 use walker_common::{
-    cli::{client::ClientArguments, runner::RunnerArguments, validation::ValidationArguments},
+    cli::{
+        client::ClientArguments, runner::RunnerArguments, shutdown::cancel_on_signal,
+        validation::ValidationArguments,
+    },
     progress::Progress,
+    since::Since,
     validate::ValidationOptions,
 };
 
@@ -18,7 +24,7 @@ pub async fn walk_standard<V, P>(
     progress: P,
     client: ClientArguments,
     runner: RunnerArguments,
-    discover: impl Into<DiscoverConfig>,
+    discover: impl Into<Discovery>,
     filter: impl Into<FilterConfig>,
     validation: ValidationArguments,
     visitor: V,
@@ -26,7 +32,7 @@ pub async fn walk_standard<V, P>(
 where
     V: ValidatedVisitor<DispatchSource>,
     V::Error: Send + Sync + 'static,
-    P: Progress,
+    P: Progress + Clone,
 {
     let options: ValidationOptions = validation.into();
 
@@ -36,6 +42,7 @@ where
         discover,
         filter,
         runner,
+        None,
         async move |source| {
             Ok(RetrievingVisitor::new(
                 source.clone(),
@@ -51,6 +58,71 @@ impl From<DiscoverArguments> for DiscoverConfig {
         Self {
             since: None,
             source: value.source,
+            rootless: value.rootless,
+            conditional: None,
+            aggregator: value.aggregator,
+        }
+    }
+}
+
+/// The discovery of a walk, along with the options of remote sources and of the walker.
+pub struct Discovery {
+    pub config: DiscoverConfig,
+    pub http: HttpOptions,
+    /// Fail if the provider metadata is invalid
+    pub strict_metadata: bool,
+    /// Process the discovered advisories ordered by their URL
+    pub sorted: bool,
+    /// Walk one distribution at a time
+    pub per_distribution: bool,
+    /// The TLP labels of the ROLIE feeds to walk, all if empty
+    pub tlp: Vec<TlpLabel>,
+}
+
+impl Discovery {
+    pub fn with_since(mut self, since: impl Into<Option<SystemTime>>) -> Self {
+        self.config = self.config.with_since(since);
+        self
+    }
+
+    pub fn with_conditional(mut self, conditional: impl Into<Option<PathBuf>>) -> Self {
+        self.config = self.config.with_conditional(conditional);
+        self
+    }
+
+    /// Create the sources to walk, one per entry of a list of sources.
+    pub async fn sources(&self, client: ClientArguments) -> anyhow::Result<Vec<DispatchSource>> {
+        new_sources_with_options(self.config.clone(), client, self.http.clone()).await
+    }
+
+    /// Apply the options of the walker.
+    pub fn walker<P: Progress>(
+        &self,
+        walker: Walker<DispatchSource, P>,
+    ) -> Walker<DispatchSource, P> {
+        let walker = walker
+            .with_strict_metadata(self.strict_metadata)
+            .with_sorted(self.sorted)
+            .with_per_distribution(self.per_distribution);
+
+        if self.tlp.is_empty() {
+            return walker;
+        }
+
+        let tlp = self.tlp.clone();
+        walker.with_feed_filter(move |feed| tlp.contains(&feed.tlp_label))
+    }
+}
+
+impl From<DiscoverArguments> for Discovery {
+    fn from(value: DiscoverArguments) -> Self {
+        Self {
+            http: value.http_options(),
+            strict_metadata: value.strict_metadata,
+            sorted: value.sorted,
+            per_distribution: value.per_distribution,
+            tlp: value.tlp.iter().copied().map(Into::into).collect(),
+            config: value.into(),
         }
     }
 }
@@ -69,9 +141,10 @@ where
 pub async fn walk_visitor<F, Fut, V, P>(
     progress: P,
     client: ClientArguments,
-    discover: impl Into<DiscoverConfig>,
+    discover: impl Into<Discovery>,
     filter: impl Into<FilterConfig>,
     runner: RunnerArguments,
+    since: Option<&mut Since>,
     f: F,
 ) -> anyhow::Result<()>
 where
@@ -79,18 +152,26 @@ where
     Fut: Future<Output = anyhow::Result<V>>,
     V: DiscoveredVisitor,
     V::Error: Send + Sync + 'static,
-    P: Progress,
+    P: Progress + Clone,
 {
-    let source = new_source(discover, client).await?;
+    let discover = discover.into();
+    let sources = discover.sources(client).await?;
 
-    walk_source(progress, source, filter, runner, f).await
+    walk_sources(progress, sources, filter, runner, &discover, since, f).await
 }
 
-pub async fn walk_source<F, Fut, V, P>(
+/// Walk the sources, one after the other, using the same visitor.
+///
+/// The visitor is created once, using the first source. The sources of a list are all remote
+/// sources, sharing the same fetcher and options, so that each of them can retrieve the
+/// advisories discovered by any other.
+pub async fn walk_sources<F, Fut, V, P>(
     progress: P,
-    source: DispatchSource,
+    sources: Vec<DispatchSource>,
     filter_config: impl Into<FilterConfig>,
     runner: RunnerArguments,
+    discover: &Discovery,
+    mut since: Option<&mut Since>,
     f: F,
 ) -> anyhow::Result<()>
 where
@@ -98,25 +179,137 @@ where
     Fut: Future<Output = anyhow::Result<V>>,
     V: DiscoveredVisitor,
     V::Error: Send + Sync + 'static,
-    P: Progress,
+    P: Progress + Clone,
 {
-    let visitor = f(source.clone()).await?;
-    let walker = Walker::new(source).with_progress(progress);
+    let Some(first) = sources.first() else {
+        anyhow::bail!("No source to walk");
+    };
+    let visitor = filter(filter_config, f(first.clone()).await?);
+
+    let cancellation = CancellationToken::new();
+    let signals = cancel_on_signal(cancellation.clone());
+
+    let total = sources.len();
+    let mut failed = 0;
 
-    match runner.workers {
-        1 => {
-            walker.walk(filter(filter_config, visitor)).await?;
+    for source in sources {
+        if cancellation.is_cancelled() {
+            signals.abort();
+            anyhow::bail!("Walk was cancelled");
         }
-        n => {
-            walker
-                .walk_parallel(n, filter(filter_config, visitor))
-                .await?;
+
+        let result = walk_source(
+            &progress,
+            source,
+            &visitor,
+            &runner,
+            discover,
+            since.as_deref_mut(),
+            cancellation.clone(),
+        )
+        .await;
+
+        match result {
+            Ok(()) => {}
+            // keep walking the other sources of a list
+            Err(err) if total > 1 && !matches!(err, walker::Error::Cancelled) => {
+                log::warn!("Failed to walk source: {err}");
+                failed += 1;
+            }
+            Err(err) => {
+                signals.abort();
+                return Err(err.into());
+            }
         }
     }
 
+    signals.abort();
+
+    if failed > 0 {
+        anyhow::bail!("{failed} of {total} sources failed");
+    }
+
+    Ok(())
+}
+
+/// Walk a single source.
+async fn walk_source<V, P>(
+    progress: &P,
+    source: DispatchSource,
+    visitor: &V,
+    runner: &RunnerArguments,
+    discover: &Discovery,
+    since: Option<&mut Since>,
+    cancellation: CancellationToken,
+) -> Result<(), walker::Error<V::Error, <DispatchSource as walker_common::source::Source>::Error>>
+where
+    V: DiscoveredVisitor,
+    P: Progress + Clone,
+{
+    let mut walker = discover.walker(
+        Walker::new(source)
+            .with_progress(progress.clone())
+            .with_memory_budget(runner.memory_budget())
+            .with_cancellation(cancellation)
+            .continue_on_error(runner.continue_on_error),
+    );
+
+    // the source only applies the earliest since, apply the one of each distribution
+    if let Some(since) = &since
+        && !since.distributions.is_empty()
+    {
+        let fallback = since.since;
+        let distributions = since.distributions.clone();
+        walker = walker.with_advisory_filter(move |advisory| {
+            match distributions
+                .get(advisory.context.url().as_str())
+                .copied()
+                .or(fallback)
+            {
+                Some(since) => advisory.modified >= since,
+                None => true,
+            }
+        });
+    }
+
+    let result = match runner.workers {
+        1 => walker.walk(Shared(visitor)).await,
+        n => walker.walk_parallel(n, Shared(visitor)).await,
+    };
+
+    if let (Some(since), Err(walker::Error::Failed { distributions, .. })) = (since, &result) {
+        since.failed(distributions.iter().map(ToString::to_string));
+    }
+
+    let stats = result?;
+    log::info!("Walk complete: {stats}");
+
     Ok(())
 }
 
+/// A visitor, shared between the walks of several sources.
+struct Shared<'a, V>(&'a V);
+
+impl<V: DiscoveredVisitor> DiscoveredVisitor for Shared<'_, V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.0.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        self.0.visit_advisory(context, advisory).await
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -159,6 +352,21 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_path() {
+        let source = SourceDescriptor::from_str("./data/store");
+        println!("Result: {source:?}");
+        assert!(
+            matches!(source, Ok(SourceDescriptor::File(path)) if path.to_string_lossy() == "./data/store")
+        );
+
+        let source = SourceDescriptor::from_str("/data/store");
+        println!("Result: {source:?}");
+        assert!(
+            matches!(source, Ok(SourceDescriptor::File(path)) if path.to_string_lossy() == "/data/store")
+        );
+    }
+
     #[tokio::test]
     async fn test_base() {
         let source = SourceDescriptor::from_str("base.domain");
@@ -175,6 +383,47 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_list_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            "# providers\nredhat.com\n\n  https://example.com/provider-metadata.json  \n",
+        )
+        .unwrap();
+
+        let sources = SourceDescriptor::load_all(&format!("@{}", file.path().display()));
+        println!("Result: {sources:?}");
+        assert!(matches!(
+            sources.as_deref(),
+            Ok([SourceDescriptor::Lookup(base), SourceDescriptor::Url(url)])
+                if base == "redhat.com" && url.as_str() == "https://example.com/provider-metadata.json"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_local() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            "redhat.com
+./data/store
+",
+        )
+        .unwrap();
+
+        let sources = SourceDescriptor::load_all(&format!("@{}", file.path().display()));
+        println!("Result: {sources:?}");
+        assert!(sources.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_not_parsed() {
+        let source = SourceDescriptor::from_str("@sources.txt");
+        println!("Result: {source:?}");
+        assert!(source.is_err());
+    }
+
     #[tokio::test]
     async fn test_gopher() {
         let source = SourceDescriptor::from_str("gopher://base.domain");
@@ -6,7 +6,7 @@ mod common;
 use clap::Parser;
 use cmd::{
     discover::Discover, download::Download, fetch::Fetch, metadata::Metadata, parse::Parse,
-    report::Report, scan::Scan, scoop::Scoop, send::Send, sync::Sync,
+    report::Report, scan::Scan, scoop::Scoop, send::Send, sync::Sync, verify::Verify,
 };
 use std::{ops::Deref, process::ExitCode};
 use walker_common::{
@@ -33,6 +33,7 @@ enum Command {
     Discover(Discover),
     Sync(Sync),
     Report(Report),
+    Verify(Verify),
     Send(Send),
     Metadata(Metadata),
     Scoop(Scoop),
@@ -50,6 +51,7 @@ impl Deref for Command {
             Self::Discover(cmd) => cmd,
             Self::Sync(cmd) => cmd,
             Self::Report(cmd) => cmd,
+            Self::Verify(cmd) => cmd,
             Self::Send(cmd) => cmd,
             Self::Metadata(cmd) => cmd,
             Self::Scoop(cmd) => cmd,
@@ -67,6 +69,7 @@ impl Command {
             Self::Discover(cmd) => cmd.run(progress).await,
             Self::Sync(cmd) => cmd.run(progress).await,
             Self::Report(cmd) => cmd.run(progress).await,
+            Self::Verify(cmd) => cmd.run(progress).await,
             Self::Send(cmd) => cmd.run(progress).await,
             Self::Metadata(cmd) => cmd.run().await,
             Self::Scoop(cmd) => cmd.run(progress).await,
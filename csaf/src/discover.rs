@@ -1,14 +1,17 @@
 //! Discovering
 
-use crate::model::metadata::ProviderMetadata;
+use crate::model::metadata::{ProviderMetadata, Publisher, TlpLabel};
 use std::fmt::Debug;
 use std::future::Future;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::SystemTime;
 use url::Url;
-use walker_common::utils::url::Urlify;
+use walker_common::utils::{budget::MemoryBudget, url::Urlify};
 
 /// Discovery configuration
+#[derive(Clone)]
 pub struct DiscoverConfig {
     /// The source to locate the provider metadata.
     ///
@@ -19,6 +22,16 @@ pub struct DiscoverConfig {
     /// Only report documents which have changed since the provided date. If a document has no
     /// change information, or this field is [`None`], it will always be reported.
     pub since: Option<SystemTime>,
+
+    /// Treat a local source as flat directory of advisories, without provider metadata.
+    pub rootless: bool,
+
+    /// The base directory of previously stored documents, enabling conditional requests for
+    /// remote sources.
+    pub conditional: Option<PathBuf>,
+
+    /// Treat a remote source as a CSAF aggregator, walking all listed providers and publishers.
+    pub aggregator: bool,
 }
 
 impl DiscoverConfig {
@@ -26,6 +39,21 @@ impl DiscoverConfig {
         self.since = since.into();
         self
     }
+
+    pub fn with_rootless(mut self, rootless: bool) -> Self {
+        self.rootless = rootless;
+        self
+    }
+
+    pub fn with_conditional(mut self, conditional: impl Into<Option<PathBuf>>) -> Self {
+        self.conditional = conditional.into();
+        self
+    }
+
+    pub fn with_aggregator(mut self, aggregator: bool) -> Self {
+        self.aggregator = aggregator;
+        self
+    }
 }
 
 impl From<&str> for DiscoverConfig {
@@ -33,6 +61,9 @@ impl From<&str> for DiscoverConfig {
         Self {
             since: None,
             source: value.to_string(),
+            rootless: false,
+            conditional: None,
+            aggregator: false,
         }
     }
 }
@@ -40,7 +71,16 @@ impl From<&str> for DiscoverConfig {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DistributionContext {
     Directory(Url),
-    Feed(Url),
+    /// A ROLIE feed, with the TLP label declared by the provider metadata
+    Feed {
+        url: Url,
+        tlp_label: TlpLabel,
+    },
+    /// A distribution of a publisher, listed by an aggregator
+    Aggregated {
+        publisher: Arc<Publisher>,
+        distribution: Box<DistributionContext>,
+    },
 }
 
 impl DistributionContext {
@@ -48,7 +88,32 @@ impl DistributionContext {
     pub fn url(&self) -> &Url {
         match self {
             Self::Directory(url) => url,
-            Self::Feed(url) => url,
+            Self::Feed { url, .. } => url,
+            Self::Aggregated { distribution, .. } => distribution.url(),
+        }
+    }
+
+    /// Get the publisher of the distribution, if it was listed by an aggregator
+    pub fn publisher(&self) -> Option<&Publisher> {
+        match self {
+            Self::Directory(_) | Self::Feed { .. } => None,
+            Self::Aggregated { publisher, .. } => Some(publisher),
+        }
+    }
+
+    /// Get the TLP label of the distribution, if it is a ROLIE feed
+    pub fn tlp_label(&self) -> Option<&TlpLabel> {
+        match self.distribution() {
+            Self::Feed { tlp_label, .. } => Some(tlp_label),
+            _ => None,
+        }
+    }
+
+    /// Get the actual distribution, unwrapping an aggregated one
+    pub fn distribution(&self) -> &DistributionContext {
+        match self {
+            Self::Directory(_) | Self::Feed { .. } => self,
+            Self::Aggregated { distribution, .. } => distribution.distribution(),
         }
     }
 }
@@ -94,6 +159,51 @@ impl Urlify for DiscoveredAdvisory {
 #[derive(Debug)]
 pub struct DiscoveredContext<'c> {
     pub metadata: &'c ProviderMetadata,
+    /// The memory budget of the walk, if any.
+    ///
+    /// Visitors retrieving documents should reserve the size of the retrieved data, until they
+    /// are done processing it.
+    pub memory_budget: Option<&'c MemoryBudget>,
+    /// The transfer counter of the walk, if any.
+    ///
+    /// Visitors retrieving documents should add the size of the retrieved data.
+    pub transfer: Option<&'c TransferCounter>,
+}
+
+/// Counts the bytes of retrieved documents.
+#[derive(Clone, Debug, Default)]
+pub struct TransferCounter {
+    inner: Arc<TransferCounterInner>,
+}
+
+#[derive(Debug, Default)]
+struct TransferCounterInner {
+    downloaded: AtomicU64,
+    not_modified: AtomicU64,
+}
+
+impl TransferCounter {
+    /// Add the size of a retrieved document.
+    ///
+    /// Documents which were not modified (HTTP 304) are counted separately, as their data didn't
+    /// need to be downloaded.
+    pub fn add(&self, bytes: usize, not_modified: bool) {
+        let counter = match not_modified {
+            true => &self.inner.not_modified,
+            false => &self.inner.downloaded,
+        };
+        counter.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// The number of downloaded bytes.
+    pub fn downloaded(&self) -> u64 {
+        self.inner.downloaded.load(Ordering::Relaxed)
+    }
+
+    /// The number of bytes of documents which were not modified.
+    pub fn not_modified(&self) -> u64 {
+        self.inner.not_modified.load(Ordering::Relaxed)
+    }
 }
 
 /// Visiting discovered advisories
@@ -0,0 +1,90 @@
+use super::{Error, MetadataSource};
+use crate::model::{
+    aggregator::Aggregator,
+    metadata::{MetadataVersion, ProviderMetadata, Publisher, PublisherCategory, Role},
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+use url::Url;
+use walker_common::fetcher::{Fetcher, Json};
+
+/// A metadata source, loading all providers and publishers listed by a CSAF aggregator.
+///
+/// The resulting metadata contains the union of the distributions and keys of all listed
+/// parties, each distribution and key carrying the identity of its publisher. Documents of a
+/// publisher are only validated using the keys of the same publisher. Parties whose metadata
+/// cannot be retrieved are skipped, with a warning.
+#[derive(Clone, Debug)]
+pub struct AggregatorMetadataSource {
+    /// The URL of the `aggregator.json`
+    pub url: Url,
+}
+
+impl AggregatorMetadataSource {
+    pub fn new(url: Url) -> Self {
+        Self { url }
+    }
+
+    /// Create a source for the well-known location of the aggregator of a domain.
+    pub fn from_domain(domain: &str) -> Result<Self, url::ParseError> {
+        Ok(Self::new(Url::parse(&format!(
+            "https://{domain}/.well-known/csaf-aggregator/aggregator.json"
+        ))?))
+    }
+}
+
+#[async_trait(?Send)]
+impl MetadataSource for AggregatorMetadataSource {
+    async fn load_metadata(&self, fetcher: &Fetcher) -> Result<ProviderMetadata, Error> {
+        let aggregator = fetcher
+            .fetch::<Json<Aggregator>>(self.url.clone())
+            .await?
+            .into_inner();
+
+        let mut distributions = vec![];
+        let mut public_openpgp_keys = vec![];
+
+        for entry in aggregator.entries() {
+            let url = &entry.metadata.url;
+            log::debug!("Loading listed provider metadata: {url}");
+
+            let metadata = match url.clone().load_metadata(fetcher).await {
+                Ok(metadata) => metadata,
+                Err(err) => {
+                    log::warn!("Failed to load provider metadata, skipping: {url}: {err}");
+                    continue;
+                }
+            };
+
+            let publisher = Arc::new(metadata.publisher);
+            distributions.extend(metadata.distributions.into_iter().map(|mut distribution| {
+                distribution.publisher = Some(publisher.clone());
+                distribution
+            }));
+            public_openpgp_keys.extend(metadata.public_openpgp_keys.into_iter().map(|mut key| {
+                key.publisher = Some(publisher.clone());
+                key
+            }));
+        }
+
+        let info = aggregator.aggregator;
+
+        Ok(ProviderMetadata {
+            canonical_url: aggregator.canonical_url,
+            distributions,
+            last_updated: aggregator.last_updated,
+            list_on_csaf_aggregators: false,
+            metadata_version: MetadataVersion::V2_0,
+            mirror_on_csaf_aggregators: false,
+            public_openpgp_keys,
+            publisher: Publisher {
+                category: PublisherCategory::Other,
+                contact_details: info.contact_details,
+                issuing_authority: info.issuing_authority,
+                name: info.name,
+                namespace: info.namespace,
+            },
+            role: Role::Provider,
+        })
+    }
+}
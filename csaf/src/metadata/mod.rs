@@ -1,3 +1,9 @@
+mod aggregator;
+mod validate;
+
+pub use aggregator::*;
+pub use validate::*;
+
 use crate::model::metadata::ProviderMetadata;
 use async_trait::async_trait;
 use hickory_resolver::Resolver;
@@ -48,6 +54,15 @@ impl MetadataSource for String {
 }
 
 /// A metadata source implementing the CSAF metadata discovery process.
+///
+/// Given a bare domain, this tries (in order):
+///
+/// * `https://<domain>/.well-known/csaf/provider-metadata.json`
+/// * the `CSAF` fields of `https://<domain>/.well-known/security.txt`
+/// * the `CSAF` fields of `https://<domain>/security.txt`
+/// * `https://csaf.data.security.<domain>`
+///
+/// If the value is a full URL instead, it is used as the location of the provider metadata.
 #[derive(Clone, Debug)]
 pub struct MetadataRetriever {
     pub base_url: String,
@@ -0,0 +1,160 @@
+use crate::model::metadata::{MetadataVersion, ProviderMetadata, PublisherCategory};
+use std::fmt::{Display, Formatter};
+use url::Url;
+
+/// A problem of the provider metadata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataIssue {
+    /// A JSON pointer (RFC 6901) to the invalid field
+    pub pointer: String,
+    /// The description of the problem
+    pub message: String,
+}
+
+impl MetadataIssue {
+    fn new(pointer: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            pointer: pointer.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for MetadataIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+/// Validate the content of the provider metadata, beyond what is required to parse it.
+///
+/// This checks the publisher, the metadata version, and that there is at least one
+/// distribution, each having a directory URL or a ROLIE feed.
+pub fn validate_metadata(metadata: &ProviderMetadata) -> Vec<MetadataIssue> {
+    let mut issues = vec![];
+
+    if let MetadataVersion::Unknown(version) = &metadata.metadata_version {
+        issues.push(MetadataIssue::new(
+            "/metadata_version",
+            format!("unsupported version '{version}'"),
+        ));
+    }
+
+    let publisher = &metadata.publisher;
+    if let PublisherCategory::Unknown(category) = &publisher.category {
+        issues.push(MetadataIssue::new(
+            "/publisher/category",
+            format!("unknown category '{category}'"),
+        ));
+    }
+    if publisher.name.trim().is_empty() {
+        issues.push(MetadataIssue::new("/publisher/name", "must not be empty"));
+    }
+    if let Err(err) = Url::parse(&publisher.namespace) {
+        issues.push(MetadataIssue::new(
+            "/publisher/namespace",
+            format!("must be a URL: {err}"),
+        ));
+    }
+
+    if metadata.distributions.is_empty() {
+        issues.push(MetadataIssue::new(
+            "/distributions",
+            "no distributions, there is nothing to walk",
+        ));
+    }
+
+    for (i, distribution) in metadata.distributions.iter().enumerate() {
+        match &distribution.rolie {
+            Some(rolie) if rolie.feeds.is_empty() => {
+                issues.push(MetadataIssue::new(
+                    format!("/distributions/{i}/rolie/feeds"),
+                    "must not be empty",
+                ));
+            }
+            None if distribution.directory_url.is_none() => {
+                issues.push(MetadataIssue::new(
+                    format!("/distributions/{i}"),
+                    "neither a directory URL nor a ROLIE feed",
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::metadata::{Distribution, Rolie};
+
+    fn metadata() -> ProviderMetadata {
+        serde_json::from_value(serde_json::json!({
+            "canonical_url": "https://example.com/.well-known/csaf/provider-metadata.json",
+            "distributions": [{ "directory_url": "https://example.com/advisories/" }],
+            "last_updated": "2024-01-01T00:00:00Z",
+            "metadata_version": "2.0",
+            "publisher": {
+                "category": "vendor",
+                "name": "Example",
+                "namespace": "https://example.com"
+            },
+            "role": "csaf_provider"
+        }))
+        .expect("must parse")
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(validate_metadata(&metadata()), vec![]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut metadata = metadata();
+        metadata.publisher.name = " ".into();
+        metadata.publisher.namespace = "example".into();
+        metadata.distributions = vec![
+            Distribution {
+                directory_url: None,
+                rolie: None,
+                publisher: None,
+            },
+            Distribution {
+                directory_url: None,
+                rolie: Some(Rolie {
+                    categories: vec![],
+                    feeds: vec![],
+                    services: vec![],
+                }),
+                publisher: None,
+            },
+        ];
+
+        let pointers = validate_metadata(&metadata)
+            .into_iter()
+            .map(|issue| issue.pointer)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pointers,
+            vec![
+                "/publisher/name",
+                "/publisher/namespace",
+                "/distributions/0",
+                "/distributions/1/rolie/feeds",
+            ]
+        );
+    }
+
+    #[test]
+    fn no_distributions() {
+        let mut metadata = metadata();
+        metadata.distributions.clear();
+
+        let issues = validate_metadata(&metadata);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].pointer, "/distributions");
+    }
+}
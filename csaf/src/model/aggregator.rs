@@ -0,0 +1,75 @@
+use super::metadata::{Publisher, Role};
+use chrono::{DateTime, Utc};
+use url::Url;
+
+/// A CSAF aggregator document (`aggregator.json`), listing providers and publishers.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Aggregator {
+    pub aggregator: AggregatorInfo,
+
+    pub aggregator_version: AggregatorVersion,
+
+    pub canonical_url: Url,
+
+    #[serde(default)]
+    pub csaf_providers: Vec<AggregatorEntry>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub csaf_publishers: Vec<AggregatorEntry>,
+
+    pub last_updated: DateTime<Utc>,
+}
+
+impl Aggregator {
+    /// Iterate over all listed providers and publishers.
+    pub fn entries(&self) -> impl Iterator<Item = &AggregatorEntry> {
+        self.csaf_providers.iter().chain(&self.csaf_publishers)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct AggregatorInfo {
+    pub category: AggregatorCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact_details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuing_authority: Option<String>,
+    pub name: String,
+    pub namespace: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregatorCategory {
+    Aggregator,
+    Lister,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum AggregatorVersion {
+    #[serde(rename = "2.0")]
+    V2_0,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// A provider or publisher, listed by an aggregator.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct AggregatorEntry {
+    pub metadata: AggregatorEntryMetadata,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<Url>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct AggregatorEntryMetadata {
+    pub last_updated: DateTime<Utc>,
+    pub publisher: Publisher,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// The URL of the `provider-metadata.json`
+    pub url: Url,
+}
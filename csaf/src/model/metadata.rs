@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use std::sync::Arc;
 use url::Url;
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -7,6 +8,11 @@ pub struct Distribution {
     pub directory_url: Option<Url>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rolie: Option<Rolie>,
+    /// The publisher of the distribution, if it was listed by an aggregator.
+    ///
+    /// This is not part of the provider metadata, but set when loading the metadata.
+    #[serde(skip)]
+    pub publisher: Option<Arc<Publisher>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -42,6 +48,11 @@ pub struct Key {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub fingerprint: Option<String>,
     pub url: Url,
+    /// The publisher the key belongs to, if it was listed by an aggregator.
+    ///
+    /// This is not part of the provider metadata, but set when loading the metadata.
+    #[serde(skip)]
+    pub publisher: Option<Arc<Publisher>>,
 }
 
 impl<'a> From<&'a Key> for walker_common::validate::source::Key<'a> {
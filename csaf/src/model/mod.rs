@@ -1,3 +1,5 @@
 //! Data models
+pub mod aggregator;
 pub mod metadata;
-pub(crate) mod store;
+pub mod store;
+pub mod tracking;
@@ -1,3 +1,5 @@
+use super::metadata::TlpLabel;
+use chrono::{DateTime, Utc};
 use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
 use std::path::{Path, PathBuf};
 
@@ -6,3 +8,36 @@ pub fn distribution_base(base: impl AsRef<Path>, url: &str) -> PathBuf {
     base.as_ref()
         .join(utf8_percent_encode(url, NON_ALPHANUMERIC).to_string())
 }
+
+/// The name of the partition directory for a TLP label.
+///
+/// Distributions without a label, like directory based ones, use the `tlp-unlabeled` partition.
+pub fn tlp_partition(label: Option<&TlpLabel>) -> &'static str {
+    match label {
+        None | Some(TlpLabel::Unlabeled) => "tlp-unlabeled",
+        Some(TlpLabel::White) => "tlp-white",
+        Some(TlpLabel::Green) => "tlp-green",
+        Some(TlpLabel::Amber) => "tlp-amber",
+        Some(TlpLabel::Red) => "tlp-red",
+    }
+}
+
+/// An index of all advisories stored in an output directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct StoreIndex {
+    pub advisories: Vec<StoreIndexEntry>,
+}
+
+/// A stored advisory.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct StoreIndexEntry {
+    /// The path of the advisory, relative to the output directory
+    pub path: String,
+    /// The SHA-256 digest of the stored content, hex encoded
+    pub sha256: String,
+    /// The modification timestamp of the stored file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modification: Option<DateTime<Utc>>,
+    /// Whether a signature is stored alongside the advisory
+    pub signature: bool,
+}
@@ -0,0 +1,95 @@
+//! Lightweight access to the `document.tracking` section of a CSAF document
+//!
+//! This doesn't require the full CSAF model (feature `csaf`), and only extracts what is needed
+//! to make decisions about the lifecycle of a document.
+
+use std::{
+    convert::Infallible,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// The status of a CSAF document (`/document/tracking/status`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackingStatus {
+    Draft,
+    Final,
+    Interim,
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl FromStr for TrackingStatus {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "draft" => Self::Draft,
+            "final" => Self::Final,
+            "interim" => Self::Interim,
+            other => Self::Other(other.to_string()),
+        })
+    }
+}
+
+impl Display for TrackingStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Draft => f.write_str("draft"),
+            Self::Final => f.write_str("final"),
+            Self::Interim => f.write_str("interim"),
+            Self::Other(other) => f.write_str(other),
+        }
+    }
+}
+
+/// The tracking information of a CSAF document.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Tracking {
+    pub status: TrackingStatus,
+    pub version: String,
+}
+
+impl Tracking {
+    /// Extract the tracking information from the raw JSON document.
+    pub fn from_slice(data: &[u8]) -> Result<Self, serde_json::Error> {
+        #[derive(serde::Deserialize)]
+        struct Document {
+            tracking: Tracking,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Csaf {
+            document: Document,
+        }
+
+        Ok(serde_json::from_slice::<Csaf>(data)?.document.tracking)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_tracking() {
+        let tracking = Tracking::from_slice(include_bytes!("../../test-data/rhsa-2021_3029.json"))
+            .expect("example data must parse");
+        assert_eq!(tracking.status, TrackingStatus::Final);
+        assert_eq!(tracking.version, "1");
+    }
+
+    #[test]
+    fn parse_other_status() {
+        let tracking = Tracking::from_slice(
+            br#"{"document":{"tracking":{"status":"retracted","version":"2"}}}"#,
+        )
+        .expect("must parse");
+        assert_eq!(
+            tracking.status,
+            TrackingStatus::Other("retracted".to_string())
+        );
+        assert_eq!("retracted".parse(), Ok(tracking.status));
+    }
+}
@@ -189,15 +189,22 @@ impl HtmlReport<'_> {
                     label = html_escape::encode_text(&label),
                 )?;
 
-                for text in v {
+                for warning in v {
+                    let pointer = match &warning.pointer {
+                        Some(pointer) => format!(
+                            r#" <small class="text-body-secondary">{}</small>"#,
+                            html_escape::encode_text(pointer)
+                        ),
+                        None => String::new(),
+                    };
                     writeln!(
                         f,
                         r#"
             <li>
-                <code>{v}</code>
+                <code>{v}</code>{pointer}
             </li>
             "#,
-                        v = html_escape::encode_text(&text),
+                        v = html_escape::encode_text(&warning.message),
                     )?;
                 }
 
@@ -1,23 +1,37 @@
 //! Retrieval
 
 use crate::{
-    discover::{AsDiscovered, DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
+    discover::{
+        AsDiscovered, DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor, TransferCounter,
+    },
+    model::tracking::Tracking,
     source::Source,
 };
 use bytes::Bytes;
-use sha2::{Sha256, Sha512};
+use sha2::{Sha256, Sha384, Sha512};
 use std::{
     fmt::Debug,
     future::Future,
     ops::{Deref, DerefMut},
+    time::SystemTime,
 };
 use url::Url;
 use walker_common::{
     retrieve::{RetrievalError, RetrievalMetadata, RetrievedDigest, RetrievedDocument},
-    utils::{openpgp::PublicKey, url::Urlify},
+    utils::{budget::MemoryBudget, openpgp::PublicKey, url::Urlify},
     validate::source::{KeySource, KeySourceError},
 };
 
+/// An error parsing a retrieved advisory into a CSAF document.
+#[cfg(feature = "csaf")]
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error("failed to decompress document: {0:#}")]
+    Decompress(anyhow::Error),
+    #[error("failed to parse document: {0}")]
+    Parse(#[from] walker_common::utils::json::JsonParseError),
+}
+
 /// A retrieved (but unverified) advisory
 #[derive(Clone, Debug)]
 pub struct RetrievedAdvisory {
@@ -31,13 +45,52 @@ pub struct RetrievedAdvisory {
 
     /// SHA-256 digest
     pub sha256: Option<RetrievedDigest<Sha256>>,
+    /// SHA-384 digest
+    pub sha384: Option<RetrievedDigest<Sha384>>,
     /// SHA-512 digest
     pub sha512: Option<RetrievedDigest<Sha512>>,
 
-    /// Metadata from the retrieval process
+    /// Metadata from the retrieval process, like the last modification time and the ETag
     pub metadata: RetrievalMetadata,
 }
 
+impl RetrievedAdvisory {
+    /// The last modification time of the advisory.
+    ///
+    /// This is the time reported during the retrieval, falling back to the "last changed" date
+    /// of the discovery (e.g. from the `changes.csv` file).
+    pub fn last_modification(&self) -> SystemTime {
+        self.metadata
+            .last_modification
+            .map(SystemTime::from)
+            .unwrap_or(self.discovered.modified)
+    }
+
+    /// Check if the advisory was modified after the provided time, see
+    /// [`Self::last_modification`].
+    ///
+    /// This can be used to skip advisories which are already known, e.g. by comparing against
+    /// the modification time of a stored copy.
+    pub fn is_newer_than(&self, time: SystemTime) -> bool {
+        self.last_modification() > time
+    }
+
+    /// Parse the tracking information (like status and version) from the advisory data.
+    pub fn tracking(&self) -> Result<Tracking, serde_json::Error> {
+        Tracking::from_slice(&self.data)
+    }
+
+    /// Parse the advisory data into a CSAF document, decompressing it first if necessary.
+    ///
+    /// The document is parsed on every call, and not cached.
+    #[cfg(feature = "csaf")]
+    pub fn parse(&self) -> Result<csaf::Csaf, ParseError> {
+        let data = walker_common::compression::decompress(self.data.clone(), self.url.path())
+            .map_err(ParseError::Decompress)?;
+        Ok(walker_common::utils::json::from_slice(&data)?)
+    }
+}
+
 impl Urlify for RetrievedAdvisory {
     fn url(&self) -> &Url {
         &self.url
@@ -85,6 +138,7 @@ impl RetrievedDocument for RetrievedAdvisory {
 
 pub struct RetrievalContext<'c> {
     pub discovered: &'c DiscoveredContext<'c>,
+    /// The loaded public keys, in the order of the `public_openpgp_keys` of the metadata
     pub keys: &'c Vec<PublicKey>,
 }
 
@@ -168,6 +222,13 @@ where
     Visitor(VE),
 }
 
+/// The context of the [`RetrievingVisitor`].
+pub struct RetrievingContext<C> {
+    context: C,
+    memory_budget: Option<MemoryBudget>,
+    transfer: Option<TransferCounter>,
+}
+
 impl<V, S> DiscoveredVisitor for RetrievingVisitor<V, S>
 where
     V: RetrievedVisitor<S>,
@@ -175,7 +236,7 @@ where
 {
     type Error =
         Error<V::Error, <S as walker_common::source::Source>::Error, <S as KeySource>::Error>;
-    type Context = V::Context;
+    type Context = RetrievingContext<V::Context>;
 
     async fn visit_context(
         &self,
@@ -210,13 +271,20 @@ where
             }
         }
 
-        self.visitor
+        let inner = self
+            .visitor
             .visit_context(&RetrievalContext {
                 discovered: context,
                 keys: &keys,
             })
             .await
-            .map_err(Error::Visitor)
+            .map_err(Error::Visitor)?;
+
+        Ok(RetrievingContext {
+            context: inner,
+            memory_budget: context.memory_budget.cloned(),
+            transfer: context.transfer.cloned(),
+        })
     }
 
     async fn visit_advisory(
@@ -230,11 +298,64 @@ where
             .await
             .map_err(|err| RetrievalError::Source { err, discovered });
 
+        if let (Some(transfer), Ok(advisory)) = (&context.transfer, &advisory) {
+            transfer.add(advisory.data.len(), advisory.metadata.not_modified);
+        }
+
+        // hold the reservation until the document was processed
+        let _reservation = match (&context.memory_budget, &advisory) {
+            (Some(memory_budget), Ok(advisory)) => Some(memory_budget.reserve(advisory.data.len())),
+            _ => None,
+        };
+
         self.visitor
-            .visit_advisory(context, advisory)
+            .visit_advisory(&context.context, advisory)
             .await
             .map_err(Error::Visitor)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::discover::DistributionContext;
+    use std::{sync::Arc, time::Duration};
+    use time::OffsetDateTime;
+
+    fn advisory(last_modification: Option<SystemTime>) -> RetrievedAdvisory {
+        let url = Url::parse("https://example.com/advisories/a.json").unwrap();
+        RetrievedAdvisory {
+            discovered: DiscoveredAdvisory {
+                context: Arc::new(DistributionContext::Directory(url.join("./").unwrap())),
+                url,
+                digest: None,
+                signature: None,
+                modified: SystemTime::UNIX_EPOCH + Duration::from_secs(100),
+            },
+            data: Bytes::new(),
+            signature: None,
+            sha256: None,
+            sha384: None,
+            sha512: None,
+            metadata: RetrievalMetadata {
+                last_modification: last_modification.map(OffsetDateTime::from),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn is_newer_than() {
+        let time = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+
+        // falls back to the discovered modification time
+        assert!(advisory(None).is_newer_than(time(50)));
+        assert!(!advisory(None).is_newer_than(time(100)));
+
+        // prefers the retrieved modification time
+        assert!(advisory(Some(time(200))).is_newer_than(time(150)));
+        assert!(!advisory(Some(time(20))).is_newer_than(time(50)));
+    }
+}
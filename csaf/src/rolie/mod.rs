@@ -3,6 +3,7 @@ mod roliefeed;
 pub use roliefeed::*;
 
 use crate::source::HttpSourceError;
+use std::collections::{HashSet, VecDeque};
 use time::OffsetDateTime;
 use url::{ParseError, Url};
 use walker_common::fetcher::Json;
@@ -50,15 +51,55 @@ pub struct RolieSource {
     pub files: Vec<SourceFile>,
 }
 
+/// The default maximum number of pages of a paginated feed.
+pub const DEFAULT_MAX_PAGES: usize = 100;
+
 impl RolieSource {
-    pub async fn retrieve(fetcher: &Fetcher, base_url: Url) -> Result<Self, Error> {
+    /// Retrieve the files of a feed, following its `next` and `previous` links.
+    ///
+    /// At most `max_pages` pages are loaded, guarding against runaway pagination. Files listed
+    /// on more than one page are only reported once.
+    pub async fn retrieve(
+        fetcher: &Fetcher,
+        base_url: Url,
+        max_pages: usize,
+    ) -> Result<Self, Error> {
         let mut files = vec![];
-        let Json(result) = fetcher.fetch::<Json<RolieFeed>>(base_url).await?;
-        for entry in result.feed.entry {
-            files.push(find_file(entry));
+        let mut seen_files = HashSet::new();
+
+        let mut pages = 0;
+        let mut visited = HashSet::from([base_url.clone()]);
+        let mut queue = VecDeque::from([base_url]);
+
+        while let Some(url) = queue.pop_front() {
+            if pages >= max_pages {
+                log::warn!(
+                    "Reached the maximum of {max_pages} feed pages, ignoring remaining pages, starting with: {url}"
+                );
+                break;
+            }
+            pages += 1;
+
+            let Json(result) = fetcher.fetch::<Json<RolieFeed>>(url.clone()).await?;
+
+            for link in &result.feed.link {
+                if matches!(&*link.rel, "next" | "previous") {
+                    let page = url.join(&link.href)?;
+                    if visited.insert(page.clone()) {
+                        queue.push_back(page);
+                    }
+                }
+            }
+
+            for entry in result.feed.entry {
+                let file = find_file(entry);
+                if seen_files.insert(file.file.clone()) {
+                    files.push(file);
+                }
+            }
         }
 
-        log::debug!("found {:?} files", files.len());
+        log::info!("Found {} files, in {pages} page(s)", files.len());
 
         Ok(Self { files })
     }
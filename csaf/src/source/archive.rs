@@ -0,0 +1,375 @@
+use crate::{
+    discover::{DiscoveredAdvisory, DistributionContext},
+    model::{
+        metadata::{self, ProviderMetadata},
+        store::distribution_base,
+    },
+    retrieve::RetrievedAdvisory,
+    source::{DEFAULT_MAX_DOCUMENT_SIZE, FileOptions, Source},
+    visitors::store::DIR_METADATA,
+};
+use anyhow::{Context, anyhow, bail};
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use percent_encoding::percent_decode_str;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+use url::Url;
+use walker_common::{
+    retrieve::RetrievalMetadata,
+    source::file::{read_sha384_with, read_sig_and_digests_with},
+    utils::{self, openpgp::PublicKey},
+    validate::source::{Key, KeySource, KeySourceError},
+};
+
+/// An entry of an archive
+#[derive(Debug)]
+struct Entry {
+    data: Bytes,
+    modified: SystemTime,
+}
+
+/// A source reading from a tar (possibly gzip compressed) or zip archive.
+///
+/// The archive must have the same layout as a directory used by the [`super::FileSource`],
+/// possibly nested in a single top-level directory. The archive is read into memory when creating
+/// the source, without extracting it to disk.
+#[derive(Clone, Debug)]
+pub struct ArchiveSource {
+    /// the URL of the archive, used as base for the URLs of its entries
+    base: Url,
+    /// the entries of the archive, by their path, relative to the root of the layout
+    entries: Arc<BTreeMap<String, Entry>>,
+    options: FileOptions,
+}
+
+impl ArchiveSource {
+    pub fn new(
+        archive: impl AsRef<Path>,
+        options: impl Into<Option<FileOptions>>,
+    ) -> anyhow::Result<Self> {
+        let archive = fs::canonicalize(archive)?;
+        let options = options.into().unwrap_or_default();
+
+        let data = fs::read(&archive)
+            .with_context(|| format!("Failed to read archive: {}", archive.display()))?;
+        let max_size = options
+            .max_document_size
+            .unwrap_or(DEFAULT_MAX_DOCUMENT_SIZE);
+        let entries = read_entries(&data, max_size)
+            .with_context(|| format!("Failed to read archive: {}", archive.display()))?;
+
+        let entries = match options.rootless {
+            true => entries,
+            false => strip_root(entries)?,
+        };
+
+        let base = Url::from_directory_path(&archive)
+            .map_err(|()| anyhow!("Failed to convert into URL: {}", archive.display()))?;
+
+        Ok(Self {
+            base,
+            entries: Arc::new(entries),
+            options,
+        })
+    }
+
+    /// Create the URL of a path in the archive.
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.base.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().extend(path.split('/'));
+        }
+        url
+    }
+
+    /// Get the path in the archive from a URL.
+    fn path(&self, url: &Url) -> anyhow::Result<String> {
+        let path = url
+            .path()
+            .strip_prefix(self.base.path())
+            .ok_or_else(|| anyhow!("URL is not part of the archive: {url}"))?;
+
+        Ok(percent_decode_str(path).decode_utf8()?.into_owned())
+    }
+
+    fn entry(&self, path: &str) -> anyhow::Result<&Entry> {
+        self.entries
+            .get(path)
+            .ok_or_else(|| anyhow!("Missing archive entry: {path}"))
+    }
+
+    fn scan_keys(&self) -> Vec<metadata::Key> {
+        let prefix = format!("{DIR_METADATA}/keys/");
+
+        self.entries
+            .keys()
+            .filter_map(|path| {
+                let name = path.strip_prefix(&prefix)?;
+                let (fingerprint, "txt") = name.rsplit_once('.')? else {
+                    return None;
+                };
+                (!fingerprint.contains('/')).then(|| metadata::Key {
+                    fingerprint: Some(fingerprint.to_string()),
+                    url: self.url(path),
+                    publisher: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Map a distribution URL to the URL of its directory in the archive.
+    fn distribution_url(&self, url: &Url) -> Url {
+        let base = distribution_base("", url.as_str());
+        self.url(&format!("{}/", base.to_string_lossy()))
+    }
+}
+
+/// Read all file entries of an archive, detecting the format from its content.
+///
+/// Fails if an entry exceeds the maximum size, in bytes.
+fn read_entries(data: &[u8], max_size: u64) -> anyhow::Result<BTreeMap<String, Entry>> {
+    match data {
+        [b'P', b'K', 0x03, 0x04, ..] => read_zip(data, max_size),
+        [0x1f, 0x8b, ..] => read_tar(GzDecoder::new(data), max_size),
+        _ => read_tar(data, max_size),
+    }
+}
+
+/// Read the content of an entry, not trusting its declared size.
+fn read_entry(entry: impl Read, path: &str, size: u64, max_size: u64) -> anyhow::Result<Bytes> {
+    if size > max_size {
+        bail!("Archive entry exceeds the maximum size of {max_size} bytes: {path}");
+    }
+
+    let mut data = Vec::with_capacity(size as usize);
+    entry.take(max_size + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > max_size {
+        bail!("Archive entry exceeds the maximum size of {max_size} bytes: {path}");
+    }
+
+    Ok(data.into())
+}
+
+fn read_tar(reader: impl Read, max_size: u64) -> anyhow::Result<BTreeMap<String, Entry>> {
+    let mut result = BTreeMap::new();
+
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = normalize(&entry.path()?.to_string_lossy());
+        let modified =
+            SystemTime::UNIX_EPOCH + Duration::from_secs(entry.header().mtime().unwrap_or(0));
+
+        let size = entry.size();
+        let data = read_entry(&mut entry, &path, size, max_size)?;
+
+        result.insert(path, Entry { data, modified });
+    }
+
+    Ok(result)
+}
+
+fn read_zip(data: &[u8], max_size: u64) -> anyhow::Result<BTreeMap<String, Entry>> {
+    let mut result = BTreeMap::new();
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() {
+            continue;
+        }
+
+        let path = normalize(&entry.name()?);
+        let modified = entry
+            .last_modified()
+            .and_then(zip_modified)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let size = entry.size();
+        let data = read_entry(&mut entry, &path, size, max_size)?;
+
+        result.insert(path, Entry { data, modified });
+    }
+
+    Ok(result)
+}
+
+/// Convert the modification timestamp of a zip entry, which carries no timezone, assuming UTC.
+fn zip_modified(modified: zip::DateTime) -> Option<SystemTime> {
+    let date = Date::from_calendar_date(
+        modified.year() as i32,
+        Month::try_from(modified.month()).ok()?,
+        modified.day(),
+    )
+    .ok()?;
+    let time = Time::from_hms(modified.hour(), modified.minute(), modified.second()).ok()?;
+
+    Some(PrimitiveDateTime::new(date, time).assume_utc().into())
+}
+
+fn normalize(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+/// Strip the common top-level directory, if any, locating the provider metadata.
+fn strip_root(entries: BTreeMap<String, Entry>) -> anyhow::Result<BTreeMap<String, Entry>> {
+    let metadata = format!("{DIR_METADATA}/provider-metadata.json");
+
+    let Some(root) = entries
+        .keys()
+        .filter_map(|path| path.strip_suffix(&metadata))
+        .filter(|root| root.is_empty() || (root.ends_with('/') && root.matches('/').count() == 1))
+        .min_by_key(|root| root.len())
+        .map(ToString::to_string)
+    else {
+        bail!("Missing provider metadata ({metadata}) in archive");
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|(path, entry)| Some((path.strip_prefix(&root)?.to_string(), entry)))
+        .collect())
+}
+
+impl walker_common::source::Source for ArchiveSource {
+    type Error = anyhow::Error;
+    type Retrieved = RetrievedAdvisory;
+}
+
+impl Source for ArchiveSource {
+    async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+        if self.options.rootless {
+            return super::file::rootless_metadata(
+                self.base.clone(),
+                self.base
+                    .path_segments()
+                    .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+                    .map(|name| percent_decode_str(name).decode_utf8_lossy().into_owned())
+                    .unwrap_or_else(|| self.base.to_string()),
+            );
+        }
+
+        let entry = self.entry(&format!("{DIR_METADATA}/provider-metadata.json"))?;
+        let mut metadata: ProviderMetadata = serde_json::from_slice(&entry.data)
+            .context("Failed to read archived provider metadata")?;
+
+        metadata.public_openpgp_keys = self.scan_keys();
+
+        for dist in &mut metadata.distributions {
+            if let Some(directory_url) = &mut dist.directory_url {
+                *directory_url = self.distribution_url(directory_url);
+            }
+
+            if let Some(rolie) = &mut dist.rolie {
+                for feed in &mut rolie.feeds {
+                    feed.url = self.distribution_url(&feed.url);
+                }
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    async fn load_index(
+        &self,
+        context: DistributionContext,
+    ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
+        log::info!("Loading index - since: {:?}", self.options.since);
+
+        let prefix = self.path(context.url())?;
+        let context = Arc::new(context);
+
+        let mut result = vec![];
+
+        for (path, entry) in self.entries.range(prefix.clone()..) {
+            if !path.starts_with(&prefix) {
+                break;
+            }
+
+            if !path.ends_with(".json") {
+                continue;
+            }
+
+            if let Some(since) = self.options.since
+                && entry.modified < since
+            {
+                log::debug!(
+                    "Skipping entry due to modification constraint: {:?}",
+                    entry.modified
+                );
+                continue;
+            }
+
+            result.push(DiscoveredAdvisory {
+                url: self.url(path),
+                modified: entry.modified,
+                digest: None,
+                signature: None,
+                context: context.clone(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn load_advisory(
+        &self,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<RetrievedAdvisory, Self::Error> {
+        let path = self.path(&discovered.url)?;
+        let entry = self.entry(&path)?;
+        let data = entry.data.clone();
+
+        let read = async |sibling: String| {
+            self.entries
+                .get(&sibling)
+                .map(|entry| String::from_utf8(entry.data.to_vec()))
+                .transpose()
+                .map_err(anyhow::Error::from)
+        };
+        let (signature, sha256, sha512) = read_sig_and_digests_with(&path, &data, read).await?;
+        let sha384 = read_sha384_with(&path, &data, read).await?;
+
+        Ok(RetrievedAdvisory {
+            discovered,
+            data,
+            signature,
+            sha256,
+            sha384,
+            sha512,
+            metadata: RetrievalMetadata {
+                last_modification: Some(OffsetDateTime::from(entry.modified)),
+                etag: None,
+                not_modified: false,
+                transformed: false,
+            },
+        })
+    }
+}
+
+impl KeySource for ArchiveSource {
+    type Error = anyhow::Error;
+
+    async fn load_public_key(
+        &self,
+        key: Key<'_>,
+    ) -> Result<PublicKey, KeySourceError<Self::Error>> {
+        let data = self
+            .path(key.url)
+            .and_then(|path| Ok(self.entry(&path)?.data.clone()))
+            .map_err(KeySourceError::Source)?;
+        utils::openpgp::validate_keys(data, key.fingerprint).map_err(KeySourceError::OpenPgp)
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{
     discover::DiscoverConfig,
-    metadata::MetadataRetriever,
+    metadata::{AggregatorMetadataSource, MetadataRetriever},
     source::{DispatchSource, FileOptions, FileSource, HttpOptions, HttpSource},
 };
-use anyhow::bail;
+use anyhow::{Context, bail};
 use fluent_uri::UriRef;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -13,9 +13,9 @@ use walker_common::fetcher::{Fetcher, FetcherOptions};
 /// A descriptor of the source.
 #[derive(Clone, Debug)]
 pub enum SourceDescriptor {
-    /// A local file source
+    /// A local file source, either a directory or an archive (with the `archive` feature)
     File(PathBuf),
-    /// A remote URL source, pointing to the `provider-metadata.json`
+    /// A remote URL source, pointing to the `provider-metadata.json` (or the `aggregator.json`)
     Url(Url),
     /// A source discovered by the lookup process, given the domain.
     Lookup(String),
@@ -25,6 +25,18 @@ impl FromStr for SourceDescriptor {
     type Err = anyhow::Error;
 
     fn from_str(source: &str) -> Result<Self, Self::Err> {
+        if source == "-" || source.starts_with('@') {
+            bail!("A list of sources must be loaded using `SourceDescriptor::load_all`: {source}");
+        }
+
+        // a local path, like the base of a store, which can't be a domain name
+        if ["/", "./", "../"]
+            .iter()
+            .any(|prefix| source.starts_with(prefix))
+        {
+            return Ok(SourceDescriptor::File(PathBuf::from(source)));
+        }
+
         match UriRef::parse(source) {
             Ok(uri) => match uri.scheme().map(|s| s.as_str()) {
                 Some("https") => Ok(SourceDescriptor::Url(Url::parse(source)?)),
@@ -40,37 +52,120 @@ impl FromStr for SourceDescriptor {
     }
 }
 
+/// Parse a list of sources, one per line, ignoring blank lines and `#` comments.
+fn parse_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToString::to_string)
+        .collect()
+}
+
 impl SourceDescriptor {
     /// Parse a string into a source descriptor.
     pub fn parse(source: impl AsRef<str>) -> anyhow::Result<Self> {
         Self::from_str(source.as_ref())
     }
 
+    /// Load the sources described by a string.
+    ///
+    /// This reads a list of remote sources from a file (`@path`) or stdin (`-`), and otherwise
+    /// parses a single source.
+    pub fn load_all(source: &str) -> anyhow::Result<Vec<Self>> {
+        let content = if source == "-" {
+            std::io::read_to_string(std::io::stdin()).context("Read sources from stdin")?
+        } else if let Some(path) = source.strip_prefix('@') {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Read sources from file: {path}"))?
+        } else {
+            return Ok(vec![Self::from_str(source)?]);
+        };
+
+        let entries = parse_list(&content);
+        if entries.is_empty() {
+            bail!("The list of sources is empty");
+        }
+
+        entries
+            .into_iter()
+            .map(|entry| match Self::from_str(&entry)? {
+                Self::File(_) => bail!("Only remote sources are supported in a list: {entry}"),
+                descriptor => Ok(descriptor),
+            })
+            .collect()
+    }
+
     /// If possible, turn this into a source.
     pub async fn into_source(
         self,
         discover: DiscoverConfig,
         fetcher: FetcherOptions,
+    ) -> anyhow::Result<DispatchSource> {
+        self.into_source_with_options(discover, fetcher, HttpOptions::new())
+            .await
+    }
+
+    /// If possible, turn this into a source, using the options for remote sources.
+    ///
+    /// The `since` and `conditional` options are taken from the discovery configuration.
+    pub async fn into_source_with_options(
+        self,
+        discover: DiscoverConfig,
+        fetcher: FetcherOptions,
+        http: HttpOptions,
     ) -> anyhow::Result<DispatchSource> {
         match self {
-            Self::File(path) => {
-                Ok(FileSource::new(path, FileOptions::new().since(discover.since))?.into())
+            #[cfg(feature = "archive")]
+            Self::File(path) if path.is_file() => Ok(super::ArchiveSource::new(
+                path,
+                FileOptions::new()
+                    .since(discover.since)
+                    .rootless(discover.rootless),
+            )?
+            .into()),
+            Self::File(path) => Ok(FileSource::new(
+                path,
+                FileOptions::new()
+                    .since(discover.since)
+                    .rootless(discover.rootless),
+            )?
+            .into()),
+            remote => remote.into_remote_source(discover, Fetcher::new(fetcher).await?, http),
+        }
+    }
+
+    /// Turn this into a remote source, using an existing fetcher.
+    ///
+    /// This allows sharing a single client between several sources. Local sources are rejected.
+    pub fn into_remote_source(
+        self,
+        discover: DiscoverConfig,
+        fetcher: Fetcher,
+        http: HttpOptions,
+    ) -> anyhow::Result<DispatchSource> {
+        let http = http_options(&discover, http);
+        Ok(match self {
+            Self::File(path) => bail!("Not a remote source: {}", path.display()),
+            Self::Url(url) if discover.aggregator => {
+                HttpSource::new(AggregatorMetadataSource::new(url), fetcher, http).into()
             }
-            Self::Url(url) => Ok(HttpSource::new(
-                url,
-                Fetcher::new(fetcher).await?,
-                HttpOptions::new().since(discover.since),
+            Self::Url(url) => HttpSource::new(url, fetcher, http).into(),
+            Self::Lookup(source) if discover.aggregator => HttpSource::new(
+                AggregatorMetadataSource::from_domain(&source)?,
+                fetcher,
+                http,
             )
-            .into()),
+            .into(),
             Self::Lookup(source) => {
-                let fetcher = Fetcher::new(fetcher).await?;
-                Ok(HttpSource::new(
-                    MetadataRetriever::new(source),
-                    fetcher,
-                    HttpOptions::new().since(discover.since),
-                )
-                .into())
+                HttpSource::new(MetadataRetriever::new(source), fetcher, http).into()
             }
-        }
+        })
     }
 }
+
+/// The options of an HTTP source, as configured for discovery.
+fn http_options(discover: &DiscoverConfig, http: HttpOptions) -> HttpOptions {
+    http.since(discover.since)
+        .conditional(discover.conditional.clone())
+}
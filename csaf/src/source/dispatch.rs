@@ -1,8 +1,9 @@
-use super::{HttpSourceError, Source};
+use super::{ExpectedDigest, HttpSourceError, Source, SourceError};
 use crate::discover::{DiscoveredAdvisory, DistributionContext};
 use crate::model::metadata::ProviderMetadata;
 use crate::retrieve::RetrievedAdvisory;
-use crate::source::{FileSource, HttpSource};
+use crate::source::{FileSource, HttpSource, OverlaySource, OverlaySourceError};
+use reqwest::StatusCode;
 use walker_common::{
     utils::openpgp::PublicKey,
     validate::source::{Key, KeySource, KeySourceError, MapSourceError},
@@ -19,6 +20,10 @@ use walker_common::{
 pub enum DispatchSource {
     File(FileSource),
     Http(HttpSource),
+    /// A local store, falling back to HTTP for advisories which are not stored
+    Overlay(OverlaySource),
+    #[cfg(feature = "archive")]
+    Archive(super::ArchiveSource),
 }
 
 impl From<FileSource> for DispatchSource {
@@ -33,12 +38,42 @@ impl From<HttpSource> for DispatchSource {
     }
 }
 
+impl From<OverlaySource> for DispatchSource {
+    fn from(value: OverlaySource) -> Self {
+        Self::Overlay(value)
+    }
+}
+
+#[cfg(feature = "archive")]
+impl From<super::ArchiveSource> for DispatchSource {
+    fn from(value: super::ArchiveSource) -> Self {
+        Self::Archive(value)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DispatchSourceError {
     #[error(transparent)]
     File(anyhow::Error),
     #[error(transparent)]
     Http(HttpSourceError),
+    #[error(transparent)]
+    Overlay(OverlaySourceError),
+    #[cfg(feature = "archive")]
+    #[error(transparent)]
+    Archive(anyhow::Error),
+}
+
+impl SourceError for DispatchSourceError {
+    fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::File(err) => err.status(),
+            Self::Http(err) => err.status(),
+            Self::Overlay(err) => err.status(),
+            #[cfg(feature = "archive")]
+            Self::Archive(err) => err.status(),
+        }
+    }
 }
 
 impl walker_common::source::Source for DispatchSource {
@@ -57,6 +92,15 @@ impl Source for DispatchSource {
                 .load_metadata()
                 .await
                 .map_err(DispatchSourceError::Http),
+            Self::Overlay(source) => source
+                .load_metadata()
+                .await
+                .map_err(DispatchSourceError::Overlay),
+            #[cfg(feature = "archive")]
+            Self::Archive(source) => source
+                .load_metadata()
+                .await
+                .map_err(DispatchSourceError::Archive),
         }
     }
 
@@ -73,6 +117,15 @@ impl Source for DispatchSource {
                 .load_index(context)
                 .await
                 .map_err(DispatchSourceError::Http),
+            Self::Overlay(source) => source
+                .load_index(context)
+                .await
+                .map_err(DispatchSourceError::Overlay),
+            #[cfg(feature = "archive")]
+            Self::Archive(source) => source
+                .load_index(context)
+                .await
+                .map_err(DispatchSourceError::Archive),
         }
     }
 
@@ -89,6 +142,40 @@ impl Source for DispatchSource {
                 .load_advisory(advisory)
                 .await
                 .map_err(DispatchSourceError::Http),
+            Self::Overlay(source) => source
+                .load_advisory(advisory)
+                .await
+                .map_err(DispatchSourceError::Overlay),
+            #[cfg(feature = "archive")]
+            Self::Archive(source) => source
+                .load_advisory(advisory)
+                .await
+                .map_err(DispatchSourceError::Archive),
+        }
+    }
+
+    async fn load_digest(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<ExpectedDigest>, Self::Error> {
+        match self {
+            Self::File(source) => source
+                .load_digest(advisory)
+                .await
+                .map_err(DispatchSourceError::File),
+            Self::Http(source) => source
+                .load_digest(advisory)
+                .await
+                .map_err(DispatchSourceError::Http),
+            Self::Overlay(source) => source
+                .load_digest(advisory)
+                .await
+                .map_err(DispatchSourceError::Overlay),
+            #[cfg(feature = "archive")]
+            Self::Archive(source) => source
+                .load_digest(advisory)
+                .await
+                .map_err(DispatchSourceError::Archive),
         }
     }
 }
@@ -106,6 +193,12 @@ impl KeySource for DispatchSource {
                 .load_public_key(key)
                 .await
                 .map_source(|err| err.into()),
+            Self::Overlay(source) => source
+                .load_public_key(key)
+                .await
+                .map_source(|err| err.into()),
+            #[cfg(feature = "archive")]
+            Self::Archive(source) => source.load_public_key(key).await,
         }
     }
 }
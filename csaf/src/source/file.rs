@@ -2,15 +2,20 @@ use crate::{
     discover::DiscoveredAdvisory,
     discover::DistributionContext,
     model::{
-        metadata::{self, ProviderMetadata},
+        metadata::{
+            self, Distribution, MetadataVersion, ProviderMetadata, Publisher, PublisherCategory,
+            Role,
+        },
         store::distribution_base,
     },
     retrieve::RetrievedAdvisory,
     source::Source,
     visitors::store::DIR_METADATA,
 };
-use anyhow::{Context, anyhow};
+use anyhow::{Context, anyhow, bail};
 use bytes::Bytes;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
@@ -21,16 +26,46 @@ use tokio::sync::mpsc;
 use url::Url;
 use walkdir::WalkDir;
 use walker_common::{
+    compression::decompress,
     retrieve::RetrievalMetadata,
-    source::file::{read_sig_and_digests, to_path},
+    source::file::{read_sha384, read_sig_and_digests, to_path},
+    store::ATTR_CONTENT_LENGTH,
     utils::{self, openpgp::PublicKey},
     validate::source::{Key, KeySource, KeySourceError},
 };
 
 #[non_exhaustive]
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FileOptions {
     pub since: Option<SystemTime>,
+    /// Treat the base directory as a flat directory of advisories, without provider metadata.
+    pub rootless: bool,
+    /// Glob patterns of advisories to include, relative to the distribution base. Empty means all.
+    pub include: Vec<String>,
+    /// Glob patterns of advisories to exclude, relative to the distribution base. Takes precedence
+    /// over [`Self::include`].
+    pub exclude: Vec<String>,
+    /// Follow symbolic links, as long as they resolve to a location inside the base directory.
+    ///
+    /// Defaults to `true`.
+    pub follow_symlinks: bool,
+    /// The maximum size of an entry of an [`ArchiveSource`](super::ArchiveSource), in bytes.
+    ///
+    /// Defaults to [`DEFAULT_MAX_DOCUMENT_SIZE`](super::DEFAULT_MAX_DOCUMENT_SIZE).
+    pub max_document_size: Option<u64>,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self {
+            since: None,
+            rootless: false,
+            include: vec![],
+            exclude: vec![],
+            follow_symlinks: true,
+            max_document_size: None,
+        }
+    }
 }
 
 impl FileOptions {
@@ -42,6 +77,131 @@ impl FileOptions {
         self.since = since.into();
         self
     }
+
+    pub fn rootless(mut self, rootless: bool) -> Self {
+        self.rootless = rootless;
+        self
+    }
+
+    pub fn include<I>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.include = globs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn exclude<I>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.exclude = globs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    pub fn max_document_size(mut self, max_document_size: impl Into<Option<u64>>) -> Self {
+        self.max_document_size = max_document_size.into();
+        self
+    }
+}
+
+/// Build a glob set from a list of patterns.
+fn glob_set(globs: &[String]) -> Result<GlobSet, anyhow::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).with_context(|| format!("Invalid glob pattern: {glob}"))?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Filter for advisories, based on their path relative to the distribution base.
+struct PathFilter {
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    fn new(options: &FileOptions) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            include: glob_set(&options.include)?,
+            exclude: glob_set(&options.exclude)?,
+        })
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        !self.exclude.is_match(path) && (self.include.is_empty() || self.include.is_match(path))
+    }
+}
+
+/// File name extensions of compressed advisories, in the order of lookup.
+const COMPRESSED_EXTENSIONS: &[&str] = &["gz", "bz2"];
+
+/// Get the logical advisory name of a file name, stripping a compression extension.
+///
+/// Returns `None` if the file is not a (possibly compressed) advisory.
+fn advisory_name(name: &str) -> Option<&str> {
+    let name = COMPRESSED_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext)?.strip_suffix('.'))
+        .unwrap_or(name);
+
+    name.ends_with(".json").then_some(name)
+}
+
+/// Locate the file storing an advisory, which might be stored compressed.
+pub(crate) fn advisory_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    COMPRESSED_EXTENSIONS.iter().find_map(|ext| {
+        let mut compressed = path.as_os_str().to_owned();
+        compressed.push(".");
+        compressed.push(ext);
+        let compressed = PathBuf::from(compressed);
+        compressed.is_file().then_some(compressed)
+    })
+}
+
+/// Check if a path resolves to a location inside the (canonicalized) base directory.
+fn is_contained(base: &Path, path: &Path) -> bool {
+    fs::canonicalize(path).is_ok_and(|resolved| resolved.starts_with(base))
+}
+
+/// Synthesize provider metadata for a flat directory of advisories, using it as single directory
+/// distribution.
+pub(super) fn rootless_metadata(
+    directory_url: Url,
+    name: String,
+) -> Result<ProviderMetadata, anyhow::Error> {
+    Ok(ProviderMetadata {
+        canonical_url: directory_url.join("provider-metadata.json")?,
+        distributions: vec![Distribution {
+            directory_url: Some(directory_url.clone()),
+            rolie: None,
+            publisher: None,
+        }],
+        last_updated: chrono::Utc::now(),
+        list_on_csaf_aggregators: false,
+        metadata_version: MetadataVersion::V2_0,
+        mirror_on_csaf_aggregators: false,
+        public_openpgp_keys: vec![],
+        publisher: Publisher {
+            category: PublisherCategory::Other,
+            contact_details: None,
+            issuing_authority: None,
+            name,
+            namespace: directory_url.to_string(),
+        },
+        role: Role::Publisher,
+    })
 }
 
 /// A file based source, possibly created by the [`crate::visitors::store::StoreVisitor`].
@@ -63,6 +223,11 @@ impl FileSource {
         })
     }
 
+    /// The path to the storage base, an absolute path.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
     async fn scan_keys(&self) -> Result<Vec<metadata::Key>, anyhow::Error> {
         let dir = self.base.join(DIR_METADATA).join("keys");
 
@@ -96,6 +261,7 @@ impl FileSource {
                     url: Url::from_file_path(&path).map_err(|()| {
                         anyhow!("Failed to build file URL for: {}", path.display())
                     })?,
+                    publisher: None,
                 }),
                 Some((_, _)) | None => {}
             }
@@ -104,6 +270,25 @@ impl FileSource {
         Ok(result)
     }
 
+    /// Synthesize provider metadata for a flat directory, using the base as single directory
+    /// distribution.
+    fn rootless_metadata(&self) -> Result<ProviderMetadata, anyhow::Error> {
+        let directory_url = Url::from_directory_path(&self.base).map_err(|()| {
+            anyhow!(
+                "Failed to convert directory into URL: {}",
+                self.base.display()
+            )
+        })?;
+
+        rootless_metadata(
+            directory_url,
+            self.base
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.base.display().to_string()),
+        )
+    }
+
     /// walk a distribution directory
     fn walk_distribution(
         &self,
@@ -117,11 +302,40 @@ impl FileSource {
             .to_file_path()
             .map_err(|()| anyhow!("Failed to convert into path: {:?}", &context.url()))?;
 
+        let filter = PathFilter::new(&self.options)?;
+        let follow_symlinks = self.options.follow_symlinks;
+        let base = self.base.clone();
+
         tokio::task::spawn_blocking(move || {
-            for entry in WalkDir::new(path).into_iter().filter_entry(|entry| {
-                // if it's a file but doesn't end with .json -> skip it
-                !entry.file_type().is_file()
-                    || entry.file_name().to_string_lossy().ends_with(".json")
+            let walker = WalkDir::new(&path).follow_links(follow_symlinks);
+            for entry in walker.into_iter().filter_entry(|entry| {
+                if entry.path_is_symlink() {
+                    if !follow_symlinks {
+                        log::debug!("Skipping symlink: {}", entry.path().display());
+                        return false;
+                    }
+                    if !is_contained(&base, entry.path()) {
+                        log::warn!(
+                            "Skipping symlink resolving outside of the base directory: {}",
+                            entry.path().display()
+                        );
+                        return false;
+                    }
+                }
+
+                if !entry.file_type().is_file() {
+                    return true;
+                }
+
+                // if it isn't a (compressed) advisory -> skip it
+                let file_name = entry.file_name().to_string_lossy();
+                let Some(name) = advisory_name(&file_name) else {
+                    return false;
+                };
+
+                // match the logical path, relative to the distribution base
+                let relative = entry.path().strip_prefix(&path).unwrap_or(entry.path());
+                filter.matches(&relative.with_file_name(name))
             }) {
                 if let Err(err) = tx.blocking_send(entry) {
                     // channel closed, abort
@@ -143,6 +357,10 @@ impl walker_common::source::Source for FileSource {
 
 impl Source for FileSource {
     async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+        if self.options.rootless {
+            return self.rootless_metadata();
+        }
+
         let metadata = self.base.join(DIR_METADATA).join("provider-metadata.json");
         let file = fs::File::open(&metadata)
             .with_context(|| format!("Failed to open file: {}", metadata.display()))?;
@@ -192,6 +410,10 @@ impl Source for FileSource {
 
         let mut entries = self.walk_distribution(context.clone())?;
         let mut result = vec![];
+        // lowercase name to actual name, for detecting names only differing by case
+        let mut names = HashMap::<String, PathBuf>::new();
+        // logical paths of advisories, for detecting advisories stored with different compression
+        let mut seen = HashSet::<PathBuf>::new();
 
         while let Some(entry) = entries.recv().await {
             let entry = entry?;
@@ -199,27 +421,47 @@ impl Source for FileSource {
             if !path.is_file() {
                 continue;
             }
-            let name = match path.file_name().and_then(|s| s.to_str()) {
-                Some(name) => name,
-                None => continue,
+            let Some(name) = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .and_then(advisory_name)
+            else {
+                continue;
             };
 
-            if !name.ends_with(".json") {
+            let modified = path.metadata()?.modified()?;
+
+            if let Some(since) = self.options.since
+                && modified < since
+            {
+                log::debug!("Skipping file due to modification constraint: {modified:?}");
                 continue;
             }
 
-            if let Some(since) = self.options.since {
-                let modified = path.metadata()?.modified()?;
-                if modified < since {
-                    log::debug!("Skipping file due to modification constraint: {modified:?}");
-                    continue;
-                }
+            // the logical path of the advisory, without a compression extension
+            let logical = path.with_file_name(name);
+
+            if !seen.insert(logical.clone()) {
+                log::warn!(
+                    "Advisory {} is stored multiple times, ignoring: {}",
+                    logical.display(),
+                    path.display()
+                );
+                continue;
             }
 
-            let url = Url::from_file_path(path)
-                .map_err(|()| anyhow!("Failed to convert to URL: {}", path.display()))?;
+            if let Some(existing) =
+                names.insert(logical.to_string_lossy().to_lowercase(), logical.clone())
+            {
+                log::warn!(
+                    "File name {} collides with {}, only differing by case. This will cause data loss when storing on a case-insensitive filesystem.",
+                    path.display(),
+                    existing.display()
+                );
+            }
 
-            let modified = path.metadata()?.modified()?;
+            let url = Url::from_file_path(&logical)
+                .map_err(|()| anyhow!("Failed to convert to URL: {}", logical.display()))?;
 
             result.push(DiscoveredAdvisory {
                 url,
@@ -242,17 +484,54 @@ impl Source for FileSource {
             .to_file_path()
             .map_err(|()| anyhow!("Unable to convert URL into path: {}", discovered.url))?;
 
-        let data = Bytes::from(tokio::fs::read(&path).await?);
+        let file = advisory_file(&path)
+            .ok_or_else(|| anyhow!("Unable to find advisory file: {}", path.display()))?;
+
+        if !is_contained(&self.base, &file) {
+            bail!(
+                "Advisory file resolves outside of the base directory: {}",
+                file.display()
+            );
+        }
+
+        let data = Bytes::from(tokio::fs::read(&file).await?);
+        let data = match file == path {
+            true => data,
+            false => {
+                let name = file.to_string_lossy().into_owned();
+                let data = tokio::task::spawn_blocking(move || decompress(data, &name))
+                    .await?
+                    .with_context(|| format!("Failed to decompress: {}", file.display()))?;
+
+                // if the original length was recorded when storing, it must match
+                if let Some(expected) = fsquirrel::get(&file, ATTR_CONTENT_LENGTH)
+                    .ok()
+                    .flatten()
+                    .and_then(|s| String::from_utf8(s).ok())
+                    .and_then(|s| s.parse::<usize>().ok())
+                    && expected != data.len()
+                {
+                    bail!(
+                        "Decompressed length ({}) doesn't match the recorded one ({expected}): {}",
+                        data.len(),
+                        file.display()
+                    );
+                }
+
+                data
+            }
+        };
 
         let (signature, sha256, sha512) = read_sig_and_digests(&path, &data).await?;
+        let sha384 = read_sha384(&path, &data).await?;
 
-        let last_modification = path
+        let last_modification = file
             .metadata()
             .ok()
             .and_then(|md| md.modified().ok())
             .map(OffsetDateTime::from);
 
-        let etag = fsquirrel::get(&path, walker_common::store::ATTR_ETAG)
+        let etag = fsquirrel::get(&file, walker_common::store::ATTR_ETAG)
             .transpose()
             .and_then(|r| r.ok())
             .and_then(|s| String::from_utf8(s).ok());
@@ -262,10 +541,13 @@ impl Source for FileSource {
             data,
             signature,
             sha256,
+            sha384,
             sha512,
             metadata: RetrievalMetadata {
                 last_modification,
                 etag,
+                not_modified: false,
+                transformed: false,
             },
         })
     }
@@ -285,3 +567,158 @@ impl KeySource for FileSource {
             .map_err(KeySourceError::OpenPgp)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn rootless() {
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        fs::write(
+            dir.path().join("rhsa-2021_3029.json"),
+            include_bytes!("../../test-data/rhsa-2021_3029.json"),
+        )
+        .expect("must write advisory");
+
+        let source = FileSource::new(dir.path(), FileOptions::new().rootless(true))
+            .expect("must create source");
+
+        let metadata = source
+            .load_metadata()
+            .await
+            .expect("must synthesize metadata");
+        assert_eq!(metadata.distributions.len(), 1);
+        let directory_url = metadata.distributions[0]
+            .directory_url
+            .clone()
+            .expect("must have directory distribution");
+
+        let advisories = source
+            .load_index(DistributionContext::Directory(directory_url))
+            .await
+            .expect("must load index");
+        assert_eq!(advisories.len(), 1);
+        assert!(advisories[0].url.path().ends_with("/rhsa-2021_3029.json"));
+    }
+
+    #[tokio::test]
+    async fn compressed() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let data = include_bytes!("../../test-data/rhsa-2021_3029.json");
+
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        let mut encoder = GzEncoder::new(
+            fs::File::create(dir.path().join("rhsa-2021_3029.json.gz")).expect("must create file"),
+            Compression::default(),
+        );
+        encoder.write_all(data).expect("must write advisory");
+        encoder.finish().expect("must finish compression");
+
+        let source = FileSource::new(dir.path(), FileOptions::new().rootless(true))
+            .expect("must create source");
+
+        let advisories = source
+            .load_index(DistributionContext::Directory(
+                Url::from_directory_path(&source.base).expect("must convert into URL"),
+            ))
+            .await
+            .expect("must load index");
+        assert_eq!(advisories.len(), 1);
+        assert!(advisories[0].url.path().ends_with("/rhsa-2021_3029.json"));
+
+        let advisory = source
+            .load_advisory(advisories.into_iter().next().expect("must have advisory"))
+            .await
+            .expect("must load advisory");
+        assert_eq!(&advisory.data[..], &data[..]);
+    }
+
+    #[tokio::test]
+    async fn include_exclude() {
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        for name in ["2024/a.json", "2024/b-deprecated.json", "2023/c.json"] {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().expect("must have parent")).expect("must create dir");
+            fs::write(path, b"{}").expect("must write advisory");
+        }
+
+        let source = FileSource::new(
+            dir.path(),
+            FileOptions::new()
+                .rootless(true)
+                .include(["2024/**"])
+                .exclude(["*-deprecated.json"]),
+        )
+        .expect("must create source");
+
+        let advisories = source
+            .load_index(DistributionContext::Directory(
+                Url::from_directory_path(&source.base).expect("must convert into URL"),
+            ))
+            .await
+            .expect("must load index");
+        assert_eq!(advisories.len(), 1);
+        assert!(advisories[0].url.path().ends_with("/2024/a.json"));
+    }
+
+    #[cfg(unix)]
+    async fn symlinked(follow_symlinks: bool) -> Vec<String> {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        let base = dir.path().join("store");
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(&base).expect("must create dir");
+        fs::create_dir_all(&outside).expect("must create dir");
+
+        fs::write(base.join("a.json"), b"{}").expect("must write advisory");
+        fs::write(outside.join("x.json"), b"{}").expect("must write advisory");
+        symlink(base.join("a.json"), base.join("inside.json")).expect("must create symlink");
+        symlink(outside.join("x.json"), base.join("outside.json")).expect("must create symlink");
+        symlink(&outside, base.join("dir")).expect("must create symlink");
+
+        let source = FileSource::new(
+            &base,
+            FileOptions::new()
+                .rootless(true)
+                .follow_symlinks(follow_symlinks),
+        )
+        .expect("must create source");
+
+        let advisories = source
+            .load_index(DistributionContext::Directory(
+                Url::from_directory_path(&source.base).expect("must convert into URL"),
+            ))
+            .await
+            .expect("must load index");
+
+        let mut names = advisories
+            .iter()
+            .filter_map(|advisory| {
+                advisory
+                    .url
+                    .path_segments()?
+                    .next_back()
+                    .map(str::to_string)
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlinks_rejected() {
+        assert_eq!(symlinked(false).await, ["a.json"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlinks_followed() {
+        assert!(FileOptions::default().follow_symlinks);
+        assert_eq!(symlinked(true).await, ["a.json", "inside.json"]);
+    }
+}
@@ -1,34 +1,98 @@
 use crate::{
     discover::{DiscoveredAdvisory, DistributionContext},
     metadata::{self, MetadataSource},
-    model::metadata::ProviderMetadata,
+    model::{metadata::ProviderMetadata, store::distribution_base},
     retrieve::RetrievedAdvisory,
-    rolie::{RolieSource, SourceFile},
-    source::Source,
+    rolie::{self, RolieSource, SourceFile},
+    source::{ExpectedDigest, Source, SourceError, file::advisory_file},
 };
+use async_trait::async_trait;
 use bytes::{BufMut, Bytes, BytesMut};
 use digest::Digest;
 use futures::try_join;
-use reqwest::Response;
-use sha2::{Sha256, Sha512};
-use std::{sync::Arc, time::SystemTime};
+use parking_lot::Mutex;
+use reqwest::{
+    RequestBuilder, Response, StatusCode, header,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use sha2::{Sha256, Sha384, Sha512};
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    fmt::Debug,
+    num::{NonZeroU32, NonZeroUsize},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 use time::{OffsetDateTime, format_description::well_known::Rfc2822};
+use tokio::io::AsyncWriteExt;
 use url::{ParseError, Url};
 use walker_common::utils::url::ensure_slash;
 use walker_common::{
     changes::{self, ChangeEntry, ChangeSource},
+    compression::decompress,
     fetcher::{self, DataProcessor, Fetcher},
-    retrieve::{RetrievalMetadata, RetrievedDigest, RetrievingDigest},
+    metrics::Metrics,
+    retrieve::{
+        DigestFormatError, RetrievalMetadata, RetrievedDigest, RetrievingDigest, parse_digest,
+    },
+    store::ATTR_ETAG,
     utils::openpgp::PublicKey,
     validate::source::{Key, KeySource, KeySourceError},
 };
 
+/// The digests to retrieve for an advisory.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DigestPreference {
+    /// Retrieve the SHA-256 digest, falling back to SHA-512 and then SHA-384 if it's missing.
+    Sha256,
+    /// Retrieve the SHA-512 digest, falling back to SHA-256 and then SHA-384 if it's missing.
+    Sha512,
+    /// Retrieve the SHA-256 and SHA-512 digests, falling back to SHA-384 if both are missing.
+    #[default]
+    Sha256AndSha512,
+}
+
 #[non_exhaustive]
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct HttpOptions {
     pub since: Option<SystemTime>,
+    /// The base directory of previously stored documents.
+    ///
+    /// If set, advisories will be requested conditionally, using the ETag and modification
+    /// timestamp of the stored document. If the advisory was not modified, the stored content is
+    /// used and marked as "not modified".
+    pub conditional: Option<PathBuf>,
+    /// The digests to retrieve.
+    pub preferred_digest: DigestPreference,
+    /// Additional headers, sent with every request.
+    pub headers: HeaderMap,
+    /// Equivalent base URLs, serving the same tree, tried in order when a host is unavailable.
+    pub mirrors: Vec<Url>,
+    /// The maximum number of requests per second.
+    pub rate_limit: Option<NonZeroU32>,
+    /// The maximum number of requests in flight, across all advisories.
+    pub max_requests: Option<NonZeroUsize>,
+    /// The maximum number of pages loaded of a paginated ROLIE feed.
+    ///
+    /// Defaults to [`rolie::DEFAULT_MAX_PAGES`].
+    pub max_feed_pages: Option<usize>,
+    /// The maximum duration of retrieving a single advisory, including its digests and signature.
+    pub per_advisory_timeout: Option<Duration>,
+    /// Fail if a `changes.csv` index lists a file more than once.
+    pub strict_changes: bool,
+    /// The maximum size of an advisory document, in bytes.
+    ///
+    /// Defaults to [`DEFAULT_MAX_DOCUMENT_SIZE`].
+    pub max_document_size: Option<u64>,
+    /// Fail if an advisory document isn't served as JSON (or gzip), instead of only reporting it.
+    pub strict_content_type: bool,
 }
 
+/// The default maximum size of an advisory document: 256 MiB
+pub const DEFAULT_MAX_DOCUMENT_SIZE: u64 = 256 * 1024 * 1024;
+
 impl HttpOptions {
     pub fn new() -> Self {
         Self::default()
@@ -38,13 +102,179 @@ impl HttpOptions {
         self.since = since.into();
         self
     }
+
+    pub fn conditional(mut self, conditional: impl Into<Option<PathBuf>>) -> Self {
+        self.conditional = conditional.into();
+        self
+    }
+
+    pub fn preferred_digest(mut self, preferred_digest: DigestPreference) -> Self {
+        self.preferred_digest = preferred_digest;
+        self
+    }
+
+    /// Add a header, which will be sent with every request (including digests and signatures).
+    ///
+    /// Sensitive values, like API keys, should be marked using [`HeaderValue::set_sensitive`], so
+    /// that they don't get logged.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Set equivalent base URLs, serving the same tree, including the primary one.
+    ///
+    /// If a request below one of the bases fails with a connection or server error, the same
+    /// relative path is requested from the following bases. This includes loading the metadata.
+    pub fn mirrors(mut self, mirrors: impl IntoIterator<Item = Url>) -> Self {
+        self.mirrors = mirrors.into_iter().collect();
+        self
+    }
+
+    /// Limit the number of requests per second, for all requests of the source.
+    ///
+    /// The limit is shared by all tasks of a parallel walk, and applies in addition to its
+    /// concurrency limit: the concurrency limit bounds the number of requests in flight, while
+    /// the rate limit bounds how many get started per second. Whichever is stricter wins.
+    pub fn rate_limit(mut self, requests_per_second: impl Into<Option<NonZeroU32>>) -> Self {
+        self.rate_limit = requests_per_second.into();
+        self
+    }
+
+    /// Limit the number of requests in flight, for all requests of the source.
+    ///
+    /// Retrieving an advisory first fetches its signature and digests concurrently, and then the
+    /// document itself. With [`DigestPreference::Sha256AndSha512`] that's up to three requests in
+    /// flight per advisory, with a single preferred digest it's two. So walking `n` advisories in
+    /// parallel may have up to `3 * n` (or `2 * n`) requests in flight. This limit is shared by
+    /// all of them, including loading the metadata and indexes, bounding the requests in flight
+    /// to `min(max_requests, 3 * n)`.
+    ///
+    /// A limit below the requests per advisory is valid, the requests of an advisory are then
+    /// queued up, in addition to the ones of other advisories.
+    pub fn max_requests(mut self, max_requests: impl Into<Option<NonZeroUsize>>) -> Self {
+        self.max_requests = max_requests.into();
+        self
+    }
+
+    /// Set the maximum number of pages to load of a paginated ROLIE feed.
+    ///
+    /// Pages are discovered by following the `next` and `previous` links of a feed. Once the
+    /// limit is reached, the remaining pages are ignored, and a warning is logged.
+    pub fn max_feed_pages(mut self, max_feed_pages: impl Into<Option<usize>>) -> Self {
+        self.max_feed_pages = max_feed_pages.into();
+        self
+    }
+
+    /// Limit the duration of retrieving a single advisory.
+    ///
+    /// This covers the document, its digests, and its signature, in addition to the timeout of
+    /// each individual request. If the limit is exceeded, retrieving the advisory fails with
+    /// [`HttpSourceError::Timeout`].
+    pub fn per_advisory_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.per_advisory_timeout = timeout.into();
+        self
+    }
+
+    /// Fail loading a `changes.csv` index, if it lists the same file more than once.
+    ///
+    /// By default, duplicate entries are collapsed into one, keeping the most recent timestamp,
+    /// and a warning is logged.
+    pub fn strict_changes(mut self, strict_changes: bool) -> Self {
+        self.strict_changes = strict_changes;
+        self
+    }
+
+    /// Limit the size of an advisory document, in bytes.
+    ///
+    /// Retrieving a larger document fails with [`HttpSourceError::TooLarge`], as soon as its
+    /// declared length, or the content received so far, exceeds the limit. This guards against
+    /// buffering huge responses of misbehaving servers.
+    pub fn max_document_size(mut self, max_document_size: impl Into<Option<u64>>) -> Self {
+        self.max_document_size = max_document_size.into();
+        self
+    }
+
+    /// Fail retrieving an advisory document which isn't served as JSON.
+    ///
+    /// A response with a `Content-Type` other than JSON (`application/json`, or a `+json` type)
+    /// or gzip is most likely an error page. By default, the content is processed anyway, and a
+    /// warning is logged for the first of such documents of each distribution. If strict,
+    /// retrieving the advisory fails with [`HttpSourceError::ContentType`] before reading the
+    /// content. A response without a `Content-Type` is always accepted.
+    pub fn strict_content_type(mut self, strict_content_type: bool) -> Self {
+        self.strict_content_type = strict_content_type;
+        self
+    }
 }
 
+/// Reads the content of an advisory from its response.
+///
+/// The default implementation ([`BufferingBodyReader`]) collects the content in memory. Other
+/// implementations may e.g. stream the content to a file, returning a memory mapped view of it
+/// (see [`Bytes::from_owner`]).
+#[async_trait(?Send)]
+pub trait BodyReader: Debug {
+    /// Read the content, passing each chunk to `inspect` (calculating the digests) as it arrives.
+    ///
+    /// Once `inspect` breaks (e.g. when the content exceeds the maximum size), reading must stop.
+    /// The content returned in that case is discarded.
+    async fn read(
+        &self,
+        response: Response,
+        inspect: &mut dyn for<'a> FnMut(&'a [u8]) -> ControlFlow<()>,
+    ) -> Result<Bytes, BodyReadError>;
+}
+
+/// An error reading the content of an advisory.
+#[derive(Debug, thiserror::Error)]
+pub enum BodyReadError {
+    /// Receiving the content failed. The request will be retried.
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    /// Handling the received content failed. The request will not be retried.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Reads the content of an advisory into memory.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BufferingBodyReader;
+
+#[async_trait(?Send)]
+impl BodyReader for BufferingBodyReader {
+    async fn read(
+        &self,
+        mut response: Response,
+        inspect: &mut dyn for<'a> FnMut(&'a [u8]) -> ControlFlow<()>,
+    ) -> Result<Bytes, BodyReadError> {
+        let mut data = BytesMut::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            if inspect(&chunk).is_break() {
+                break;
+            }
+            data.put(chunk);
+        }
+
+        Ok(data.freeze())
+    }
+}
+
+/// A source, retrieving advisories from a remote provider.
+///
+/// The transport encoding of responses (`Content-Encoding`, like `gzip`) is decoded
+/// transparently. So the content of an advisory, and the digests calculated over it, are the
+/// bytes of the published document, which its digest files (like `.sha256`) describe. Documents
+/// published as compressed files are not affected.
 #[derive(Clone, Debug)]
 pub struct HttpSource {
     fetcher: Fetcher,
     metadata_source: Arc<dyn MetadataSource>,
     options: HttpOptions,
+    body_reader: Arc<dyn BodyReader>,
+    /// Distributions which were already reported to serve documents not as JSON
+    content_type_warnings: Arc<Mutex<HashSet<Url>>>,
 }
 
 impl HttpSource {
@@ -53,12 +283,34 @@ impl HttpSource {
         fetcher: Fetcher,
         options: HttpOptions,
     ) -> Self {
+        let mut fetcher = fetcher.with_headers(options.headers.clone());
+        if !options.mirrors.is_empty() {
+            fetcher = fetcher.with_mirrors(options.mirrors.clone());
+        }
+        if let Some(rate_limit) = options.rate_limit {
+            fetcher = fetcher.with_rate_limit(rate_limit);
+        }
+        if let Some(max_requests) = options.max_requests {
+            fetcher = fetcher.with_max_requests(max_requests);
+        }
+
         Self {
             metadata_source: Arc::new(metadata),
             fetcher,
             options,
+            body_reader: Arc::new(BufferingBodyReader),
+            content_type_warnings: Default::default(),
         }
     }
+
+    /// Set the reader of the content of advisories, replacing the [`BufferingBodyReader`].
+    ///
+    /// This only applies to the advisory documents, digests and signatures are still read into
+    /// memory.
+    pub fn with_body_reader(mut self, body_reader: impl BodyReader + 'static) -> Self {
+        self.body_reader = Arc::new(body_reader);
+        self
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -73,6 +325,33 @@ pub enum HttpSourceError {
     Csv(#[from] csv::Error),
     #[error("JSON parse error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Timeout retrieving advisory after {timeout:?}: {url}")]
+    Timeout { url: Url, timeout: Duration },
+    #[error("Invalid digest file of {url}: {err}")]
+    DigestFormat { url: Url, err: DigestFormatError },
+    #[error("Truncated download, expected {expected} bytes, but received {actual}: {url}")]
+    TruncatedDownload {
+        url: Url,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("Index lists {duplicates} duplicate entries: {url}")]
+    DuplicateEntries { url: Url, duplicates: usize },
+    #[error("Document exceeds the maximum size of {limit} bytes: {url}")]
+    TooLarge { url: Url, limit: u64 },
+    #[error("Document is served as '{content_type}', instead of JSON: {url}")]
+    ContentType { url: Url, content_type: String },
+}
+
+impl SourceError for HttpSourceError {
+    fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Fetcher(err) => err.status(),
+            _ => None,
+        }
+    }
 }
 
 impl From<changes::Error> for HttpSourceError {
@@ -95,81 +374,151 @@ impl Source for HttpSource {
         Ok(self.metadata_source.load_metadata(&self.fetcher).await?)
     }
 
+    async fn load_digest(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<ExpectedDigest>, Self::Error> {
+        let format_error = |err| HttpSourceError::DigestFormat {
+            url: advisory.url.clone(),
+            err,
+        };
+
+        // prefer the digest referenced by the index, e.g. a ROLIE feed
+        if advisory
+            .digest
+            .as_ref()
+            .is_some_and(|digest| digest.as_str().ends_with(".sha512"))
+        {
+            return self
+                .fetch_digest(advisory, "sha512")
+                .await?
+                .map(|digest| parse_digest::<Sha512>(&digest).map(ExpectedDigest::Sha512))
+                .transpose()
+                .map_err(format_error);
+        }
+
+        self.fetch_digest(advisory, "sha256")
+            .await?
+            .map(|digest| parse_digest::<Sha256>(&digest).map(ExpectedDigest::Sha256))
+            .transpose()
+            .map_err(format_error)
+    }
+
     async fn load_index(
         &self,
         context: DistributionContext,
     ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
         let discover_context = Arc::new(context);
 
-        // filter out advisories based on since, but only if we can be sure
-        let since_filter = |advisory: &Result<_, _>| match (advisory, &self.options.since) {
-            (
-                Ok(DiscoveredAdvisory {
-                    url: _,
-                    context: _,
-                    digest: _,
-                    signature: _,
-                    modified,
-                }),
-                Some(since),
-            ) => modified >= since,
-            _ => true,
+        // filter out advisories based on since
+        let since_filter = |advisory: &DiscoveredAdvisory| {
+            self.options
+                .since
+                .is_none_or(|since| advisory.modified >= since)
         };
 
-        match discover_context.as_ref() {
+        // entries which can't be resolved to a URL, reported instead of failing the whole index
+        let mut invalid = vec![];
+
+        match discover_context.distribution() {
             DistributionContext::Directory(base) => {
                 let base = ensure_slash(base.clone());
                 let changes = ChangeSource::retrieve(&self.fetcher, &base).await?;
 
-                Ok(changes
-                    .entries
+                // collapse duplicate entries, keeping the first position and the most recent timestamp
+                let mut entries = Vec::<(Url, SystemTime)>::new();
+                let mut positions = HashMap::new();
+                let mut duplicates = 0;
+
+                for ChangeEntry { file, timestamp } in changes.entries {
+                    let Some(url) = resolve(&base, file, &mut invalid) else {
+                        continue;
+                    };
+                    let modified: SystemTime = timestamp.into();
+
+                    match positions.entry(url.clone()) {
+                        Entry::Occupied(position) => {
+                            duplicates += 1;
+                            let (_, existing) = &mut entries[*position.get()];
+                            *existing = modified.max(*existing);
+                        }
+                        Entry::Vacant(position) => {
+                            position.insert(entries.len());
+                            entries.push((url, modified));
+                        }
+                    }
+                }
+
+                report_invalid(&base, &invalid);
+
+                if duplicates > 0 {
+                    if self.options.strict_changes {
+                        return Err(HttpSourceError::DuplicateEntries {
+                            url: base,
+                            duplicates,
+                        });
+                    }
+                    log::warn!("Collapsed {duplicates} duplicate entries of index: {base}");
+                }
+
+                Ok(entries
                     .into_iter()
-                    .map(|ChangeEntry { file, timestamp }| {
-                        let modified = timestamp.into();
-                        let url = base.join(&file)?;
-
-                        Ok::<_, ParseError>(DiscoveredAdvisory {
-                            context: discover_context.clone(),
-                            url,
-                            modified,
-                            signature: None,
-                            digest: None,
-                        })
+                    .map(|(url, modified)| DiscoveredAdvisory {
+                        context: discover_context.clone(),
+                        url,
+                        modified,
+                        signature: None,
+                        digest: None,
                     })
                     .filter(since_filter)
-                    .collect::<Result<_, _>>()?)
+                    .collect())
             }
 
-            DistributionContext::Feed(feed) => {
-                let source_files = RolieSource::retrieve(&self.fetcher, feed.clone()).await?;
-                Ok(source_files
+            DistributionContext::Feed { url: feed, .. } => {
+                let source_files = RolieSource::retrieve(
+                    &self.fetcher,
+                    feed.clone(),
+                    self.options
+                        .max_feed_pages
+                        .unwrap_or(rolie::DEFAULT_MAX_PAGES),
+                )
+                .await?;
+
+                let advisories = source_files
                     .files
                     .into_iter()
-                    .map(
+                    .filter_map(
                         |SourceFile {
                              file,
                              timestamp,
                              digest,
                              signature,
                          }| {
-                            let modified = timestamp.into();
-                            let url = Url::parse(&file)?;
-                            let digest = digest.map(|digest| Url::parse(&digest)).transpose()?;
+                            let url = resolve(feed, file, &mut invalid)?;
+                            // without a valid companion URL, the conventional name will be used
+                            let digest =
+                                digest.and_then(|digest| resolve(feed, digest, &mut invalid));
                             let signature = signature
-                                .map(|signature| Url::parse(&signature))
-                                .transpose()?;
+                                .and_then(|signature| resolve(feed, signature, &mut invalid));
 
-                            Ok::<_, ParseError>(DiscoveredAdvisory {
+                            Some(DiscoveredAdvisory {
                                 context: discover_context.clone(),
                                 url,
                                 digest,
                                 signature,
-                                modified,
+                                modified: timestamp.into(),
                             })
                         },
                     )
                     .filter(since_filter)
-                    .collect::<Result<_, _>>()?)
+                    .collect();
+
+                report_invalid(feed, &invalid);
+                Ok(advisories)
+            }
+
+            DistributionContext::Aggregated { .. } => {
+                unreachable!("aggregated distributions are unwrapped by `distribution()`")
             }
         }
     }
@@ -178,83 +527,494 @@ impl Source for HttpSource {
         &self,
         discovered: DiscoveredAdvisory,
     ) -> Result<RetrievedAdvisory, Self::Error> {
-        let digest_result = try_join!(
-            async {
-                // If we have a signature source, use it. Otherwise, guess.
-                match discovered.signature.clone() {
-                    Some(signature) => self.fetcher.fetch::<Option<String>>(signature).await,
-                    None => {
-                        self.fetcher
-                            .fetch::<Option<String>>(format!("{url}.asc", url = discovered.url))
-                            .await
-                    }
-                }
-            },
-            async {
-                match discovered.digest.clone() {
-                    Some(digest) if digest.as_str().ends_with(".sha256") => {
-                        self.fetcher.fetch::<Option<String>>(digest).await
-                    }
-                    Some(_) => Ok(None),
-                    None => {
-                        self.fetcher
-                            .fetch::<Option<String>>(format!("{url}.sha256", url = discovered.url))
-                            .await
-                    }
-                }
-            },
-            async {
-                match discovered.digest.clone() {
-                    Some(digest) if digest.as_str().ends_with(".sha512") => {
-                        self.fetcher.fetch::<Option<String>>(digest).await
-                    }
-                    Some(_) => Ok(None),
-                    None => {
-                        self.fetcher
-                            .fetch::<Option<String>>(format!("{url}.sha512", url = discovered.url))
-                            .await
-                    }
-                }
-            },
-        );
+        let Some(timeout) = self.options.per_advisory_timeout else {
+            return self.fetch_advisory(discovered).await;
+        };
 
-        let (signature, sha256, sha512) = digest_result.map_err(HttpSourceError::Fetcher)?;
+        let url = discovered.url.clone();
+        tokio::time::timeout(timeout, self.fetch_advisory(discovered))
+            .await
+            .map_err(|_| HttpSourceError::Timeout { url, timeout })?
+    }
+}
 
-        let sha256 = sha256
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
-                current: Sha256::new(),
-            });
-        let sha512 = sha512
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
-                current: Sha512::new(),
-            });
+/// Resolve an entry of an index, which may be relative to the index, recording it if it is invalid.
+fn resolve(base: &Url, entry: String, invalid: &mut Vec<(String, ParseError)>) -> Option<Url> {
+    match base.join(&entry) {
+        Ok(url) => Some(url),
+        Err(err) => {
+            invalid.push((entry, err));
+            None
+        }
+    }
+}
+
+/// Report the entries of an index which were ignored, as they are invalid.
+fn report_invalid(index: &Url, invalid: &[(String, ParseError)]) {
+    if invalid.is_empty() {
+        return;
+    }
+
+    log::warn!(
+        "Ignoring {} invalid entries of index: {index}",
+        invalid.len()
+    );
+    for (entry, err) in invalid {
+        log::warn!("  {entry}: {err}");
+    }
+}
+
+impl HttpSource {
+    /// Retrieve an advisory, along with its digests and signature.
+    async fn fetch_advisory(
+        &self,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<RetrievedAdvisory, HttpSourceError> {
+        let Companions {
+            signature,
+            sha256,
+            sha384,
+            sha512,
+        } = self.fetch_companions(&discovered).await?;
+
+        let stored = match self.stored_path(&discovered) {
+            // the stored advisory might be compressed
+            Some(path) => tokio::task::spawn_blocking(move || advisory_file(&path))
+                .await
+                .ok()
+                .flatten(),
+            None => None,
+        };
+        let previous = match &stored {
+            Some(path) => PreviousRetrieval::load(path).await?,
+            None => None,
+        };
 
         let advisory = self
             .fetcher
             .fetch_processed(
                 discovered.url.clone(),
-                FetchingRetrievedAdvisory { sha256, sha512 },
+                FetchingRetrievedAdvisory {
+                    sha256: sha256.clone(),
+                    sha384: sha384.clone(),
+                    sha512: sha512.clone(),
+                    previous: previous.clone(),
+                    body_reader: self.body_reader.clone(),
+                    max_size: self.max_document_size(),
+                    strict_content_type: self.options.strict_content_type,
+                },
             )
-            .await?;
+            .await??;
+
+        let advisory = match (advisory.metadata.not_modified, stored, previous) {
+            (true, Some(path), Some(previous)) => {
+                log::debug!("Not modified, using stored document: {}", path.display());
+                FetchedRetrievedAdvisory::from_stored(&path, previous, sha256, sha384, sha512)
+                    .await?
+            }
+            _ => advisory,
+        };
+
+        if let Some(content_type) = &advisory.content_type {
+            self.report_content_type(&discovered, content_type);
+        }
+
+        if let Err((expected, actual)) = advisory.check_length() {
+            return Err(HttpSourceError::TruncatedDownload {
+                url: discovered.url,
+                expected,
+                actual,
+            });
+        }
+
+        if let Some(metrics) = self.fetcher.metrics() {
+            advisory.report(metrics, &discovered.url);
+        }
 
         Ok(advisory.into_retrieved(discovered, signature))
     }
+
+    /// Retrieve an advisory, streaming its content to a file instead of into memory.
+    ///
+    /// The digests are calculated while writing the content, but not validated. The file is
+    /// created, or truncated, for every attempt of retrieving the advisory. Conditional requests
+    /// are not used, the advisory is always retrieved.
+    pub async fn stream_advisory(
+        &self,
+        discovered: DiscoveredAdvisory,
+        file: &Path,
+    ) -> Result<StreamedAdvisory, HttpSourceError> {
+        let Some(timeout) = self.options.per_advisory_timeout else {
+            return self.fetch_streamed_advisory(discovered, file).await;
+        };
+
+        let url = discovered.url.clone();
+        tokio::time::timeout(timeout, self.fetch_streamed_advisory(discovered, file))
+            .await
+            .map_err(|_| HttpSourceError::Timeout { url, timeout })?
+    }
+
+    async fn fetch_streamed_advisory(
+        &self,
+        discovered: DiscoveredAdvisory,
+        file: &Path,
+    ) -> Result<StreamedAdvisory, HttpSourceError> {
+        let Companions {
+            signature,
+            sha256,
+            sha384,
+            sha512,
+        } = self.fetch_companions(&discovered).await?;
+
+        let body = self
+            .fetcher
+            .fetch_processed(
+                discovered.url.clone(),
+                StreamingRetrievedAdvisory {
+                    sha256,
+                    sha384,
+                    sha512,
+                    file,
+                    max_size: self.max_document_size(),
+                    strict_content_type: self.options.strict_content_type,
+                },
+            )
+            .await??;
+
+        if let Some(content_type) = &body.content_type {
+            self.report_content_type(&discovered, content_type);
+        }
+
+        if let Some(expected) = body.content_length
+            && expected != body.size
+        {
+            return Err(HttpSourceError::TruncatedDownload {
+                url: discovered.url,
+                expected,
+                actual: body.size,
+            });
+        }
+
+        if let Some(metrics) = self.fetcher.metrics() {
+            metrics.bytes_received(&discovered.url, body.size);
+        }
+
+        Ok(StreamedAdvisory {
+            discovered,
+            signature,
+            sha256: body.sha256,
+            sha384: body.sha384,
+            sha512: body.sha512,
+            metadata: body.metadata,
+            size: body.size,
+        })
+    }
+
+    /// Report an advisory which isn't served as JSON.
+    ///
+    /// Providers tend to serve all documents of a distribution the same way, so this only warns
+    /// once per distribution.
+    fn report_content_type(&self, discovered: &DiscoveredAdvisory, content_type: &str) {
+        let first = self
+            .content_type_warnings
+            .lock()
+            .insert(discovered.context.url().clone());
+
+        if first {
+            log::warn!(
+                "Document is served as '{content_type}', instead of JSON: {} (further documents of the distribution are logged at debug level)",
+                discovered.url
+            );
+        } else {
+            log::debug!(
+                "Document is served as '{content_type}', instead of JSON: {}",
+                discovered.url
+            );
+        }
+    }
+
+    fn max_document_size(&self) -> u64 {
+        self.options
+            .max_document_size
+            .unwrap_or(DEFAULT_MAX_DOCUMENT_SIZE)
+    }
+
+    /// Fetch the signature and digests of an advisory.
+    async fn fetch_companions(
+        &self,
+        discovered: &DiscoveredAdvisory,
+    ) -> Result<Companions, HttpSourceError> {
+        let signature = async {
+            // If we have a signature source, use it. Otherwise, guess.
+            match discovered.signature.clone() {
+                Some(signature) => self.fetcher.fetch::<Option<String>>(signature).await,
+                None => {
+                    self.fetcher
+                        .fetch::<Option<String>>(format!("{url}.asc", url = discovered.url))
+                        .await
+                }
+            }
+        };
+
+        let (signature, sha256, sha384, sha512) = match self.options.preferred_digest {
+            DigestPreference::Sha256AndSha512 => {
+                let (signature, sha256, sha512) = try_join!(
+                    signature,
+                    self.fetch_digest(discovered, "sha256"),
+                    self.fetch_digest(discovered, "sha512"),
+                )?;
+                let sha384 = match (&sha256, &sha512) {
+                    (None, None) => self.fetch_digest(discovered, "sha384").await?,
+                    _ => None,
+                };
+                (signature, sha256, sha384, sha512)
+            }
+            DigestPreference::Sha256 => {
+                let (signature, sha256) =
+                    try_join!(signature, self.fetch_digest(discovered, "sha256"))?;
+                let (sha384, sha512) = match sha256 {
+                    Some(_) => (None, None),
+                    None => self.fetch_fallback_digest(discovered, "sha512").await?,
+                };
+                (signature, sha256, sha384, sha512)
+            }
+            DigestPreference::Sha512 => {
+                let (signature, sha512) =
+                    try_join!(signature, self.fetch_digest(discovered, "sha512"))?;
+                let (sha384, sha256) = match sha512 {
+                    Some(_) => (None, None),
+                    None => self.fetch_fallback_digest(discovered, "sha256").await?,
+                };
+                (signature, sha256, sha384, sha512)
+            }
+        };
+
+        let sha256 = retrieving_digest::<Sha256>(discovered, sha256)?;
+        let sha384 = retrieving_digest::<Sha384>(discovered, sha384)?;
+        let sha512 = retrieving_digest::<Sha512>(discovered, sha512)?;
+
+        Ok(Companions {
+            signature,
+            sha256,
+            sha384,
+            sha512,
+        })
+    }
+
+    /// Fetch a digest of an advisory, by its file extension (e.g. `sha256`).
+    ///
+    /// If the discovered advisory references a digest, only that digest is fetched. Otherwise, the
+    /// location of the digest is guessed.
+    async fn fetch_digest(
+        &self,
+        discovered: &DiscoveredAdvisory,
+        extension: &str,
+    ) -> Result<Option<String>, fetcher::Error> {
+        match discovered.digest.clone() {
+            Some(digest) if digest.as_str().ends_with(&format!(".{extension}")) => {
+                self.fetcher.fetch::<Option<String>>(digest).await
+            }
+            Some(_) => Ok(None),
+            None => {
+                self.fetcher
+                    .fetch::<Option<String>>(format!("{url}.{extension}", url = discovered.url))
+                    .await
+            }
+        }
+    }
+
+    /// Fetch the digest of a fallback algorithm, if the preferred one is missing.
+    ///
+    /// Returns the SHA-384 digest, and the digest of the fallback algorithm. The SHA-384 digest is
+    /// only requested if the fallback digest is missing too.
+    async fn fetch_fallback_digest(
+        &self,
+        discovered: &DiscoveredAdvisory,
+        extension: &str,
+    ) -> Result<(Option<String>, Option<String>), fetcher::Error> {
+        match self.fetch_digest(discovered, extension).await? {
+            Some(fallback) => Ok((None, Some(fallback))),
+            None => Ok((self.fetch_digest(discovered, "sha384").await?, None)),
+        }
+    }
+
+    /// The path of the stored document, if conditional requests are enabled.
+    fn stored_path(&self, discovered: &DiscoveredAdvisory) -> Option<PathBuf> {
+        let base = self.options.conditional.as_ref()?;
+        let name = discovered.context.url().make_relative(&discovered.url)?;
+        Some(distribution_base(base, discovered.context.url().as_str()).join(name))
+    }
+}
+
+/// An advisory, which was streamed to a file, instead of being retrieved into memory.
+#[derive(Clone, Debug)]
+pub struct StreamedAdvisory {
+    /// The discovered advisory
+    pub discovered: DiscoveredAdvisory,
+    /// Signature data
+    pub signature: Option<String>,
+
+    /// SHA-256 digest
+    pub sha256: Option<RetrievedDigest<Sha256>>,
+    /// SHA-384 digest
+    pub sha384: Option<RetrievedDigest<Sha384>>,
+    /// SHA-512 digest
+    pub sha512: Option<RetrievedDigest<Sha512>>,
+
+    /// Metadata from the retrieval process
+    pub metadata: RetrievalMetadata,
+    /// The number of bytes written to the file
+    pub size: u64,
+}
+
+/// The signature of an advisory, and the digests to calculate while retrieving it.
+struct Companions {
+    signature: Option<String>,
+    sha256: Option<RetrievingDigest<Sha256>>,
+    sha384: Option<RetrievingDigest<Sha384>>,
+    sha512: Option<RetrievingDigest<Sha512>>,
+}
+
+/// Prepare a digest for being calculated while retrieving the document.
+fn retrieving_digest<D: Digest>(
+    discovered: &DiscoveredAdvisory,
+    expected: Option<String>,
+) -> Result<Option<RetrievingDigest<D>>, HttpSourceError> {
+    expected
+        .map(|expected| {
+            Ok(RetrievingDigest {
+                expected: parse_digest::<D>(&expected).map_err(|err| {
+                    HttpSourceError::DigestFormat {
+                        url: discovered.url.clone(),
+                        err,
+                    }
+                })?,
+                current: D::new(),
+            })
+        })
+        .transpose()
+}
+
+/// The state of a previously retrieved document, used for conditional requests.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreviousRetrieval {
+    pub etag: Option<String>,
+    pub last_modification: Option<SystemTime>,
+}
+
+impl PreviousRetrieval {
+    /// Load the state from a stored document, returns `None` if it doesn't exist.
+    ///
+    /// The path must be the one of the stored file, which might be a compressed variant.
+    async fn load(path: &Path) -> Result<Option<Self>, std::io::Error> {
+        if !tokio::fs::try_exists(path).await? {
+            return Ok(None);
+        }
+
+        let last_modification = tokio::fs::metadata(path).await?.modified().ok();
+        let etag = fsquirrel::get(path, ATTR_ETAG)
+            .transpose()
+            .and_then(|r| r.ok())
+            .and_then(|s| String::from_utf8(s).ok());
+
+        Ok(Some(Self {
+            etag,
+            last_modification,
+        }))
+    }
 }
 
 pub struct FetchedRetrievedAdvisory {
     data: Bytes,
     sha256: Option<RetrievedDigest<Sha256>>,
+    sha384: Option<RetrievedDigest<Sha384>>,
     sha512: Option<RetrievedDigest<Sha512>>,
     metadata: RetrievalMetadata,
+    /// The length of the content, as declared by the server
+    content_length: Option<u64>,
+    /// The content type, if the content isn't served as JSON
+    content_type: Option<String>,
 }
 
 impl FetchedRetrievedAdvisory {
+    /// Use the stored content of a document which was not modified.
+    async fn from_stored(
+        path: &Path,
+        previous: PreviousRetrieval,
+        mut sha256: Option<RetrievingDigest<Sha256>>,
+        mut sha384: Option<RetrievingDigest<Sha384>>,
+        mut sha512: Option<RetrievingDigest<Sha512>>,
+    ) -> Result<Self, std::io::Error> {
+        let data = Bytes::from(tokio::fs::read(path).await?);
+        let data = match path.extension().is_some_and(|ext| ext == "json") {
+            true => data,
+            false => {
+                // digests are calculated over the uncompressed content
+                let name = path.to_string_lossy().into_owned();
+                tokio::task::spawn_blocking(move || decompress(data, &name))
+                    .await
+                    .map_err(std::io::Error::other)?
+                    .map_err(std::io::Error::other)?
+            }
+        };
+
+        if let Some(d) = &mut sha256 {
+            d.update(&data);
+        }
+        if let Some(d) = &mut sha384 {
+            d.update(&data);
+        }
+        if let Some(d) = &mut sha512 {
+            d.update(&data);
+        }
+
+        Ok(Self {
+            data,
+            sha256: sha256.map(|d| d.into()),
+            sha384: sha384.map(|d| d.into()),
+            sha512: sha512.map(|d| d.into()),
+            metadata: RetrievalMetadata {
+                last_modification: previous.last_modification.map(OffsetDateTime::from),
+                etag: previous.etag,
+                not_modified: true,
+                transformed: false,
+            },
+            content_length: None,
+            content_type: None,
+        })
+    }
+
+    /// Check that the received content has the declared length.
+    ///
+    /// Returns the expected and actual length in case of a mismatch.
+    fn check_length(&self) -> Result<(), (u64, u64)> {
+        let actual = self.data.len() as u64;
+        match self.content_length {
+            Some(expected) if expected != actual => Err((expected, actual)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Report the outcome of retrieving the advisory.
+    fn report(&self, metrics: &dyn Metrics, url: &Url) {
+        if self.metadata.not_modified {
+            metrics.not_modified(url);
+        } else {
+            metrics.bytes_received(url, self.data.len() as u64);
+        }
+
+        let digests = [
+            ("sha256", self.sha256.as_ref().map(|d| d.validate().is_ok())),
+            ("sha384", self.sha384.as_ref().map(|d| d.validate().is_ok())),
+            ("sha512", self.sha512.as_ref().map(|d| d.validate().is_ok())),
+        ];
+        for (algorithm, valid) in digests {
+            if let Some(valid) = valid {
+                metrics.digest_verified(url, algorithm, valid);
+            }
+        }
+    }
+
     fn into_retrieved(
         self,
         discovered: DiscoveredAdvisory,
@@ -265,59 +1025,349 @@ impl FetchedRetrievedAdvisory {
             data: self.data,
             signature,
             sha256: self.sha256,
+            sha384: self.sha384,
             sha512: self.sha512,
             metadata: self.metadata,
         }
     }
 }
 
+#[non_exhaustive]
 pub struct FetchingRetrievedAdvisory {
     pub sha256: Option<RetrievingDigest<Sha256>>,
+    pub sha384: Option<RetrievingDigest<Sha384>>,
     pub sha512: Option<RetrievingDigest<Sha512>>,
+    /// The previous state, for performing a conditional request
+    pub previous: Option<PreviousRetrieval>,
+    /// The reader of the content
+    pub body_reader: Arc<dyn BodyReader>,
+    /// The maximum size of the content, in bytes
+    pub max_size: u64,
+    /// Fail if the content isn't served as JSON
+    pub strict_content_type: bool,
+}
+
+impl FetchingRetrievedAdvisory {
+    /// Create a new processor, performing an unconditional request, reading the content into
+    /// memory, up to the [`DEFAULT_MAX_DOCUMENT_SIZE`].
+    pub fn new(
+        sha256: Option<RetrievingDigest<Sha256>>,
+        sha384: Option<RetrievingDigest<Sha384>>,
+        sha512: Option<RetrievingDigest<Sha512>>,
+    ) -> Self {
+        Self {
+            sha256,
+            sha384,
+            sha512,
+            previous: None,
+            body_reader: Arc::new(BufferingBodyReader),
+            max_size: DEFAULT_MAX_DOCUMENT_SIZE,
+            strict_content_type: false,
+        }
+    }
 }
 
+/// Check that a response is served as JSON (or gzip), before reading it.
+///
+/// Otherwise, it is most likely an error page. This only fails if strict, and returns the content
+/// type otherwise, so that it can be reported.
+fn check_content_type(
+    response: &Response,
+    strict: bool,
+) -> Result<Option<String>, HttpSourceError> {
+    let Some(content_type) = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    else {
+        return Ok(None);
+    };
+
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if matches!(
+        essence.as_str(),
+        "application/json" | "application/gzip" | "application/x-gzip"
+    ) || essence.ends_with("+json")
+    {
+        return Ok(None);
+    }
+
+    if strict {
+        return Err(HttpSourceError::ContentType {
+            url: response.url().clone(),
+            content_type,
+        });
+    }
+
+    Ok(Some(content_type))
+}
+
+/// Fail if a response declares content exceeding the maximum size, before reading it.
+fn check_declared_size(response: &Response, max_size: u64) -> Result<(), HttpSourceError> {
+    match declared_content_length(response.headers()) {
+        Some(length) if length > max_size => Err(HttpSourceError::TooLarge {
+            url: response.url().clone(),
+            limit: max_size,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// An accepted response of an advisory document.
+struct AcceptedResponse {
+    metadata: RetrievalMetadata,
+    /// The length of the content, as declared by the server
+    content_length: Option<u64>,
+    /// The content type, if the content isn't served as JSON
+    content_type: Option<String>,
+}
+
+/// Check the response of an advisory document, before reading its content.
+fn accept_response(
+    response: &Response,
+    max_size: u64,
+    strict_content_type: bool,
+) -> Result<AcceptedResponse, HttpSourceError> {
+    let content_type = check_content_type(response, strict_content_type)?;
+    check_declared_size(response, max_size)?;
+
+    let headers = response.headers();
+    let etag = headers
+        .get(header::ETAG)
+        .and_then(|s| s.to_str().ok())
+        .map(ToString::to_string);
+    let last_modification = headers
+        .get(header::LAST_MODIFIED)
+        .and_then(|s| s.to_str().ok())
+        .and_then(|s| OffsetDateTime::parse(s, &Rfc2822).ok());
+
+    Ok(AcceptedResponse {
+        metadata: RetrievalMetadata {
+            last_modification,
+            etag,
+            not_modified: false,
+            transformed: false,
+        },
+        content_length: declared_content_length(headers),
+        content_type,
+    })
+}
+
+/// Calculates the digests and the size of an advisory's content, while it is being read.
+struct ContentInspector {
+    sha256: Option<RetrievingDigest<Sha256>>,
+    sha384: Option<RetrievingDigest<Sha384>>,
+    sha512: Option<RetrievingDigest<Sha512>>,
+    size: u64,
+    max_size: u64,
+}
+
+impl ContentInspector {
+    /// Inspect the next chunk of the content, breaking once it exceeds the maximum size.
+    fn inspect(&mut self, chunk: &[u8]) -> ControlFlow<()> {
+        self.size += chunk.len() as u64;
+        if self.exceeded() {
+            return ControlFlow::Break(());
+        }
+
+        if let Some(d) = &mut self.sha256 {
+            d.update(chunk);
+        }
+        if let Some(d) = &mut self.sha384 {
+            d.update(chunk);
+        }
+        if let Some(d) = &mut self.sha512 {
+            d.update(chunk);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn exceeded(&self) -> bool {
+        self.size > self.max_size
+    }
+}
+
+/// Retrieves the content of an advisory, using the [`BodyReader`].
+///
+/// Exceeding the maximum size is not a failure of the request, so it is returned as the result
+/// of processing, and not retried.
 impl DataProcessor for FetchingRetrievedAdvisory {
-    type Type = FetchedRetrievedAdvisory;
+    type Type = Result<FetchedRetrievedAdvisory, HttpSourceError>;
+
+    fn prepare(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(previous) = &self.previous {
+            if let Some(etag) = &previous.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modification) = previous.last_modification {
+                request = request.header(
+                    header::IF_MODIFIED_SINCE,
+                    httpdate::fmt_http_date(last_modification),
+                );
+            }
+        }
+        request
+    }
+
+    async fn process(&self, response: Response) -> Result<Self::Type, reqwest::Error> {
+        if self.previous.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+            // the caller will use the stored content
+            return Ok(Ok(FetchedRetrievedAdvisory {
+                data: Bytes::new(),
+                sha256: None,
+                sha384: None,
+                sha512: None,
+                metadata: RetrievalMetadata {
+                    not_modified: true,
+                    ..Default::default()
+                },
+                content_length: None,
+                content_type: None,
+            }));
+        }
+
+        let response = response.error_for_status()?;
+        let accepted = match accept_response(&response, self.max_size, self.strict_content_type) {
+            Ok(accepted) => accepted,
+            Err(err) => return Ok(Err(err)),
+        };
+        let url = response.url().clone();
+
+        let mut inspector = ContentInspector {
+            sha256: self.sha256.clone(),
+            sha384: self.sha384.clone(),
+            sha512: self.sha512.clone(),
+            size: 0,
+            max_size: self.max_size,
+        };
+
+        let data = match self
+            .body_reader
+            .read(response, &mut |chunk| inspector.inspect(chunk))
+            .await
+        {
+            Ok(data) => data,
+            Err(BodyReadError::Request(err)) => return Err(err),
+            Err(BodyReadError::Io(err)) => return Ok(Err(err.into())),
+        };
+
+        if inspector.exceeded() {
+            return Ok(Err(HttpSourceError::TooLarge {
+                url,
+                limit: self.max_size,
+            }));
+        }
+
+        Ok(Ok(FetchedRetrievedAdvisory {
+            data,
+            sha256: inspector.sha256.map(|d| d.into()),
+            sha384: inspector.sha384.map(|d| d.into()),
+            sha512: inspector.sha512.map(|d| d.into()),
+            metadata: accepted.metadata,
+            content_length: accepted.content_length,
+            content_type: accepted.content_type,
+        }))
+    }
+}
+
+struct StreamedBody {
+    sha256: Option<RetrievedDigest<Sha256>>,
+    sha384: Option<RetrievedDigest<Sha384>>,
+    sha512: Option<RetrievedDigest<Sha512>>,
+    metadata: RetrievalMetadata,
+    size: u64,
+    content_length: Option<u64>,
+    content_type: Option<String>,
+}
+
+/// Streams the content of an advisory to a file, calculating its digests on the way.
+///
+/// Failing to write the file, or exceeding the maximum size, is not a failure of the request, so
+/// it is returned as the result of processing, and not retried.
+struct StreamingRetrievedAdvisory<'a> {
+    sha256: Option<RetrievingDigest<Sha256>>,
+    sha384: Option<RetrievingDigest<Sha384>>,
+    sha512: Option<RetrievingDigest<Sha512>>,
+    file: &'a Path,
+    max_size: u64,
+    strict_content_type: bool,
+}
+
+impl DataProcessor for StreamingRetrievedAdvisory<'_> {
+    type Type = Result<StreamedBody, HttpSourceError>;
 
     async fn process(&self, response: Response) -> Result<Self::Type, reqwest::Error> {
         let mut response = response.error_for_status()?;
+        let accepted = match accept_response(&response, self.max_size, self.strict_content_type) {
+            Ok(accepted) => accepted,
+            Err(err) => return Ok(Err(err)),
+        };
 
-        let mut data = BytesMut::new();
-        let mut sha256 = self.sha256.clone();
-        let mut sha512 = self.sha512.clone();
+        let mut file = match tokio::fs::File::create(self.file).await {
+            Ok(file) => file,
+            Err(err) => return Ok(Err(err.into())),
+        };
+
+        let mut inspector = ContentInspector {
+            sha256: self.sha256.clone(),
+            sha384: self.sha384.clone(),
+            sha512: self.sha512.clone(),
+            size: 0,
+            max_size: self.max_size,
+        };
 
         while let Some(chunk) = response.chunk().await? {
-            if let Some(d) = &mut sha256 {
-                d.update(&chunk);
+            if inspector.inspect(&chunk).is_break() {
+                return Ok(Err(HttpSourceError::TooLarge {
+                    url: response.url().clone(),
+                    limit: self.max_size,
+                }));
             }
-            if let Some(d) = &mut sha512 {
-                d.update(&chunk);
+
+            if let Err(err) = file.write_all(&chunk).await {
+                return Ok(Err(err.into()));
             }
-            data.put(chunk);
         }
 
-        let etag = response
-            .headers()
-            .get(reqwest::header::ETAG)
-            .and_then(|s| s.to_str().ok())
-            .map(ToString::to_string);
+        if let Err(err) = file.flush().await {
+            return Ok(Err(err.into()));
+        }
 
-        let last_modification = response
-            .headers()
-            .get(reqwest::header::LAST_MODIFIED)
-            .and_then(|s| s.to_str().ok())
-            .and_then(|s| OffsetDateTime::parse(s, &Rfc2822).ok());
+        Ok(Ok(StreamedBody {
+            sha256: inspector.sha256.map(|d| d.into()),
+            sha384: inspector.sha384.map(|d| d.into()),
+            sha512: inspector.sha512.map(|d| d.into()),
+            metadata: accepted.metadata,
+            size: inspector.size,
+            content_length: accepted.content_length,
+            content_type: accepted.content_type,
+        }))
+    }
+}
 
-        Ok(FetchedRetrievedAdvisory {
-            data: data.freeze(),
-            sha256: sha256.map(|d| d.into()),
-            sha512: sha512.map(|d| d.into()),
-            metadata: RetrievalMetadata {
-                last_modification,
-                etag,
-            },
-        })
+/// The declared length of the content.
+///
+/// This is `None` if the header is absent, or the content is transferred chunked or encoded, as
+/// then the header doesn't describe the received data.
+fn declared_content_length(headers: &HeaderMap) -> Option<u64> {
+    if headers.contains_key(header::TRANSFER_ENCODING)
+        || headers.contains_key(header::CONTENT_ENCODING)
+    {
+        return None;
     }
+
+    headers
+        .get(header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
 }
 
 impl KeySource for HttpSource {
@@ -330,3 +1380,55 @@ impl KeySource for HttpSource {
         self.fetcher.load_public_key(key_source).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn headers<const N: usize>(headers: [(HeaderName, &str); N]) -> HeaderMap {
+        headers
+            .into_iter()
+            .map(|(name, value)| (name, HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn content_length() {
+        assert_eq!(
+            declared_content_length(&headers([(header::CONTENT_LENGTH, "42")])),
+            Some(42)
+        );
+        assert_eq!(declared_content_length(&headers([])), None);
+        assert_eq!(
+            declared_content_length(&headers([
+                (header::CONTENT_LENGTH, "42"),
+                (header::TRANSFER_ENCODING, "chunked")
+            ])),
+            None
+        );
+        assert_eq!(
+            declared_content_length(&headers([
+                (header::CONTENT_LENGTH, "42"),
+                (header::CONTENT_ENCODING, "gzip")
+            ])),
+            None
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let advisory = |content_length| FetchedRetrievedAdvisory {
+            data: Bytes::from_static(br#"{"document":{}}"#),
+            sha256: None,
+            sha384: None,
+            sha512: None,
+            metadata: Default::default(),
+            content_length,
+            content_type: None,
+        };
+
+        assert_eq!(advisory(None).check_length(), Ok(()));
+        assert_eq!(advisory(Some(15)).check_length(), Ok(()));
+        assert_eq!(advisory(Some(100)).check_length(), Err((100, 15)));
+    }
+}
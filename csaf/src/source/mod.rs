@@ -1,22 +1,33 @@
 //! Sources
 
+#[cfg(feature = "archive")]
+mod archive;
 mod descriptor;
 mod dispatch;
 mod file;
 mod http;
+mod overlay;
 
+#[cfg(feature = "archive")]
+pub use archive::*;
 pub use descriptor::*;
 pub use dispatch::*;
 pub use file::*;
 pub use http::*;
+pub use overlay::*;
 
 use crate::{
     discover::{DiscoverConfig, DiscoveredAdvisory, DistributionContext},
     model::metadata::ProviderMetadata,
     retrieve::RetrievedAdvisory,
 };
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256, Sha512};
 use std::{fmt::Debug, future::Future, str::FromStr};
-use walker_common::fetcher::FetcherOptions;
+use walker_common::{
+    fetcher::{self, Fetcher, FetcherOptions},
+    utils::hex::Hex,
+};
 
 /// A source of CSAF documents
 pub trait Source: walker_common::source::Source + Clone + Debug {
@@ -31,15 +42,118 @@ pub trait Source: walker_common::source::Source + Clone + Debug {
         &self,
         advisory: DiscoveredAdvisory,
     ) -> impl Future<Output = Result<RetrievedAdvisory, Self::Error>>;
+
+    /// Load the expected digest of an advisory, without retrieving the advisory itself.
+    ///
+    /// This is the digest referenced by the discovered advisory (see
+    /// [`DiscoveredAdvisory::digest`]), or the SHA-256 digest if it doesn't reference one. Returns
+    /// [`None`] if the source doesn't provide one.
+    fn load_digest(
+        &self,
+        #[allow(unused_variables)] advisory: &DiscoveredAdvisory,
+    ) -> impl Future<Output = Result<Option<ExpectedDigest>, Self::Error>> {
+        async { Ok(None) }
+    }
+}
+
+/// The expected digest of an advisory, see [`Source::load_digest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpectedDigest {
+    Sha256(String),
+    Sha512(String),
+}
+
+impl ExpectedDigest {
+    /// The file extension of the digest, like `sha256`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Sha256(_) => "sha256",
+            Self::Sha512(_) => "sha512",
+        }
+    }
+
+    /// The hex encoded value of the digest.
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Sha256(value) | Self::Sha512(value) => value,
+        }
+    }
+
+    /// Calculate the hex encoded digest of the data, using the same algorithm.
+    pub fn calculate(&self, data: &[u8]) -> String {
+        match self {
+            Self::Sha256(_) => Hex(&Sha256::digest(data)).to_lower(),
+            Self::Sha512(_) => Hex(&Sha512::digest(data)).to_lower(),
+        }
+    }
+}
+
+/// An error of a [`Source`], which might have been caused by an HTTP response.
+pub trait SourceError {
+    /// The HTTP status code of the response causing the error, if any.
+    fn status(&self) -> Option<StatusCode>;
+}
+
+impl SourceError for anyhow::Error {
+    fn status(&self) -> Option<StatusCode> {
+        self.downcast_ref::<HttpSourceError>()
+            .and_then(HttpSourceError::status)
+            .or_else(|| self.downcast_ref::<fetcher::Error>()?.status())
+    }
 }
 
 /// A common way to create a new CSAF source.
 pub async fn new_source(
     discover: impl Into<DiscoverConfig>,
     fetcher: impl Into<FetcherOptions>,
+) -> anyhow::Result<DispatchSource> {
+    new_source_with_options(discover, fetcher, HttpOptions::new()).await
+}
+
+/// Create a new CSAF source, using the options for remote sources.
+///
+/// The `since` and `conditional` options are taken from the discovery configuration.
+pub async fn new_source_with_options(
+    discover: impl Into<DiscoverConfig>,
+    fetcher: impl Into<FetcherOptions>,
+    http: HttpOptions,
 ) -> anyhow::Result<DispatchSource> {
     let discover = discover.into();
 
     let descriptor = SourceDescriptor::from_str(&discover.source)?;
-    descriptor.into_source(discover, fetcher.into()).await
+    descriptor
+        .into_source_with_options(discover, fetcher.into(), http)
+        .await
+}
+
+/// Create the sources described by the discovery configuration, using the options for remote
+/// sources.
+///
+/// A list of sources (see [`SourceDescriptor::load_all`]) results in one source per entry, all
+/// sharing the same fetcher. Each of them must be walked on its own.
+pub async fn new_sources_with_options(
+    discover: impl Into<DiscoverConfig>,
+    fetcher: impl Into<FetcherOptions>,
+    http: HttpOptions,
+) -> anyhow::Result<Vec<DispatchSource>> {
+    let discover = discover.into();
+
+    let mut descriptors = SourceDescriptor::load_all(&discover.source)?;
+    if descriptors.len() == 1
+        && let Some(descriptor) = descriptors.pop()
+    {
+        return Ok(vec![
+            descriptor
+                .into_source_with_options(discover, fetcher.into(), http)
+                .await?,
+        ]);
+    }
+
+    let fetcher = Fetcher::new(fetcher.into()).await?;
+    descriptors
+        .into_iter()
+        .map(|descriptor| {
+            descriptor.into_remote_source(discover.clone(), fetcher.clone(), http.clone())
+        })
+        .collect()
 }
@@ -0,0 +1,229 @@
+use super::{
+    ExpectedDigest, FileSource, HttpSource, HttpSourceError, Source, SourceError,
+    file::advisory_file,
+};
+use crate::{
+    discover::{DiscoveredAdvisory, DistributionContext},
+    model::{metadata::ProviderMetadata, store::distribution_base},
+    retrieve::RetrievedAdvisory,
+    visitors::store::advisory_path,
+};
+use anyhow::anyhow;
+use reqwest::StatusCode;
+use std::{collections::HashMap, sync::Arc};
+use url::Url;
+use walker_common::{
+    fetcher,
+    utils::openpgp::PublicKey,
+    validate::source::{Key, KeySource, KeySourceError},
+};
+
+/// A source, reading advisories from a local store first, and falling back to HTTP.
+///
+/// The store is expected to be created by the [`crate::visitors::store::StoreVisitor`]. The
+/// metadata is loaded from the [`HttpSource`], so advisories always use their remote URLs.
+/// Advisories which are stored locally are loaded from the [`FileSource`], all others are
+/// retrieved from the [`HttpSource`]. This makes it a read-through cache for incremental mirroring.
+///
+/// A stored advisory is only used if it is at least as recent as the remote one, according to
+/// the modification time of the stored file and the remote index. Outdated advisories are
+/// retrieved again.
+#[derive(Clone, Debug)]
+pub struct OverlaySource {
+    file: FileSource,
+    http: HttpSource,
+}
+
+impl OverlaySource {
+    pub fn new(file: FileSource, http: HttpSource) -> Self {
+        Self { file, http }
+    }
+
+    /// The location of an advisory in the local store, if it is stored there and is not
+    /// outdated.
+    async fn local_file(&self, advisory: &DiscoveredAdvisory) -> Option<Url> {
+        let path = advisory_path(self.file.base(), advisory)?;
+
+        let file = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || advisory_file(&path))
+                .await
+                .ok()??
+        };
+        let modified = tokio::fs::metadata(&file).await.ok()?.modified().ok()?;
+
+        if advisory.modified > modified {
+            log::debug!("Stored copy is outdated, retrieving: {}", advisory.url);
+            return None;
+        }
+
+        Url::from_file_path(path).ok()
+    }
+
+    /// Load the index of the local copy of a distribution, using remote URLs.
+    async fn load_local_index(
+        &self,
+        context: &DistributionContext,
+    ) -> Result<Vec<DiscoveredAdvisory>, anyhow::Error> {
+        let base = distribution_base(self.file.base(), context.url().as_str());
+        if !base.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let local = Url::from_directory_path(&base)
+            .map_err(|()| anyhow!("Failed to convert directory into URL: {}", base.display()))?;
+
+        let advisories = self
+            .file
+            .load_index(DistributionContext::Directory(local.clone()))
+            .await?;
+
+        let context = Arc::new(context.clone());
+        let mut result = Vec::with_capacity(advisories.len());
+        for advisory in advisories {
+            let Some(name) = local.make_relative(&advisory.url) else {
+                log::warn!("Unable to map stored advisory: {}", advisory.url);
+                continue;
+            };
+
+            result.push(DiscoveredAdvisory {
+                url: context.url().join(&name)?,
+                modified: advisory.modified,
+                digest: None,
+                signature: None,
+                context: context.clone(),
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OverlaySourceError {
+    #[error(transparent)]
+    File(anyhow::Error),
+    #[error(transparent)]
+    Http(HttpSourceError),
+}
+
+impl SourceError for OverlaySourceError {
+    fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::File(err) => err.status(),
+            Self::Http(err) => err.status(),
+        }
+    }
+}
+
+impl walker_common::source::Source for OverlaySource {
+    type Error = OverlaySourceError;
+    type Retrieved = RetrievedAdvisory;
+}
+
+impl Source for OverlaySource {
+    async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+        self.http
+            .load_metadata()
+            .await
+            .map_err(OverlaySourceError::Http)
+    }
+
+    /// Load the union of the remote and the local index, preferring local advisories unless the
+    /// remote ones are newer.
+    async fn load_index(
+        &self,
+        context: DistributionContext,
+    ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
+        let local = self
+            .load_local_index(&context)
+            .await
+            .map_err(OverlaySourceError::File)?;
+        let remote = self
+            .http
+            .load_index(context)
+            .await
+            .map_err(OverlaySourceError::Http)?;
+
+        let mut result = Vec::with_capacity(remote.len().max(local.len()));
+        let mut local = local
+            .into_iter()
+            .map(|advisory| (advisory.url.clone(), advisory))
+            .collect::<HashMap<_, _>>();
+
+        for advisory in remote {
+            match local.remove(&advisory.url) {
+                Some(stored) if advisory.modified <= stored.modified => {
+                    result.push(DiscoveredAdvisory {
+                        context: advisory.context,
+                        ..stored
+                    })
+                }
+                _ => result.push(advisory),
+            }
+        }
+
+        // advisories only known locally, in a stable order
+        let mut local = local.into_values().collect::<Vec<_>>();
+        local.sort_by(|a, b| a.url.cmp(&b.url));
+        result.extend(local);
+
+        Ok(result)
+    }
+
+    async fn load_advisory(
+        &self,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<RetrievedAdvisory, Self::Error> {
+        let Some(url) = self.local_file(&advisory).await else {
+            log::debug!("Not stored, retrieving: {}", advisory.url);
+            return self
+                .http
+                .load_advisory(advisory)
+                .await
+                .map_err(OverlaySourceError::Http);
+        };
+
+        let retrieved = self
+            .file
+            .load_advisory(DiscoveredAdvisory {
+                url,
+                digest: None,
+                signature: None,
+                ..advisory.clone()
+            })
+            .await
+            .map_err(OverlaySourceError::File)?;
+
+        Ok(RetrievedAdvisory {
+            discovered: advisory,
+            ..retrieved
+        })
+    }
+
+    async fn load_digest(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<ExpectedDigest>, Self::Error> {
+        match self.local_file(advisory).await {
+            Some(_) => Ok(None),
+            None => self
+                .http
+                .load_digest(advisory)
+                .await
+                .map_err(OverlaySourceError::Http),
+        }
+    }
+}
+
+impl KeySource for OverlaySource {
+    type Error = fetcher::Error;
+
+    async fn load_public_key(
+        &self,
+        key: Key<'_>,
+    ) -> Result<PublicKey, KeySourceError<Self::Error>> {
+        // keys are referenced by the remote metadata
+        self.http.load_public_key(key).await
+    }
+}
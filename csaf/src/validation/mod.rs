@@ -2,6 +2,7 @@
 
 use crate::{
     discover::{AsDiscovered, DiscoveredAdvisory},
+    model::metadata::Publisher,
     retrieve::{AsRetrieved, RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
     source::Source,
 };
@@ -10,12 +11,17 @@ use std::{
     future::Future,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 use url::Url;
 use walker_common::{
     retrieve::RetrievalError,
     utils::{openpgp::PublicKey, url::Urlify},
-    validate::{ValidationOptions, digest::validate_digest, openpgp},
+    validate::{
+        MissingSignature, ValidationOptions,
+        digest::validate_digest,
+        openpgp::{self, Signer},
+    },
 };
 
 /// A validated CSAF document
@@ -28,6 +34,11 @@ use walker_common::{
 pub struct ValidatedAdvisory {
     /// The retrieved advisory
     pub retrieved: RetrievedAdvisory,
+    /// The creator of the signature, if the advisory was signed
+    ///
+    /// This contains the fingerprints of the certificate and key, as well as the creation time of
+    /// the signature.
+    pub signer: Option<Signer>,
 }
 
 impl Urlify for ValidatedAdvisory {
@@ -75,10 +86,13 @@ pub enum ValidationError<S: Source> {
         actual: String,
         retrieved: RetrievedAdvisory,
     },
-    Signature {
+    SignatureInvalid {
         error: anyhow::Error,
         retrieved: RetrievedAdvisory,
     },
+    SignatureMissing {
+        retrieved: RetrievedAdvisory,
+    },
 }
 
 impl<S: Source + Debug> AsDiscovered for ValidationError<S> {
@@ -86,7 +100,8 @@ impl<S: Source + Debug> AsDiscovered for ValidationError<S> {
         match self {
             Self::Retrieval(err) => err.discovered(),
             Self::DigestMismatch { retrieved, .. } => retrieved.as_discovered(),
-            Self::Signature { retrieved, .. } => retrieved.as_discovered(),
+            Self::SignatureInvalid { retrieved, .. } => retrieved.as_discovered(),
+            Self::SignatureMissing { retrieved } => retrieved.as_discovered(),
         }
     }
 }
@@ -96,7 +111,8 @@ impl<S: Source> Urlify for ValidationError<S> {
         match self {
             Self::Retrieval(err) => err.url(),
             Self::DigestMismatch { retrieved, .. } => &retrieved.url,
-            Self::Signature { retrieved, .. } => &retrieved.url,
+            Self::SignatureInvalid { retrieved, .. } => &retrieved.url,
+            Self::SignatureMissing { retrieved } => &retrieved.url,
         }
     }
 }
@@ -113,12 +129,13 @@ impl<S: Source> Display for ValidationError<S> {
                 f,
                 "Digest mismatch - expected: {expected}, actual: {actual}",
             ),
-            Self::Signature {
+            Self::SignatureInvalid {
                 error,
                 retrieved: _,
             } => {
                 write!(f, "Invalid signature: {error}",)
             }
+            Self::SignatureMissing { retrieved: _ } => write!(f, "Missing signature"),
         }
     }
 }
@@ -242,6 +259,15 @@ where
                 },
             ));
         }
+        if let Err((expected, actual)) = validate_digest(&retrieved.sha384) {
+            return Err(ValidationProcessError::Proceed(
+                ValidationError::DigestMismatch {
+                    expected,
+                    actual,
+                    retrieved,
+                },
+            ));
+        }
         if let Err((expected, actual)) = validate_digest(&retrieved.sha512) {
             return Err(ValidationProcessError::Proceed(
                 ValidationError::DigestMismatch {
@@ -253,26 +279,76 @@ where
         }
 
         if let Some(signature) = &retrieved.signature {
-            match openpgp::validate_signature(
+            match openpgp::verify_signature(
                 &self.options,
-                &context.keys,
+                context.keys_for(retrieved.context.publisher()),
                 signature,
                 &retrieved.data,
             ) {
-                Ok(()) => Ok(ValidatedAdvisory { retrieved }),
+                Ok(signer) => {
+                    log::debug!(
+                        "Signature of {} created by: {} ({:?})",
+                        retrieved.url,
+                        signer.fingerprint,
+                        signer.created.map(humantime::Timestamp::from)
+                    );
+                    Ok(ValidatedAdvisory {
+                        retrieved,
+                        signer: Some(signer),
+                    })
+                }
                 Err(error) => Err(ValidationProcessError::Proceed(
-                    ValidationError::Signature { error, retrieved },
+                    ValidationError::SignatureInvalid { error, retrieved },
                 )),
             }
         } else {
-            Ok(ValidatedAdvisory { retrieved })
+            match self.options.missing_signature {
+                MissingSignature::Allow => {
+                    log::debug!("Missing signature: {}", retrieved.url);
+                    Ok(ValidatedAdvisory {
+                        retrieved,
+                        signer: None,
+                    })
+                }
+                MissingSignature::Warn => {
+                    log::warn!("Missing signature: {}", retrieved.url);
+                    Ok(ValidatedAdvisory {
+                        retrieved,
+                        signer: None,
+                    })
+                }
+                MissingSignature::Reject => Err(ValidationProcessError::Proceed(
+                    ValidationError::SignatureMissing { retrieved },
+                )),
+            }
         }
     }
 }
 
 pub struct InnerValidationContext<VC> {
     context: VC,
+    /// Keys not belonging to a specific publisher
     keys: Vec<PublicKey>,
+    /// Keys of publishers listed by an aggregator
+    publisher_keys: Vec<(Arc<Publisher>, Vec<PublicKey>)>,
+}
+
+impl<VC> InnerValidationContext<VC> {
+    /// Get the keys to validate a document of the provided publisher with.
+    ///
+    /// Documents of a publisher listed by an aggregator must only be validated using the keys
+    /// of that publisher.
+    fn keys_for(&self, publisher: Option<&Publisher>) -> &[PublicKey] {
+        match publisher {
+            None => &self.keys,
+            Some(publisher) => self
+                .publisher_keys
+                .iter()
+                .find(|(p, _)| p.as_ref() == publisher)
+                .map(|(_, keys)| keys.as_slice())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 impl<V, S> RetrievedVisitor<S> for ValidationVisitor<V, S>
@@ -287,7 +363,23 @@ where
         &self,
         context: &RetrievalContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
-        let keys = context.keys.clone();
+        let mut keys = vec![];
+        let mut publisher_keys: Vec<(Arc<Publisher>, Vec<PublicKey>)> = vec![];
+
+        for (metadata, key) in context
+            .metadata
+            .public_openpgp_keys
+            .iter()
+            .zip(context.keys)
+        {
+            match &metadata.publisher {
+                None => keys.push(key.clone()),
+                Some(publisher) => match publisher_keys.iter_mut().find(|(p, _)| p == publisher) {
+                    Some((_, keys)) => keys.push(key.clone()),
+                    None => publisher_keys.push((publisher.clone(), vec![key.clone()])),
+                },
+            }
+        }
 
         let context = self
             .visitor
@@ -295,7 +387,11 @@ where
             .await
             .map_err(Error::Visitor)?;
 
-        Ok(Self::Context { context, keys })
+        Ok(Self::Context {
+            context,
+            keys,
+            publisher_keys,
+        })
     }
 
     async fn visit_advisory(
@@ -1,4 +1,4 @@
-use crate::verification::check::{Check, CheckError, Checking};
+use crate::verification::check::{Check, CheckError, Checking, Severity};
 use csaf::Csaf;
 
 pub fn check_csaf_base(csaf: &Csaf) -> Vec<CheckError> {
@@ -35,7 +35,8 @@ pub fn check_csaf_document_tracking_revision_history(csaf: &Csaf) -> Vec<CheckEr
                     ),
                     !revision.number.is_empty(),
                 )
-                .require(
+                .require_with(
+                    Severity::Info,
                     "The CSAF file's document revision_history summary is empty",
                     !revision.summary.is_empty(),
                 )
@@ -70,6 +71,49 @@ pub fn check_csaf_document_tracking_revision_history(csaf: &Csaf) -> Vec<CheckEr
     result
 }
 
+/// Each vulnerability must be identifiable, by having at least one of
+/// /vulnerabilities[]/cve
+/// /vulnerabilities[]/ids
+pub fn check_vulnerability_identifiable(csaf: &Csaf) -> Vec<CheckError> {
+    let mut result = vec![];
+
+    for (index, vuln) in csaf.vulnerabilities.iter().flatten().enumerate() {
+        let identifiable =
+            vuln.cve.is_some() || vuln.ids.as_ref().is_some_and(|ids| !ids.is_empty());
+
+        if identifiable {
+            continue;
+        }
+
+        // try to find something which helps locating the vulnerability
+        let hint = vuln.title.as_deref().or_else(|| {
+            vuln.notes
+                .iter()
+                .flatten()
+                .map(|note| note.title.as_deref().unwrap_or(&note.text))
+                .next()
+        });
+
+        result.extend(
+            Checking::new()
+                .require(
+                    match hint {
+                        Some(hint) => format!(
+                            "The vulnerability at index {index} ({hint:?}) has neither a CVE nor any IDs"
+                        ),
+                        None => format!(
+                            "The vulnerability at index {index} has neither a CVE nor any IDs"
+                        ),
+                    },
+                    identifiable,
+                )
+                .done(),
+        );
+    }
+
+    result
+}
+
 pub fn init_csaf_base_verifying_visitor() -> Vec<(&'static str, Box<dyn Check>)> {
     vec![
         ("check_csaf_base", Box::new(check_csaf_base)),
@@ -77,6 +121,10 @@ pub fn init_csaf_base_verifying_visitor() -> Vec<(&'static str, Box<dyn Check>)>
             "check_csaf_document_tracking_revision_history",
             Box::new(check_csaf_document_tracking_revision_history),
         ),
+        (
+            "check_vulnerability_identifiable",
+            Box::new(check_vulnerability_identifiable),
+        ),
     ]
 }
 
@@ -84,6 +132,7 @@ pub fn init_csaf_base_verifying_visitor() -> Vec<(&'static str, Box<dyn Check>)>
 mod tests {
     use crate::verification::check::base::{
         check_csaf_base, check_csaf_document_tracking_revision_history,
+        check_vulnerability_identifiable,
     };
     use csaf::Csaf;
 
@@ -105,4 +154,24 @@ mod tests {
             2
         )
     }
+
+    #[tokio::test]
+    async fn test_check_vulnerability_identifiable() {
+        let mut csaf: Csaf =
+            serde_json::from_str(include_str!("../../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        assert_eq!(check_vulnerability_identifiable(&csaf).len(), 0);
+
+        let vuln = csaf
+            .vulnerabilities
+            .as_mut()
+            .and_then(|vulns| vulns.first_mut())
+            .expect("example data must have a vulnerability");
+        vuln.cve = None;
+        vuln.ids = Some(vec![]);
+
+        let result = check_vulnerability_identifiable(&csaf);
+        assert_eq!(result.len(), 1);
+        assert!(result[0].contains("index 0"));
+    }
 }
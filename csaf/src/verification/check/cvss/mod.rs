@@ -0,0 +1,236 @@
+use crate::verification::check::{Check, CheckError};
+use async_trait::async_trait;
+use csaf::Csaf;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// The parts of a CSAF document relevant to checking CVSS scores.
+///
+/// The [`Csaf`] model only retains the vector of a CVSS v3 score, but neither its declared base
+/// score, nor any CVSS v4 scores. So those are taken from the original source.
+#[derive(Debug, Deserialize)]
+struct Document {
+    #[serde(default)]
+    vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Vulnerability {
+    cve: Option<String>,
+    #[serde(default)]
+    scores: Vec<Score>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Score {
+    cvss_v3: Option<Cvss>,
+    cvss_v4: Option<Cvss>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Cvss {
+    vector_string: String,
+    base_score: Option<f64>,
+}
+
+impl From<&Csaf> for Document {
+    fn from(csaf: &Csaf) -> Self {
+        let vulnerabilities = csaf
+            .vulnerabilities
+            .iter()
+            .flatten()
+            .map(|vuln| Vulnerability {
+                cve: vuln.cve.clone(),
+                scores: vuln
+                    .scores
+                    .iter()
+                    .flatten()
+                    .map(|score| Score {
+                        cvss_v3: score.cvss_v3.as_ref().map(|base| Cvss {
+                            vector_string: base.to_string(),
+                            base_score: Some(base.score().value()),
+                        }),
+                        cvss_v4: None,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self { vulnerabilities }
+    }
+}
+
+/// Verify that the base scores of /vulnerabilities[]/scores[]/cvss_v3 and
+/// /vulnerabilities[]/scores[]/cvss_v4 match the ones computed from their vector strings.
+///
+/// The declared scores and CVSS v4 scores are only available when checking the original source of
+/// the document, so it can't be used as a plain function. Also, a malformed CVSS v3 vector already
+/// fails parsing the [`Csaf`] document.
+#[derive(Clone, Debug)]
+pub struct CvssScores {
+    /// The maximum difference between declared and computed score, to allow for rounding.
+    pub tolerance: f64,
+}
+
+impl Default for CvssScores {
+    fn default() -> Self {
+        Self { tolerance: 0.05 }
+    }
+}
+
+impl CvssScores {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    fn check_score(
+        &self,
+        cve: &str,
+        cvss: &Cvss,
+        compute: impl FnOnce(&str) -> Result<f64, cvss::Error>,
+        pointer: String,
+    ) -> Option<CheckError> {
+        let Some(base_score) = cvss.base_score else {
+            return Some(
+                CheckError::new(format!(
+                    "The CVSS score of vulnerability {cve} with vector {:?} is missing its base score",
+                    cvss.vector_string
+                ))
+                .at(format!("{pointer}/baseScore")),
+            );
+        };
+
+        match compute(&cvss.vector_string) {
+            Ok(score) if (score - base_score).abs() <= self.tolerance => None,
+            Ok(score) => Some(
+                CheckError::new(format!(
+                    "The base score of vulnerability {cve} ({base_score}) does not match the one computed from its vector {:?} ({score})",
+                    cvss.vector_string
+                ))
+                .at(format!("{pointer}/baseScore")),
+            ),
+            Err(err) => Some(
+                CheckError::new(format!(
+                    "The CVSS vector {:?} of vulnerability {cve} is malformed: {err}",
+                    cvss.vector_string
+                ))
+                .at(format!("{pointer}/vectorString")),
+            ),
+        }
+    }
+
+    fn check_document(&self, document: &Document) -> Vec<CheckError> {
+        let mut results = vec![];
+
+        for (i, vuln) in document.vulnerabilities.iter().enumerate() {
+            let cve = vuln.cve.as_deref().unwrap_or("<unknown>");
+
+            for (j, score) in vuln.scores.iter().enumerate() {
+                if let Some(cvss) = &score.cvss_v3 {
+                    results.extend(self.check_score(
+                        cve,
+                        cvss,
+                        |vector| Ok(cvss::v3::Base::from_str(vector)?.score().value()),
+                        format!("/vulnerabilities/{i}/scores/{j}/cvss_v3"),
+                    ));
+                }
+                if let Some(cvss) = &score.cvss_v4 {
+                    results.extend(self.check_score(
+                        cve,
+                        cvss,
+                        |vector| Ok(cvss::v4::Vector::from_str(vector)?.score().value()),
+                        format!("/vulnerabilities/{i}/scores/{j}/cvss_v4"),
+                    ));
+                }
+            }
+        }
+
+        results
+    }
+}
+
+#[async_trait(?Send)]
+impl Check for CvssScores {
+    async fn check(&self, csaf: &Csaf) -> anyhow::Result<Vec<CheckError>> {
+        // without the source, only the CVSS v3 scores retained by the model can be checked
+        Ok(self.check_document(&Document::from(csaf)))
+    }
+
+    async fn check_source(&self, _csaf: &Csaf, source: &[u8]) -> anyhow::Result<Vec<CheckError>> {
+        let document: Document = serde_json::from_slice(source)?;
+        Ok(self.check_document(&document))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verification::check::{Check, cvss::CvssScores};
+    use csaf::Csaf;
+
+    const SOURCE: &str = include_str!("../../../../test-data/rhsa-2021_3029.json");
+
+    #[tokio::test]
+    async fn test_check_cvss_scores() {
+        let csaf: Csaf = serde_json::from_str(SOURCE).expect("example data must parse");
+        let check = CvssScores::new();
+
+        let result = check.check_source(&csaf, SOURCE.as_bytes()).await.unwrap();
+        assert_eq!(result.len(), 0);
+
+        let mut source: serde_json::Value = serde_json::from_str(SOURCE).unwrap();
+        source["vulnerabilities"][0]["scores"][0]["cvss_v3"]["baseScore"] = 9.9.into();
+        source["vulnerabilities"][1]["scores"][0]["cvss_v4"] = serde_json::json!({
+            "vectorString": "CVSS:4.0/AV:X",
+            "baseScore": 1.0,
+        });
+        let source = serde_json::to_vec(&source).unwrap();
+
+        let result = check.check_source(&csaf, &source).await.unwrap();
+        assert_eq!(result.len(), 2);
+        assert!(result[0].contains("9.9"));
+        assert!(result[0].contains("6.5"));
+        assert_eq!(
+            result[0].pointer.as_deref(),
+            Some("/vulnerabilities/0/scores/0/cvss_v3/baseScore")
+        );
+        assert!(result[1].contains("malformed"));
+        assert_eq!(
+            result[1].pointer.as_deref(),
+            Some("/vulnerabilities/1/scores/0/cvss_v4/vectorString")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_cvss_scores_missing_base_score() {
+        let csaf: Csaf = serde_json::from_str(SOURCE).expect("example data must parse");
+        let check = CvssScores::new();
+
+        let mut source: serde_json::Value = serde_json::from_str(SOURCE).unwrap();
+        source["vulnerabilities"][1]["scores"][0]["cvss_v4"] = serde_json::json!({
+            "vectorString": "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N",
+        });
+        let source = serde_json::to_vec(&source).unwrap();
+
+        let result = check.check_source(&csaf, &source).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].contains("missing its base score"));
+        assert_eq!(
+            result[0].pointer.as_deref(),
+            Some("/vulnerabilities/1/scores/0/cvss_v4/baseScore")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_cvss_scores_model() {
+        let csaf: Csaf = serde_json::from_str(SOURCE).expect("example data must parse");
+
+        let result = CvssScores::new().check(&csaf).await.unwrap();
+        assert_eq!(result.len(), 0);
+    }
+}
@@ -1,37 +1,196 @@
 use crate::verification::check::{
-    base::{check_csaf_base, check_csaf_document_tracking_revision_history},
-    informational_advisory::check_vulnerabilities_not_exits,
-    security_incident_response::{check_csaf_document_notes, check_csaf_document_references},
+    base::{
+        check_csaf_base, check_csaf_document_tracking_revision_history,
+        check_vulnerability_identifiable, init_csaf_base_verifying_visitor,
+    },
+    cvss::CvssScores,
+    informational_advisory::{
+        check_vulnerabilities_not_exits, init_csaf_informational_advisory_verifying_visitor,
+    },
+    security_advisory::{
+        check_product_tree_relationship_cycles, check_vulnerabilities_cve_format,
+        init_csaf_security_advisory_verifying_visitor,
+    },
+    security_incident_response::{
+        check_csaf_document_notes, check_csaf_document_references,
+        init_csaf_is_security_incident_response_verifying_visitor,
+    },
+    tracking::check_tracking_dates,
     vex::{
         check_all_products_v11ies_exits_in_product_tree,
+        check_all_remediation_products_exist_in_product_tree,
         check_branches_relationships_product_match, check_csaf_vex, check_history,
-        check_vulnerabilities_cve_ids, check_vulnerabilities_product_status,
-        check_vulnerabilities_size,
+        check_vulnerabilities_product_status, check_vulnerabilities_size,
+        init_vex_fmt_verifying_visitor,
     },
 };
 use async_trait::async_trait;
-use csaf::Csaf;
-use std::borrow::Cow;
+use csaf::{Csaf, document::Category};
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    ops::Deref,
+    str::FromStr,
+};
 
 pub mod base;
+pub mod cvss;
 pub mod informational_advisory;
 pub mod security_advisory;
 pub mod security_incident_response;
+pub mod tracking;
 pub mod vex;
 
-pub type CheckError = Cow<'static, str>;
+/// The severity of a finding, ordered from the least to the most severe.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    #[default]
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Self; 3] = [Self::Info, Self::Warning, Self::Error];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown severity '{0}', expected one of: info, warning, error")]
+pub struct UnknownSeverity(pub String);
+
+impl FromStr for Severity {
+    type Err = UnknownSeverity;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|severity| severity.name() == s)
+            .ok_or_else(|| UnknownSeverity(s.to_string()))
+    }
+}
+
+/// A finding of a check.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CheckError {
+    /// The description of the problem
+    pub message: Cow<'static, str>,
+    /// A JSON pointer (RFC 6901) to the node of the document causing the problem, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    /// The severity of the problem, defaults to [`Severity::Error`]
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+impl CheckError {
+    pub fn new(message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            message: message.into(),
+            pointer: None,
+            severity: Severity::default(),
+        }
+    }
+
+    /// Set the JSON pointer to the node causing the problem.
+    pub fn at(mut self, pointer: impl Into<String>) -> Self {
+        self.pointer = Some(pointer.into());
+        self
+    }
+
+    /// Set the severity of the problem.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+impl Deref for CheckError {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.message
+    }
+}
 
+impl Display for CheckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(pointer) = &self.pointer {
+            write!(f, " (at {pointer})")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&'static str> for CheckError {
+    fn from(value: &'static str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for CheckError {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Cow<'static, str>> for CheckError {
+    fn from(value: Cow<'static, str>) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A check on a CSAF document.
+///
+/// Checks must be [`Send`] and [`Sync`], as the visitor running them may be shared between the
+/// workers of a parallel walk.
 #[async_trait(?Send)]
-pub trait Check {
+pub trait Check: Send + Sync {
     /// Perform a check on a CSAF document
     async fn check(&self, csaf: &Csaf) -> anyhow::Result<Vec<CheckError>>;
+
+    /// Perform a check on a CSAF document, having access to its original JSON source.
+    ///
+    /// Some information (like the declared CVSS base scores) is not retained by the [`Csaf`]
+    /// model. By default, this delegates to [`Self::check`].
+    async fn check_source(&self, csaf: &Csaf, source: &[u8]) -> anyhow::Result<Vec<CheckError>> {
+        let _ = source;
+        self.check(csaf).await
+    }
 }
 
 /// Implementation to allow a simple function style check
 #[async_trait(?Send)]
 impl<F> Check for F
 where
-    F: Fn(&Csaf) -> Vec<CheckError>,
+    F: Fn(&Csaf) -> Vec<CheckError> + Send + Sync,
 {
     async fn check(&self, csaf: &Csaf) -> anyhow::Result<Vec<CheckError>> {
         Ok((self)(csaf))
@@ -48,9 +207,19 @@ impl Checking {
         Default::default()
     }
 
-    pub fn require(mut self, msg: impl Into<CheckError>, ok: bool) -> Self {
+    pub fn require(self, msg: impl Into<CheckError>, ok: bool) -> Self {
+        self.require_with(Severity::Error, msg, ok)
+    }
+
+    /// Like [`Self::require`], reporting a failure with the provided severity.
+    pub fn require_with(
+        mut self,
+        severity: Severity,
+        msg: impl Into<CheckError>,
+        ok: bool,
+    ) -> Self {
         if !ok {
-            self.results.push(msg.into());
+            self.results.push(msg.into().severity(severity));
         }
         self
     }
@@ -88,18 +257,265 @@ pub fn init_verifying_visitor() -> Vec<(&'static str, Box<dyn Check>)> {
             Box::new(check_vulnerabilities_product_status),
         ),
         (
-            "check_vulnerabilities_cve_ids",
-            Box::new(check_vulnerabilities_cve_ids),
+            "check_vulnerabilities_cve_format",
+            Box::new(check_vulnerabilities_cve_format),
+        ),
+        (
+            "check_vulnerability_identifiable",
+            Box::new(check_vulnerability_identifiable),
         ),
         (
             "check_all_products_v11ies_exits_in_product_tree",
             Box::new(check_all_products_v11ies_exits_in_product_tree),
         ),
+        (
+            "check_all_remediation_products_exist_in_product_tree",
+            Box::new(check_all_remediation_products_exist_in_product_tree),
+        ),
         ("check_history", Box::new(check_history)),
         ("check_csaf_vex", Box::new(check_csaf_vex)),
         (
             "check_branches_relationships_product_match",
             Box::new(check_branches_relationships_product_match),
         ),
+        (
+            "check_product_tree_relationship_cycles",
+            Box::new(check_product_tree_relationship_cycles),
+        ),
+        ("check_cvss_scores", Box::new(CvssScores::default())),
+        ("check_tracking_dates", Box::new(check_tracking_dates)),
     ]
 }
+
+/// A profile of the CSAF specification, defining which checks apply to a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CsafProfile {
+    Base,
+    SecurityIncidentResponse,
+    InformationalAdvisory,
+    SecurityAdvisory,
+    Vex,
+}
+
+impl CsafProfile {
+    pub const ALL: [Self; 5] = [
+        Self::Base,
+        Self::SecurityIncidentResponse,
+        Self::InformationalAdvisory,
+        Self::SecurityAdvisory,
+        Self::Vex,
+    ];
+
+    /// The name of the profile, as used by `/document/category` (without the `csaf_` prefix).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Base => "base",
+            Self::SecurityIncidentResponse => "security_incident_response",
+            Self::InformationalAdvisory => "informational_advisory",
+            Self::SecurityAdvisory => "security_advisory",
+            Self::Vex => "vex",
+        }
+    }
+
+    /// Detect the profile of a document from its `/document/category`.
+    ///
+    /// Categories not matching any of the profiles fall back to [`Self::Base`].
+    pub fn detect(csaf: &Csaf) -> Self {
+        match &csaf.document.category {
+            Category::SecurityAdvisory => Self::SecurityAdvisory,
+            Category::Vex => Self::Vex,
+            Category::Other(category) => match category.as_str() {
+                "csaf_security_incident_response" => Self::SecurityIncidentResponse,
+                "csaf_informational_advisory" => Self::InformationalAdvisory,
+                _ => Self::Base,
+            },
+            Category::Base => Self::Base,
+        }
+    }
+
+    /// The checks of the profile, which include the checks of the base profile.
+    pub fn checks(&self) -> Vec<(&'static str, Box<dyn Check>)> {
+        let checks = match self {
+            Self::Base => vec![],
+            Self::SecurityIncidentResponse => {
+                init_csaf_is_security_incident_response_verifying_visitor()
+            }
+            Self::InformationalAdvisory => init_csaf_informational_advisory_verifying_visitor(),
+            Self::SecurityAdvisory => init_csaf_security_advisory_verifying_visitor(),
+            Self::Vex => init_vex_fmt_verifying_visitor(),
+        };
+
+        let mut result = init_csaf_base_verifying_visitor();
+        for (name, check) in checks {
+            if !result.iter().any(|(existing, _)| *existing == name) {
+                result.push((name, check));
+            }
+        }
+        result
+    }
+}
+
+impl Display for CsafProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "unknown profile '{0}', expected one of: base, security_incident_response, informational_advisory, security_advisory, vex"
+)]
+pub struct UnknownProfile(pub String);
+
+impl FromStr for CsafProfile {
+    type Err = UnknownProfile;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.strip_prefix("csaf_").unwrap_or(s);
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name() == name)
+            .ok_or_else(|| UnknownProfile(s.to_string()))
+    }
+}
+
+/// A check which only runs on documents of some profiles.
+struct ProfileCheck {
+    profiles: Vec<CsafProfile>,
+    check: Box<dyn Check>,
+}
+
+#[async_trait(?Send)]
+impl Check for ProfileCheck {
+    async fn check(&self, csaf: &Csaf) -> anyhow::Result<Vec<CheckError>> {
+        if !self.profiles.contains(&CsafProfile::detect(csaf)) {
+            return Ok(vec![]);
+        }
+        self.check.check(csaf).await
+    }
+
+    async fn check_source(&self, csaf: &Csaf, source: &[u8]) -> anyhow::Result<Vec<CheckError>> {
+        if !self.profiles.contains(&CsafProfile::detect(csaf)) {
+            return Ok(vec![]);
+        }
+        self.check.check_source(csaf, source).await
+    }
+}
+
+/// Create the checks of a profile.
+///
+/// If no profile is provided, the profile of each document is detected from its
+/// `/document/category`, running only the checks of that profile.
+pub fn init_profile_verifying_visitor(
+    profile: Option<CsafProfile>,
+) -> Vec<(&'static str, Box<dyn Check>)> {
+    if let Some(profile) = profile {
+        return profile.checks();
+    }
+
+    let mut result: Vec<(&'static str, ProfileCheck)> = vec![];
+    for profile in CsafProfile::ALL {
+        for (name, check) in profile.checks() {
+            match result.iter_mut().find(|(existing, _)| *existing == name) {
+                Some((_, existing)) => existing.profiles.push(profile),
+                None => result.push((
+                    name,
+                    ProfileCheck {
+                        profiles: vec![profile],
+                        check,
+                    },
+                )),
+            }
+        }
+    }
+
+    result
+        .into_iter()
+        .map(|(name, check)| (name, Box::new(check) as Box<dyn Check>))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_from_str() {
+        for profile in CsafProfile::ALL {
+            assert_eq!(profile.name().parse::<CsafProfile>().ok(), Some(profile));
+        }
+        assert_eq!(
+            "csaf_vex".parse::<CsafProfile>().ok(),
+            Some(CsafProfile::Vex)
+        );
+        assert!("foo".parse::<CsafProfile>().is_err());
+    }
+
+    #[tokio::test]
+    async fn profile_detected() {
+        let mut csaf: Csaf =
+            serde_json::from_str(include_str!("../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        assert_eq!(CsafProfile::detect(&csaf), CsafProfile::Vex);
+
+        csaf.document.category = Category::SecurityAdvisory;
+        assert_eq!(CsafProfile::detect(&csaf), CsafProfile::SecurityAdvisory);
+
+        let checks = init_profile_verifying_visitor(None);
+        let find = |name| {
+            checks
+                .iter()
+                .find(|(check, _)| *check == name)
+                .map(|(_, check)| check)
+                .expect("check must be registered")
+        };
+
+        // checks of other profiles don't apply
+        csaf.vulnerabilities = None;
+        assert_eq!(check_vulnerabilities_not_exits(&csaf).len(), 1);
+        let not_exists = find("check_vulnerabilities_not_exits");
+        assert!(not_exists.check(&csaf).await.expect("must run").is_empty());
+
+        let relationships = csaf
+            .product_tree
+            .as_mut()
+            .and_then(|tree| tree.relationships.as_mut())
+            .expect("example data must have relationships");
+        relationships[0].product_reference = relationships[0].full_product_name.product_id.clone();
+        let cycles = find("check_product_tree_relationship_cycles");
+        assert_eq!(cycles.check(&csaf).await.expect("must run").len(), 1);
+
+        csaf.document.category = Category::Vex;
+        assert_eq!(CsafProfile::detect(&csaf), CsafProfile::Vex);
+        assert!(cycles.check(&csaf).await.expect("must run").is_empty());
+    }
+
+    #[tokio::test]
+    async fn vex_unidentifiable_reported_once() {
+        let mut csaf: Csaf =
+            serde_json::from_str(include_str!("../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        assert_eq!(CsafProfile::detect(&csaf), CsafProfile::Vex);
+
+        let vuln = csaf
+            .vulnerabilities
+            .iter_mut()
+            .flatten()
+            .next()
+            .expect("example data must have vulnerabilities");
+        vuln.cve = None;
+        vuln.ids = None;
+
+        let mut findings = 0;
+        for (_, check) in CsafProfile::Vex.checks() {
+            findings += check
+                .check(&csaf)
+                .await
+                .expect("must run")
+                .iter()
+                .filter(|error| error.contains("CVE") && error.contains("IDs"))
+                .count();
+        }
+        assert_eq!(findings, 1);
+    }
+}
@@ -1,10 +1,405 @@
-use crate::verification::{
-    check::Check, check::vex::check_all_products_v11ies_exits_in_product_tree,
+use crate::verification::check::{
+    Check, CheckError,
+    cvss::CvssScores,
+    tracking::check_tracking_dates,
+    vex::{
+        check_all_products_v11ies_exits_in_product_tree,
+        check_all_remediation_products_exist_in_product_tree,
+    },
 };
+use csaf::Csaf;
+use std::{cmp::Ordering, collections::HashMap};
+
+/// A revision number, using either integer or semantic versioning.
+#[derive(Debug)]
+enum RevisionNumber {
+    Integer(u64),
+    Semantic(semver::Version),
+}
+
+impl RevisionNumber {
+    fn parse(number: &str) -> Option<Self> {
+        if let Ok(number) = number.parse() {
+            return Some(Self::Integer(number));
+        }
+        semver::Version::parse(number).ok().map(Self::Semantic)
+    }
+
+    /// Compare two revision numbers, `None` if they use different versioning schemes.
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(b)),
+            (Self::Semantic(a), Self::Semantic(b)) => Some(a.cmp_precedence(b)),
+            _ => None,
+        }
+    }
+}
+
+/// Verify that the revision numbers of /document/tracking/revision_history are strictly
+/// increasing when ordered by date, and that /document/tracking/version matches the latest one.
+pub fn check_revision_history_version_monotonicity(csaf: &Csaf) -> Vec<CheckError> {
+    let mut results = vec![];
+
+    let mut revisions = vec![];
+    for (i, revision) in csaf.document.tracking.revision_history.iter().enumerate() {
+        match RevisionNumber::parse(&revision.number) {
+            Some(number) => revisions.push((i, revision, number)),
+            None => results.push(
+                CheckError::new(format!(
+                    "The revision number {:?} is neither an integer nor a semantic version",
+                    revision.number
+                ))
+                .at(format!("/document/tracking/revision_history/{i}/number")),
+            ),
+        }
+    }
+
+    // stable, so revisions of the same date keep their document order
+    revisions.sort_by_key(|(_, revision, _)| revision.date);
+
+    for pair in revisions.windows(2) {
+        let (_, previous, previous_number) = &pair[0];
+        let (i, current, current_number) = &pair[1];
+        let (previous, current) = (&previous.number, &current.number);
+
+        let message = match current_number.compare(previous_number) {
+            Some(Ordering::Greater) => continue,
+            Some(Ordering::Equal) => format!("The revision number {current:?} is duplicated"),
+            Some(Ordering::Less) => format!(
+                "The revision number {current:?} is lower than the one of the previous revision ({previous:?})"
+            ),
+            None => format!(
+                "The revision number {current:?} uses a different versioning scheme than the previous revision ({previous:?})"
+            ),
+        };
+
+        results.push(
+            CheckError::new(message).at(format!("/document/tracking/revision_history/{i}/number")),
+        );
+    }
+
+    if let Some((_, latest, latest_number)) = revisions.last() {
+        let latest = &latest.number;
+        let version = &csaf.document.tracking.version;
+        let matches = match RevisionNumber::parse(version) {
+            Some(version) => version.compare(latest_number) == Some(Ordering::Equal),
+            None => version == latest,
+        };
+
+        if !matches {
+            results.push(
+                CheckError::new(format!(
+                    "The tracking version ({version}) does not match the number of the latest revision ({latest})"
+                ))
+                .at("/document/tracking/version"),
+            );
+        }
+    }
+
+    results
+}
+
+/// Check if an ID has the format of a CVE ID: `CVE-\d{4}-\d{4,}`.
+fn is_cve_id(id: &str) -> bool {
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    match id.strip_prefix("CVE-").and_then(|id| id.split_once('-')) {
+        Some((year, number)) => {
+            year.len() == 4 && digits(year) && number.len() >= 4 && digits(number)
+        }
+        None => false,
+    }
+}
+
+/// Verify that each /vulnerabilities[]/cve is a well-formed CVE ID, and that no CVE ID is used
+/// by more than one vulnerability.
+pub fn check_vulnerabilities_cve_format(csaf: &Csaf) -> Vec<CheckError> {
+    let mut results = vec![];
+    let mut seen = HashMap::new();
+
+    for (i, vuln) in csaf.vulnerabilities.iter().flatten().enumerate() {
+        let Some(cve) = &vuln.cve else {
+            continue;
+        };
+
+        if !is_cve_id(cve) {
+            results.push(
+                CheckError::new(format!(
+                    "The CVE ID {cve:?} of vulnerability {i} is malformed"
+                ))
+                .at(format!("/vulnerabilities/{i}/cve")),
+            );
+        }
+
+        if let Some(first) = seen.insert(cve.as_str(), i) {
+            results.push(
+                CheckError::new(format!(
+                    "The CVE ID {cve:?} of vulnerability {i} is already used by vulnerability {first}"
+                ))
+                .at(format!("/vulnerabilities/{i}/cve")),
+            );
+        }
+    }
+
+    results
+}
+
+/// The state of a product during the search for cycles.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    Visiting,
+    Done,
+}
+
+/// The products referenced by the relationships defining a product, along with the index of the
+/// relationship.
+type RelationshipGraph<'a> = HashMap<&'a str, Vec<(usize, &'a str)>>;
+
+/// Search for cycles reachable from a product, using an explicit stack, as documents might
+/// contain long chains of relationships.
+fn find_cycles<'a>(
+    product: &'a str,
+    graph: &RelationshipGraph<'a>,
+    state: &mut HashMap<&'a str, VisitState>,
+    results: &mut Vec<CheckError>,
+) {
+    // the products of the current path, along with the index of the next edge to follow
+    let mut path = vec![(product, 0usize)];
+    state.insert(product, VisitState::Visiting);
+
+    while let Some((product, edge)) = path.last_mut() {
+        let product = *product;
+        let Some(&(i, next)) = graph.get(product).and_then(|edges| edges.get(*edge)) else {
+            path.pop();
+            state.insert(product, VisitState::Done);
+            continue;
+        };
+        *edge += 1;
+
+        match state.get(next) {
+            Some(VisitState::Visiting) => {
+                let start = path
+                    .iter()
+                    .position(|(id, _)| *id == next)
+                    .unwrap_or_default();
+                let cycle = path[start..]
+                    .iter()
+                    .map(|(id, _)| *id)
+                    .chain([next])
+                    .collect::<Vec<_>>();
+                results.push(
+                    CheckError::new(format!(
+                        "The relationships of the product tree form a cycle: {}",
+                        cycle.join(" -> ")
+                    ))
+                    .at(format!("/product_tree/relationships/{i}")),
+                );
+            }
+            Some(VisitState::Done) => {}
+            None => {
+                state.insert(next, VisitState::Visiting);
+                path.push((next, 0));
+            }
+        }
+    }
+}
+
+/// Verify that the products defined by /product_tree/relationships don't (directly or
+/// indirectly) refer to themselves.
+pub fn check_product_tree_relationship_cycles(csaf: &Csaf) -> Vec<CheckError> {
+    let Some(relationships) = csaf
+        .product_tree
+        .as_ref()
+        .and_then(|tree| tree.relationships.as_ref())
+    else {
+        return vec![];
+    };
+
+    let mut graph = RelationshipGraph::new();
+    for (i, relationship) in relationships.iter().enumerate() {
+        graph
+            .entry(relationship.full_product_name.product_id.0.as_str())
+            .or_default()
+            .extend([
+                (i, relationship.product_reference.0.as_str()),
+                (i, relationship.relates_to_product_reference.0.as_str()),
+            ]);
+    }
+
+    let mut results = vec![];
+    let mut state = HashMap::new();
+    for relationship in relationships {
+        let product = relationship.full_product_name.product_id.0.as_str();
+        if !state.contains_key(product) {
+            find_cycles(product, &graph, &mut state, &mut results);
+        }
+    }
+
+    results
+}
 
 pub fn init_csaf_security_advisory_verifying_visitor() -> Vec<(&'static str, Box<dyn Check>)> {
-    vec![(
-        "check_all_products_v11ies_exits_in_product_tree",
-        Box::new(check_all_products_v11ies_exits_in_product_tree),
-    )]
+    vec![
+        (
+            "check_all_products_v11ies_exits_in_product_tree",
+            Box::new(check_all_products_v11ies_exits_in_product_tree),
+        ),
+        (
+            "check_all_remediation_products_exist_in_product_tree",
+            Box::new(check_all_remediation_products_exist_in_product_tree),
+        ),
+        (
+            "check_revision_history_version_monotonicity",
+            Box::new(check_revision_history_version_monotonicity),
+        ),
+        (
+            "check_vulnerabilities_cve_format",
+            Box::new(check_vulnerabilities_cve_format),
+        ),
+        (
+            "check_product_tree_relationship_cycles",
+            Box::new(check_product_tree_relationship_cycles),
+        ),
+        ("check_cvss_scores", Box::new(CvssScores::default())),
+        ("check_tracking_dates", Box::new(check_tracking_dates)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verification::check::security_advisory::{
+        check_product_tree_relationship_cycles, check_revision_history_version_monotonicity,
+        check_vulnerabilities_cve_format, is_cve_id,
+    };
+    use csaf::{Csaf, document::Revision};
+
+    #[tokio::test]
+    async fn test_check_product_tree_relationship_cycles() {
+        let mut csaf: Csaf =
+            serde_json::from_str(include_str!("../../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        assert_eq!(check_product_tree_relationship_cycles(&csaf).len(), 0);
+
+        let relationships = csaf
+            .product_tree
+            .as_mut()
+            .and_then(|tree| tree.relationships.as_mut())
+            .expect("example data must have relationships");
+        let first = relationships[0].full_product_name.product_id.clone();
+        let second = relationships[1].full_product_name.product_id.clone();
+        relationships[0].product_reference = second.clone();
+        relationships[1].product_reference = first.clone();
+        relationships[2].relates_to_product_reference =
+            relationships[2].full_product_name.product_id.clone();
+
+        let result = check_product_tree_relationship_cycles(&csaf);
+        assert_eq!(result.len(), 2);
+        assert!(result[0].contains(&format!("{} -> {} -> {}", first.0, second.0, first.0)));
+        assert_eq!(
+            result[0].pointer.as_deref(),
+            Some("/product_tree/relationships/1")
+        );
+        assert_eq!(
+            result[1].pointer.as_deref(),
+            Some("/product_tree/relationships/2")
+        );
+    }
+
+    #[test]
+    fn test_check_product_tree_relationship_cycles_long_chain() {
+        const LENGTH: usize = 100_000;
+
+        let mut source: serde_json::Value =
+            serde_json::from_str(include_str!("../../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        let relationships = &mut source["product_tree"]["relationships"];
+        let template = relationships[0].clone();
+        *relationships = (0..LENGTH)
+            .map(|n| {
+                let mut relationship = template.clone();
+                relationship["full_product_name"]["product_id"] = format!("chain-{n}").into();
+                // the last product refers back to the first one
+                relationship["product_reference"] = format!("chain-{}", (n + 1) % LENGTH).into();
+                relationship
+            })
+            .collect();
+        let csaf: Csaf = serde_json::from_value(source).expect("chain must parse");
+
+        let result = check_product_tree_relationship_cycles(&csaf);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].pointer.as_deref(),
+            Some(format!("/product_tree/relationships/{}", LENGTH - 1).as_str())
+        );
+    }
+
+    #[test]
+    fn test_is_cve_id() {
+        assert!(is_cve_id("CVE-2024-1234"));
+        assert!(is_cve_id("CVE-2024-1234567"));
+        assert!(!is_cve_id("CVE-20240-1"));
+        assert!(!is_cve_id("CVE-2024-123"));
+        assert!(!is_cve_id("cve-2024-1234"));
+        assert!(!is_cve_id("CVE-2024-1234 "));
+        assert!(!is_cve_id("CVE-2024"));
+    }
+
+    #[tokio::test]
+    async fn test_check_vulnerabilities_cve_format() {
+        let mut csaf: Csaf =
+            serde_json::from_str(include_str!("../../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        assert_eq!(check_vulnerabilities_cve_format(&csaf).len(), 0);
+
+        let vulnerabilities = csaf
+            .vulnerabilities
+            .as_mut()
+            .expect("example data must have vulnerabilities");
+        let first = vulnerabilities[0].cve.clone();
+        vulnerabilities[1].cve = first;
+        vulnerabilities[2].cve = Some("CVE-20240-1".to_string());
+
+        let result = check_vulnerabilities_cve_format(&csaf);
+        assert_eq!(result.len(), 2);
+        assert!(result[0].contains("already used by vulnerability 0"));
+        assert_eq!(result[0].pointer.as_deref(), Some("/vulnerabilities/1/cve"));
+        assert!(result[1].contains("\"CVE-20240-1\""));
+        assert_eq!(result[1].pointer.as_deref(), Some("/vulnerabilities/2/cve"));
+    }
+
+    #[tokio::test]
+    async fn test_check_revision_history_version_monotonicity() {
+        let mut csaf: Csaf =
+            serde_json::from_str(include_str!("../../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        assert_eq!(check_revision_history_version_monotonicity(&csaf).len(), 0);
+
+        // add a later revision, re-using the number of the first one
+        let history = &mut csaf.document.tracking.revision_history;
+        let first = history.first().expect("example data must have a revision");
+        let revision = Revision {
+            date: first.date + chrono::Duration::days(1),
+            legacy_version: None,
+            number: first.number.clone(),
+            summary: "Duplicate".to_string(),
+        };
+        history.push(revision);
+
+        let result = check_revision_history_version_monotonicity(&csaf);
+        assert_eq!(result.len(), 1);
+        assert!(result[0].contains("duplicated"));
+        assert_eq!(
+            result[0].pointer.as_deref(),
+            Some("/document/tracking/revision_history/1/number")
+        );
+
+        // and bump the version of the tracking information, which no longer matches
+        csaf.document.tracking.version = "2".to_string();
+        let result = check_revision_history_version_monotonicity(&csaf);
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result[1].pointer.as_deref(),
+            Some("/document/tracking/version")
+        );
+    }
 }
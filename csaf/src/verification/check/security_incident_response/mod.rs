@@ -1,4 +1,4 @@
-use crate::verification::check::{Check, CheckError, Checking};
+use crate::verification::check::{Check, CheckError, Checking, Severity};
 use csaf::{
     Csaf,
     definitions::{NoteCategory, ReferenceCategory},
@@ -22,7 +22,7 @@ pub fn check_csaf_document_notes(csaf: &Csaf) -> Vec<CheckError> {
             result |= is_invalid_note;
         }
     }
-    Checking::new().require("The document note with at least one item which has a category of description, details, general or summary", result).done()
+    Checking::new().require_with(Severity::Warning, "The document note with at least one item which has a category of description, details, general or summary", result).done()
 }
 
 pub fn check_csaf_document_references(csaf: &Csaf) -> Vec<CheckError> {
@@ -41,7 +41,8 @@ pub fn check_csaf_document_references(csaf: &Csaf) -> Vec<CheckError> {
         }
     }
     Checking::new()
-        .require(
+        .require_with(
+            Severity::Warning,
             "The document references with at least one item which has a category of external",
             result,
         )
@@ -64,7 +65,7 @@ pub fn init_csaf_is_security_incident_response_verifying_visitor()
             Box::new(check_csaf_document_notes),
         ),
         (
-            "check_csaf_document_tracking_revision_history",
+            "check_csaf_document_references",
             Box::new(check_csaf_document_references),
         ),
     ]
@@ -0,0 +1,80 @@
+use crate::verification::check::{CheckError, Severity};
+use csaf::Csaf;
+
+/// Verify that /document/tracking/current_release_date doesn't precede
+/// /document/tracking/initial_release_date, and that no date of
+/// /document/tracking/revision_history is later than the current release date.
+///
+/// A revision later than the current release date is only reported as a warning.
+pub fn check_tracking_dates(csaf: &Csaf) -> Vec<CheckError> {
+    let mut results = vec![];
+    let tracking = &csaf.document.tracking;
+    let initial = tracking.initial_release_date;
+    let current = tracking.current_release_date;
+
+    if current < initial {
+        results.push(
+            CheckError::new(format!(
+                "The current release date ({current}) precedes the initial release date ({initial})"
+            ))
+            .at("/document/tracking/current_release_date"),
+        );
+    }
+
+    let latest = tracking
+        .revision_history
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, revision)| revision.date);
+
+    if let Some((i, revision)) = latest
+        && revision.date > current
+    {
+        results.push(
+            CheckError::new(format!(
+                "The date of the latest revision ({}) is later than the current release date ({current})",
+                revision.date
+            ))
+            .at(format!("/document/tracking/revision_history/{i}/date"))
+            .severity(Severity::Warning),
+        );
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verification::check::{Severity, tracking::check_tracking_dates};
+    use csaf::Csaf;
+
+    const SOURCE: &str = include_str!("../../../../test-data/rhsa-2021_3029.json");
+
+    #[test]
+    fn test_check_tracking_dates() {
+        let csaf: Csaf = serde_json::from_str(SOURCE).expect("example data must parse");
+        assert_eq!(check_tracking_dates(&csaf).len(), 0);
+
+        let mut source: serde_json::Value = serde_json::from_str(SOURCE).unwrap();
+        let tracking = &mut source["document"]["tracking"];
+        tracking["initial_release_date"] = "2030-01-01T00:00:00Z".into();
+        tracking["current_release_date"] = "2020-01-01T00:00:00Z".into();
+        let csaf: Csaf = serde_json::from_value(source).unwrap();
+
+        let result = check_tracking_dates(&csaf);
+        assert_eq!(result.len(), 2);
+        assert!(result[0].contains("precedes"));
+        assert!(result[0].contains("2030-01-01"));
+        assert_eq!(result[0].severity, Severity::Error);
+        assert_eq!(
+            result[0].pointer.as_deref(),
+            Some("/document/tracking/current_release_date")
+        );
+        assert!(result[1].contains("latest revision"));
+        assert_eq!(result[1].severity, Severity::Warning);
+        assert_eq!(
+            result[1].pointer.as_deref(),
+            Some("/document/tracking/revision_history/0/date")
+        );
+    }
+}
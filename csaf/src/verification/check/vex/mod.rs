@@ -80,6 +80,9 @@ pub fn check_vulnerabilities_product_status(csaf: &Csaf) -> Vec<CheckError> {
 /// There needs at least one of
 /// /vulnerabilities[]/cve
 /// /vulnerabilities[]/ids
+///
+/// This is covered by [`check_vulnerability_identifiable`](crate::verification::check::base::check_vulnerability_identifiable)
+/// for all profiles, so it isn't part of the VEX checks.
 pub fn check_vulnerabilities_cve_ids(csaf: &Csaf) -> Vec<CheckError> {
     if !is_vex(csaf) {
         return vec![];
@@ -183,16 +186,15 @@ fn get_all_product_names(product_tree: &ProductTree, products: &mut HashSet<Stri
 fn check_product(
     product_names: &mut HashSet<String>,
     product_id_t: &ProductIdT,
+    pointer: String,
     erroies: &mut Vec<CheckError>,
 ) {
-    erroies.extend(
-        Checking::new()
-            .require(
-                format!("The product under the 'product status' section of the vulnerabilities division, identified as {}, is missing from the product tree.", &product_id_t.0),
-                product_names.contains(&product_id_t.0),
-            )
-            .done(),
-    );
+    if !product_names.contains(&product_id_t.0) {
+        erroies.push(
+            CheckError::new(format!("The product under the 'product status' section of the vulnerabilities division, identified as {}, is missing from the product tree.", &product_id_t.0))
+                .at(pointer),
+        );
+    }
 }
 
 /// Verify that all vulnerabilities present in /vulnerabilities are also contained within product tree.
@@ -210,64 +212,43 @@ pub fn check_all_products_v11ies_exits_in_product_tree(csaf: &Csaf) -> Vec<Check
 
         get_all_product_names(products_tree, &mut product_names);
         if let Some(v11y) = &csaf.vulnerabilities {
-            for v in v11y {
+            for (i, v) in v11y.iter().enumerate() {
                 if let Some(product_status) = &v.product_status {
-                    if let Some(product_its) = &product_status.known_affected {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
-                        }
-                    }
-                    if let Some(product_its) = &product_status.known_not_affected {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
-                        }
-                    }
-                    if let Some(product_its) = &product_status.fixed {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
-                        }
-                    }
-                    if let Some(product_its) = &product_status.first_fixed {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
-                        }
-                    }
-                    if let Some(product_its) = &product_status.first_affected {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
-                        }
-                    }
-                    if let Some(product_its) = &product_status.last_affected {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
-                        }
-                    }
-                    if let Some(product_its) = &product_status.recommended {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
-                        }
-                    }
-                    if let Some(product_its) = &product_status.under_investigation {
-                        for product in product_its {
-                            check_product(&mut product_names, product, &mut results);
+                    for (name, product_its) in [
+                        ("known_affected", &product_status.known_affected),
+                        ("known_not_affected", &product_status.known_not_affected),
+                        ("fixed", &product_status.fixed),
+                        ("first_fixed", &product_status.first_fixed),
+                        ("first_affected", &product_status.first_affected),
+                        ("last_affected", &product_status.last_affected),
+                        ("recommended", &product_status.recommended),
+                        ("under_investigation", &product_status.under_investigation),
+                    ] {
+                        for (j, product) in product_its.iter().flatten().enumerate() {
+                            check_product(
+                                &mut product_names,
+                                product,
+                                format!("/vulnerabilities/{i}/product_status/{name}/{j}"),
+                                &mut results,
+                            );
                         }
                     }
                 }
                 if let Some(rs) = &v.remediations {
-                    for remediation in rs {
+                    for (k, remediation) in rs.iter().enumerate() {
                         if let Some(product_ids) = &remediation.product_ids {
-                            for product_id in product_ids {
-                                results.extend(
-                                    Checking::new()
-                                        .require(
-                                            format!(
-                                                "The product under the 'remediation' section of the vulnerabilities division, identified as {:?}, is missing from the product tree.",
-                                                product_id.clone().0
-                                            ),
-                                            product_names.contains(&product_id.0),
-                                        )
-                                        .done(),
-                                );
+                            for (j, product_id) in product_ids.iter().enumerate() {
+                                if !product_names.contains(&product_id.0) {
+                                    results.push(
+                                        CheckError::new(format!(
+                                            "The product under the 'remediation' section of the vulnerabilities division, identified as {:?}, is missing from the product tree.",
+                                            product_id.clone().0
+                                        ))
+                                        .at(format!(
+                                            "/vulnerabilities/{i}/remediations/{k}/product_ids/{j}"
+                                        )),
+                                    );
+                                }
                             }
                         }
                     }
@@ -278,6 +259,69 @@ pub fn check_all_products_v11ies_exits_in_product_tree(csaf: &Csaf) -> Vec<Check
     results
 }
 
+/// Verify that all products referenced by /vulnerabilities[]/remediations (including those
+/// requiring a restart), /vulnerabilities[]/scores and /vulnerabilities[]/flags are contained
+/// within the product tree.
+pub fn check_all_remediation_products_exist_in_product_tree(csaf: &Csaf) -> Vec<CheckError> {
+    if !is_vex(csaf) && !is_security_advisory(csaf) {
+        return vec![];
+    }
+    let Some(products_tree) = &csaf.product_tree else {
+        return vec![];
+    };
+
+    let mut product_names = HashSet::new();
+    if let Some(branches) = &products_tree.branches {
+        get_all_product_id_from_product_tree_branches(branches, &mut product_names);
+    }
+    get_all_product_names(products_tree, &mut product_names);
+    for product in products_tree.full_product_names.iter().flatten() {
+        product_names.insert(product.product_id.0.clone());
+    }
+
+    let mut results = vec![];
+    for (i, v) in csaf.vulnerabilities.iter().flatten().enumerate() {
+        let cve = v.cve.as_deref().unwrap_or("<unknown>");
+
+        let remediations = v.remediations.iter().flatten().enumerate().map(|(k, r)| {
+            (
+                "remediations",
+                format!("/vulnerabilities/{i}/remediations/{k}/product_ids"),
+                r.product_ids.as_ref(),
+            )
+        });
+        let scores = v.scores.iter().flatten().enumerate().map(|(k, s)| {
+            (
+                "scores",
+                format!("/vulnerabilities/{i}/scores/{k}/products"),
+                Some(&s.products),
+            )
+        });
+        let flags = v.flags.iter().flatten().enumerate().map(|(k, f)| {
+            (
+                "flags",
+                format!("/vulnerabilities/{i}/flags/{k}/product_ids"),
+                f.product_ids.as_ref(),
+            )
+        });
+
+        for (section, pointer, product_ids) in remediations.chain(scores).chain(flags) {
+            for (j, product_id) in product_ids.into_iter().flatten().enumerate() {
+                if !product_names.contains(&product_id.0) {
+                    results.push(
+                        CheckError::new(format!(
+                            "The product under the '{section}' section of vulnerability {cve}, identified as {:?}, is missing from the product tree.",
+                            product_id.0
+                        ))
+                        .at(format!("{pointer}/{j}")),
+                    );
+                }
+            }
+        }
+    }
+    results
+}
+
 /// Check revision history
 pub fn check_history(csaf: &Csaf) -> Vec<CheckError> {
     if !is_vex(csaf) {
@@ -321,14 +365,14 @@ pub fn init_vex_fmt_verifying_visitor() -> Vec<(&'static str, Box<dyn Check>)> {
             "check_vulnerabilities_product_status",
             Box::new(check_vulnerabilities_product_status),
         ),
-        (
-            "check_vulnerabilities_cve_ids",
-            Box::new(check_vulnerabilities_cve_ids),
-        ),
         (
             "check_all_products_v11ies_exits_in_product_tree",
             Box::new(check_all_products_v11ies_exits_in_product_tree),
         ),
+        (
+            "check_all_remediation_products_exist_in_product_tree",
+            Box::new(check_all_remediation_products_exist_in_product_tree),
+        ),
         ("check_history", Box::new(check_history)),
         ("check_csaf_vex", Box::new(check_csaf_vex)),
         (
@@ -342,11 +386,12 @@ pub fn init_vex_fmt_verifying_visitor() -> Vec<(&'static str, Box<dyn Check>)> {
 mod tests {
     use crate::verification::check::vex::{
         check_all_products_v11ies_exits_in_product_tree,
+        check_all_remediation_products_exist_in_product_tree,
         check_branches_relationships_product_match, check_csaf_vex, check_history,
         check_vulnerabilities_cve_ids, check_vulnerabilities_product_status,
         check_vulnerabilities_size,
     };
-    use csaf::Csaf;
+    use csaf::{Csaf, definitions::ProductIdT};
 
     /// Verify notexits-7ComputeNode-7.7.EUS:microcode_ctl-2:2.1-53.18.el7_7.src does not exits in product tree
     #[tokio::test]
@@ -354,12 +399,59 @@ mod tests {
         let csaf: Csaf =
             serde_json::from_str(include_str!("../../../../test-data/rhsa-2021_3029.json"))
                 .expect("example data must parse");
+        let result = check_all_products_v11ies_exits_in_product_tree(&csaf);
+        let first = result.first().expect("must return an item");
+        assert!(first.contains("notexits"));
+        assert_eq!(
+            first.pointer.as_deref(),
+            Some("/vulnerabilities/5/product_status/fixed/0")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_all_remediation_products_exist_in_product_tree() {
+        let mut csaf: Csaf =
+            serde_json::from_str(include_str!("../../../../test-data/rhsa-2021_3029.json"))
+                .expect("example data must parse");
+        assert_eq!(
+            check_all_remediation_products_exist_in_product_tree(&csaf).len(),
+            0
+        );
+
+        let existing = check_all_products_v11ies_exits_in_product_tree(&csaf).len();
+
+        let vuln = csaf
+            .vulnerabilities
+            .as_mut()
+            .and_then(|vulns| vulns.first_mut())
+            .expect("example data must have a vulnerability");
+        let cve = vuln.cve.clone().expect("example data must have a CVE");
+        vuln.remediations
+            .as_mut()
+            .and_then(|remediations| remediations.first_mut())
+            .and_then(|remediation| remediation.product_ids.as_mut())
+            .expect("example data must have remediation products")
+            .push(ProductIdT("dangling-product".to_string()));
+
+        // the existing check keeps reporting remediations
+        let result = check_all_products_v11ies_exits_in_product_tree(&csaf);
+        assert_eq!(result.len(), existing + 1);
         assert!(
-            check_all_products_v11ies_exits_in_product_tree(&csaf)
-                .first()
-                .expect("must return an item")
-                .contains("notexits")
-        )
+            result
+                .iter()
+                .any(|error| error.contains("dangling-product"))
+        );
+
+        let result = check_all_remediation_products_exist_in_product_tree(&csaf);
+        assert_eq!(result.len(), 1);
+        assert!(result[0].contains("dangling-product"));
+        assert!(result[0].contains(&cve));
+        assert!(
+            result[0]
+                .pointer
+                .as_deref()
+                .is_some_and(|pointer| pointer.starts_with("/vulnerabilities/0/remediations/0/"))
+        );
     }
 
     #[tokio::test]
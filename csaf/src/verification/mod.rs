@@ -9,6 +9,7 @@ use crate::{
     validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
     verification::check::{Check, CheckError},
 };
+use bytes::Bytes;
 use csaf::Csaf;
 use serde::de::Error as _;
 use std::{
@@ -18,9 +19,16 @@ use std::{
     hash::Hash,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 use url::Url;
-use walker_common::{retrieve::RetrievalError, utils::url::Urlify};
+use walker_common::{
+    retrieve::RetrievalError,
+    utils::{
+        json::{self, JsonParseError},
+        url::Urlify,
+    },
+};
 
 pub mod check;
 
@@ -67,10 +75,7 @@ where
     #[error(transparent)]
     Upstream(UE),
     #[error("document parsing error: {error}")]
-    Parsing {
-        advisory: A,
-        error: serde_json::Error,
-    },
+    Parsing { advisory: A, error: JsonParseError },
     #[error("check runtime error: {error}")]
     Check { advisory: A, error: anyhow::Error },
 }
@@ -145,7 +150,8 @@ where
     I: Clone + PartialEq + Eq + Hash,
 {
     visitor: V,
-    checks: Vec<(I, Box<dyn Check>)>,
+    checks: Vec<(I, Arc<dyn Check>)>,
+    parallel: bool,
     _marker: PhantomData<(A, E)>,
 }
 
@@ -160,6 +166,7 @@ where
         Self {
             visitor,
             checks: vec![],
+            parallel: false,
             _marker: Default::default(),
         }
     }
@@ -167,40 +174,78 @@ where
     pub fn with_checks(visitor: V, checks: Vec<(I, Box<dyn Check>)>) -> Self {
         Self {
             visitor,
-            checks,
+            checks: checks
+                .into_iter()
+                .map(|(index, check)| (index, Arc::from(check)))
+                .collect(),
+            parallel: false,
             _marker: Default::default(),
         }
     }
 
     pub fn add<F: Check + 'static>(mut self, index: I, check: F) -> Self {
-        self.checks.push((index, Box::new(check)));
+        self.checks.push((index, Arc::new(check)));
+        self
+    }
+
+    /// Run the checks of a document concurrently, instead of one after the other.
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
         self
     }
 
+    /// Run all checks on a document, returning the results in the order of the checks.
+    async fn run_checks(
+        &self,
+        csaf: Csaf,
+        source: Bytes,
+    ) -> anyhow::Result<(Csaf, Vec<Vec<CheckError>>)> {
+        if !self.parallel {
+            let mut results = Vec::with_capacity(self.checks.len());
+            for (_, check) in &self.checks {
+                results.push(check.check_source(&csaf, &source).await?);
+            }
+            return Ok((csaf, results));
+        }
+
+        let results = futures::future::try_join_all(
+            self.checks
+                .iter()
+                .map(|(_, check)| check.check_source(&csaf, &source)),
+        )
+        .await?;
+
+        Ok((csaf, results))
+    }
+
     async fn verify(&self, advisory: A) -> Result<VerifiedAdvisory<A, I>, VerificationError<E, A>> {
         let data = advisory.as_retrieved().data.clone();
 
-        let csaf = match tokio::task::spawn_blocking(move || serde_json::from_slice::<Csaf>(&data))
-            .await
+        let source = data.clone();
+        let csaf = match tokio::task::spawn_blocking(move || json::from_slice::<Csaf>(&data)).await
         {
             Ok(Ok(csaf)) => csaf,
             Ok(Err(error)) => return Err(VerificationError::Parsing { error, advisory }),
             Err(_) => {
                 return Err(VerificationError::Parsing {
-                    error: serde_json::error::Error::custom("failed to wait for deserialization"),
+                    error: JsonParseError {
+                        pointer: String::new(),
+                        error: serde_json::Error::custom("failed to wait for deserialization"),
+                    },
                     advisory,
                 });
             }
         };
 
+        let (csaf, results) = match self.run_checks(csaf, source).await {
+            Ok(result) => result,
+            Err(error) => return Err(VerificationError::Check { error, advisory }),
+        };
+
         let mut failures = HashMap::new();
         let mut successes = HashSet::new();
 
-        for (index, check) in &self.checks {
-            let result = match check.as_ref().check(&csaf).await {
-                Ok(result) => result,
-                Err(error) => return Err(VerificationError::Check { error, advisory }),
-            };
+        for ((index, _), result) in self.checks.iter().zip(results) {
             if !result.is_empty() {
                 failures.insert(index.clone(), result);
             } else {
@@ -321,3 +366,40 @@ where
         self(outcome).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{source::FileSource, verification::check::init_verifying_visitor};
+
+    type TestVisitor = VerifyingVisitor<
+        RetrievedAdvisory,
+        RetrievalError<DiscoveredAdvisory, FileSource>,
+        fn(
+            Result<
+                VerifiedAdvisory<RetrievedAdvisory, &'static str>,
+                VerificationError<
+                    RetrievalError<DiscoveredAdvisory, FileSource>,
+                    RetrievedAdvisory,
+                >,
+            >,
+        ) -> std::future::Ready<anyhow::Result<()>>,
+        &'static str,
+    >;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parallel_checks_match_sequential() {
+        let source = Bytes::from_static(include_bytes!("../../test-data/rhba-2023_0564.json"));
+        let csaf = || -> Csaf { serde_json::from_slice(&source).expect("example data must parse") };
+
+        let visitor: TestVisitor =
+            VerifyingVisitor::with_checks(|_| std::future::ready(Ok(())), init_verifying_visitor());
+        let (_, sequential) = visitor.run_checks(csaf(), source.clone()).await.unwrap();
+
+        let visitor = visitor.parallel(true);
+        let (_, parallel) = visitor.run_checks(csaf(), source.clone()).await.unwrap();
+
+        assert_eq!(sequential.len(), visitor.checks.len());
+        assert_eq!(sequential, parallel);
+    }
+}
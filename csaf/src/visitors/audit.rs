@@ -0,0 +1,179 @@
+use crate::{
+    source::Source,
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{Debug, Display},
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    sync::Arc,
+};
+use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
+use url::Url;
+use walker_common::utils::{hex::Hex, url::Urlify};
+
+/// The format of an audit log.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AuditFormat {
+    /// One JSON object per line
+    #[default]
+    JsonLines,
+    /// Comma separated values, with a header row
+    Csv,
+}
+
+/// A record of a single advisory, written to the audit log.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct AuditRecord {
+    /// The URL of the advisory
+    pub url: Url,
+    /// The SHA-256 digest of the retrieved content, unless the retrieval failed
+    pub sha256: Option<String>,
+    /// If the advisory passed the validation
+    pub validated: bool,
+    /// The fingerprint of the certificate which signed the advisory, if it was signed
+    pub signer: Option<String>,
+    /// The reason the validation failed
+    pub error: Option<String>,
+    /// The time the advisory was processed
+    pub timestamp: DateTime<Utc>,
+}
+
+impl AuditRecord {
+    fn new<S: Source>(result: &Result<ValidatedAdvisory, ValidationError<S>>) -> Self {
+        let (retrieved, signer) = match result {
+            Ok(advisory) => (Some(&advisory.retrieved), advisory.signer.as_ref()),
+            Err(
+                ValidationError::DigestMismatch { retrieved, .. }
+                | ValidationError::SignatureInvalid { retrieved, .. }
+                | ValidationError::SignatureMissing { retrieved },
+            ) => (Some(retrieved), None),
+            Err(ValidationError::Retrieval(_)) => (None, None),
+        };
+
+        Self {
+            url: match result {
+                Ok(advisory) => advisory.retrieved.url.clone(),
+                Err(err) => err.url().clone(),
+            },
+            sha256: retrieved.map(|retrieved| Hex(&Sha256::digest(&retrieved.data)).to_lower()),
+            validated: result.is_ok(),
+            signer: signer.map(|signer| signer.fingerprint.to_hex()),
+            error: result.as_ref().err().map(ToString::to_string),
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// An append-only log of processed advisories.
+///
+/// Can be cloned, sharing the underlying file.
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    format: AuditFormat,
+    file: Arc<Mutex<File>>,
+}
+
+impl AuditLog {
+    /// Open a log file for appending, creating it if it doesn't exist.
+    ///
+    /// When using CSV, the header row is written to new (empty) files only.
+    pub fn open(path: impl AsRef<Path>, format: AuditFormat) -> std::io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        if format == AuditFormat::Csv && file.metadata()?.len() == 0 {
+            file.write_all(b"url,sha256,validated,signer,error,timestamp\n")?;
+        }
+
+        Ok(Self {
+            format,
+            file: Arc::new(Mutex::new(File::from_std(file))),
+        })
+    }
+
+    /// Append a record to the log, writing it to the file immediately.
+    pub async fn append(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut row = match self.format {
+            AuditFormat::JsonLines => serde_json::to_vec(record)?,
+            AuditFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .terminator(csv::Terminator::Any(b'\n'))
+                    .from_writer(vec![]);
+                writer.serialize(record)?;
+                writer.into_inner()?
+            }
+        };
+        if !row.ends_with(b"\n") {
+            row.push(b'\n');
+        }
+
+        // write a full row at once, so that concurrent records don't interleave
+        let mut file = self.file.lock().await;
+        file.write_all(&row).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<VE>
+where
+    VE: Display + Debug,
+{
+    #[error("{0}")]
+    Visitor(VE),
+    #[error("Failed to write audit record: {0}")]
+    Audit(anyhow::Error),
+}
+
+/// A visitor, recording every advisory to an [`AuditLog`] before forwarding it.
+///
+/// This records the outcome of the validation, including failed advisories. The record is
+/// written before the advisory is forwarded, so it doesn't depend on the outcome of the next
+/// visitor.
+pub struct AuditVisitor<V> {
+    pub visitor: V,
+    pub log: AuditLog,
+}
+
+impl<V> AuditVisitor<V> {
+    pub fn new(visitor: V, log: AuditLog) -> Self {
+        Self { visitor, log }
+    }
+}
+
+impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for AuditVisitor<V> {
+    type Error = Error<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(Error::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        self.log
+            .append(&AuditRecord::new(&result))
+            .await
+            .map_err(Error::Audit)?;
+
+        self.visitor
+            .visit_advisory(context, result)
+            .await
+            .map_err(Error::Visitor)
+    }
+}
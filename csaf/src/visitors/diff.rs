@@ -0,0 +1,370 @@
+//! Reporting the changes of a run, compared to previously stored advisories
+
+use crate::{
+    discover::DiscoveredAdvisory,
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::Source,
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+    visitors::store::{advisory_path, index_name, partition_base},
+};
+use anyhow::Context;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{Debug, Display, Formatter},
+    fs::File,
+    io::{BufWriter, ErrorKind},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use url::Url;
+use walker_common::{
+    compression::decompress, retrieve::RetrievalError, retrieve::parse_digest, utils::hex::Hex,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<VE: Display + Debug> {
+    #[error("{0}")]
+    Visitor(VE),
+}
+
+/// The change of an advisory, compared to the stored one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    /// The advisory wasn't stored before
+    Added,
+    /// The content of the advisory differs from the stored one
+    Modified,
+    /// The content of the advisory is the same as the stored one
+    Unchanged,
+    /// The stored advisory was removed, as it is no longer part of the index
+    Removed,
+}
+
+/// An entry of the diff report, one per changed advisory.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct DiffEntry {
+    /// The URL of the advisory, or the file URL of a removed advisory
+    pub url: Url,
+    pub change: Change,
+    /// The SHA-256 digest of the new content, unless removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// The number of advisories per kind of change.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub modified: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+impl Display for DiffSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} modified, {} unchanged, {} removed",
+            self.added, self.modified, self.unchanged, self.removed
+        )
+    }
+}
+
+/// The content of a diff file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct DiffFile {
+    pub summary: DiffSummary,
+    /// The added, modified, and removed advisories, ordered by their URL
+    pub changes: Vec<DiffEntry>,
+}
+
+#[derive(Default)]
+struct DiffState {
+    summary: DiffSummary,
+    changes: Vec<DiffEntry>,
+}
+
+/// A report of the changes of a run.
+///
+/// Can be cloned, sharing the collected changes. Unchanged advisories are only counted.
+#[derive(Clone, Default)]
+pub struct DiffReport {
+    state: Arc<Mutex<DiffState>>,
+}
+
+impl DiffReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the change of an advisory.
+    pub fn record(&self, entry: DiffEntry) {
+        let mut state = self.state.lock();
+        match entry.change {
+            Change::Added => state.summary.added += 1,
+            Change::Modified => state.summary.modified += 1,
+            Change::Unchanged => state.summary.unchanged += 1,
+            Change::Removed => state.summary.removed += 1,
+        }
+        if entry.change != Change::Unchanged {
+            state.changes.push(entry);
+        }
+    }
+
+    /// Record the advisories among the files removed by a [`crate::visitors::store::Pruner`].
+    pub fn removed<'a>(&self, files: impl IntoIterator<Item = &'a PathBuf>) {
+        for file in files {
+            // skip companion files, like digests and signatures
+            let is_advisory = file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(index_name)
+                .is_some();
+            if !is_advisory {
+                continue;
+            }
+
+            let Ok(url) = Url::from_file_path(file) else {
+                log::warn!("Unable to report removed file: {}", file.display());
+                continue;
+            };
+
+            self.record(DiffEntry {
+                url,
+                change: Change::Removed,
+                sha256: None,
+            });
+        }
+    }
+
+    /// The number of advisories per kind of change, so far.
+    pub fn summary(&self) -> DiffSummary {
+        self.state.lock().summary
+    }
+
+    /// Write the changes to a JSON file.
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        let mut file = {
+            let state = self.state.lock();
+            DiffFile {
+                summary: state.summary,
+                changes: state.changes.clone(),
+            }
+        };
+        file.changes.sort_by(|a, b| a.url.cmp(&b.url));
+
+        let writer = BufWriter::new(
+            File::create(path)
+                .with_context(|| format!("Failed to create diff file: {}", path.display()))?,
+        );
+        serde_json::to_writer_pretty(writer, &file)
+            .with_context(|| format!("Failed to write diff file: {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// A visitor, recording how advisories changed compared to the stored ones, before forwarding them.
+///
+/// This must wrap the visitor storing the advisories, e.g. a
+/// [`StoreVisitor`](crate::visitors::store::StoreVisitor) using the same base. The SHA-256 digest
+/// of each advisory is compared to the stored `.sha256` file, or, if that is missing, to the
+/// digest of the stored content. If the stored advisory can't be read, it is recorded as modified.
+/// Failed advisories are not recorded.
+pub struct DiffVisitor<V> {
+    pub visitor: V,
+    /// the output base of the store
+    pub base: PathBuf,
+    /// whether the store is partitioned by TLP label
+    pub partition_tlp: bool,
+    /// The report to record to. If `None`, nothing will be recorded.
+    pub report: Option<DiffReport>,
+}
+
+impl<V> DiffVisitor<V> {
+    pub fn new(
+        visitor: V,
+        base: impl Into<PathBuf>,
+        report: impl Into<Option<DiffReport>>,
+    ) -> Self {
+        Self {
+            visitor,
+            base: base.into(),
+            partition_tlp: false,
+            report: report.into(),
+        }
+    }
+
+    pub fn partition_tlp(mut self, partition_tlp: bool) -> Self {
+        self.partition_tlp = partition_tlp;
+        self
+    }
+
+    async fn record(&self, advisory: &RetrievedAdvisory) {
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        let data = advisory.data.clone();
+        let not_modified = advisory.metadata.not_modified;
+        let base = partition_base(&self.base, &advisory.context, self.partition_tlp);
+        let file = advisory_path(&base, &advisory.discovered);
+
+        let (sha256, change) = tokio::task::spawn_blocking(move || {
+            let sha256 = Hex(&Sha256::digest(&data)).to_lower();
+            let change = if not_modified {
+                Change::Unchanged
+            } else {
+                match file.map(|file| stored_digest(&file)) {
+                    None | Some(Ok(None)) => Change::Added,
+                    Some(Ok(Some(stored))) if stored.eq_ignore_ascii_case(&sha256) => {
+                        Change::Unchanged
+                    }
+                    Some(Ok(Some(_))) => Change::Modified,
+                    Some(Err(err)) => {
+                        log::warn!(
+                            "Failed to compare with stored advisory, assuming it was modified: {err:#}"
+                        );
+                        Change::Modified
+                    }
+                }
+            };
+            (sha256, change)
+        })
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()));
+
+        report.record(DiffEntry {
+            url: advisory.url.clone(),
+            change,
+            sha256: Some(sha256),
+        });
+    }
+}
+
+/// The SHA-256 digest of the stored advisory, or `None` if it isn't stored.
+fn stored_digest(file: &Path) -> anyhow::Result<Option<String>> {
+    let sidecar = file.with_added_extension("sha256");
+    match std::fs::read_to_string(&sidecar) {
+        Ok(content) => match parse_digest::<Sha256>(&content) {
+            Ok(digest) => return Ok(Some(digest)),
+            Err(err) => log::info!("Ignoring invalid digest file {}: {err}", sidecar.display()),
+        },
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("Failed to read digest: {}", sidecar.display()));
+        }
+    }
+
+    // digests are calculated over the uncompressed content
+    let candidates = [None, Some("gz"), Some("bz2"), Some("xz")]
+        .map(|ext| ext.map_or_else(|| file.to_path_buf(), |ext| file.with_added_extension(ext)));
+    for candidate in candidates {
+        let data = match std::fs::read(&candidate) {
+            Ok(data) => data,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Failed to read advisory: {}", candidate.display()));
+            }
+        };
+        let name = candidate.to_string_lossy();
+        let data = decompress(data.into(), &name)
+            .with_context(|| format!("Failed to decompress advisory: {name}"))?;
+        return Ok(Some(Hex(&Sha256::digest(&data)).to_lower()));
+    }
+
+    Ok(None)
+}
+
+impl<V: RetrievedVisitor<S>, S: Source> RetrievedVisitor<S> for DiffVisitor<V> {
+    type Error = Error<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &RetrievalContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(Error::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>,
+    ) -> Result<(), Self::Error> {
+        if let Ok(advisory) = &result {
+            self.record(advisory).await;
+        }
+
+        self.visitor
+            .visit_advisory(context, result)
+            .await
+            .map_err(Error::Visitor)
+    }
+}
+
+impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for DiffVisitor<V> {
+    type Error = Error<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(Error::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        if let Ok(advisory) = &result {
+            self.record(&advisory.retrieved).await;
+        }
+
+        self.visitor
+            .visit_advisory(context, result)
+            .await
+            .map_err(Error::Visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn removed_advisories() {
+        let report = DiffReport::new();
+        let files = [
+            "/data/dist/a.json",
+            "/data/dist/a.json.sha256",
+            "/data/dist/a.json.asc",
+            "/data/dist/b.json.gz",
+            "/data/dist/b.json.sha256",
+        ]
+        .map(PathBuf::from);
+        report.removed(&files);
+
+        assert_eq!(
+            report.summary(),
+            DiffSummary {
+                removed: 2,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Recording failed documents to a machine-readable report
+
+use crate::{
+    discover::DiscoveredAdvisory,
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::{Source, SourceError},
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+};
+use std::{
+    fmt::{Debug, Display},
+    path::Path,
+    sync::Arc,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt, BufWriter},
+    sync::Mutex,
+};
+use url::Url;
+use walker_common::{retrieve::RetrievalError, utils::url::Urlify};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<VE: Display + Debug> {
+    #[error("{0}")]
+    Visitor(VE),
+    #[error("Failed to write error report: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// An entry of the error report, one per failed document.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ErrorEntry {
+    /// The URL of the document
+    pub url: Url,
+    /// The HTTP status code, if the failure was caused by an HTTP response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// The reason of the failure
+    pub reason: String,
+}
+
+impl ErrorEntry {
+    fn from_retrieval<S: Source>(err: &RetrievalError<DiscoveredAdvisory, S>) -> Self
+    where
+        S::Error: SourceError,
+    {
+        let RetrievalError::Source { err: source, .. } = err;
+
+        Self {
+            url: err.url().clone(),
+            status: source.status().map(|status| status.as_u16()),
+            reason: err.to_string(),
+        }
+    }
+
+    fn from_validation<S: Source>(err: &ValidationError<S>) -> Self
+    where
+        S::Error: SourceError,
+    {
+        match err {
+            ValidationError::Retrieval(err) => Self::from_retrieval(err),
+            err => Self {
+                url: err.url().clone(),
+                status: None,
+                reason: err.to_string(),
+            },
+        }
+    }
+}
+
+/// A report of failed documents, written as newline delimited JSON.
+///
+/// Entries are written as they occur, so that the report is available even if the walk gets
+/// aborted.
+#[derive(Clone, Debug)]
+pub struct ErrorReport {
+    writer: Arc<Mutex<BufWriter<File>>>,
+}
+
+impl ErrorReport {
+    /// Create a new report, truncating an existing file.
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::from_std(std::fs::File::create(path)?);
+        Ok(Self {
+            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+        })
+    }
+
+    /// Append an entry to the report.
+    pub async fn append(&self, entry: &ErrorEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut writer = self.writer.lock().await;
+        writer.write_all(&line).await?;
+        writer.flush().await
+    }
+}
+
+/// A visitor, recording failed documents to an [`ErrorReport`] before forwarding them.
+pub struct ErrorReportVisitor<V> {
+    pub visitor: V,
+    /// The report to write to. If `None`, nothing will be recorded.
+    pub report: Option<ErrorReport>,
+}
+
+impl<V> ErrorReportVisitor<V> {
+    pub fn new(visitor: V, report: impl Into<Option<ErrorReport>>) -> Self {
+        Self {
+            visitor,
+            report: report.into(),
+        }
+    }
+
+    async fn record(&self, entry: impl FnOnce() -> ErrorEntry) -> std::io::Result<()> {
+        match &self.report {
+            Some(report) => report.append(&entry()).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl<V: RetrievedVisitor<S>, S: Source> RetrievedVisitor<S> for ErrorReportVisitor<V>
+where
+    S::Error: SourceError,
+{
+    type Error = Error<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &RetrievalContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(Error::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>,
+    ) -> Result<(), Self::Error> {
+        if let Err(err) = &result {
+            self.record(|| ErrorEntry::from_retrieval(err)).await?;
+        }
+
+        self.visitor
+            .visit_advisory(context, result)
+            .await
+            .map_err(Error::Visitor)
+    }
+}
+
+impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for ErrorReportVisitor<V>
+where
+    S::Error: SourceError,
+{
+    type Error = Error<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(Error::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        if let Err(err) = &result {
+            self.record(|| ErrorEntry::from_validation(err)).await?;
+        }
+
+        self.visitor
+            .visit_advisory(context, result)
+            .await
+            .map_err(Error::Visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn write_ndjson() {
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        let path = dir.path().join("errors.ndjson");
+
+        let report = ErrorReport::create(&path).expect("must create report");
+        let entries = [
+            ErrorEntry {
+                url: Url::parse("https://example.com/a.json").expect("must parse"),
+                status: Some(404),
+                reason: "not found".into(),
+            },
+            ErrorEntry {
+                url: Url::parse("https://example.com/b.json").expect("must parse"),
+                status: None,
+                reason: "digest mismatch".into(),
+            },
+        ];
+        for entry in &entries {
+            report.append(entry).await.expect("must append");
+        }
+
+        let content = std::fs::read_to_string(&path).expect("must read report");
+        let read = content
+            .lines()
+            .map(|line| serde_json::from_str::<ErrorEntry>(line).expect("must parse line"))
+            .collect::<Vec<_>>();
+        assert_eq!(read, entries);
+    }
+}
@@ -1,6 +1,14 @@
 //! Ready-to use visitors
 
+pub mod audit;
+pub mod diff;
 pub mod duplicates;
+pub mod errors;
 pub mod filter;
+#[cfg(feature = "object-store")]
+pub mod object;
 pub mod skip;
+pub mod status;
 pub mod store;
+pub mod streaming;
+pub mod transform;
@@ -0,0 +1,215 @@
+//! Storing advisories in an object store, like S3, GCS, or Azure blob storage.
+
+use crate::{
+    discover::DiscoveredAdvisory,
+    model::{metadata::ProviderMetadata, store::distribution_base},
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::Source,
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+    visitors::store::DIR_METADATA,
+};
+use bytes::Bytes;
+use object_store::{ObjectStore, ObjectStoreExt, PutPayload, path::Path};
+use sequoia_openpgp::{Cert, armor::Kind, serialize::SerializeInto};
+use std::{fmt::Debug, io::Write, sync::Arc};
+use url::Url;
+use walker_common::{retrieve::RetrievalError, utils::openpgp::PublicKey};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ObjectStoreError {
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+    #[error("Invalid object name: {0}")]
+    Name(#[from] object_store::path::Error),
+    #[error("Failed to construct object name from URL: {0}")]
+    Filename(String),
+    #[error("Serialize error: {0:#}")]
+    Serialize(anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::large_enum_variant)]
+pub enum ObjectStoreRetrievedError<S: Source> {
+    #[error(transparent)]
+    Store(#[from] ObjectStoreError),
+    #[error(transparent)]
+    Retrieval(#[from] RetrievalError<DiscoveredAdvisory, S>),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::large_enum_variant)]
+pub enum ObjectStoreValidatedError<S: Source> {
+    #[error(transparent)]
+    Store(#[from] ObjectStoreError),
+    #[error(transparent)]
+    Validation(#[from] ValidationError<S>),
+}
+
+/// Stores all data in an object store, using the same layout as the
+/// [`crate::visitors::store::StoreVisitor`] does on disk.
+///
+/// All objects are stored below a key prefix. Advisories are stored along with their digests
+/// and signature, the provider metadata and keys in the `metadata` "directory".
+#[derive(Clone)]
+pub struct ObjectStoreVisitor {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+}
+
+impl Debug for ObjectStoreVisitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectStoreVisitor")
+            .field("store", &self.store.to_string())
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl ObjectStoreVisitor {
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            store,
+            prefix: Path::default(),
+        }
+    }
+
+    /// Create a new visitor from a URL, like `s3://bucket/prefix`.
+    ///
+    /// The path of the URL is used as key prefix. Credentials and other options are taken from
+    /// the standard environment variables of the store, like `AWS_ACCESS_KEY_ID` or
+    /// `GOOGLE_SERVICE_ACCOUNT`.
+    pub fn from_url(url: &Url) -> Result<Self, ObjectStoreError> {
+        let (store, prefix) = object_store::parse_url_opts(url, std::env::vars())?;
+        Ok(Self::new(store.into()).prefix(prefix))
+    }
+
+    /// Set the prefix of all keys.
+    pub fn prefix(mut self, prefix: impl Into<Path>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Get the key of an object, relative to the prefix.
+    fn key(&self, name: &str) -> Result<Path, ObjectStoreError> {
+        Ok(match self.prefix.as_ref() {
+            "" => Path::parse(name)?,
+            prefix => Path::parse(format!("{prefix}/{name}"))?,
+        })
+    }
+
+    async fn put(&self, name: &str, data: impl Into<PutPayload>) -> Result<(), ObjectStoreError> {
+        let key = self.key(name)?;
+        log::debug!("Storing object: {key}");
+        self.store.put(&key, data.into()).await?;
+        Ok(())
+    }
+
+    async fn store_provider_metadata(
+        &self,
+        metadata: &ProviderMetadata,
+    ) -> Result<(), ObjectStoreError> {
+        let data = serde_json::to_vec_pretty(metadata)
+            .map_err(|err| ObjectStoreError::Serialize(err.into()))?;
+        self.put(&format!("{DIR_METADATA}/provider-metadata.json"), data)
+            .await
+    }
+
+    async fn store_keys(&self, keys: &[PublicKey]) -> Result<(), ObjectStoreError> {
+        for cert in keys.iter().flat_map(|k| &k.certs) {
+            log::info!("Storing key: {}", cert.fingerprint());
+            let data = serialize_key(cert).map_err(ObjectStoreError::Serialize)?;
+            self.put(
+                &format!("{DIR_METADATA}/keys/{}.txt", cert.fingerprint().to_hex()),
+                data,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn store_advisory(&self, advisory: &RetrievedAdvisory) -> Result<(), ObjectStoreError> {
+        log::info!("Storing: {}", advisory.url);
+
+        let name = advisory
+            .context
+            .url()
+            .make_relative(&advisory.url)
+            .ok_or_else(|| ObjectStoreError::Filename(advisory.url.to_string()))?;
+
+        // same layout as on disk, only using the distribution directory name
+        let distribution = distribution_base("", advisory.context.url().as_str());
+        let name = format!("{}/{name}", distribution.display());
+
+        if let Some(sha256) = &advisory.sha256 {
+            self.put(&format!("{name}.sha256"), sha256.expected.clone())
+                .await?;
+        }
+        if let Some(sha384) = &advisory.sha384 {
+            self.put(&format!("{name}.sha384"), sha384.expected.clone())
+                .await?;
+        }
+        if let Some(sha512) = &advisory.sha512 {
+            self.put(&format!("{name}.sha512"), sha512.expected.clone())
+                .await?;
+        }
+        if let Some(signature) = &advisory.signature {
+            self.put(&format!("{name}.asc"), signature.clone()).await?;
+        }
+
+        // store the advisory last, so that readers only see it with its companions
+        self.put(&name, Bytes::clone(&advisory.data)).await
+    }
+}
+
+fn serialize_key(cert: &Cert) -> Result<Vec<u8>, anyhow::Error> {
+    let mut writer = sequoia_openpgp::armor::Writer::new(Vec::new(), Kind::PublicKey)?;
+    writer.write_all(&cert.to_vec()?)?;
+    Ok(writer.finalize()?)
+}
+
+impl<S: Source> RetrievedVisitor<S> for ObjectStoreVisitor {
+    type Error = ObjectStoreRetrievedError<S>;
+    type Context = ();
+
+    async fn visit_context(
+        &self,
+        context: &RetrievalContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.store_provider_metadata(context.metadata).await?;
+        self.store_keys(context.keys).await?;
+        Ok(())
+    }
+
+    async fn visit_advisory(
+        &self,
+        _context: &Self::Context,
+        result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>,
+    ) -> Result<(), Self::Error> {
+        self.store_advisory(&result?).await?;
+        Ok(())
+    }
+}
+
+impl<S: Source> ValidatedVisitor<S> for ObjectStoreVisitor {
+    type Error = ObjectStoreValidatedError<S>;
+    type Context = ();
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.store_provider_metadata(context.metadata).await?;
+        self.store_keys(context.retrieval.keys).await?;
+        Ok(())
+    }
+
+    async fn visit_advisory(
+        &self,
+        _context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        self.store_advisory(&result?.retrieved).await?;
+        Ok(())
+    }
+}
@@ -1,12 +1,20 @@
 use crate::discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor};
 use crate::model::store::distribution_base;
-use crate::source::Source;
+use crate::source::{ExpectedDigest, Source, advisory_file};
 use crate::validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError};
+use crate::visitors::store::partition_base;
+use sha2::{Sha256, Sha512};
 use std::fmt::{Debug, Display};
+use std::io::ErrorKind;
 use std::path::PathBuf;
 use std::time::SystemTime;
 use tokio::fs;
-use walker_common::utils::url::Urlify;
+use walker_common::{compression::decompress, retrieve::parse_digest, utils::url::Urlify};
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<VE: Display + Debug> {
@@ -26,6 +34,64 @@ pub struct SkipExistingVisitor<V: DiscoveredVisitor> {
     ///
     /// Overrides the "file modified" timestamp which is used by default.
     pub since: Option<SystemTime>,
+    /// Whether the output is partitioned by TLP label, see
+    /// [`crate::visitors::store::StoreVisitor::partition_tlp`]
+    pub partition_tlp: bool,
+}
+
+/// An existing, stored advisory.
+struct Existing {
+    /// The path of the uncompressed advisory, next to which the digests are stored.
+    path: PathBuf,
+    /// The actual, possibly compressed, file.
+    stored: PathBuf,
+}
+
+impl<V: DiscoveredVisitor> SkipExistingVisitor<V> {
+    /// Compare the digest of existing files with the one provided by the source before skipping
+    /// them.
+    ///
+    /// This catches changes of documents which didn't update the modification timestamp. Without
+    /// a source, this behaves like the plain visitor.
+    pub fn with_digests<S: Source>(self, source: Option<S>) -> SkipExistingDigestVisitor<V, S> {
+        SkipExistingDigestVisitor { skip: self, source }
+    }
+
+    /// Find the stored file of an advisory, if it is at least as recent as the advisory.
+    async fn existing(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<Existing>, Error<V::Error>> {
+        let name = match advisory.context.url().clone().make_relative(&advisory.url) {
+            Some(name) => name,
+            None => return Err(Error::Name),
+        };
+        let output = partition_base(&self.output, &advisory.context, self.partition_tlp);
+        let path = distribution_base(&output, advisory.context.url().as_str()).join(&name);
+
+        // the advisory might be stored compressed
+        let Some(stored) = advisory_file(&path) else {
+            log::debug!("File did not exist: {}", path.display());
+            return Ok(None);
+        };
+
+        // if we have a "since", we use it as the file modification timestamp
+        let file_modified = match self.since {
+            Some(since) => since,
+            None => fs::metadata(&stored).await?.modified()?,
+        };
+
+        log::debug!(
+            "Advisory modified: {}, file ({}) modified: {} ({:?})",
+            humantime::Timestamp::from(advisory.modified),
+            name,
+            humantime::Timestamp::from(file_modified),
+            self.since.map(humantime::Timestamp::from)
+        );
+
+        // the file was modified after the change date
+        Ok((file_modified >= advisory.modified).then_some(Existing { path, stored }))
+    }
 }
 
 impl<V: DiscoveredVisitor> DiscoveredVisitor for SkipExistingVisitor<V> {
@@ -47,36 +113,118 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for SkipExistingVisitor<V> {
         context: &Self::Context,
         advisory: DiscoveredAdvisory,
     ) -> Result<(), Self::Error> {
-        let name = match advisory.context.url().clone().make_relative(&advisory.url) {
-            Some(name) => name,
-            None => return Err(Error::Name),
+        if self.existing(&advisory).await?.is_some() {
+            return Ok(());
+        }
+
+        self.visitor
+            .visit_advisory(context, advisory)
+            .await
+            .map_err(Error::Visitor)
+    }
+}
+
+/// A [`SkipExistingVisitor`], which only skips existing files if their digest matches the one
+/// provided by the source.
+///
+/// Created by [`SkipExistingVisitor::with_digests`].
+pub struct SkipExistingDigestVisitor<V: DiscoveredVisitor, S: Source> {
+    pub skip: SkipExistingVisitor<V>,
+    /// The source to load the expected digests from
+    pub source: Option<S>,
+}
+
+impl<V: DiscoveredVisitor, S: Source> SkipExistingDigestVisitor<V, S> {
+    /// Check if the digest of an existing file differs from the one provided by the source.
+    ///
+    /// The digest of the file is taken from the stored digest file, or calculated over the
+    /// (decompressed) stored file if that is missing. If the digest can't be loaded from the
+    /// source, the file is considered changed.
+    async fn digest_changed(
+        &self,
+        existing: &Existing,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<bool, std::io::Error> {
+        let Some(source) = &self.source else {
+            return Ok(false);
         };
-        let path = distribution_base(&self.output, advisory.context.url().as_str()).join(&name);
-
-        if fs::try_exists(&path).await? {
-            // if we have a "since", we use it as the file modification timestamp
-            let file_modified = match self.since {
-                Some(since) => since,
-                None => fs::metadata(&path).await?.modified()?,
-            };
-
-            log::debug!(
-                "Advisory modified: {}, file ({}) modified: {} ({:?})",
-                humantime::Timestamp::from(advisory.modified),
-                name,
-                humantime::Timestamp::from(file_modified),
-                self.since.map(humantime::Timestamp::from)
-            );
-
-            if file_modified >= advisory.modified {
-                // the file was modified after the change date, skip it
+
+        let expected = match source.load_digest(advisory).await {
+            Ok(Some(expected)) => expected,
+            Ok(None) => return Ok(false),
+            Err(err) => {
+                log::warn!("Failed to load digest of {}: {err}", advisory.url);
+                return Ok(true);
+            }
+        };
+
+        let mut sidecar = existing.path.as_os_str().to_owned();
+        sidecar.push(".");
+        sidecar.push(expected.extension());
+
+        let actual = match fs::read_to_string(&sidecar).await {
+            Ok(stored) => {
+                let stored = match expected {
+                    ExpectedDigest::Sha256(_) => parse_digest::<Sha256>(&stored),
+                    ExpectedDigest::Sha512(_) => parse_digest::<Sha512>(&stored),
+                };
+                match stored {
+                    Ok(digest) => digest,
+                    Err(err) => {
+                        log::warn!("Invalid stored digest of {}: {err}", advisory.url);
+                        return Ok(true);
+                    }
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let data = fs::read(&existing.stored).await?;
+                // digests are calculated over the uncompressed content
+                match decompress(data.into(), &existing.stored.to_string_lossy()) {
+                    Ok(data) => expected.calculate(&data),
+                    Err(err) => {
+                        log::warn!("Failed to decompress stored {}: {err}", advisory.url);
+                        return Ok(true);
+                    }
+                }
+            }
+            Err(err) => return Err(err),
+        };
+
+        log::debug!(
+            "Digest ({}) - expected: {}, stored: {actual}",
+            expected.extension(),
+            expected.value()
+        );
+
+        Ok(!actual.eq_ignore_ascii_case(expected.value()))
+    }
+}
+
+impl<V: DiscoveredVisitor, S: Source> DiscoveredVisitor for SkipExistingDigestVisitor<V, S> {
+    type Error = Error<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.skip.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        if let Some(existing) = self.skip.existing(&advisory).await? {
+            if !self.digest_changed(&existing, &advisory).await? {
                 return Ok(());
             }
-        } else {
-            log::debug!("File did not exist: {}", path.display());
+            log::info!("Digest changed, fetching again: {}", advisory.url);
         }
 
-        self.visitor
+        self.skip
+            .visitor
             .visit_advisory(context, advisory)
             .await
             .map_err(Error::Visitor)
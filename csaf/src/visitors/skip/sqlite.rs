@@ -0,0 +1,405 @@
+use crate::{
+    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::Source,
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+};
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{Debug, Display},
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+use walker_common::{retrieve::RetrievalError, utils::hex::Hex};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SeenIndexError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Database task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SqliteSkipError<VE: Display + Debug> {
+    #[error("{0}")]
+    Visitor(VE),
+    #[error(transparent)]
+    Index(#[from] SeenIndexError),
+}
+
+/// An entry of the [`SeenIndex`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Seen {
+    /// The SHA-256 digest of the content, hex encoded
+    pub sha256: String,
+    /// The time the advisory was processed, with a precision of seconds
+    pub timestamp: SystemTime,
+}
+
+/// An index of processed advisories, stored in an SQLite database.
+///
+/// For each advisory, the URL, the SHA-256 digest of its content, and the time of processing
+/// (seconds since the epoch) are recorded. Database operations run on a blocking thread.
+#[derive(Clone, Debug)]
+pub struct SeenIndex {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SeenIndex {
+    /// Open an index, creating the database and schema if necessary.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, rusqlite::Error> {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// Use an existing connection, creating the schema if necessary.
+    pub fn new(connection: Connection) -> Result<Self, rusqlite::Error> {
+        connection.execute_batch(
+            r#"
+CREATE TABLE IF NOT EXISTS seen (
+    url TEXT NOT NULL PRIMARY KEY,
+    sha256 TEXT NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+"#,
+        )?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Run an operation on the connection, on a blocking thread.
+    async fn run<T, F>(&self, f: F) -> Result<T, SeenIndexError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, rusqlite::Error> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        Ok(tokio::task::spawn_blocking(move || f(&connection.lock())).await??)
+    }
+
+    /// Get the entry recorded for a URL.
+    pub async fn get(&self, url: &Url) -> Result<Option<Seen>, SeenIndexError> {
+        let url = url.to_string();
+        self.run(move |connection| {
+            connection
+                .prepare_cached("SELECT sha256, timestamp FROM seen WHERE url = ?1")?
+                .query_row(params![url], |row| {
+                    Ok(Seen {
+                        sha256: row.get(0)?,
+                        timestamp: UNIX_EPOCH + Duration::from_secs(row.get::<_, i64>(1)? as u64),
+                    })
+                })
+                .optional()
+        })
+        .await
+    }
+
+    /// Record the digest of a processed URL, replacing a previous entry.
+    pub async fn record(&self, url: &Url, sha256: &str) -> Result<(), SeenIndexError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let (url, sha256) = (url.to_string(), sha256.to_string());
+
+        self.run(move |connection| {
+            connection
+                .prepare_cached(
+                    r#"
+INSERT INTO seen (url, sha256, timestamp) VALUES (?1, ?2, ?3)
+ON CONFLICT(url) DO UPDATE SET sha256 = excluded.sha256, timestamp = excluded.timestamp
+"#,
+                )?
+                .execute(params![url, sha256, timestamp])
+        })
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// A visitor, skipping advisories which have already been processed, according to a [`SeenIndex`].
+///
+/// As a [`DiscoveredVisitor`], an advisory is skipped before it gets downloaded, if it was
+/// processed after its last modification. As a retrieved or validated visitor, an advisory is
+/// skipped if the index has an entry for its URL with the same digest, and recorded once the
+/// wrapped visitor processed it successfully. Failed documents are always forwarded.
+///
+/// To skip downloads and record the processed advisories, use one instance at the discovered
+/// stage and another one, sharing the same index, at the retrieved or validated stage.
+pub struct SqliteSkipVisitor<V> {
+    pub visitor: V,
+    pub index: SeenIndex,
+}
+
+impl<V> SqliteSkipVisitor<V> {
+    pub fn new(visitor: V, index: SeenIndex) -> Self {
+        Self { visitor, index }
+    }
+
+    /// Check if the advisory can be skipped, returning the digest to record otherwise.
+    async fn check(&self, advisory: &RetrievedAdvisory) -> Result<Option<String>, SeenIndexError> {
+        let sha256 = Hex(&Sha256::digest(&advisory.data)).to_lower();
+
+        match self.index.get(&advisory.url).await? {
+            Some(seen) if seen.sha256 == sha256 => {
+                log::debug!("Skipping already processed advisory: {}", advisory.url);
+                Ok(None)
+            }
+            Some(_) => {
+                log::debug!("Digest changed, processing again: {}", advisory.url);
+                Ok(Some(sha256))
+            }
+            None => Ok(Some(sha256)),
+        }
+    }
+}
+
+impl<V: DiscoveredVisitor> DiscoveredVisitor for SqliteSkipVisitor<V> {
+    type Error = SqliteSkipError<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(SqliteSkipError::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        if let Some(seen) = self.index.get(&advisory.url).await?
+            && seen.timestamp >= advisory.modified
+        {
+            log::debug!("Skipping already processed advisory: {}", advisory.url);
+            return Ok(());
+        }
+
+        self.visitor
+            .visit_advisory(context, advisory)
+            .await
+            .map_err(SqliteSkipError::Visitor)
+    }
+}
+
+impl<V: RetrievedVisitor<S>, S: Source> RetrievedVisitor<S> for SqliteSkipVisitor<V> {
+    type Error = SqliteSkipError<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &RetrievalContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(SqliteSkipError::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>,
+    ) -> Result<(), Self::Error> {
+        let advisory = match result {
+            Ok(advisory) => advisory,
+            Err(err) => {
+                return self
+                    .visitor
+                    .visit_advisory(context, Err(err))
+                    .await
+                    .map_err(SqliteSkipError::Visitor);
+            }
+        };
+
+        let Some(sha256) = self.check(&advisory).await? else {
+            return Ok(());
+        };
+        let url = advisory.url.clone();
+
+        self.visitor
+            .visit_advisory(context, Ok(advisory))
+            .await
+            .map_err(SqliteSkipError::Visitor)?;
+
+        Ok(self.index.record(&url, &sha256).await?)
+    }
+}
+
+impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for SqliteSkipVisitor<V> {
+    type Error = SqliteSkipError<V::Error>;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor
+            .visit_context(context)
+            .await
+            .map_err(SqliteSkipError::Visitor)
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        let advisory = match result {
+            Ok(advisory) => advisory,
+            Err(err) => {
+                return self
+                    .visitor
+                    .visit_advisory(context, Err(err))
+                    .await
+                    .map_err(SqliteSkipError::Visitor);
+            }
+        };
+
+        let Some(sha256) = self.check(&advisory.retrieved).await? else {
+            return Ok(());
+        };
+        let url = advisory.url.clone();
+
+        self.visitor
+            .visit_advisory(context, Ok(advisory))
+            .await
+            .map_err(SqliteSkipError::Visitor)?;
+
+        Ok(self.index.record(&url, &sha256).await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{discover::DistributionContext, source::FileSource};
+    use bytes::Bytes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use walker_common::retrieve::RetrievalMetadata;
+
+    fn advisory(data: &'static [u8]) -> RetrievedAdvisory {
+        let url = Url::parse("https://example.com/advisories/test.json").expect("must parse");
+        RetrievedAdvisory {
+            discovered: DiscoveredAdvisory {
+                context: Arc::new(DistributionContext::Directory(
+                    url.join("./").expect("must join"),
+                )),
+                url,
+                digest: None,
+                signature: None,
+                modified: SystemTime::now(),
+            },
+            data: Bytes::from_static(data),
+            signature: None,
+            sha256: None,
+            sha384: None,
+            sha512: None,
+            metadata: RetrievalMetadata {
+                last_modification: None,
+                etag: None,
+                not_modified: false,
+                transformed: false,
+            },
+        }
+    }
+
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    impl RetrievedVisitor<FileSource> for Counting {
+        type Error = std::convert::Infallible;
+        type Context = ();
+
+        async fn visit_context(&self, _: &RetrievalContext<'_>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn visit_advisory(
+            &self,
+            _: &(),
+            _: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, FileSource>>,
+        ) -> Result<(), Self::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn skip_seen() {
+        let index = SeenIndex::new(Connection::open_in_memory().expect("must open"))
+            .expect("must create index");
+        let visitor = SqliteSkipVisitor::new(Counting::default(), index.clone());
+
+        for data in [&b"{}"[..], b"{}", br#"{"changed":true}"#] {
+            RetrievedVisitor::<FileSource>::visit_advisory(&visitor, &(), Ok(advisory(data)))
+                .await
+                .expect("must visit");
+        }
+
+        // the second one is skipped, the third one has a different digest
+        assert_eq!(visitor.visitor.0.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            index
+                .get(&advisory(b"").url)
+                .await
+                .expect("must query")
+                .map(|seen| seen.sha256),
+            Some(Hex(&Sha256::digest(br#"{"changed":true}"#)).to_lower())
+        );
+    }
+
+    #[tokio::test]
+    async fn skip_seen_before_download() {
+        let index = SeenIndex::new(Connection::open_in_memory().expect("must open"))
+            .expect("must create index");
+        let discovered = Arc::new(AtomicUsize::new(0));
+        let visitor = SqliteSkipVisitor::new(
+            |_: DiscoveredAdvisory| {
+                discovered.fetch_add(1, Ordering::SeqCst);
+                async { Ok::<_, std::convert::Infallible>(()) }
+            },
+            index.clone(),
+        );
+
+        let advisory = advisory(b"{}").discovered;
+        index
+            .record(&advisory.url, "digest")
+            .await
+            .expect("must record");
+
+        // not modified since it was processed
+        let unchanged = DiscoveredAdvisory {
+            modified: UNIX_EPOCH,
+            ..advisory.clone()
+        };
+        visitor
+            .visit_advisory(&(), unchanged)
+            .await
+            .expect("must visit");
+        assert_eq!(discovered.load(Ordering::SeqCst), 0);
+
+        // modified since it was processed
+        let modified = DiscoveredAdvisory {
+            modified: SystemTime::now() + Duration::from_secs(60),
+            ..advisory
+        };
+        visitor
+            .visit_advisory(&(), modified)
+            .await
+            .expect("must visit");
+        assert_eq!(discovered.load(Ordering::SeqCst), 1);
+    }
+}
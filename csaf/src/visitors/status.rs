@@ -0,0 +1,117 @@
+use crate::{
+    discover::DiscoveredAdvisory,
+    model::tracking::TrackingStatus,
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::Source,
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+};
+use std::collections::HashSet;
+use walker_common::retrieve::RetrievalError;
+
+/// A visitor, dropping advisories which don't have one of the requested tracking states.
+///
+/// As this requires the content of the document, it must be used after the retrieval. Documents
+/// for which the tracking information cannot be parsed will be forwarded, so that later stages
+/// can report the problem.
+pub struct StatusFilteringVisitor<V> {
+    pub visitor: V,
+
+    /// The accepted states. If empty, all documents will be forwarded.
+    pub only_status: HashSet<TrackingStatus>,
+}
+
+impl<V> StatusFilteringVisitor<V> {
+    pub fn new(visitor: V) -> Self {
+        Self {
+            visitor,
+            only_status: Default::default(),
+        }
+    }
+
+    pub fn only_status<I>(mut self, only_status: I) -> Self
+    where
+        I: IntoIterator<Item = TrackingStatus>,
+    {
+        self.only_status = HashSet::from_iter(only_status);
+        self
+    }
+
+    pub fn add_only_status(mut self, only_status: TrackingStatus) -> Self {
+        self.only_status.insert(only_status);
+        self
+    }
+
+    /// Check if the advisory should be forwarded.
+    fn accept(&self, advisory: &RetrievedAdvisory) -> bool {
+        if self.only_status.is_empty() {
+            return true;
+        }
+
+        match advisory.tracking() {
+            Ok(tracking) => {
+                let accept = self.only_status.contains(&tracking.status);
+                if !accept {
+                    log::debug!(
+                        "Skipping advisory due to tracking status ({}): {}",
+                        tracking.status,
+                        advisory.url
+                    );
+                }
+                accept
+            }
+            Err(err) => {
+                log::debug!(
+                    "Unable to parse tracking information, forwarding ({}): {err}",
+                    advisory.url
+                );
+                true
+            }
+        }
+    }
+}
+
+impl<V: RetrievedVisitor<S>, S: Source> RetrievedVisitor<S> for StatusFilteringVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &RetrievalContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>,
+    ) -> Result<(), Self::Error> {
+        match &result {
+            Ok(advisory) if !self.accept(advisory) => Ok(()),
+            _ => self.visitor.visit_advisory(context, result).await,
+        }
+    }
+}
+
+impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for StatusFilteringVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        match &result {
+            Ok(advisory) if !self.accept(&advisory.retrieved) => Ok(()),
+            _ => self.visitor.visit_advisory(context, result).await,
+        }
+    }
+}
@@ -1,30 +1,47 @@
 use crate::{
-    discover::DiscoveredAdvisory,
-    model::{metadata::ProviderMetadata, store::distribution_base},
+    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor, DistributionContext},
+    model::{
+        metadata::{Distribution, ProviderMetadata, Rolie},
+        store::{StoreIndex, StoreIndexEntry, distribution_base, tlp_partition},
+    },
     retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
     source::{HttpSourceError, Source},
     validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
 };
 use anyhow::Context;
+use parking_lot::Mutex;
 use sequoia_openpgp::{Cert, armor::Kind, serialize::SerializeInto};
+use sha2::{Digest, Sha256};
 use std::{
     any::Any,
-    collections::HashSet,
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Debug,
     io::{ErrorKind, Write},
     path::{Path, PathBuf},
     rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 use tokio::fs;
+use walkdir::WalkDir;
 use walker_common::{
+    compression::{Compression, decompress},
     fetcher,
     retrieve::RetrievalError,
-    store::{Document, ErrorData, StoreError, store_document, store_errors},
-    utils::openpgp::PublicKey,
+    store::{
+        CaseCollisions, Document, ErrorData, StoreError, is_case_insensitive, link_document,
+        store_document, store_errors, temp_file,
+    },
+    utils::{hex::Hex, openpgp::PublicKey},
 };
 
 pub const DIR_METADATA: &str = "metadata";
 
+/// The name of the index file, in the metadata directory.
+pub const FILE_INDEX: &str = "index.json";
+
 /// Stores all data so that it can be used as a [`crate::source::Source`] later.
 #[non_exhaustive]
 pub struct StoreVisitor {
@@ -39,15 +56,48 @@ pub struct StoreVisitor {
 
     /// the clients errors which can be ignored
     pub allowed_client_errors: HashSet<reqwest::StatusCode>,
+
+    /// whether to always check for file names only differing by case, not only when the target
+    /// filesystem is detected as case-insensitive
+    pub check_case_collisions: bool,
+
+    /// whether to track stored files, so that stale ones can be removed using a [`Pruner`]
+    pub prune: bool,
+
+    /// the compression to apply to stored advisories
+    pub compression: Compression,
+
+    /// whether to hard link advisories identical to ones already stored during this run
+    pub dedup: bool,
+
+    /// whether to store distributions in a separate directory per TLP label
+    pub partition_tlp: bool,
+
+    case_insensitive: AtomicBool,
+    case_collisions: CaseCollisions,
+    pruner: Pruner,
+    indexer: Indexer,
+    stored: Mutex<HashMap<String, PathBuf>>,
 }
 
 impl StoreVisitor {
     pub fn new(base: impl Into<PathBuf>) -> Self {
+        let base = base.into();
         Self {
-            base: base.into(),
+            base: base.clone(),
             no_timestamps: false,
             no_xattrs: false,
             allowed_client_errors: Default::default(),
+            check_case_collisions: false,
+            prune: false,
+            compression: Compression::None,
+            dedup: false,
+            partition_tlp: false,
+            case_insensitive: AtomicBool::new(false),
+            case_collisions: Default::default(),
+            pruner: Default::default(),
+            indexer: Indexer::new(base),
+            stored: Default::default(),
         }
     }
 
@@ -61,6 +111,78 @@ impl StoreVisitor {
         self
     }
 
+    pub fn check_case_collisions(mut self, check_case_collisions: bool) -> Self {
+        self.check_case_collisions = check_case_collisions;
+        self
+    }
+
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Store advisories compressed, e.g. as `.json.gz`.
+    ///
+    /// Digests and signatures are stored as-is, next to the compressed file, using the name of
+    /// the uncompressed advisory. They still refer to the uncompressed content, and so stay
+    /// valid for the original document. A [`crate::source::FileSource`] transparently
+    /// decompresses such advisories when reading them.
+    pub fn compress(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Store advisories identical to one already stored during this run as a hard link.
+    ///
+    /// The same advisory is often published in several distributions, e.g. a ROLIE feed and
+    /// the directory. Advisories are considered identical if their content has the same
+    /// SHA-256 digest. If a hard link cannot be created, the advisory is stored as a copy.
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Store distributions below a directory per TLP label, e.g. `tlp-white` or `tlp-amber`.
+    ///
+    /// Each partition gets its own `metadata` directory, containing the provider metadata
+    /// limited to the distributions of the partition, and the keys. Directory based
+    /// distributions have no label, and are stored in `tlp-unlabeled`. This allows applying
+    /// different access controls to each partition.
+    ///
+    /// Pruning isn't supported in combination with partitioning, as the [`PruneTracker`] is
+    /// not aware of the partitions.
+    pub fn partition_tlp(mut self, partition_tlp: bool) -> Self {
+        self.partition_tlp = partition_tlp;
+        self
+    }
+
+    /// Enable the [`Indexer`], writing an index of all stored advisories.
+    ///
+    /// The visitor doesn't know when the walk is complete, so it never writes the index on its
+    /// own. Acquire the [`Indexer`] using [`Self::indexer`] before starting the walk, and call
+    /// [`Indexer::write`] once the walk completed.
+    pub fn enable_indexer(self, enable: bool) -> Self {
+        self.indexer.enabled.store(enable, Ordering::Relaxed);
+        self
+    }
+
+    /// Get a handle for writing the index of stored advisories once the walk is complete.
+    ///
+    /// The visitor is consumed by the walker, so the handle must be acquired before starting
+    /// the walk. It only has an effect if writing the index is enabled using
+    /// [`Self::enable_indexer`].
+    pub fn indexer(&self) -> Indexer {
+        self.indexer.clone()
+    }
+
+    /// Get a handle for removing stale files once the walk is complete.
+    ///
+    /// The visitor is consumed by the walker, so the handle must be acquired before starting
+    /// the walk. It only has an effect if pruning is enabled using [`Self::prune`].
+    pub fn pruner(&self) -> Pruner {
+        self.pruner.clone()
+    }
+
     pub fn allow_client_errors(
         mut self,
         allowed_client_errors: HashSet<reqwest::StatusCode>,
@@ -108,9 +230,7 @@ where
         &self,
         context: &RetrievalContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
-        self.store_provider_metadata(context.metadata).await?;
-        self.prepare_distributions(context.metadata).await?;
-        self.store_keys(context.keys).await?;
+        self.store_context(context.metadata, context.keys).await?;
 
         Ok(Rc::new(context.metadata.clone()))
     }
@@ -148,9 +268,8 @@ impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
         &self,
         context: &ValidationContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
-        self.store_provider_metadata(context.metadata).await?;
-        self.prepare_distributions(context.metadata).await?;
-        self.store_keys(context.retrieval.keys).await?;
+        self.store_context(context.metadata, context.retrieval.keys)
+            .await?;
         Ok(())
     }
 
@@ -165,11 +284,115 @@ impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
 }
 
 impl StoreVisitor {
-    async fn prepare_distributions(&self, metadata: &ProviderMetadata) -> Result<(), StoreError> {
+    /// The base directory of a distribution's partition.
+    fn partition_base(&self, context: &DistributionContext) -> PathBuf {
+        partition_base(&self.base, context, self.partition_tlp)
+    }
+
+    /// Split the metadata into the partitions it is stored in, along with their base directory.
+    ///
+    /// Without partitioning, this is the metadata itself, stored in the output base.
+    fn partitions(&self, metadata: &ProviderMetadata) -> Vec<(PathBuf, ProviderMetadata)> {
+        if !self.partition_tlp {
+            return vec![(self.base.clone(), metadata.clone())];
+        }
+
+        let mut partitions = BTreeMap::<&str, Vec<Distribution>>::new();
+        for dist in &metadata.distributions {
+            if let Some(directory_url) = &dist.directory_url {
+                partitions
+                    .entry(tlp_partition(None))
+                    .or_default()
+                    .push(Distribution {
+                        directory_url: Some(directory_url.clone()),
+                        rolie: None,
+                        publisher: dist.publisher.clone(),
+                    });
+            }
+            if let Some(rolie) = &dist.rolie {
+                for feed in &rolie.feeds {
+                    partitions
+                        .entry(tlp_partition(Some(&feed.tlp_label)))
+                        .or_default()
+                        .push(Distribution {
+                            directory_url: None,
+                            rolie: Some(Rolie {
+                                feeds: vec![feed.clone()],
+                                ..rolie.clone()
+                            }),
+                            publisher: dist.publisher.clone(),
+                        });
+                }
+            }
+        }
+
+        partitions
+            .into_iter()
+            .map(|(name, distributions)| {
+                (
+                    self.base.join(name),
+                    ProviderMetadata {
+                        distributions,
+                        ..metadata.clone()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Store the metadata and keys, and prepare the distributions, of all partitions.
+    async fn store_context(
+        &self,
+        metadata: &ProviderMetadata,
+        keys: &[PublicKey],
+    ) -> Result<(), StoreError> {
+        for (base, metadata) in self.partitions(metadata) {
+            fs::create_dir_all(&base)
+                .await
+                .with_context(|| format!("Unable to create directory: {}", base.display()))
+                .map_err(StoreError::Io)?;
+
+            store_provider_metadata(&base, &metadata).await?;
+            self.prepare_distributions(&base, &metadata).await?;
+            self.store_keys(&base, keys).await?;
+        }
+
+        self.probe_case_sensitivity()
+    }
+
+    fn probe_case_sensitivity(&self) -> Result<(), StoreError> {
+        let case_insensitive = is_case_insensitive(&self.base)
+            .with_context(|| {
+                format!(
+                    "Unable to probe filesystem case sensitivity: {}",
+                    self.base.display()
+                )
+            })
+            .map_err(StoreError::Io)?;
+
+        if case_insensitive {
+            log::debug!(
+                "Target filesystem is case-insensitive: {}",
+                self.base.display()
+            );
+        }
+
+        self.case_insensitive
+            .store(case_insensitive, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    async fn prepare_distributions(
+        &self,
+        base: &Path,
+        metadata: &ProviderMetadata,
+    ) -> Result<(), StoreError> {
         for dist in &metadata.distributions {
             if let Some(directory_url) = &dist.directory_url {
-                let base = distribution_base(&self.base, directory_url.as_str());
+                let base = distribution_base(base, directory_url.as_str());
                 log::debug!("Creating base distribution directory: {}", base.display());
+                self.track_distribution(&base);
 
                 fs::create_dir_all(&base)
                     .await
@@ -183,7 +406,8 @@ impl StoreVisitor {
             }
             if let Some(rolie) = &dist.rolie {
                 for feed in &rolie.feeds {
-                    let base = distribution_base(&self.base, feed.url.as_str());
+                    let base = distribution_base(base, feed.url.as_str());
+                    self.track_distribution(&base);
                     fs::create_dir_all(&base)
                         .await
                         .with_context(|| {
@@ -200,35 +424,8 @@ impl StoreVisitor {
         Ok(())
     }
 
-    async fn store_provider_metadata(&self, metadata: &ProviderMetadata) -> Result<(), StoreError> {
-        let metadir = self.base.join(DIR_METADATA);
-
-        fs::create_dir(&metadir)
-            .await
-            .or_else(|err| match err.kind() {
-                ErrorKind::AlreadyExists => Ok(()),
-                _ => Err(err),
-            })
-            .with_context(|| format!("Failed to create metadata directory: {}", metadir.display()))
-            .map_err(StoreError::Io)?;
-
-        let file = metadir.join("provider-metadata.json");
-        let mut out = std::fs::File::create(&file)
-            .with_context(|| {
-                format!(
-                    "Unable to open provider metadata file for writing: {}",
-                    file.display()
-                )
-            })
-            .map_err(StoreError::Io)?;
-        serde_json::to_writer_pretty(&mut out, metadata)
-            .context("Failed serializing provider metadata")
-            .map_err(StoreError::Io)?;
-        Ok(())
-    }
-
-    async fn store_keys(&self, keys: &[PublicKey]) -> Result<(), StoreError> {
-        let metadata = self.base.join(DIR_METADATA).join("keys");
+    async fn store_keys(&self, base: &Path, keys: &[PublicKey]) -> Result<(), StoreError> {
+        let metadata = base.join(DIR_METADATA).join("keys");
         std::fs::create_dir(&metadata)
             // ignore if the directory already exists
             .or_else(|err| match err.kind() {
@@ -269,7 +466,34 @@ impl StoreVisitor {
         Ok(writer.finalize()?)
     }
 
+    fn track_distribution(&self, base: &Path) {
+        if self.prune {
+            self.pruner.distribution(base);
+        }
+    }
+
+    fn track_file(&self, file: &Path) {
+        if self.prune {
+            self.pruner.seen(file);
+        }
+    }
+
     async fn store_advisory(&self, advisory: &RetrievedAdvisory) -> Result<(), StoreError> {
+        let base = self.partition_base(&advisory.context);
+
+        // also track unmodified advisories, as their files are still part of the index
+        if let Some(file) = advisory_path(&base, &advisory.discovered) {
+            self.track_file(&file);
+        }
+
+        if advisory.metadata.not_modified {
+            log::debug!("Not modified, keeping stored file: {}", advisory.url);
+            if let Some(file) = advisory_path(&base, &advisory.discovered) {
+                self.track_stored(&advisory.data, file);
+            }
+            return Ok(());
+        }
+
         log::info!(
             "Storing: {} (modified: {:?})",
             advisory.url,
@@ -283,29 +507,65 @@ impl StoreVisitor {
         };
 
         // create a distribution base
-        let distribution_base = distribution_base(&self.base, advisory.context.url().as_str());
+        let distribution_base = distribution_base(&base, advisory.context.url().as_str());
 
         // put the file there
         let file = distribution_base.join(name);
 
-        store_document(
-            &file,
-            Document {
-                data: &advisory.data,
-                changed: advisory.modified,
-                metadata: &advisory.metadata,
-                sha256: &advisory.sha256,
-                sha512: &advisory.sha512,
-                signature: &advisory.signature,
-                no_timestamps: self.no_timestamps,
-                no_xattrs: self.no_xattrs,
-            },
-        )
-        .await?;
+        if self.check_case_collisions || self.case_insensitive.load(Ordering::Relaxed) {
+            self.case_collisions.check(&file)?;
+        }
+
+        let document = Document {
+            data: &advisory.data,
+            changed: advisory.modified,
+            metadata: &advisory.metadata,
+            sha256: &advisory.sha256,
+            sha384: &advisory.sha384,
+            sha512: &advisory.sha512,
+            signature: &advisory.signature,
+            no_timestamps: self.no_timestamps,
+            no_xattrs: self.no_xattrs,
+            compression: self.compression,
+        };
+
+        if let Some(existing) = self.find_stored(&advisory.data, &file) {
+            match link_document(&existing, &file, document).await {
+                Ok(()) => return Ok(()),
+                Err(err) => log::info!(
+                    "Failed to link identical advisory, storing a copy: {}: {err}",
+                    file.display()
+                ),
+            }
+        }
+
+        store_document(&file, document).await?;
+        self.track_stored(&advisory.data, file);
 
         Ok(())
     }
 
+    /// Find an identical advisory, stored during this run, if deduplication is enabled.
+    fn find_stored(&self, data: &[u8], file: &Path) -> Option<PathBuf> {
+        if !self.dedup {
+            return None;
+        }
+
+        let digest = Hex(&Sha256::digest(data)).to_lower();
+        self.stored
+            .lock()
+            .get(&digest)
+            .filter(|existing| *existing != file)
+            .cloned()
+    }
+
+    fn track_stored(&self, data: &[u8], file: PathBuf) {
+        if self.dedup {
+            let digest = Hex(&Sha256::digest(data)).to_lower();
+            self.stored.lock().entry(digest).or_insert(file);
+        }
+    }
+
     fn get_client_error_status_code<S: Source + Debug>(
         err: &RetrievalError<DiscoveredAdvisory, S>,
     ) -> Option<reqwest::StatusCode>
@@ -339,8 +599,10 @@ impl StoreVisitor {
             None => return Err(StoreError::Filename(discovered.url.to_string())),
         };
 
-        let distribution_base = distribution_base(&self.base, discovered.context.url().as_str());
+        let base = self.partition_base(&discovered.context);
+        let distribution_base = distribution_base(&base, discovered.context.url().as_str());
         let file = distribution_base.join(name);
+        self.track_file(&file);
 
         store_errors(
             &file,
@@ -353,3 +615,342 @@ impl StoreVisitor {
         Ok(())
     }
 }
+
+/// Store the provider metadata, in the metadata directory of the base.
+pub(crate) async fn store_provider_metadata(
+    base: &Path,
+    metadata: &ProviderMetadata,
+) -> Result<(), StoreError> {
+    let metadir = base.join(DIR_METADATA);
+
+    fs::create_dir(&metadir)
+        .await
+        .or_else(|err| match err.kind() {
+            ErrorKind::AlreadyExists => Ok(()),
+            _ => Err(err),
+        })
+        .with_context(|| format!("Failed to create metadata directory: {}", metadir.display()))
+        .map_err(StoreError::Io)?;
+
+    let file = metadir.join("provider-metadata.json");
+    let mut out = std::fs::File::create(&file)
+        .with_context(|| {
+            format!(
+                "Unable to open provider metadata file for writing: {}",
+                file.display()
+            )
+        })
+        .map_err(StoreError::Io)?;
+    serde_json::to_writer_pretty(&mut out, metadata)
+        .context("Failed serializing provider metadata")
+        .map_err(StoreError::Io)?;
+    Ok(())
+}
+
+/// The base directory of a distribution, below the output base, when partitioning by TLP label.
+pub(crate) fn partition_base(
+    base: &Path,
+    context: &DistributionContext,
+    partition_tlp: bool,
+) -> PathBuf {
+    match partition_tlp {
+        true => base.join(tlp_partition(context.tlp_label())),
+        false => base.to_path_buf(),
+    }
+}
+
+/// The local path of a discovered advisory, below the output base.
+pub(crate) fn advisory_path(base: &Path, advisory: &DiscoveredAdvisory) -> Option<PathBuf> {
+    let name = advisory.context.url().make_relative(&advisory.url)?;
+    Some(distribution_base(base, advisory.context.url().as_str()).join(name))
+}
+
+/// Extensions of files stored alongside an advisory, including its compressed variants.
+const COMPANION_EXTENSIONS: &[&str] = &[
+    "asc", "sha256", "sha384", "sha512", "errors", "gz", "bz2", "xz",
+];
+
+#[derive(Default)]
+struct PrunerState {
+    distributions: HashSet<PathBuf>,
+    seen: HashSet<PathBuf>,
+}
+
+/// Removes files which were not part of the last walk, turning the output into a mirror.
+///
+/// Acquired from [`StoreVisitor::pruner`]. Only distributions visited during the walk are
+/// considered, and the metadata directory is never touched.
+#[derive(Clone, Default)]
+pub struct Pruner {
+    state: Arc<Mutex<PrunerState>>,
+}
+
+impl Pruner {
+    fn distribution(&self, base: &Path) {
+        self.state.lock().distributions.insert(base.to_path_buf());
+    }
+
+    fn seen(&self, file: &Path) {
+        self.state.lock().seen.insert(file.to_path_buf());
+    }
+
+    /// Wrap a visitor, marking all discovered advisories as seen.
+    ///
+    /// This is required when advisories may be skipped before reaching the [`StoreVisitor`],
+    /// e.g. when using [`crate::visitors::skip::SkipExistingVisitor`].
+    pub fn track<V: DiscoveredVisitor>(
+        &self,
+        base: impl Into<PathBuf>,
+        visitor: V,
+    ) -> PruneTracker<V> {
+        PruneTracker {
+            visitor,
+            base: base.into(),
+            pruner: self.clone(),
+        }
+    }
+
+    fn is_seen(seen: &HashSet<PathBuf>, path: &Path) -> bool {
+        if seen.contains(path) {
+            return true;
+        }
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if COMPANION_EXTENSIONS.contains(&ext) => {
+                seen.contains(&path.with_extension(""))
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove all files of visited distributions which have not been seen during the walk.
+    ///
+    /// This must only be called after the walk completed successfully, as otherwise files
+    /// not yet visited would be removed as well. Returns the paths of the removed files.
+    pub fn prune(&self) -> Result<Vec<PathBuf>, StoreError> {
+        let state = self.state.lock();
+        let mut removed = vec![];
+
+        for base in &state.distributions {
+            if base.file_name().is_some_and(|name| name == DIR_METADATA) {
+                continue;
+            }
+
+            for entry in WalkDir::new(base) {
+                let entry = entry
+                    .with_context(|| format!("Failed to walk directory: {}", base.display()))
+                    .map_err(StoreError::Io)?;
+
+                if !entry.file_type().is_file() || Self::is_seen(&state.seen, entry.path()) {
+                    continue;
+                }
+
+                log::info!("Removing stale file: {}", entry.path().display());
+                std::fs::remove_file(entry.path())
+                    .with_context(|| {
+                        format!("Failed to remove stale file: {}", entry.path().display())
+                    })
+                    .map_err(StoreError::Io)?;
+                removed.push(entry.into_path());
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Writes an index of all advisories stored in the output directory.
+///
+/// Acquired from [`StoreVisitor::indexer`]. The index is written to `metadata/index.json`, and
+/// lists every advisory on disk, with its path relative to the output directory, its SHA-256
+/// digest, modification timestamp, and whether a signature is present.
+#[derive(Clone)]
+pub struct Indexer {
+    base: PathBuf,
+    enabled: Arc<AtomicBool>,
+}
+
+impl Indexer {
+    fn new(base: PathBuf) -> Self {
+        Self {
+            base,
+            enabled: Default::default(),
+        }
+    }
+
+    /// Write the index, if enabled.
+    ///
+    /// This should be called after the walk completed, so that the index reflects all stored
+    /// advisories. Returns the path of the index file, if it was written.
+    ///
+    /// Collecting the advisories reads all stored files, which runs on a blocking thread. The
+    /// index is written to a temporary file first, and then renamed into place.
+    pub async fn write(&self) -> Result<Option<PathBuf>, StoreError> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        let indexer = self.clone();
+        tokio::task::spawn_blocking(move || indexer.write_blocking())
+            .await
+            .context("Failed to write index")
+            .map_err(StoreError::Io)?
+            .map(Some)
+    }
+
+    fn write_blocking(&self) -> Result<PathBuf, StoreError> {
+        let index = self.collect()?;
+
+        let file = self.base.join(DIR_METADATA).join(FILE_INDEX);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| {
+                    format!("Failed to create metadata directory: {}", parent.display())
+                })
+                .map_err(StoreError::Io)?;
+        }
+
+        let temp = temp_file(&file);
+        let result = Self::write_file(&temp, &index)
+            .and_then(|()| std::fs::rename(&temp, &file).map_err(anyhow::Error::from))
+            .with_context(|| format!("Failed to write index file: {}", file.display()));
+        if let Err(err) = result {
+            // best effort, the original error is more relevant
+            let _ = std::fs::remove_file(&temp);
+            return Err(StoreError::Io(err));
+        }
+
+        log::info!(
+            "Wrote index of {} advisories: {}",
+            index.advisories.len(),
+            file.display()
+        );
+
+        Ok(file)
+    }
+
+    fn write_file(file: &Path, index: &StoreIndex) -> anyhow::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(file)?);
+        serde_json::to_writer_pretty(&mut out, index)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Collect all advisories stored below the base, ignoring the metadata directories.
+    fn collect(&self) -> Result<StoreIndex, StoreError> {
+        let mut advisories = vec![];
+
+        let entries = WalkDir::new(&self.base)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| !is_metadata_dir(entry));
+
+        for entry in entries {
+            let entry = entry
+                .with_context(|| format!("Failed to walk directory: {}", self.base.display()))
+                .map_err(StoreError::Io)?;
+
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy();
+            let Some(logical) = index_name(&name) else {
+                continue;
+            };
+            if !entry.file_type().is_file() || name.starts_with('.') {
+                continue;
+            }
+
+            let data = std::fs::read(path)
+                .with_context(|| format!("Failed to read advisory: {}", path.display()))
+                .map_err(StoreError::Io)?;
+            // digests are calculated over the uncompressed content
+            let data = decompress(data.into(), &name)
+                .with_context(|| format!("Failed to decompress advisory: {}", path.display()))
+                .map_err(StoreError::Io)?;
+            let last_modification = entry
+                .metadata()
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .map(Into::into);
+
+            let Ok(relative) = path.strip_prefix(&self.base) else {
+                continue;
+            };
+            let relative = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            advisories.push(StoreIndexEntry {
+                path: relative,
+                sha256: Hex(&Sha256::digest(&data)).to_lower(),
+                last_modification,
+                signature: path.with_file_name(format!("{logical}.asc")).is_file(),
+            });
+        }
+
+        Ok(StoreIndex { advisories })
+    }
+}
+
+/// Check if an entry is the metadata directory of the base, or of a TLP partition.
+fn is_metadata_dir(entry: &walkdir::DirEntry) -> bool {
+    if entry.file_name() != DIR_METADATA {
+        return false;
+    }
+
+    match entry.depth() {
+        1 => true,
+        2 => entry
+            .path()
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|name| name.to_string_lossy().starts_with("tlp-")),
+        _ => false,
+    }
+}
+
+/// Get the name of an advisory from a stored file name, stripping a compression extension.
+///
+/// Returns `None` if the file is not a (possibly compressed) advisory.
+pub(crate) fn index_name(name: &str) -> Option<&str> {
+    let name = ["gz", "bz2", "xz"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext)?.strip_suffix('.'))
+        .unwrap_or(name);
+
+    name.ends_with(".json").then_some(name)
+}
+
+/// A visitor, marking all discovered advisories as seen for a [`Pruner`].
+pub struct PruneTracker<V: DiscoveredVisitor> {
+    pub visitor: V,
+    /// the output base
+    pub base: PathBuf,
+    pub pruner: Pruner,
+}
+
+impl<V: DiscoveredVisitor> DiscoveredVisitor for PruneTracker<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        if let Some(file) = advisory_path(&self.base, &advisory) {
+            self.pruner.seen(&file);
+        }
+
+        self.visitor.visit_advisory(context, advisory).await
+    }
+}
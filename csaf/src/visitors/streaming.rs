@@ -0,0 +1,222 @@
+use crate::{
+    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
+    source::{HttpSource, HttpSourceError, StreamedAdvisory},
+    visitors::store::{advisory_path, store_provider_metadata},
+};
+use anyhow::Context;
+use parking_lot::Mutex;
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::fs;
+use url::Url;
+use walker_common::{
+    lock::Lock,
+    store::{ATTR_ETAG, StoreError, temp_file},
+};
+
+/// Stores advisories by streaming them to disk, without holding their content in memory.
+///
+/// Unlike the [`StoreVisitor`](super::store::StoreVisitor), this visits discovered advisories,
+/// and retrieves them from an [`HttpSource`] itself. The content is written to a temporary file
+/// while calculating its digests, and moved into place once the digests are validated. So the
+/// memory required doesn't depend on the size of the advisories.
+///
+/// The signature is stored, but not verified. Compression, deduplication, pruning, and writing
+/// an index are not supported.
+///
+/// The lock of the store is acquired when visiting the first context, and held until the visitor
+/// is dropped. So visiting fails if another run is using the same store.
+#[non_exhaustive]
+pub struct StreamingStoreVisitor {
+    /// the source to retrieve advisories from
+    pub source: HttpSource,
+
+    /// the output base
+    pub base: PathBuf,
+
+    /// whether to set the file modification timestamps
+    pub no_timestamps: bool,
+
+    /// whether to store additional metadata (like the etag) using extended attributes
+    pub no_xattrs: bool,
+
+    /// the lock of the store, once acquired
+    lock: Mutex<Option<Lock>>,
+}
+
+impl StreamingStoreVisitor {
+    pub fn new(source: HttpSource, base: impl Into<PathBuf>) -> Self {
+        Self {
+            source,
+            base: base.into(),
+            no_timestamps: false,
+            no_xattrs: false,
+            lock: Mutex::new(None),
+        }
+    }
+
+    pub fn no_timestamps(mut self, no_timestamps: bool) -> Self {
+        self.no_timestamps = no_timestamps;
+        self
+    }
+
+    pub fn no_xattrs(mut self, no_xattrs: bool) -> Self {
+        self.no_xattrs = no_xattrs;
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StreamingStoreError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error(transparent)]
+    Source(#[from] HttpSourceError),
+    #[error("Digest mismatch - expected: {expected}, actual: {actual} ({url})")]
+    DigestMismatch {
+        url: Url,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl DiscoveredVisitor for StreamingStoreVisitor {
+    type Error = StreamingStoreError;
+    type Context = ();
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        {
+            let mut lock = self.lock.lock();
+            if lock.is_none() {
+                *lock = Some(Lock::store(&self.base).map_err(StoreError::Io)?);
+            }
+        }
+
+        store_provider_metadata(&self.base, context.metadata).await?;
+        Ok(())
+    }
+
+    async fn visit_advisory(
+        &self,
+        _context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        let file = advisory_path(&self.base, &advisory)
+            .ok_or_else(|| StoreError::Filename(advisory.url.to_string()))?;
+
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create parent directory: {}", parent.display()))
+                .map_err(StoreError::Io)?;
+        }
+
+        let temp = temp_file(&file);
+        let result = self.store_advisory(advisory, &file, &temp).await;
+
+        if result.is_err() {
+            // best effort, the original error is more relevant
+            let _ = fs::remove_file(&temp).await;
+        }
+
+        result
+    }
+}
+
+impl StreamingStoreVisitor {
+    async fn store_advisory(
+        &self,
+        advisory: DiscoveredAdvisory,
+        file: &Path,
+        temp: &Path,
+    ) -> Result<(), StreamingStoreError> {
+        log::debug!("Streaming {} to {}", advisory.url, file.display());
+
+        let changed = advisory.modified;
+        let streamed = self.source.stream_advisory(advisory, temp).await?;
+
+        if let Err((expected, actual)) = validate_digests(&streamed) {
+            return Err(StreamingStoreError::DigestMismatch {
+                url: streamed.discovered.url,
+                expected,
+                actual,
+            });
+        }
+
+        let companions = [
+            ("sha256", streamed.sha256.as_ref().map(|d| &d.expected)),
+            ("sha384", streamed.sha384.as_ref().map(|d| &d.expected)),
+            ("sha512", streamed.sha512.as_ref().map(|d| &d.expected)),
+            ("asc", streamed.signature.as_ref()),
+        ];
+
+        for (ext, content) in companions {
+            if let Some(content) = content {
+                let target = file.with_added_extension(ext);
+                fs::write(&target, content)
+                    .await
+                    .with_context(|| format!("Failed to write {ext} file: {}", target.display()))
+                    .map_err(StoreError::Io)?;
+            }
+        }
+
+        if !self.no_timestamps {
+            // prefer the timestamp reported by the server, like the store visitor does
+            let mtime = streamed
+                .metadata
+                .last_modification
+                .map(SystemTime::from)
+                .unwrap_or(changed);
+            std::fs::File::options()
+                .write(true)
+                .open(temp)
+                .and_then(|temp| temp.set_modified(mtime))
+                .with_context(|| {
+                    format!(
+                        "Failed to set last modification timestamp: {}",
+                        file.display()
+                    )
+                })
+                .map_err(StoreError::Io)?;
+        }
+
+        if !self.no_xattrs
+            && let Some(etag) = &streamed.metadata.etag
+        {
+            fsquirrel::set(temp, ATTR_ETAG, etag.as_bytes())
+                .with_context(|| format!("Failed to store {}: {}", ATTR_ETAG, file.display()))
+                .map_err(StoreError::Io)?;
+        }
+
+        fs::rename(temp, file)
+            .await
+            .with_context(|| format!("Failed to move advisory into place: {}", file.display()))
+            .map_err(StoreError::Io)?;
+
+        Ok(())
+    }
+}
+
+/// Validate the digests calculated while streaming the advisory.
+///
+/// Returns the expected and actual digest of the first mismatch.
+fn validate_digests(streamed: &StreamedAdvisory) -> Result<(), (String, String)> {
+    let results = [
+        streamed.sha256.as_ref().map(|d| d.validate()),
+        streamed.sha384.as_ref().map(|d| d.validate()),
+        streamed.sha512.as_ref().map(|d| d.validate()),
+    ];
+
+    for result in results.into_iter().flatten() {
+        if let Err((expected, actual)) = result {
+            return Err((expected.to_string(), actual));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//! Rewriting advisory documents
+
+use crate::{
+    source::Source,
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+};
+use bytes::Bytes;
+use digest::Digest;
+use json_patch::Patch;
+use walker_common::{
+    retrieve::RetrievedDigest,
+    utils::{hex::Hex, url::Urlify},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransformError {
+    #[error("Failed to parse document: {0}")]
+    Parse(serde_json::Error),
+    #[error("Failed to apply patch: {0}")]
+    Patch(#[from] json_patch::PatchError),
+    #[error("Failed to serialize document: {0}")]
+    Serialize(serde_json::Error),
+}
+
+/// A transformation of a document's content.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform {
+    /// The JSON patch (RFC 6902) to apply
+    pub patch: Patch,
+}
+
+impl Transform {
+    pub fn new(patch: Patch) -> Self {
+        Self { patch }
+    }
+
+    /// Parse a transformation from a JSON patch document.
+    pub fn from_json(data: &[u8]) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(serde_json::from_slice(data)?))
+    }
+
+    /// Apply the transformation to the content of an advisory.
+    ///
+    /// As the content is no longer the original content, this drops the signature, which would no
+    /// longer match, recalculates the present digests over the new content, and marks the
+    /// advisory as transformed.
+    pub fn apply(&self, advisory: &mut ValidatedAdvisory) -> Result<(), TransformError> {
+        let mut doc: serde_json::Value =
+            serde_json::from_slice(&advisory.data).map_err(TransformError::Parse)?;
+
+        json_patch::patch(&mut doc, &self.patch)?;
+
+        let data = Bytes::from(serde_json::to_vec(&doc).map_err(TransformError::Serialize)?);
+
+        let retrieved = &mut advisory.retrieved;
+        retrieved.signature = None;
+        retrieved.sha256 = retrieved.sha256.as_ref().map(|_| digest(&data));
+        retrieved.sha384 = retrieved.sha384.as_ref().map(|_| digest(&data));
+        retrieved.sha512 = retrieved.sha512.as_ref().map(|_| digest(&data));
+        retrieved.metadata.transformed = true;
+        retrieved.data = data;
+
+        Ok(())
+    }
+}
+
+/// Calculate the digest of transformed content, which is expected to be what it is.
+fn digest<D: Digest>(data: &[u8]) -> RetrievedDigest<D> {
+    let actual = D::digest(data);
+    RetrievedDigest {
+        expected: Hex(&actual).to_lower(),
+        actual,
+    }
+}
+
+/// A visitor, transforming validated advisories before forwarding them.
+///
+/// Advisories which fail to transform are skipped, with a warning, so that a single document
+/// doesn't stop the processing of the others.
+pub struct TransformVisitor<V> {
+    pub visitor: V,
+    /// The transformation. If `None`, advisories are forwarded unchanged.
+    pub transform: Option<Transform>,
+}
+
+impl<V> TransformVisitor<V> {
+    pub fn new(visitor: V, transform: impl Into<Option<Transform>>) -> Self {
+        Self {
+            visitor,
+            transform: transform.into(),
+        }
+    }
+}
+
+impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for TransformVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        let result = match (&self.transform, result) {
+            (Some(transform), Ok(mut advisory)) => {
+                if let Err(err) = transform.apply(&mut advisory) {
+                    log::warn!(
+                        "Skipping advisory, failed to transform ({}): {err}",
+                        advisory.url()
+                    );
+                    return Ok(());
+                }
+                Ok(advisory)
+            }
+            (_, result) => result,
+        };
+
+        self.visitor.visit_advisory(context, result).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        discover::DiscoveredAdvisory, discover::DistributionContext, retrieve::RetrievedAdvisory,
+    };
+    use serde_json::json;
+    use sha2::Sha256;
+    use std::{sync::Arc, time::SystemTime};
+    use url::Url;
+    use walker_common::retrieve::RetrievalMetadata;
+
+    fn advisory(data: serde_json::Value) -> ValidatedAdvisory {
+        let url = Url::parse("https://example.com/advisories/test.json").expect("must parse");
+        ValidatedAdvisory {
+            retrieved: RetrievedAdvisory {
+                discovered: DiscoveredAdvisory {
+                    context: Arc::new(DistributionContext::Directory(
+                        url.join("./").expect("must join"),
+                    )),
+                    url,
+                    digest: None,
+                    signature: None,
+                    modified: SystemTime::now(),
+                },
+                data: Bytes::from(serde_json::to_vec(&data).expect("must serialize")),
+                signature: Some("signature".into()),
+                sha256: Some(digest(b"original")),
+                sha384: None,
+                sha512: None,
+                metadata: RetrievalMetadata {
+                    last_modification: None,
+                    etag: None,
+                    not_modified: false,
+                    transformed: false,
+                },
+            },
+            signer: None,
+        }
+    }
+
+    #[test]
+    fn apply_patch() {
+        let transform = Transform::from_json(
+            br#"[
+                {"op": "remove", "path": "/document/notes"},
+                {"op": "add", "path": "/document/lang", "value": "en"}
+            ]"#,
+        )
+        .expect("must parse");
+
+        let mut advisory = advisory(json!({"document": {"notes": [], "title": "Test"}}));
+        transform.apply(&mut advisory).expect("must apply");
+
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&advisory.data).expect("must parse"),
+            json!({"document": {"lang": "en", "title": "Test"}})
+        );
+        assert_eq!(advisory.signature, None);
+        assert!(advisory.metadata.transformed);
+
+        // the digest describes the transformed content
+        let sha256 = advisory.sha256.as_ref().expect("must have digest");
+        assert!(sha256.validate().is_ok());
+        assert_eq!(sha256.actual, Sha256::digest(&advisory.data));
+        assert!(advisory.sha512.is_none());
+    }
+
+    #[test]
+    fn apply_patch_fails() {
+        let transform = Transform::from_json(br#"[{"op": "remove", "path": "/document/notes"}]"#)
+            .expect("must parse");
+
+        let mut advisory = advisory(json!({"document": {}}));
+        assert!(transform.apply(&mut advisory).is_err());
+    }
+}
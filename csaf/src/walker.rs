@@ -1,15 +1,43 @@
 //! The actual walker
 
+#[cfg(feature = "csaf")]
+use crate::verification::{
+    self, VerificationError, VerifiedAdvisory, VerifyingVisitor, check::Check,
+};
 use crate::{
-    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor, DistributionContext},
-    model::metadata::Distribution,
+    discover::{
+        DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor, DistributionContext,
+        TransferCounter,
+    },
+    metadata::{MetadataIssue, validate_metadata},
+    model::metadata::{Distribution, Feed, ProviderMetadata},
+    retrieve::{self, RetrievingVisitor},
     source::Source,
+    validation::{self, ValidatedAdvisory, ValidationError, ValidationVisitor},
+};
+use futures::{
+    SinkExt, Stream, StreamExt, TryStream, TryStreamExt,
+    channel::{mpsc, oneshot},
+    future, stream,
+};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Debug,
+    io::ErrorKind,
+    path::PathBuf,
+    pin::pin,
+    sync::Arc,
+    time::Instant,
+};
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex};
+use tokio_util::sync::CancellationToken;
+use url::{ParseError, Url};
+pub use walker_common::walker::WalkStats;
+use walker_common::{
+    progress::{Progress, ProgressBar},
+    utils::budget::MemoryBudget,
+    validate::{ValidationOptions, source::KeySource},
 };
-use futures::{Stream, StreamExt, TryFutureExt, TryStream, TryStreamExt, stream};
-use std::{fmt::Debug, sync::Arc};
-use tokio::sync::Mutex;
-use url::ParseError;
-use walker_common::progress::{Progress, ProgressBar};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<VE, SE>
@@ -21,16 +49,106 @@ where
     Source(SE),
     #[error("URL error: {0}")]
     Url(#[from] ParseError),
+    /// The visitor failed, outside of visiting an advisory
     #[error("Visitor error: {0}")]
     Visitor(VE),
+    /// The visitor failed visiting an advisory
+    #[error("Failed visiting {url}: {error}")]
+    Advisory {
+        /// The URL of the advisory
+        url: Url,
+        /// The distribution the advisory belongs to
+        distribution: Arc<DistributionContext>,
+        error: VE,
+    },
+    #[error("Checkpoint error: {0}")]
+    Checkpoint(#[source] std::io::Error),
+    #[error("Walk was cancelled")]
+    Cancelled,
+    #[error("Invalid provider metadata: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    InvalidMetadata(Vec<MetadataIssue>),
+    #[error("{} of {} advisories failed", .stats.failed, .stats.visited())]
+    Failed {
+        stats: WalkStats,
+        /// The URLs of the failed advisories, with their errors.
+        errors: Vec<(Url, VE)>,
+        /// The URLs of the distributions the failed advisories belong to.
+        distributions: Vec<Url>,
+    },
+}
+
+/// The consumer of [`Walker::into_stream`] stopped receiving advisories.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("Stream closed")]
+pub struct StreamClosed;
+
+/// The error of the walk driving [`Walker::into_stream`].
+pub type StreamWalkError<S> = Error<
+    retrieve::Error<
+        validation::Error<StreamClosed>,
+        <S as walker_common::source::Source>::Error,
+        <S as KeySource>::Error,
+    >,
+    <S as walker_common::source::Source>::Error,
+>;
+
+/// An error of [`Walker::into_stream`].
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::large_enum_variant)]
+pub enum StreamError<S: Source + KeySource> {
+    /// Retrieving or validating an advisory failed, the stream continues
+    #[error(transparent)]
+    Advisory(ValidationError<S>),
+    /// The walk failed, this is the last item of the stream
+    #[error(transparent)]
+    Walk(StreamWalkError<S>),
+}
+
+/// The error of the walk driving [`Walker::into_verification_stream`].
+#[cfg(feature = "csaf")]
+pub type VerificationStreamWalkError<S> = Error<
+    retrieve::Error<
+        validation::Error<verification::Error<StreamClosed>>,
+        <S as walker_common::source::Source>::Error,
+        <S as KeySource>::Error,
+    >,
+    <S as walker_common::source::Source>::Error,
+>;
+
+/// An error of [`Walker::into_verification_stream`].
+#[cfg(feature = "csaf")]
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::large_enum_variant)]
+pub enum VerificationStreamError<S: Source + KeySource> {
+    /// Retrieving, validating, or verifying an advisory failed, the stream continues
+    ///
+    /// This doesn't include failed checks, which are part of the verified advisory.
+    #[error(transparent)]
+    Advisory(VerificationError<ValidationError<S>, ValidatedAdvisory>),
+    /// The walk failed, this is the last item of the stream
+    #[error(transparent)]
+    Walk(VerificationStreamWalkError<S>),
 }
 
 pub type DistributionFilter = Box<dyn Fn(&DistributionContext) -> bool>;
 
+pub type FeedFilter = Box<dyn Fn(&Feed) -> bool>;
+
+pub type AdvisoryFilter = Box<dyn Fn(&DiscoveredAdvisory) -> bool>;
+
 pub struct Walker<S: Source, P: Progress> {
     source: S,
     progress: P,
     distribution_filter: Option<DistributionFilter>,
+    feed_filter: Option<FeedFilter>,
+    advisory_filter: Option<AdvisoryFilter>,
+    memory_budget: Option<MemoryBudget>,
+    checkpoint: Option<PathBuf>,
+    cancellation: CancellationToken,
+    continue_on_error: bool,
+    per_distribution: bool,
+    strict_metadata: bool,
+    sorted: bool,
 }
 
 impl<S: Source> Walker<S, ()> {
@@ -39,6 +157,15 @@ impl<S: Source> Walker<S, ()> {
             source,
             progress: (),
             distribution_filter: None,
+            feed_filter: None,
+            advisory_filter: None,
+            memory_budget: None,
+            checkpoint: None,
+            cancellation: CancellationToken::new(),
+            continue_on_error: false,
+            per_distribution: false,
+            strict_metadata: false,
+            sorted: false,
         }
     }
 }
@@ -49,9 +176,91 @@ impl<S: Source, P: Progress> Walker<S, P> {
             progress,
             source: self.source,
             distribution_filter: self.distribution_filter,
+            feed_filter: self.feed_filter,
+            advisory_filter: self.advisory_filter,
+            memory_budget: self.memory_budget,
+            checkpoint: self.checkpoint,
+            cancellation: self.cancellation,
+            continue_on_error: self.continue_on_error,
+            per_distribution: self.per_distribution,
+            strict_metadata: self.strict_metadata,
+            sorted: self.sorted,
         }
     }
 
+    /// Set a memory budget for [`Self::walk_parallel`].
+    ///
+    /// Once the retrieved, but not yet processed, data exceeds the budget, no new documents will
+    /// be dispatched until enough data was processed.
+    pub fn with_memory_budget(mut self, memory_budget: impl Into<Option<MemoryBudget>>) -> Self {
+        self.memory_budget = memory_budget.into();
+        self
+    }
+
+    /// Set a checkpoint file, allowing to resume an interrupted walk.
+    ///
+    /// The URL of each successfully visited advisory gets appended to the file. Advisories which
+    /// are already recorded in the file will be skipped. Once the walk completes successfully, the
+    /// file gets removed.
+    pub fn with_checkpoint(mut self, checkpoint: impl Into<Option<PathBuf>>) -> Self {
+        self.checkpoint = checkpoint.into();
+        self
+    }
+
+    /// Set a token for cancelling the walk.
+    ///
+    /// Cancellation is checked between advisories. Advisories already being visited will be
+    /// finished, but no new ones will be started. The walk then returns [`Error::Cancelled`].
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Continue with the remaining advisories when visiting an advisory fails.
+    ///
+    /// The errors will be collected and returned as [`Error::Failed`] at the end of the walk.
+    /// Errors loading the metadata or an index still abort the walk.
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// Process the advisories of [`Self::walk_parallel`] one distribution at a time.
+    ///
+    /// By default, the indexes of all distributions are loaded before visiting the first
+    /// advisory. With this enabled, the advisories of a distribution are visited as soon as its
+    /// index is loaded, with `limit` applying to each distribution. This also avoids holding
+    /// the advisories of all distributions in memory at once.
+    pub fn with_per_distribution(mut self, per_distribution: bool) -> Self {
+        self.per_distribution = per_distribution;
+        self
+    }
+
+    /// Fail the walk if the provider metadata is invalid.
+    ///
+    /// The provider metadata is always validated after loading it. By default, problems are
+    /// only logged as warnings. With this enabled, the walk fails with
+    /// [`Error::InvalidMetadata`] before any distribution gets loaded.
+    pub fn with_strict_metadata(mut self, strict_metadata: bool) -> Self {
+        self.strict_metadata = strict_metadata;
+        self
+    }
+
+    /// Process the discovered advisories ordered by their URL.
+    ///
+    /// By default, advisories are processed in the order of the indexes, which, when loading
+    /// multiple indexes concurrently, depends on which one completes first. With this enabled,
+    /// the advisories are sorted after being discovered, so that walking the same snapshot
+    /// dispatches them in the same order. When walking one distribution at a time, as
+    /// [`Self::walk`] and [`Self::with_per_distribution`] do, the advisories are sorted within each
+    /// distribution.
+    ///
+    /// Visiting advisories in parallel may still complete them in a different order.
+    pub fn with_sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
     /// Set a filter for distributions.
     ///
     /// Each distribution from the metadata file will be passed to this function, if it returns `false`, the distribution
@@ -64,20 +273,127 @@ impl<S: Source, P: Progress> Walker<S, P> {
         self
     }
 
+    /// Set a filter for ROLIE feeds.
+    ///
+    /// Each feed from the metadata file will be passed to this function, if it returns `false`,
+    /// the feed will not even be fetched. This allows selecting feeds by their TLP label, e.g.
+    /// only walking `TLP:WHITE` feeds. Directory based distributions have no TLP label, and so
+    /// are not affected. Use [`Self::with_distribution_filter`] to select those.
+    pub fn with_feed_filter<F>(mut self, feed_filter: F) -> Self
+    where
+        F: Fn(&Feed) -> bool + 'static,
+    {
+        self.feed_filter = Some(Box::new(feed_filter));
+        self
+    }
+
+    /// Set a filter for advisories.
+    ///
+    /// Each advisory from the index of a distribution will be passed to this function, if it
+    /// returns `false`, the advisory will neither be visited nor counted for the progress.
+    pub fn with_advisory_filter<F>(mut self, advisory_filter: F) -> Self
+    where
+        F: Fn(&DiscoveredAdvisory) -> bool + 'static,
+    {
+        self.advisory_filter = Some(Box::new(advisory_filter));
+        self
+    }
+
+    /// Filter the discovered advisories, and sort them if requested.
+    fn filter_advisories(&self, advisories: Vec<DiscoveredAdvisory>) -> Vec<DiscoveredAdvisory> {
+        let mut advisories = match &self.advisory_filter {
+            Some(filter) => advisories
+                .into_iter()
+                .filter(|advisory| filter(advisory))
+                .collect(),
+            None => advisories,
+        };
+
+        if self.sorted {
+            advisories.sort_by(|a, b| a.url.cmp(&b.url));
+        }
+
+        advisories
+    }
+
+    async fn open_checkpoint<VE, SE>(&self) -> Result<Option<Checkpoint>, Error<VE, SE>>
+    where
+        VE: std::fmt::Display + Debug,
+        SE: std::fmt::Display + Debug,
+    {
+        match &self.checkpoint {
+            Some(path) => Ok(Some(
+                Checkpoint::open(path.clone())
+                    .await
+                    .map_err(Error::Checkpoint)?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn validate_metadata<VE, SE>(&self, metadata: &ProviderMetadata) -> Result<(), Error<VE, SE>>
+    where
+        VE: std::fmt::Display + Debug,
+        SE: std::fmt::Display + Debug,
+    {
+        let issues = validate_metadata(metadata);
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        if self.strict_metadata {
+            return Err(Error::InvalidMetadata(issues));
+        }
+
+        for issue in issues {
+            log::warn!("Invalid provider metadata: {issue}");
+        }
+
+        Ok(())
+    }
+
+    /// Fail with [`Error::Cancelled`] if the walk was cancelled.
+    fn check_cancelled<VE, SE>(&self) -> Result<(), Error<VE, SE>>
+    where
+        VE: std::fmt::Display + Debug,
+        SE: std::fmt::Display + Debug,
+    {
+        if self.cancellation.is_cancelled() {
+            log::info!("Walk cancelled");
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
     fn collect_distributions(&self, distributions: Vec<Distribution>) -> Vec<DistributionContext> {
         distributions
             .into_iter()
             .flat_map(|distribution| {
+                let publisher = distribution.publisher;
                 distribution
                     .rolie
                     .into_iter()
                     .flat_map(|rolie| rolie.feeds)
-                    .map(|feed| DistributionContext::Feed(feed.url))
+                    .filter(|feed| match &self.feed_filter {
+                        Some(filter) => filter(feed),
+                        None => true,
+                    })
+                    .map(|feed| DistributionContext::Feed {
+                        url: feed.url,
+                        tlp_label: feed.tlp_label,
+                    })
                     .chain(
                         distribution
                             .directory_url
                             .map(DistributionContext::Directory),
                     )
+                    .map(move |context| match &publisher {
+                        Some(publisher) => DistributionContext::Aggregated {
+                            publisher: publisher.clone(),
+                            distribution: Box::new(context),
+                        },
+                        None => context,
+                    })
             })
             .filter(|distribution| {
                 if let Some(filter) = &self.distribution_filter {
@@ -89,33 +405,51 @@ impl<S: Source, P: Progress> Walker<S, P> {
             .collect()
     }
 
-    pub async fn walk<V>(self, visitor: V) -> Result<(), Error<V::Error, S::Error>>
+    pub async fn walk<V>(self, visitor: V) -> Result<WalkStats, Error<V::Error, S::Error>>
     where
         V: DiscoveredVisitor,
     {
+        let mut outcome = Outcome::new();
         let metadata = self.source.load_metadata().await.map_err(Error::Source)?;
+        self.validate_metadata(&metadata)?;
 
         let context = visitor
             .visit_context(&DiscoveredContext {
                 metadata: &metadata,
+                memory_budget: self.memory_budget.as_ref(),
+                transfer: Some(&outcome.transfer),
             })
             .await
             .map_err(Error::Visitor)?;
 
+        let checkpoint = self.open_checkpoint().await?;
+
         let distributions = self.collect_distributions(metadata.distributions);
         log::info!("processing {} distribution URLs", distributions.len());
+        outcome.stats.distributions = distributions.len();
 
         for distribution in distributions {
+            self.check_cancelled()?;
+
             log::info!("Walking directory URL: {distribution:?}");
             let index = self
                 .source
                 .load_index(distribution)
                 .await
                 .map_err(Error::Source)?;
+            let index = self.filter_advisories(index);
+            outcome.stats.discovered += index.len();
+            let index = skip_visited(checkpoint.as_ref(), index);
 
             let mut progress = self.progress.start(index.len());
 
             for advisory in index {
+                if self.cancellation.is_cancelled() {
+                    log::info!("Walk cancelled");
+                    progress.finish().await;
+                    return Err(Error::Cancelled);
+                }
+
                 log::debug!("  Discovered advisory: {advisory:?}");
                 progress
                     .set_message(
@@ -128,75 +462,456 @@ impl<S: Source, P: Progress> Walker<S, P> {
                             .to_string(),
                     )
                     .await;
-                visitor
-                    .visit_advisory(&context, advisory)
-                    .await
-                    .map_err(Error::Visitor)?;
+                let result = visitor.visit_advisory(&context, advisory.clone()).await;
+                if outcome
+                    .add(&advisory, result, self.continue_on_error)
+                    .map_err(|error| Error::Advisory {
+                        url: advisory.url.clone(),
+                        distribution: advisory.context.clone(),
+                        error,
+                    })?
+                    && let Some(checkpoint) = &checkpoint
+                {
+                    checkpoint
+                        .record(&advisory.url)
+                        .await
+                        .map_err(Error::Checkpoint)?;
+                }
                 progress.tick().await;
             }
 
             progress.finish().await;
         }
 
-        Ok(())
+        outcome.finish(checkpoint).await
     }
 
     pub async fn walk_parallel<V>(
         self,
         limit: usize,
         visitor: V,
-    ) -> Result<(), Error<V::Error, S::Error>>
+    ) -> Result<WalkStats, Error<V::Error, S::Error>>
     where
         V: DiscoveredVisitor,
     {
+        let mut outcome = Outcome::new();
         let metadata = self.source.load_metadata().await.map_err(Error::Source)?;
+        self.validate_metadata(&metadata)?;
         let context = visitor
             .visit_context(&DiscoveredContext {
                 metadata: &metadata,
+                memory_budget: self.memory_budget.as_ref(),
+                transfer: Some(&outcome.transfer),
             })
             .await
             .map_err(Error::Visitor)?;
 
-        let context = Arc::new(context);
-        let visitor = Arc::new(visitor);
+        let checkpoint = self.open_checkpoint().await?;
 
         let distributions = self.collect_distributions(metadata.distributions);
         log::info!("processing {} distribution URLs", distributions.len());
+        outcome.stats.distributions = distributions.len();
+        let outcome = Mutex::new(outcome);
 
-        let advisories: Vec<_> = collect_advisories::<V, S>(&self.source, distributions)
-            .try_collect()
+        if self.per_distribution {
+            for distribution in distributions {
+                self.check_cancelled()?;
+
+                log::info!("Walking directory URL: {distribution:?}");
+                let index = self
+                    .source
+                    .load_index(distribution)
+                    .await
+                    .map_err(Error::Source)?;
+                let index = self.filter_advisories(index);
+                outcome.lock().await.stats.discovered += index.len();
+                let index = skip_visited(checkpoint.as_ref(), index);
+
+                self.visit_parallel(
+                    limit,
+                    &visitor,
+                    &context,
+                    checkpoint.as_ref(),
+                    &outcome,
+                    index,
+                )
+                .await?;
+            }
+        } else {
+            let mut discovery = self.progress.start_indeterminate();
+            discovery.set_message("Loading indexes".to_string()).await;
+
+            let advisories = async {
+                let mut advisories = vec![];
+                let mut sources =
+                    pin!(collect_sources::<V, S>(&self.source, distributions).into_stream());
+                while let Some(index) = sources.next().await {
+                    advisories.extend(index?.collect::<Vec<_>>().await);
+                    discovery.tick().await;
+                }
+                Ok::<_, Error<V::Error, S::Error>>(advisories)
+            }
+            .await;
+
+            discovery.finish().await;
+            let advisories = advisories?;
+
+            let advisories = self.filter_advisories(advisories);
+            outcome.lock().await.stats.discovered += advisories.len();
+            let advisories = skip_visited(checkpoint.as_ref(), advisories);
+
+            self.visit_parallel(
+                limit,
+                &visitor,
+                &context,
+                checkpoint.as_ref(),
+                &outcome,
+                advisories,
+            )
             .await?;
+        }
+
+        outcome.into_inner().finish(checkpoint).await
+    }
+
+    /// Turn the walk into a stream of validated advisories.
+    ///
+    /// This retrieves and validates the advisories, like a [`RetrievingVisitor`] together with a
+    /// [`ValidationVisitor`] would do, and yields the results instead of visiting them. Failed
+    /// advisories are yielded as [`StreamError::Advisory`]. If the walk itself fails, its error is
+    /// yielded as [`StreamError::Walk`] and the stream ends.
+    ///
+    /// The walk is driven by polling the stream, the next advisory is retrieved while the
+    /// current one is being consumed. Dropping the stream stops the walk.
+    #[allow(clippy::result_large_err)]
+    pub fn into_stream(
+        self,
+        options: impl Into<ValidationOptions>,
+    ) -> impl Stream<Item = Result<ValidatedAdvisory, StreamError<S>>>
+    where
+        S: KeySource,
+    {
+        let (tx, rx) = mpsc::channel(1);
+
+        let visitor = ValidationVisitor::new(
+            move |result: Result<ValidatedAdvisory, ValidationError<S>>| {
+                let mut tx = tx.clone();
+                async move { tx.send(result).await.map_err(|_| StreamClosed) }
+            },
+        )
+        .with_options(options);
+        let visitor = RetrievingVisitor::new(self.source.clone(), visitor);
+
+        self.drive(visitor, rx).map(|result| match result {
+            Ok(result) => result.map_err(StreamError::Advisory),
+            Err(err) => Err(StreamError::Walk(err)),
+        })
+    }
+
+    /// Turn the walk into a stream of verified advisories.
+    ///
+    /// This retrieves, validates, and verifies the advisories, like a [`RetrievingVisitor`],
+    /// [`ValidationVisitor`], and [`VerifyingVisitor`] would do, running the provided checks
+    /// (e.g. [`verification::check::init_verifying_visitor`]). The results of the checks of each
+    /// advisory are yielded as [`VerifiedAdvisory`], instead of visiting them. Advisories which
+    /// could not be verified are yielded as [`VerificationStreamError::Advisory`]. If the walk
+    /// itself fails, its error is yielded as [`VerificationStreamError::Walk`] and the stream ends.
+    ///
+    /// Like [`Self::into_stream`], the walk is driven by polling the stream, and dropping the
+    /// stream stops the walk.
+    #[cfg(feature = "csaf")]
+    #[allow(clippy::result_large_err)]
+    pub fn into_verification_stream<I>(
+        self,
+        options: impl Into<ValidationOptions>,
+        checks: Vec<(I, Box<dyn Check>)>,
+    ) -> impl Stream<Item = Result<VerifiedAdvisory<ValidatedAdvisory, I>, VerificationStreamError<S>>>
+    where
+        S: KeySource + 'static,
+        I: Clone + PartialEq + Eq + std::hash::Hash + 'static,
+    {
+        let (tx, rx) = mpsc::channel(1);
+
+        let visitor = VerifyingVisitor::with_checks(
+            move |result: Result<
+                VerifiedAdvisory<ValidatedAdvisory, I>,
+                VerificationError<ValidationError<S>, ValidatedAdvisory>,
+            >| {
+                let mut tx = tx.clone();
+                async move { tx.send(result).await.map_err(|_| StreamClosed) }
+            },
+            checks,
+        );
+        let visitor = ValidationVisitor::new(visitor).with_options(options);
+        let visitor = RetrievingVisitor::new(self.source.clone(), visitor);
+
+        self.drive(visitor, rx).map(|result| match result {
+            Ok(result) => result.map_err(VerificationStreamError::Advisory),
+            Err(err) => Err(VerificationStreamError::Walk(err)),
+        })
+    }
+
+    /// Walk using a visitor, yielding what it sends to the channel, followed by the error of the
+    /// walk, if it fails.
+    fn drive<V, T>(
+        self,
+        visitor: V,
+        rx: mpsc::Receiver<T>,
+    ) -> impl Stream<Item = Result<T, Error<V::Error, S::Error>>>
+    where
+        V: DiscoveredVisitor,
+    {
+        // once the walk completes, the visitor (and so the sender) gets dropped, ending the
+        // stream of advisories, which is then followed by the error of the walk
+        let (error_tx, error_rx) = oneshot::channel();
+        let walk = stream::once(async move {
+            if let Err(err) = self.walk(visitor).await {
+                let _ = error_tx.send(err);
+            }
+        })
+        .filter_map(|()| future::ready(None));
 
+        let advisories = rx
+            .map(Ok)
+            .chain(stream::once(error_rx).filter_map(|result| future::ready(result.ok().map(Err))));
+
+        stream::select(advisories, walk)
+    }
+
+    /// Visit a set of advisories, with up to `limit` of them concurrently.
+    async fn visit_parallel<V>(
+        &self,
+        limit: usize,
+        visitor: &V,
+        context: &V::Context,
+        checkpoint: Option<&Checkpoint>,
+        outcome: &Mutex<Outcome<V::Error>>,
+        advisories: Vec<DiscoveredAdvisory>,
+    ) -> Result<(), Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor,
+    {
         let size = advisories.len();
         log::info!("Discovered {size} advisories");
 
-        let progress = Arc::new(Mutex::new(self.progress.start(size)));
+        let progress = Mutex::new(self.progress.start(size));
+        let memory_budget = &self.memory_budget;
 
-        stream::iter(advisories)
+        let mut cancelled = false;
+
+        // stop dispatching new advisories once cancelled, but let the in-flight ones finish
+        let result = stream::iter(advisories)
+            .take_while(|_| {
+                cancelled = self.cancellation.is_cancelled();
+                future::ready(!cancelled)
+            })
             .map(Ok)
             .try_for_each_concurrent(limit, async |advisory| {
+                if let Some(memory_budget) = memory_budget {
+                    memory_budget.available().await;
+                }
+
                 log::debug!("Discovered advisory: {}", advisory.url);
 
-                let result = visitor
-                    .visit_advisory(&context, advisory.clone())
-                    .map_err(Error::Visitor)
-                    .await;
+                let result = visitor.visit_advisory(context, advisory.clone()).await;
+                let result = outcome
+                    .lock()
+                    .await
+                    .add(&advisory, result, self.continue_on_error)
+                    .map_err(|error| Error::Advisory {
+                        url: advisory.url.clone(),
+                        distribution: advisory.context.clone(),
+                        error,
+                    });
+
+                let result = match (result, checkpoint) {
+                    (Ok(true), Some(checkpoint)) => checkpoint
+                        .record(&advisory.url)
+                        .await
+                        .map_err(Error::Checkpoint),
+                    (result, _) => result.map(|_| ()),
+                };
 
                 progress.lock().await.tick().await;
 
                 result
             })
-            .await?;
+            .await;
 
-        if let Ok(progress) = Arc::try_unwrap(progress) {
-            let progress = progress.into_inner();
-            progress.finish().await;
+        progress.into_inner().finish().await;
+
+        result?;
+
+        if cancelled {
+            log::info!("Walk cancelled");
+            return Err(Error::Cancelled);
         }
 
         Ok(())
     }
 }
 
+/// Collects the results and statistics of visiting advisories.
+struct Outcome<VE> {
+    stats: WalkStats,
+    errors: Vec<(Url, VE)>,
+    distributions: BTreeSet<Url>,
+    transfer: TransferCounter,
+    start: Instant,
+}
+
+impl<VE> Outcome<VE>
+where
+    VE: std::fmt::Display + Debug,
+{
+    fn new() -> Self {
+        Self {
+            stats: Default::default(),
+            errors: vec![],
+            distributions: Default::default(),
+            transfer: Default::default(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Add the result of visiting an advisory, returning if it succeeded.
+    ///
+    /// Fails with the error of the visitor, unless continuing on errors.
+    fn add(
+        &mut self,
+        advisory: &DiscoveredAdvisory,
+        result: Result<(), VE>,
+        continue_on_error: bool,
+    ) -> Result<bool, VE> {
+        match result {
+            Ok(()) => {
+                self.stats.succeeded += 1;
+                Ok(true)
+            }
+            Err(err) if continue_on_error => {
+                log::warn!("Failed to visit advisory {}: {err}", advisory.url);
+                self.stats.failed += 1;
+                self.errors.push((advisory.url.clone(), err));
+                self.distributions.insert(advisory.context.url().clone());
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Finish the walk, only clearing the checkpoint if all advisories succeeded.
+    async fn finish<SE>(
+        mut self,
+        checkpoint: Option<Checkpoint>,
+    ) -> Result<WalkStats, Error<VE, SE>>
+    where
+        SE: std::fmt::Display + Debug,
+    {
+        self.stats.downloaded_bytes = self.transfer.downloaded();
+        self.stats.skipped_bytes = self.transfer.not_modified();
+        self.stats.duration = self.start.elapsed();
+
+        if !self.errors.is_empty() {
+            return Err(Error::Failed {
+                stats: self.stats,
+                errors: self.errors,
+                distributions: self.distributions.into_iter().collect(),
+            });
+        }
+
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.clear().await.map_err(Error::Checkpoint)?;
+        }
+
+        Ok(self.stats)
+    }
+}
+
+/// Records the URLs of visited advisories in an append-only file.
+struct Checkpoint {
+    path: PathBuf,
+    visited: HashSet<String>,
+    file: Mutex<fs::File>,
+}
+
+impl Checkpoint {
+    async fn open(path: PathBuf) -> std::io::Result<Self> {
+        let content = match fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let visited: HashSet<_> = content
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect();
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+
+        if !content.is_empty() && !content.ends_with('\n') {
+            // terminate a partially written line, from a previous crash
+            file.write_all(b"\n").await?;
+        }
+
+        if !visited.is_empty() {
+            log::info!(
+                "Resuming from checkpoint {}, with {} visited advisories",
+                path.display(),
+                visited.len()
+            );
+        }
+
+        Ok(Self {
+            path,
+            visited,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Record an advisory as visited, flushing the file.
+    async fn record(&self, url: &Url) -> std::io::Result<()> {
+        let mut file = self.file.lock().await;
+        file.write_all(format!("{url}\n").as_bytes()).await?;
+        file.flush().await
+    }
+
+    /// Remove the checkpoint file, after a successful walk.
+    async fn clear(self) -> std::io::Result<()> {
+        drop(self.file);
+        fs::remove_file(&self.path).await
+    }
+}
+
+fn skip_visited(
+    checkpoint: Option<&Checkpoint>,
+    advisories: Vec<DiscoveredAdvisory>,
+) -> Vec<DiscoveredAdvisory> {
+    let Some(checkpoint) = checkpoint else {
+        return advisories;
+    };
+
+    let total = advisories.len();
+    let advisories: Vec<_> = advisories
+        .into_iter()
+        .filter(|advisory| !checkpoint.visited.contains(advisory.url.as_str()))
+        .collect();
+
+    if advisories.len() < total {
+        log::info!(
+            "Skipping {} advisories recorded in the checkpoint",
+            total - advisories.len()
+        );
+    }
+
+    advisories
+}
+
 #[allow(clippy::needless_lifetimes)] // false positive
 fn collect_sources<'s, V: DiscoveredVisitor, S: Source>(
     source: &'s S,
@@ -214,11 +929,616 @@ fn collect_sources<'s, V: DiscoveredVisitor, S: Source>(
     })
 }
 
-fn collect_advisories<'s, V: DiscoveredVisitor + 's, S: Source>(
-    source: &'s S,
-    discover_contexts: Vec<DistributionContext>,
-) -> impl TryStream<Ok = DiscoveredAdvisory, Error = Error<V::Error, S::Error>> + 's {
-    collect_sources::<V, S>(source, discover_contexts)
-        .map_ok(|s| s.map(Ok))
-        .try_flatten()
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        retrieve::RetrievedAdvisory,
+        source::{FileOptions, FileSource},
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    impl DiscoveredVisitor for &Counting {
+        type Error = std::convert::Infallible;
+        type Context = ();
+
+        async fn visit_context(&self, _: &DiscoveredContext<'_>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn visit_advisory(&self, _: &(), _: DiscoveredAdvisory) -> Result<(), Self::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    /// Fails visiting an advisory, after visiting a number of them.
+    struct Failing(AtomicUsize);
+
+    impl DiscoveredVisitor for &Failing {
+        type Error = &'static str;
+        type Context = ();
+
+        async fn visit_context(&self, _: &DiscoveredContext<'_>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn visit_advisory(&self, _: &(), _: DiscoveredAdvisory) -> Result<(), Self::Error> {
+            match self.0.fetch_sub(1, Ordering::SeqCst) {
+                0 => Err("failed"),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn checkpoint() {
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+        let filter = |advisory: &DiscoveredAdvisory| advisory.url.path().contains("rhsa-2023_");
+
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        let path = dir.path().join("checkpoint");
+
+        // interrupt the walk after visiting one advisory
+        let result = Walker::new(source.clone())
+            .with_advisory_filter(filter)
+            .with_checkpoint(path.clone())
+            .walk(&Failing(AtomicUsize::new(1)))
+            .await;
+        let Err(Error::Advisory { url, error, .. }) = result else {
+            panic!("must fail visiting an advisory");
+        };
+        assert!(url.path().contains("rhsa-2023_"));
+        assert_eq!(error, "failed");
+        let content = std::fs::read_to_string(&path).expect("must have a checkpoint");
+        assert_eq!(content.lines().count(), 1);
+
+        // resume, skipping the recorded advisory
+        let counting = Counting::default();
+        Walker::new(source)
+            .with_advisory_filter(filter)
+            .with_checkpoint(path.clone())
+            .walk_parallel(4, &counting)
+            .await
+            .expect("must walk");
+        assert_eq!(counting.0.load(Ordering::SeqCst), 2);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn continue_on_error() {
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+        let filter = |advisory: &DiscoveredAdvisory| advisory.url.path().contains("rhsa-2023_");
+
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        let path = dir.path().join("checkpoint");
+
+        // only fails visiting the second advisory
+        let result = Walker::new(source.clone())
+            .with_advisory_filter(filter)
+            .with_checkpoint(path.clone())
+            .continue_on_error(true)
+            .walk(&Failing(AtomicUsize::new(1)))
+            .await;
+        let Err(Error::Failed {
+            stats,
+            errors,
+            distributions,
+        }) = result
+        else {
+            panic!("must fail, was: {result:?}");
+        };
+        assert_eq!((stats.discovered, stats.succeeded, stats.failed), (3, 2, 1));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(distributions.len(), 1);
+        // keep the checkpoint, for retrying the failed advisory
+        assert!(path.exists());
+
+        let stats = Walker::new(source)
+            .with_advisory_filter(filter)
+            .with_checkpoint(path.clone())
+            .continue_on_error(true)
+            .walk_parallel(4, &Counting::default())
+            .await
+            .expect("must walk");
+        assert_eq!((stats.discovered, stats.succeeded, stats.failed), (3, 1, 0));
+        assert!(!path.exists());
+    }
+
+    /// Cancels the walk when visiting the first advisory.
+    #[derive(Default)]
+    struct Cancelling(Counting, CancellationToken);
+
+    impl DiscoveredVisitor for &Cancelling {
+        type Error = std::convert::Infallible;
+        type Context = ();
+
+        async fn visit_context(&self, _: &DiscoveredContext<'_>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn visit_advisory(
+            &self,
+            _: &(),
+            advisory: DiscoveredAdvisory,
+        ) -> Result<(), Self::Error> {
+            self.1.cancel();
+            (&self.0).visit_advisory(&(), advisory).await
+        }
+    }
+
+    #[tokio::test]
+    async fn cancellation() {
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+        let filter = |advisory: &DiscoveredAdvisory| advisory.url.path().contains("rhsa-2023_");
+
+        let cancelling = Cancelling::default();
+        let result = Walker::new(source.clone())
+            .with_advisory_filter(filter)
+            .with_cancellation(cancelling.1.clone())
+            .walk(&cancelling)
+            .await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(cancelling.0.0.load(Ordering::SeqCst), 1);
+
+        let cancelling = Cancelling::default();
+        let result = Walker::new(source)
+            .with_advisory_filter(filter)
+            .with_cancellation(cancelling.1.clone())
+            .walk_parallel(1, &cancelling)
+            .await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(cancelling.0.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn advisory_filter() {
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+
+        let filter = |advisory: &DiscoveredAdvisory| advisory.url.path().contains("rhsa-2023_");
+
+        let counting = Counting::default();
+        Walker::new(source.clone())
+            .with_advisory_filter(filter)
+            .walk(&counting)
+            .await
+            .expect("must walk");
+        assert_eq!(counting.0.load(Ordering::SeqCst), 3);
+
+        let counting = Counting::default();
+        Walker::new(source)
+            .with_advisory_filter(filter)
+            .walk_parallel(4, &counting)
+            .await
+            .expect("must walk");
+        assert_eq!(counting.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn stats() {
+        use crate::retrieve::{RetrievedAdvisory, RetrievingVisitor};
+        use walker_common::retrieve::RetrievalError;
+
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+
+        let visitor = RetrievingVisitor::new(
+            source.clone(),
+            async |advisory: Result<
+                RetrievedAdvisory,
+                RetrievalError<DiscoveredAdvisory, FileSource>,
+            >| { advisory.map(|_| ()) },
+        );
+
+        let stats = Walker::new(source)
+            .with_advisory_filter(|advisory| advisory.url.path().contains("rhsa-2023_"))
+            .walk(visitor)
+            .await
+            .expect("must walk");
+
+        assert_eq!(stats.distributions, 1);
+        assert_eq!(stats.discovered, 3);
+        assert_eq!(stats.visited(), 3);
+        assert!(stats.downloaded_bytes > 0);
+        assert_eq!(stats.skipped_bytes, 0);
+    }
+
+    /// Records the URLs of visited advisories.
+    #[derive(Default)]
+    struct RecordingUrls(parking_lot::Mutex<Vec<Url>>);
+
+    impl DiscoveredVisitor for &RecordingUrls {
+        type Error = std::convert::Infallible;
+        type Context = ();
+
+        async fn visit_context(&self, _: &DiscoveredContext<'_>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn visit_advisory(
+            &self,
+            _: &(),
+            advisory: DiscoveredAdvisory,
+        ) -> Result<(), Self::Error> {
+            self.0.lock().push(advisory.url);
+            Ok(())
+        }
+    }
+
+    /// A source returning the advisories of a distribution in reverse order of their URLs.
+    #[derive(Clone, Debug)]
+    struct Reversed(FileSource);
+
+    impl walker_common::source::Source for Reversed {
+        type Error = anyhow::Error;
+        type Retrieved = RetrievedAdvisory;
+    }
+
+    impl Source for Reversed {
+        async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+            self.0.load_metadata().await
+        }
+
+        async fn load_index(
+            &self,
+            context: DistributionContext,
+        ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
+            let mut advisories = self.0.load_index(context).await?;
+            advisories.sort_by(|a, b| b.url.cmp(&a.url));
+            Ok(advisories)
+        }
+
+        async fn load_advisory(
+            &self,
+            advisory: DiscoveredAdvisory,
+        ) -> Result<RetrievedAdvisory, Self::Error> {
+            self.0.load_advisory(advisory).await
+        }
+    }
+
+    #[tokio::test]
+    async fn sorted() {
+        let source = Reversed(
+            FileSource::new(
+                concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+                FileOptions::new().rootless(true),
+            )
+            .expect("must create source"),
+        );
+
+        let walk = async |sorted| {
+            let recording = RecordingUrls::default();
+            Walker::new(source.clone())
+                .with_sorted(sorted)
+                .walk_parallel(1, &recording)
+                .await
+                .expect("must walk");
+            recording.0.into_inner()
+        };
+
+        let unsorted = walk(false).await;
+        let sorted = walk(true).await;
+        assert_eq!(sorted.len(), 5);
+        assert!(sorted.is_sorted());
+        assert_ne!(unsorted, sorted);
+
+        let mut reversed = sorted.clone();
+        reversed.reverse();
+        assert_eq!(unsorted, reversed);
+    }
+
+    #[tokio::test]
+    async fn per_distribution() {
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+
+        let counting = Counting::default();
+        Walker::new(source.clone())
+            .walk_parallel(4, &counting)
+            .await
+            .expect("must walk");
+        let expected = counting.0.load(Ordering::SeqCst);
+
+        let counting = Counting::default();
+        let stats = Walker::new(source)
+            .with_per_distribution(true)
+            .walk_parallel(4, &counting)
+            .await
+            .expect("must walk");
+        assert_eq!(counting.0.load(Ordering::SeqCst), expected);
+        assert_eq!(stats.succeeded, expected);
+    }
+
+    /// A source splitting the advisories of a directory into two distributions.
+    #[derive(Clone, Debug)]
+    struct Split(FileSource);
+
+    impl walker_common::source::Source for Split {
+        type Error = anyhow::Error;
+        type Retrieved = RetrievedAdvisory;
+    }
+
+    impl Source for Split {
+        async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+            Ok(serde_json::from_value(serde_json::json!({
+                "canonical_url": "https://example.com/.well-known/csaf/provider-metadata.json",
+                "last_updated": "2024-01-01T00:00:00Z",
+                "list_on_CSAF_aggregators": false,
+                "metadata_version": "2.0",
+                "mirror_on_CSAF_aggregators": false,
+                "publisher": {
+                    "category": "vendor",
+                    "name": "Example",
+                    "namespace": "https://example.com"
+                },
+                "role": "csaf_provider",
+                "distributions": [
+                    { "directory_url": "https://example.com/2021/" },
+                    { "directory_url": "https://example.com/2023/" }
+                ]
+            }))?)
+        }
+
+        async fn load_index(
+            &self,
+            context: DistributionContext,
+        ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
+            let year = context.url().path().trim_matches('/').to_string();
+            let base = Url::from_directory_path(self.0.base()).expect("must convert into URL");
+            let context = Arc::new(context);
+
+            Ok(self
+                .0
+                .load_index(DistributionContext::Directory(base))
+                .await?
+                .into_iter()
+                .filter(|advisory| advisory.url.path().contains(&format!("rhsa-{year}_")))
+                .map(|advisory| DiscoveredAdvisory {
+                    context: context.clone(),
+                    ..advisory
+                })
+                .collect())
+        }
+
+        async fn load_advisory(
+            &self,
+            advisory: DiscoveredAdvisory,
+        ) -> Result<RetrievedAdvisory, Self::Error> {
+            self.0.load_advisory(advisory).await
+        }
+    }
+
+    #[tokio::test]
+    async fn per_distribution_multiple() {
+        let source = Split(
+            FileSource::new(
+                concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+                FileOptions::new().rootless(true),
+            )
+            .expect("must create source"),
+        );
+
+        let recording = RecordingUrls::default();
+        let stats = Walker::new(source)
+            .with_per_distribution(true)
+            .walk_parallel(4, &recording)
+            .await
+            .expect("must walk");
+        let urls = recording.0.into_inner();
+
+        assert_eq!(stats.distributions, 2);
+        assert_eq!(stats.discovered, urls.len());
+        assert!(urls.iter().any(|url| url.path().contains("rhsa-2021_")));
+        assert!(urls.iter().any(|url| url.path().contains("rhsa-2023_")));
+
+        // all advisories of the first distribution are visited before the ones of the second
+        let first = urls
+            .iter()
+            .position(|url| url.path().contains("rhsa-2023_"))
+            .expect("must visit the second distribution");
+        assert!(
+            urls[first..]
+                .iter()
+                .all(|url| url.path().contains("rhsa-2023_"))
+        );
+    }
+
+    #[tokio::test]
+    async fn per_distribution_cancelled() {
+        let source = Split(
+            FileSource::new(
+                concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+                FileOptions::new().rootless(true),
+            )
+            .expect("must create source"),
+        );
+
+        // the first distribution has a single advisory, cancelling before the second one
+        let cancelling = Cancelling::default();
+        let result = Walker::new(source)
+            .with_per_distribution(true)
+            .with_cancellation(cancelling.1.clone())
+            .walk_parallel(4, &cancelling)
+            .await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(cancelling.0.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn strict_metadata() {
+        let dir = tempfile::tempdir().expect("must create temp dir");
+        std::fs::create_dir(dir.path().join("metadata")).expect("must create metadata dir");
+        std::fs::write(
+            dir.path().join("metadata/provider-metadata.json"),
+            serde_json::to_vec(&serde_json::json!({
+                "canonical_url": "https://example.com/.well-known/csaf/provider-metadata.json",
+                "last_updated": "2024-01-01T00:00:00Z",
+                "metadata_version": "2.0",
+                "publisher": {
+                    "category": "vendor",
+                    "name": "Example",
+                    "namespace": "https://example.com"
+                }
+            }))
+            .expect("must serialize"),
+        )
+        .expect("must write metadata");
+
+        let source = FileSource::new(dir.path(), None).expect("must create source");
+
+        // only a warning by default
+        let counting = Counting::default();
+        Walker::new(source.clone())
+            .walk(&counting)
+            .await
+            .expect("must walk");
+
+        let result = Walker::new(source)
+            .with_strict_metadata(true)
+            .walk_parallel(4, &Counting::default())
+            .await;
+        let Err(Error::InvalidMetadata(issues)) = result else {
+            panic!("must fail with invalid metadata");
+        };
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].pointer, "/distributions");
+    }
+
+    /// Records the progress events.
+    #[derive(Clone, Default)]
+    struct Recording(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl Recording {
+        fn record(&self, event: impl Into<String>) {
+            self.0.lock().expect("must lock").push(event.into());
+        }
+    }
+
+    impl Progress for Recording {
+        type Instance = Recording;
+
+        fn start(&self, work: usize) -> Self::Instance {
+            self.record(format!("start {work}"));
+            self.clone()
+        }
+
+        fn start_indeterminate(&self) -> Self::Instance {
+            self.record("start indeterminate");
+            self.clone()
+        }
+    }
+
+    impl ProgressBar for Recording {
+        async fn increment(&mut self, work: usize) {
+            self.record(format!("increment {work}"));
+        }
+
+        async fn finish(self) {
+            self.record("finish");
+        }
+
+        async fn set_message(&mut self, _msg: String) {}
+    }
+
+    #[tokio::test]
+    async fn discovery_progress() {
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+
+        let progress = Recording::default();
+        let counting = Counting::default();
+        Walker::new(source)
+            .with_progress(progress.clone())
+            .walk_parallel(1, &counting)
+            .await
+            .expect("must walk");
+
+        let visited = counting.0.load(Ordering::SeqCst);
+        let events = progress.0.lock().expect("must lock").clone();
+
+        // a single distribution index gets loaded, before visiting the advisories
+        let mut expected = vec![
+            "start indeterminate".to_string(),
+            "increment 1".to_string(),
+            "finish".to_string(),
+            format!("start {visited}"),
+        ];
+        expected.extend(std::iter::repeat_n("increment 1".to_string(), visited));
+        expected.push("finish".to_string());
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn feed_filter() {
+        use crate::model::metadata::{Rolie, TlpLabel};
+
+        let feed = |name: &str, tlp_label| Feed {
+            summary: None,
+            tlp_label,
+            url: Url::parse(&format!("https://example.com/{name}.json")).unwrap(),
+        };
+        let distributions = vec![Distribution {
+            directory_url: Some(Url::parse("https://example.com/advisories/").unwrap()),
+            rolie: Some(Rolie {
+                categories: vec![],
+                feeds: vec![
+                    feed("white", TlpLabel::White),
+                    feed("amber", TlpLabel::Amber),
+                ],
+                services: vec![],
+            }),
+            publisher: None,
+        }];
+
+        let source = FileSource::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test-data"),
+            FileOptions::new().rootless(true),
+        )
+        .expect("must create source");
+        let walker = Walker::new(source).with_feed_filter(|feed| feed.tlp_label == TlpLabel::White);
+
+        let contexts = walker.collect_distributions(distributions);
+        assert_eq!(
+            contexts,
+            [
+                DistributionContext::Feed {
+                    url: Url::parse("https://example.com/white.json").unwrap(),
+                    tlp_label: TlpLabel::White,
+                },
+                DistributionContext::Directory(
+                    Url::parse("https://example.com/advisories/").unwrap()
+                ),
+            ]
+        );
+        assert_eq!(contexts[0].tlp_label(), Some(&TlpLabel::White));
+        assert_eq!(contexts[1].tlp_label(), None);
+    }
 }
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{base, start_async_mock_server, status};
+use csaf_walker::{
+    discover::DiscoveredAdvisory,
+    retrieve::{RetrievedAdvisory, RetrievingVisitor},
+    source::{HttpOptions, HttpSource},
+    walker::{Error, Walker},
+};
+use hyper::{Response, StatusCode};
+use std::time::Duration;
+use url::Url;
+use walker_common::{
+    fetcher::{Fetcher, FetcherOptions},
+    retrieve::RetrievalError,
+};
+
+/// Start a server, serving a provider with a fast and a slow advisory.
+///
+/// The signature of the slow advisory takes longer than any test is willing to wait.
+async fn start_server() -> Url {
+    let server = start_async_mock_server(|req| async move {
+        let base = base(&req);
+        match req.uri().path() {
+            "/provider-metadata.json" => Response::new(
+                serde_json::json!({
+                    "canonical_url": format!("{base}/provider-metadata.json"),
+                    "distributions": [{ "directory_url": format!("{base}/advisories/") }],
+                    "last_updated": "2024-01-01T00:00:00Z",
+                    "metadata_version": "2.0",
+                    "publisher": {
+                        "category": "vendor",
+                        "name": "Example",
+                        "namespace": "https://example.com"
+                    }
+                })
+                .to_string(),
+            ),
+            "/advisories/changes.csv" => Response::new(
+                "\"fast.json\",\"2024-01-01T00:00:00Z\"\n\"slow.json\",\"2024-01-01T00:00:00Z\"\n"
+                    .to_string(),
+            ),
+            "/advisories/slow.json.asc" => {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                status(StatusCode::NOT_FOUND)
+            }
+            "/advisories/fast.json" | "/advisories/slow.json" => {
+                Response::new(r#"{"document":{}}"#.to_string())
+            }
+            _ => status(StatusCode::NOT_FOUND),
+        }
+    })
+    .await;
+
+    server.join("provider-metadata.json").unwrap()
+}
+
+#[tokio::test]
+async fn given_slow_advisory_when_timeout_exceeded_then_walk_continues() {
+    let metadata = start_server().await;
+
+    let source = HttpSource::new(
+        metadata,
+        Fetcher::new(FetcherOptions::new()).await.unwrap(),
+        HttpOptions::new().per_advisory_timeout(Duration::from_millis(500)),
+    );
+
+    let visitor = RetrievingVisitor::new(
+        source.clone(),
+        |result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, HttpSource>>| async move {
+            result.map(|_| ()).map_err(|err| err.to_string())
+        },
+    );
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(30),
+        Walker::new(source)
+            .continue_on_error(true)
+            .walk_parallel(2, visitor),
+    )
+    .await
+    .expect("must not wait for the slow advisory");
+
+    let Err(Error::Failed { stats, errors, .. }) = result else {
+        panic!("must fail the slow advisory");
+    };
+    assert_eq!(stats.succeeded, 1);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0.path(), "/advisories/slow.json");
+    let err = errors[0].1.to_string();
+    assert!(err.contains("Timeout"), "{err}");
+}
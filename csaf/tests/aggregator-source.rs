@@ -0,0 +1,266 @@
+mod common;
+
+use common::{base, ok_or_not_found, start_mock_server};
+use csaf_walker::{
+    discover::DiscoveredAdvisory,
+    metadata::AggregatorMetadataSource,
+    retrieve::RetrievingVisitor,
+    source::{HttpOptions, HttpSource, Source},
+    validation::{ValidatedAdvisory, ValidationError, ValidationVisitor},
+    walker::Walker,
+};
+use parking_lot::Mutex;
+use sequoia_openpgp::{
+    Cert,
+    armor::Kind,
+    cert::CertBuilder,
+    policy::StandardPolicy,
+    serialize::{
+        SerializeInto,
+        stream::{Armorer, Message, Signer},
+    },
+};
+use std::{convert::Infallible, io::Write, sync::Arc};
+use url::Url;
+use walker_common::fetcher::{Fetcher, FetcherOptions};
+
+const ADVISORY: &str = r#"{"document":{}}"#;
+
+/// The keys of the providers `a` and `b`, along with the signature of all advisories.
+struct Signing {
+    key_a: String,
+    key_b: String,
+    signature: String,
+}
+
+fn cert() -> Cert {
+    let (cert, _) = CertBuilder::general_purpose(Some("test@example.com"))
+        .generate()
+        .unwrap();
+    cert
+}
+
+fn armored(cert: &Cert) -> String {
+    String::from_utf8(cert.armored().to_vec().unwrap()).unwrap()
+}
+
+fn sign(cert: &Cert) -> String {
+    let keypair = cert
+        .keys()
+        .unencrypted_secret()
+        .with_policy(&StandardPolicy::new(), None)
+        .for_signing()
+        .next()
+        .unwrap()
+        .key()
+        .clone()
+        .into_keypair()
+        .unwrap();
+
+    let mut sink = vec![];
+    let message = Armorer::new(Message::new(&mut sink))
+        .kind(Kind::Signature)
+        .build()
+        .unwrap();
+    let mut signer = Signer::new(message, keypair)
+        .unwrap()
+        .detached()
+        .build()
+        .unwrap();
+    signer.write_all(ADVISORY.as_bytes()).unwrap();
+    signer.finalize().unwrap();
+
+    String::from_utf8(sink).unwrap()
+}
+
+fn publisher(name: &str) -> String {
+    format!(r#"{{"category":"vendor","name":"{name}","namespace":"https://{name}.example.com"}}"#)
+}
+
+fn provider_metadata(base: &str, name: &str, signed: bool) -> String {
+    let keys = match signed {
+        true => format!(r#"[{{"url": "{base}/{name}/key.asc"}}]"#),
+        false => "[]".to_string(),
+    };
+    format!(
+        r#"{{
+  "canonical_url": "{base}/{name}/provider-metadata.json",
+  "distributions": [{{"directory_url": "{base}/{name}/advisories/"}}],
+  "last_updated": "2024-01-01T00:00:00Z",
+  "list_on_CSAF_aggregators": true,
+  "metadata_version": "2.0",
+  "mirror_on_CSAF_aggregators": true,
+  "public_openpgp_keys": {keys},
+  "publisher": {publisher},
+  "role": "csaf_provider"
+}}"#,
+        publisher = publisher(name)
+    )
+}
+
+fn entry(base: &str, name: &str) -> String {
+    format!(
+        r#"{{"metadata":{{"last_updated":"2024-01-01T00:00:00Z","publisher":{publisher},"role":"csaf_provider","url":"{base}/{name}/provider-metadata.json"}}}}"#,
+        publisher = publisher(name)
+    )
+}
+
+fn aggregator(base: &str) -> String {
+    format!(
+        r#"{{
+  "aggregator": {{"category":"lister","name":"Example Lister","namespace":"https://lister.example.com"}},
+  "aggregator_version": "2.0",
+  "canonical_url": "{base}/aggregator.json",
+  "csaf_providers": [{a}, {missing}],
+  "csaf_publishers": [{b}],
+  "last_updated": "2024-01-01T00:00:00Z"
+}}"#,
+        a = entry(base, "a"),
+        missing = entry(base, "missing"),
+        b = entry(base, "b"),
+    )
+}
+
+/// Start a server, serving an aggregator listing the providers `a`, `b`, and `missing`
+///
+/// If signing information is provided, the providers publish their keys, and the advisories
+/// come with a signature.
+async fn start_aggregator_server(signing: Option<Signing>) -> Url {
+    let server = start_mock_server(move |req| {
+        let base = base(&req);
+        let signed = signing.is_some();
+        ok_or_not_found(match (req.uri().path(), signing.as_ref()) {
+            ("/aggregator.json", _) => Some(aggregator(&base)),
+            ("/a/provider-metadata.json", _) => Some(provider_metadata(&base, "a", signed)),
+            ("/b/provider-metadata.json", _) => Some(provider_metadata(&base, "b", signed)),
+            ("/a/advisories/changes.csv" | "/b/advisories/changes.csv", _) => {
+                Some(r#""2024/advisory.json","2024-01-01T00:00:00Z""#.to_string())
+            }
+            ("/a/advisories/2024/advisory.json" | "/b/advisories/2024/advisory.json", _) => {
+                Some(ADVISORY.to_string())
+            }
+            ("/a/key.asc", Some(signing)) => Some(signing.key_a.clone()),
+            ("/b/key.asc", Some(signing)) => Some(signing.key_b.clone()),
+            (
+                "/a/advisories/2024/advisory.json.asc" | "/b/advisories/2024/advisory.json.asc",
+                Some(signing),
+            ) => Some(signing.signature.clone()),
+            _ => None,
+        })
+    })
+    .await;
+
+    server.join("aggregator.json").unwrap()
+}
+
+async fn source() -> HttpSource {
+    signed_source(None).await
+}
+
+async fn signed_source(signing: Option<Signing>) -> HttpSource {
+    let url = start_aggregator_server(signing).await;
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    HttpSource::new(
+        AggregatorMetadataSource::new(url),
+        fetcher,
+        HttpOptions::new(),
+    )
+}
+
+#[tokio::test]
+async fn union_of_distributions() {
+    let metadata = source().await.load_metadata().await.unwrap();
+
+    assert_eq!(metadata.publisher.name, "Example Lister");
+
+    let publishers = metadata
+        .distributions
+        .iter()
+        .map(|distribution| distribution.publisher.as_ref().unwrap().name.clone())
+        .collect::<Vec<_>>();
+    // the missing provider gets skipped
+    assert_eq!(publishers, ["a", "b"]);
+}
+
+#[tokio::test]
+async fn publisher_in_context() {
+    let discovered = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = discovered.clone();
+    Walker::new(source().await)
+        .walk(|advisory: DiscoveredAdvisory| {
+            let recorded = recorded.clone();
+            async move {
+                let publisher = advisory.context.publisher().unwrap().name.clone();
+                recorded
+                    .lock()
+                    .push((publisher, advisory.url.path().to_string()));
+                Ok::<_, Infallible>(())
+            }
+        })
+        .await
+        .unwrap();
+
+    let mut discovered = discovered.lock().clone();
+    discovered.sort();
+    assert_eq!(
+        discovered,
+        [
+            (
+                "a".to_string(),
+                "/a/advisories/2024/advisory.json".to_string()
+            ),
+            (
+                "b".to_string(),
+                "/b/advisories/2024/advisory.json".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn keys_of_other_publisher() {
+    let a = cert();
+    let b = cert();
+    // all advisories are signed by the key of provider `a`
+    let source = signed_source(Some(Signing {
+        key_a: armored(&a),
+        key_b: armored(&b),
+        signature: sign(&a),
+    }))
+    .await;
+
+    let validated = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = validated.clone();
+    Walker::new(source.clone())
+        .walk(RetrievingVisitor::new(
+            source,
+            ValidationVisitor::new(
+                move |result: Result<ValidatedAdvisory, ValidationError<HttpSource>>| {
+                    let recorded = recorded.clone();
+                    async move {
+                        let (publisher, valid) = match result {
+                            Ok(advisory) => (advisory.context.publisher().cloned(), true),
+                            Err(ValidationError::SignatureInvalid { retrieved, .. }) => {
+                                (retrieved.context.publisher().cloned(), false)
+                            }
+                            Err(err) => return Err(err.to_string()),
+                        };
+                        recorded.lock().push((publisher.unwrap().name, valid));
+                        Ok::<_, String>(())
+                    }
+                },
+            ),
+        ))
+        .await
+        .unwrap();
+
+    let mut validated = validated.lock().clone();
+    validated.sort();
+    // the key of provider `a` must not validate the advisory of provider `b`
+    assert_eq!(
+        validated,
+        [("a".to_string(), true), ("b".to_string(), false)]
+    );
+}
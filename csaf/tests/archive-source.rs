@@ -0,0 +1,158 @@
+#![cfg(feature = "archive")]
+
+use csaf_walker::{
+    discover::DistributionContext,
+    model::metadata::{
+        Distribution, MetadataVersion, ProviderMetadata, Publisher, PublisherCategory, Role,
+    },
+    source::{ArchiveSource, FileOptions, Source},
+};
+use flate2::{Compression, write::GzEncoder};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use sha2::{Digest, Sha256};
+use std::{io::Write, path::Path};
+use url::Url;
+use walker_common::utils::hex::Hex;
+
+const ADVISORY: &[u8] = br#"{"document":{}}"#;
+
+fn metadata() -> Vec<u8> {
+    serde_json::to_vec(&ProviderMetadata {
+        canonical_url: Url::parse("https://example.com/.well-known/csaf/provider-metadata.json")
+            .unwrap(),
+        distributions: vec![Distribution {
+            directory_url: Some(Url::parse("https://example.com/advisories/").unwrap()),
+            rolie: None,
+            publisher: None,
+        }],
+        last_updated: chrono::Utc::now(),
+        list_on_csaf_aggregators: false,
+        metadata_version: MetadataVersion::V2_0,
+        mirror_on_csaf_aggregators: false,
+        public_openpgp_keys: vec![],
+        publisher: Publisher {
+            category: PublisherCategory::Vendor,
+            contact_details: None,
+            issuing_authority: None,
+            name: "Example Corp".to_string(),
+            namespace: "https://example.com".to_string(),
+        },
+        role: Role::Provider,
+    })
+    .unwrap()
+}
+
+/// The entries of an archive, nested in a top-level directory, as created by the store visitor.
+fn entries() -> Vec<(String, Vec<u8>)> {
+    let dist = utf8_percent_encode("https://example.com/advisories/", NON_ALPHANUMERIC);
+    let digest = format!(
+        "{}  advisory.json",
+        Hex(&Sha256::digest(ADVISORY)).to_lower()
+    );
+
+    vec![
+        (
+            "store/metadata/provider-metadata.json".to_string(),
+            metadata(),
+        ),
+        (format!("store/{dist}/advisory.json"), ADVISORY.to_vec()),
+        (
+            format!("store/{dist}/advisory.json.sha256"),
+            digest.into_bytes(),
+        ),
+    ]
+}
+
+fn write_tar_gz(path: &Path) {
+    let mut builder = tar::Builder::new(GzEncoder::new(
+        std::fs::File::create(path).unwrap(),
+        Compression::default(),
+    ));
+
+    for (name, data) in entries() {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(1_700_000_000);
+        header.set_cksum();
+        builder.append_data(&mut header, name, &data[..]).unwrap();
+    }
+
+    builder.into_inner().unwrap().finish().unwrap();
+}
+
+fn write_zip(path: &Path) {
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+
+    for (name, data) in entries() {
+        writer
+            .start_file(name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&data).unwrap();
+    }
+
+    writer.finish().unwrap();
+}
+
+async fn assert_source(path: &Path) {
+    let source = ArchiveSource::new(path, FileOptions::new()).unwrap();
+
+    let metadata = source.load_metadata().await.unwrap();
+    assert_eq!(metadata.distributions.len(), 1);
+    let url = metadata.distributions[0].directory_url.clone().unwrap();
+
+    let advisories = source
+        .load_index(DistributionContext::Directory(url))
+        .await
+        .unwrap();
+    assert_eq!(advisories.len(), 1);
+    assert!(advisories[0].url.path().ends_with("/advisory.json"));
+
+    let advisory = source
+        .load_advisory(advisories.into_iter().next().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(&advisory.data[..], ADVISORY);
+
+    assert!(advisory.sha256.unwrap().validate().is_ok());
+}
+
+#[tokio::test]
+async fn tar_gz() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("store.tar.gz");
+    write_tar_gz(&path);
+
+    assert_source(&path).await;
+}
+
+#[tokio::test]
+async fn zip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("store.zip");
+    write_zip(&path);
+
+    assert_source(&path).await;
+}
+
+#[tokio::test]
+async fn entry_too_large() {
+    let dir = tempfile::tempdir().unwrap();
+    let tar = dir.path().join("store.tar.gz");
+    write_tar_gz(&tar);
+    let zip = dir.path().join("store.zip");
+    write_zip(&zip);
+
+    // the provider metadata is the largest entry
+    let limit = metadata().len() as u64;
+    for path in [tar, zip] {
+        let err =
+            ArchiveSource::new(&path, FileOptions::new().max_document_size(limit - 1)).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("exceeds the maximum size"),
+            "{err:#}"
+        );
+
+        assert!(ArchiveSource::new(&path, FileOptions::new().max_document_size(limit)).is_ok());
+    }
+}
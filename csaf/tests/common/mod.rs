@@ -0,0 +1,85 @@
+//! Support shared by the integration tests
+
+#![allow(dead_code)]
+
+use hyper::{
+    Request, Response, StatusCode,
+    body::{Body, Incoming},
+    header::HOST,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use std::{convert::Infallible, error::Error, future::Future, sync::Arc};
+use tokio::net::TcpListener;
+use url::Url;
+
+/// Start a mock HTTP server, answering each request using the handler.
+///
+/// Returns the base URL of the server, like `http://127.0.0.1:1234/`.
+pub async fn start_mock_server<F, B>(handler: F) -> Url
+where
+    F: Fn(Request<Incoming>) -> Response<B> + Send + Sync + 'static,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    start_async_mock_server(move |req| std::future::ready(handler(req))).await
+}
+
+/// Start a mock HTTP server, answering each request using the async handler.
+///
+/// Returns the base URL of the server, like `http://127.0.0.1:1234/`.
+pub async fn start_async_mock_server<F, Fut, B>(handler: F) -> Url
+where
+    F: Fn(Request<Incoming>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Response<B>> + Send + 'static,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |req| {
+                    let response = handler(req);
+                    async move { Ok::<_, Infallible>(response.await) }
+                });
+
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+
+    Url::parse(&format!("http://{addr}/")).unwrap()
+}
+
+/// Respond with the body, or with "not found" if there is none.
+pub fn ok_or_not_found(body: Option<String>) -> Response<String> {
+    match body {
+        Some(body) => Response::new(body),
+        None => status(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Respond with an empty body and the status code.
+pub fn status(status: StatusCode) -> Response<String> {
+    Response::builder()
+        .status(status)
+        .body(String::new())
+        .unwrap()
+}
+
+/// The base URL of the server, as requested by the client, like `http://127.0.0.1:1234`.
+///
+/// Allows handlers to create absolute URLs pointing back to the server.
+pub fn base(req: &Request<Incoming>) -> String {
+    format!("http://{}", req.headers()[HOST].to_str().unwrap())
+}
@@ -0,0 +1,705 @@
+mod common;
+
+use common::{ok_or_not_found, start_mock_server, status};
+use csaf_walker::{
+    discover::{DiscoveredAdvisory, DistributionContext},
+    model::metadata::TlpLabel,
+    retrieve::{RetrievedAdvisory, RetrievedVisitor},
+    source::{
+        BodyReadError, BodyReader, DigestPreference, DispatchSource, HttpOptions, HttpSource,
+        HttpSourceError, Source,
+    },
+    visitors::errors::{ErrorEntry, ErrorReport, ErrorReportVisitor},
+};
+use hyper::{Response, StatusCode};
+use parking_lot::Mutex;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::{convert::Infallible, io::Write, sync::Arc, time::SystemTime};
+use url::Url;
+use walker_common::{
+    fetcher::{Fetcher, FetcherOptions},
+    metrics::Metrics,
+    retrieve::RetrievalError,
+    utils::hex::Hex,
+};
+
+/// Start a server, answering with 304 for conditional requests of the advisory
+async fn start_conditional_server() -> Url {
+    let server = start_mock_server(|req| {
+        if !req.uri().path().ends_with(".json") {
+            status(StatusCode::NOT_FOUND)
+        } else if req.headers().contains_key(hyper::header::IF_MODIFIED_SINCE) {
+            status(StatusCode::NOT_MODIFIED)
+        } else {
+            Response::new(r#"{"new":true}"#.to_string())
+        }
+    })
+    .await;
+
+    server.join("advisories/").unwrap()
+}
+
+fn discovered(base: &Url) -> DiscoveredAdvisory {
+    DiscoveredAdvisory {
+        context: Arc::new(DistributionContext::Directory(base.clone())),
+        url: base.join("test.json").unwrap(),
+        digest: None,
+        signature: None,
+        modified: SystemTime::now(),
+    }
+}
+
+#[tokio::test]
+async fn given_stored_advisory_when_not_modified_then_stored_content_is_used() {
+    let base = start_conditional_server().await;
+    let output = tempfile::tempdir().unwrap();
+
+    let path = output
+        .path()
+        .join(utf8_percent_encode(base.as_str(), NON_ALPHANUMERIC).to_string())
+        .join("test.json");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, r#"{"stored":true}"#).unwrap();
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new().conditional(output.path().to_path_buf()),
+    );
+
+    let advisory = source.load_advisory(discovered(&base)).await.unwrap();
+    assert!(advisory.metadata.not_modified);
+    assert_eq!(&advisory.data[..], br#"{"stored":true}"#);
+
+    // without a stored file, the content gets downloaded
+    std::fs::remove_file(&path).unwrap();
+    let advisory = source.load_advisory(discovered(&base)).await.unwrap();
+    assert!(!advisory.metadata.not_modified);
+    assert_eq!(&advisory.data[..], br#"{"new":true}"#);
+}
+
+#[tokio::test]
+async fn given_compressed_stored_advisory_when_not_modified_then_stored_content_is_used() {
+    use flate2::{Compression, write::GzEncoder};
+
+    let base = start_conditional_server().await;
+    let output = tempfile::tempdir().unwrap();
+
+    let path = output
+        .path()
+        .join(utf8_percent_encode(base.as_str(), NON_ALPHANUMERIC).to_string())
+        .join("test.json.gz");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(br#"{"stored":true}"#).unwrap();
+    std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new().conditional(output.path().to_path_buf()),
+    );
+
+    let advisory = source.load_advisory(discovered(&base)).await.unwrap();
+    assert!(advisory.metadata.not_modified);
+    assert_eq!(&advisory.data[..], br#"{"stored":true}"#);
+}
+
+#[tokio::test]
+async fn given_dispatch_source_when_not_found_then_status_is_reported() {
+    let base = start_conditional_server().await;
+    let output = tempfile::tempdir().unwrap();
+    let path = output.path().join("errors.ndjson");
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source: DispatchSource = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new(),
+    )
+    .into();
+
+    let discovered = DiscoveredAdvisory {
+        url: base.join("missing.txt").unwrap(),
+        ..discovered(&base)
+    };
+    let err = source.load_advisory(discovered.clone()).await.unwrap_err();
+
+    let visitor = ErrorReportVisitor::new(
+        |_: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, DispatchSource>>| async {
+            Ok::<_, Infallible>(())
+        },
+        ErrorReport::create(&path).unwrap(),
+    );
+    visitor
+        .visit_advisory(&(), Err(RetrievalError::Source { err, discovered }))
+        .await
+        .unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let entry: ErrorEntry = serde_json::from_str(content.trim()).unwrap();
+    assert_eq!(entry.url, base.join("missing.txt").unwrap());
+    assert_eq!(entry.status, Some(404));
+}
+
+const ADVISORY: &str = r#"{"new":true}"#;
+
+/// Start a server, serving an advisory and its digests, recording the requested paths
+async fn start_digest_server(
+    with_sha256: bool,
+    with_sha512: bool,
+) -> (Url, Arc<Mutex<Vec<String>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = requests.clone();
+    let server = start_mock_server(move |req| {
+        let path = req.uri().path().to_string();
+        recorded.lock().push(path.clone());
+
+        ok_or_not_found(if path.ends_with(".json") {
+            Some(ADVISORY.to_string())
+        } else if path.ends_with(".sha256") && with_sha256 {
+            // BSD format
+            Some(format!(
+                "SHA256 (test.json) = {}\n",
+                Hex(&Sha256::digest(ADVISORY)).to_lower()
+            ))
+        } else if path.ends_with(".sha384") {
+            Some(Hex(&Sha384::digest(ADVISORY)).to_lower())
+        } else if path.ends_with(".sha512") && with_sha512 {
+            // GNU format
+            Some(format!(
+                "{}  test.json\n",
+                Hex(&Sha512::digest(ADVISORY)).to_lower()
+            ))
+        } else {
+            None
+        })
+    })
+    .await;
+
+    (server.join("advisories/").unwrap(), requests)
+}
+
+async fn load_with_preference(
+    with_sha256: bool,
+    with_sha512: bool,
+    preferred_digest: DigestPreference,
+) -> (csaf_walker::retrieve::RetrievedAdvisory, Vec<String>) {
+    let (base, requests) = start_digest_server(with_sha256, with_sha512).await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new().preferred_digest(preferred_digest),
+    );
+
+    let advisory = source.load_advisory(discovered(&base)).await.unwrap();
+    let requests = requests.lock().clone();
+    (advisory, requests)
+}
+
+#[tokio::test]
+async fn given_preferred_digest_when_available_then_other_is_not_requested() {
+    let (advisory, requests) = load_with_preference(true, true, DigestPreference::Sha256).await;
+
+    assert!(requests.iter().any(|path| path.ends_with(".sha256")));
+    assert!(!requests.iter().any(|path| path.ends_with(".sha512")));
+    assert!(advisory.sha256.unwrap().validate().is_ok());
+    assert!(advisory.sha512.is_none());
+}
+
+#[tokio::test]
+async fn given_preferred_digest_when_missing_then_other_is_used() {
+    let (advisory, requests) = load_with_preference(false, true, DigestPreference::Sha256).await;
+
+    assert!(requests.iter().any(|path| path.ends_with(".sha512")));
+    assert!(advisory.sha256.is_none());
+    assert!(advisory.sha512.unwrap().validate().is_ok());
+}
+
+#[tokio::test]
+async fn given_default_preference_then_sha256_and_sha512_are_requested() {
+    let (advisory, requests) =
+        load_with_preference(true, true, DigestPreference::Sha256AndSha512).await;
+
+    assert!(!requests.iter().any(|path| path.ends_with(".sha384")));
+    assert!(advisory.sha256.unwrap().validate().is_ok());
+    assert!(advisory.sha384.is_none());
+    assert!(advisory.sha512.unwrap().validate().is_ok());
+}
+
+#[tokio::test]
+async fn given_default_preference_when_both_missing_then_sha384_is_used() {
+    let (advisory, requests) =
+        load_with_preference(false, false, DigestPreference::Sha256AndSha512).await;
+
+    assert!(requests.iter().any(|path| path.ends_with(".sha384")));
+    assert!(advisory.sha256.is_none());
+    assert!(advisory.sha384.unwrap().validate().is_ok());
+    assert!(advisory.sha512.is_none());
+}
+
+/// Records the events of retrieving documents
+#[derive(Debug, Default)]
+struct RecordingMetrics(Mutex<Vec<String>>);
+
+impl Metrics for RecordingMetrics {
+    fn bytes_received(&self, url: &Url, bytes: u64) {
+        self.0.lock().push(format!("bytes {} {bytes}", url.path()));
+    }
+
+    fn not_modified(&self, url: &Url) {
+        self.0.lock().push(format!("not modified {}", url.path()));
+    }
+
+    fn digest_verified(&self, url: &Url, algorithm: &str, valid: bool) {
+        self.0
+            .lock()
+            .push(format!("digest {} {algorithm} {valid}", url.path()));
+    }
+}
+
+#[tokio::test]
+async fn given_metrics_then_retrieval_events_are_reported() {
+    let (base, _) = start_digest_server(true, true).await;
+
+    let metrics = Arc::new(RecordingMetrics::default());
+    let fetcher = Fetcher::new(FetcherOptions::new())
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone());
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new().preferred_digest(DigestPreference::Sha256),
+    );
+
+    source.load_advisory(discovered(&base)).await.unwrap();
+
+    assert_eq!(
+        *metrics.0.lock(),
+        vec![
+            format!("bytes /advisories/test.json {}", ADVISORY.len()),
+            "digest /advisories/test.json sha256 true".to_string(),
+        ]
+    );
+}
+
+/// Streams the content to a file, instead of buffering it
+#[derive(Debug)]
+struct FileBodyReader(std::path::PathBuf);
+
+#[async_trait::async_trait(?Send)]
+impl BodyReader for FileBodyReader {
+    async fn read(
+        &self,
+        mut response: reqwest::Response,
+        inspect: &mut dyn for<'a> FnMut(&'a [u8]) -> std::ops::ControlFlow<()>,
+    ) -> Result<bytes::Bytes, BodyReadError> {
+        let path = self.0.join("advisory");
+        let mut file = std::fs::File::create(&path)?;
+
+        while let Some(chunk) = response.chunk().await? {
+            if inspect(&chunk).is_break() {
+                break;
+            }
+            file.write_all(&chunk)?;
+        }
+
+        Ok(std::fs::read(path)?.into())
+    }
+}
+
+#[tokio::test]
+async fn given_body_reader_then_it_reads_the_advisory() {
+    let (base, _) = start_digest_server(true, true).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        Fetcher::new(FetcherOptions::new()).await.unwrap(),
+        HttpOptions::new().preferred_digest(DigestPreference::Sha256),
+    )
+    .with_body_reader(FileBodyReader(dir.path().to_path_buf()));
+
+    let advisory = source.load_advisory(discovered(&base)).await.unwrap();
+
+    assert_eq!(advisory.data, ADVISORY.as_bytes());
+    assert!(dir.path().join("advisory").exists());
+    assert!(advisory.sha256.unwrap().validate().is_ok());
+
+    // failing to handle the content fails the retrieval
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        Fetcher::new(FetcherOptions::new()).await.unwrap(),
+        HttpOptions::new(),
+    )
+    .with_body_reader(FileBodyReader(dir.path().join("missing")));
+
+    let result = source.load_advisory(discovered(&base)).await;
+    assert!(matches!(result, Err(HttpSourceError::Io(_))), "{result:?}");
+}
+
+#[tokio::test]
+async fn given_custom_header_then_it_is_sent_with_all_requests() {
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    let keys = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = keys.clone();
+    let server = start_mock_server(move |req| {
+        recorded.lock().push(
+            req.headers()
+                .get("x-api-key")
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string),
+        );
+        Response::new(match req.uri().path().rsplit_once('.') {
+            Some((_, "sha256")) => Hex(&Sha256::digest(ADVISORY)).to_lower(),
+            Some((_, "sha384")) => Hex(&Sha384::digest(ADVISORY)).to_lower(),
+            Some((_, "sha512")) => Hex(&Sha512::digest(ADVISORY)).to_lower(),
+            _ => ADVISORY.to_string(),
+        })
+    })
+    .await;
+
+    let base = server.join("advisories/").unwrap();
+    let mut key = HeaderValue::from_static("secret");
+    key.set_sensitive(true);
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new().header(HeaderName::from_static("x-api-key"), key),
+    );
+
+    source.load_advisory(discovered(&base)).await.unwrap();
+
+    let keys = keys.lock().clone();
+    // the advisory, its signature, and the SHA-256 and SHA-512 digests
+    assert_eq!(keys.len(), 4);
+    assert!(keys.iter().all(|key| key.as_deref() == Some("secret")));
+}
+
+/// Start a server, serving a ROLIE feed and a changes file, with relative and invalid entries
+async fn start_index_server() -> Url {
+    const ENTRIES: &[(&str, &str)] = &[
+        ("self", "2024/a.json"),
+        ("self", "https://example.com/b.json"),
+        ("self", "http://[invalid/c.json"),
+    ];
+
+    start_mock_server(|req| {
+        ok_or_not_found(match req.uri().path() {
+            "/white/feed.json" => {
+                let entries = ENTRIES
+                    .iter()
+                    .enumerate()
+                    .map(|(id, (rel, href))| {
+                        format!(
+                            r#"{{"link": [{{"rel": "{rel}", "href": "{href}"}}, {{"rel": "signature", "href": "http://[invalid.asc"}}], "format": {{"schema": "", "version": "2.0"}}, "id": "{id}", "published": "2024-01-01T00:00:00Z", "title": "{id}", "updated": "2024-01-01T00:00:00Z", "content": {{"src": "{href}", "type": "application/json"}}}}"#
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                Some(format!(
+                    r#"{{"feed": {{"id": "feed", "title": "Feed", "updated": "2024-01-01T00:00:00Z", "link": [], "entry": [{entries}]}}}}"#
+                ))
+            }
+            "/directory/changes.csv" => Some(
+                ENTRIES
+                    .iter()
+                    .map(|(_, href)| format!("\"{href}\",\"2024-01-01T00:00:00Z\"\n"))
+                    .collect(),
+            ),
+            "/duplicates/changes.csv" => Some(
+                [
+                    r#""a.json","2024-01-01T00:00:00Z""#,
+                    r#""b.json","2024-01-02T00:00:00Z""#,
+                    r#""a.json","2024-01-03T00:00:00Z""#,
+                    r#""./a.json","2023-01-01T00:00:00Z""#,
+                ]
+                .join("\n"),
+            ),
+            _ => None,
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn given_relative_and_invalid_index_entries_then_they_are_resolved_or_ignored() {
+    let base = start_index_server().await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new(),
+    );
+
+    let feed = base.join("white/feed.json").unwrap();
+    let advisories = source
+        .load_index(DistributionContext::Feed {
+            url: feed.clone(),
+            tlp_label: TlpLabel::White,
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        advisories
+            .iter()
+            .map(|advisory| advisory.url.as_str())
+            .collect::<Vec<_>>(),
+        [
+            feed.join("2024/a.json").unwrap().as_str(),
+            "https://example.com/b.json",
+        ]
+    );
+    // invalid companions fall back to the conventional names
+    assert!(
+        advisories
+            .iter()
+            .all(|advisory| advisory.signature.is_none())
+    );
+
+    let directory = base.join("directory/").unwrap();
+    let advisories = source
+        .load_index(DistributionContext::Directory(directory.clone()))
+        .await
+        .unwrap();
+    assert_eq!(
+        advisories
+            .iter()
+            .map(|advisory| advisory.url.as_str())
+            .collect::<Vec<_>>(),
+        [
+            directory.join("2024/a.json").unwrap().as_str(),
+            "https://example.com/b.json",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn given_duplicate_changes_then_they_are_collapsed() {
+    let base = start_index_server().await;
+    let directory = base.join("duplicates/").unwrap();
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher.clone(),
+        HttpOptions::new(),
+    );
+
+    let advisories = source
+        .load_index(DistributionContext::Directory(directory.clone()))
+        .await
+        .unwrap();
+    assert_eq!(
+        advisories
+            .iter()
+            .map(|advisory| (
+                advisory.url.path(),
+                humantime::format_rfc3339(advisory.modified).to_string()
+            ))
+            .collect::<Vec<_>>(),
+        [
+            ("/duplicates/a.json", "2024-01-03T00:00:00Z".to_string()),
+            ("/duplicates/b.json", "2024-01-02T00:00:00Z".to_string()),
+        ]
+    );
+
+    // strict mode fails instead
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new().strict_changes(true),
+    );
+    let result = source
+        .load_index(DistributionContext::Directory(directory))
+        .await;
+    assert!(matches!(
+        result,
+        Err(HttpSourceError::DuplicateEntries { duplicates: 2, .. })
+    ));
+}
+
+/// Start a server, serving an advisory with a declared length, and chunked
+async fn start_large_server() -> Url {
+    let server = start_mock_server(|req| {
+        let response = match req.uri().path() {
+            "/advisories/chunked.json" => {
+                Response::builder().header(hyper::header::TRANSFER_ENCODING, "chunked")
+            }
+            "/advisories/declared.json" => Response::builder(),
+            _ => Response::builder().status(StatusCode::NOT_FOUND),
+        };
+        response.body(ADVISORY.to_string()).unwrap()
+    })
+    .await;
+
+    server.join("advisories/").unwrap()
+}
+
+#[tokio::test]
+async fn given_max_document_size_when_exceeded_then_retrieval_fails() {
+    let base = start_large_server().await;
+    let output = tempfile::tempdir().unwrap();
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = |max_document_size| {
+        HttpSource::new(
+            base.join("provider-metadata.json").unwrap(),
+            fetcher.clone(),
+            HttpOptions::new().max_document_size(max_document_size),
+        )
+    };
+    let discovered = |name| DiscoveredAdvisory {
+        url: base.join(name).unwrap(),
+        ..discovered(&base)
+    };
+
+    for name in ["declared.json", "chunked.json"] {
+        let result = source(4).load_advisory(discovered(name)).await;
+        assert!(
+            matches!(result, Err(HttpSourceError::TooLarge { limit: 4, .. })),
+            "{name}: {result:?}"
+        );
+
+        let result = source(4)
+            .stream_advisory(discovered(name), &output.path().join(name))
+            .await;
+        assert!(
+            matches!(result, Err(HttpSourceError::TooLarge { limit: 4, .. })),
+            "{name}: {result:?}"
+        );
+
+        // within the limit
+        let advisory = source(ADVISORY.len() as u64)
+            .load_advisory(discovered(name))
+            .await
+            .unwrap();
+        assert_eq!(&advisory.data[..], ADVISORY.as_bytes());
+    }
+}
+
+/// Start a server, serving advisories with the content type of their name
+async fn start_content_type_server() -> Url {
+    let server = start_mock_server(|req| match req.uri().path() {
+        "/advisories/html.json" => Response::builder()
+            .header(hyper::header::CONTENT_TYPE, "text/html")
+            .body("<html>Error</html>".to_string())
+            .unwrap(),
+        "/advisories/json.json" => Response::builder()
+            .header(
+                hyper::header::CONTENT_TYPE,
+                "application/json; charset=utf-8",
+            )
+            .body(ADVISORY.to_string())
+            .unwrap(),
+        _ => status(StatusCode::NOT_FOUND),
+    })
+    .await;
+
+    server.join("advisories/").unwrap()
+}
+
+#[tokio::test]
+async fn given_strict_content_type_when_not_json_then_retrieval_fails() {
+    let base = start_content_type_server().await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = |strict| {
+        HttpSource::new(
+            base.join("provider-metadata.json").unwrap(),
+            fetcher.clone(),
+            HttpOptions::new().strict_content_type(strict),
+        )
+    };
+    let discovered = |name| DiscoveredAdvisory {
+        url: base.join(name).unwrap(),
+        ..discovered(&base)
+    };
+
+    // only a warning by default
+    let advisory = source(false)
+        .load_advisory(discovered("html.json"))
+        .await
+        .unwrap();
+    assert_eq!(&advisory.data[..], b"<html>Error</html>");
+
+    let result = source(true).load_advisory(discovered("html.json")).await;
+    assert!(
+        matches!(
+            &result,
+            Err(HttpSourceError::ContentType { url, content_type })
+                if url.path() == "/advisories/html.json" && content_type == "text/html"
+        ),
+        "{result:?}"
+    );
+
+    let advisory = source(true)
+        .load_advisory(discovered("json.json"))
+        .await
+        .unwrap();
+    assert_eq!(&advisory.data[..], ADVISORY.as_bytes());
+}
+
+/// Start a server, serving an advisory gzip encoded for transport, if accepted by the client
+async fn start_encoding_server() -> Url {
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+
+    let server = start_mock_server(|req| {
+        let accepts_gzip = req
+            .headers()
+            .get(hyper::header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("gzip"));
+
+        let response = match req.uri().path() {
+            // digests describe the document, not its transport encoding
+            "/advisories/test.json.sha256" => Response::builder().body(Full::new(Bytes::from(
+                Hex(&Sha256::digest(ADVISORY)).to_lower(),
+            ))),
+            "/advisories/test.json" if accepts_gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(ADVISORY.as_bytes()).unwrap();
+                Response::builder()
+                    .header(hyper::header::CONTENT_ENCODING, "gzip")
+                    .body(Full::new(Bytes::from(encoder.finish().unwrap())))
+            }
+            _ => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Full::default()),
+        };
+        response.unwrap()
+    })
+    .await;
+
+    server.join("advisories/").unwrap()
+}
+
+#[tokio::test]
+async fn given_content_encoding_then_content_is_decoded() {
+    let base = start_encoding_server().await;
+
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        fetcher,
+        HttpOptions::new().preferred_digest(DigestPreference::Sha256),
+    );
+
+    let advisory = source.load_advisory(discovered(&base)).await.unwrap();
+    assert_eq!(&advisory.data[..], ADVISORY.as_bytes());
+    assert!(advisory.sha256.unwrap().validate().is_ok());
+}
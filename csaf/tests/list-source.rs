@@ -0,0 +1,50 @@
+use csaf_walker::{
+    discover::DiscoverConfig,
+    source::{DispatchSource, HttpOptions, new_sources_with_options},
+};
+use walker_common::fetcher::FetcherOptions;
+
+async fn sources(list: &str) -> anyhow::Result<Vec<DispatchSource>> {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), list).unwrap();
+
+    new_sources_with_options(
+        DiscoverConfig::from(format!("@{}", file.path().display()).as_str()),
+        FetcherOptions::new(),
+        HttpOptions::new(),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn source_per_entry() {
+    let sources =
+        sources("# providers\na.example.com\n\nhttps://b.example.com/provider-metadata.json\n")
+            .await
+            .unwrap();
+
+    assert_eq!(sources.len(), 2);
+    assert!(
+        sources
+            .iter()
+            .all(|source| matches!(source, DispatchSource::Http(_)))
+    );
+}
+
+#[tokio::test]
+async fn empty_list() {
+    assert!(sources("# no providers\n\n").await.is_err());
+}
+
+#[tokio::test]
+async fn single_source() {
+    let sources = new_sources_with_options(
+        DiscoverConfig::from("a.example.com"),
+        FetcherOptions::new(),
+        HttpOptions::new(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(sources.len(), 1);
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "object-store")]
+
+use bytes::Bytes;
+use csaf_walker::{
+    discover::{DiscoveredAdvisory, DiscoveredContext, DistributionContext},
+    model::metadata::{
+        Distribution, MetadataVersion, ProviderMetadata, Publisher, PublisherCategory, Role,
+    },
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::FileSource,
+    visitors::object::ObjectStoreVisitor,
+};
+use futures::TryStreamExt;
+use object_store::{ObjectStore, ObjectStoreExt, memory::InMemory, path::Path};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use sha2::{Digest, Sha256};
+use std::{sync::Arc, time::SystemTime};
+use url::Url;
+use walker_common::retrieve::{RetrievalMetadata, RetrievedDigest};
+
+const ADVISORY: &[u8] = br#"{"document":{}}"#;
+
+fn metadata() -> ProviderMetadata {
+    ProviderMetadata {
+        canonical_url: Url::parse("https://example.com/.well-known/csaf/provider-metadata.json")
+            .unwrap(),
+        distributions: vec![Distribution {
+            directory_url: Some(Url::parse("https://example.com/advisories/").unwrap()),
+            rolie: None,
+            publisher: None,
+        }],
+        last_updated: chrono::Utc::now(),
+        list_on_csaf_aggregators: false,
+        metadata_version: MetadataVersion::V2_0,
+        mirror_on_csaf_aggregators: false,
+        public_openpgp_keys: vec![],
+        publisher: Publisher {
+            category: PublisherCategory::Vendor,
+            contact_details: None,
+            issuing_authority: None,
+            name: "Example Corp".to_string(),
+            namespace: "https://example.com".to_string(),
+        },
+        role: Role::Provider,
+    }
+}
+
+fn advisory() -> RetrievedAdvisory {
+    let base = Url::parse("https://example.com/advisories/").unwrap();
+
+    RetrievedAdvisory {
+        discovered: DiscoveredAdvisory {
+            context: Arc::new(DistributionContext::Directory(base.clone())),
+            url: base.join("2024/advisory.json").unwrap(),
+            digest: None,
+            signature: None,
+            modified: SystemTime::now(),
+        },
+        data: Bytes::from_static(ADVISORY),
+        signature: Some("signature".to_string()),
+        sha256: Some(RetrievedDigest {
+            expected: "expected".to_string(),
+            actual: Sha256::digest(ADVISORY),
+        }),
+        sha384: None,
+        sha512: None,
+        metadata: RetrievalMetadata {
+            last_modification: None,
+            etag: None,
+            not_modified: false,
+            transformed: false,
+        },
+    }
+}
+
+#[tokio::test]
+async fn stores_with_disk_layout() {
+    let store = Arc::new(InMemory::new());
+    let cut = ObjectStoreVisitor::new(store.clone()).prefix("mirror");
+
+    let metadata = metadata();
+    let keys = vec![];
+    let discovered = DiscoveredContext {
+        metadata: &metadata,
+        memory_budget: None,
+        transfer: None,
+    };
+    RetrievedVisitor::<FileSource>::visit_context(
+        &cut,
+        &RetrievalContext {
+            discovered: &discovered,
+            keys: &keys,
+        },
+    )
+    .await
+    .unwrap();
+
+    RetrievedVisitor::<FileSource>::visit_advisory(&cut, &(), Ok(advisory()))
+        .await
+        .unwrap();
+
+    let mut keys = store
+        .list(None)
+        .map_ok(|meta| meta.location.to_string())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    keys.sort();
+
+    let dist = utf8_percent_encode("https://example.com/advisories/", NON_ALPHANUMERIC);
+    assert_eq!(
+        keys,
+        [
+            format!("mirror/{dist}/2024/advisory.json"),
+            format!("mirror/{dist}/2024/advisory.json.asc"),
+            format!("mirror/{dist}/2024/advisory.json.sha256"),
+            "mirror/metadata/provider-metadata.json".to_string(),
+        ]
+    );
+
+    let data = store
+        .get(&Path::parse(format!("mirror/{dist}/2024/advisory.json")).unwrap())
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(&data[..], ADVISORY);
+}
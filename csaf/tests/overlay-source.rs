@@ -0,0 +1,159 @@
+mod common;
+
+use common::{base, ok_or_not_found, start_mock_server};
+use csaf_walker::{
+    discover::DistributionContext,
+    model::store::distribution_base,
+    source::{FileOptions, FileSource, HttpOptions, HttpSource, OverlaySource, Source},
+};
+use parking_lot::Mutex;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use url::Url;
+use walker_common::fetcher::{Fetcher, FetcherOptions};
+
+/// Start a server, serving a provider with two advisories, recording the requested paths.
+async fn start_server(requests: Arc<Mutex<Vec<String>>>) -> Url {
+    let server = start_mock_server(move |req| {
+        requests.lock().push(req.uri().path().to_string());
+
+        let base = base(&req);
+        ok_or_not_found(match req.uri().path() {
+            "/provider-metadata.json" => Some(
+                serde_json::json!({
+                    "canonical_url": format!("{base}/provider-metadata.json"),
+                    "distributions": [{ "directory_url": format!("{base}/advisories/") }],
+                    "last_updated": "2024-01-01T00:00:00Z",
+                    "metadata_version": "2.0",
+                    "publisher": {
+                        "category": "vendor",
+                        "name": "Example",
+                        "namespace": "https://example.com"
+                    }
+                })
+                .to_string(),
+            ),
+            "/advisories/changes.csv" => Some(
+                "\"a.json\",\"2024-01-01T00:00:00Z\"\n\"b.json\",\"2024-01-01T00:00:00Z\"\n"
+                    .to_string(),
+            ),
+            "/advisories/a.json" | "/advisories/b.json" => Some(r#"{"remote":true}"#.to_string()),
+            _ => None,
+        })
+    })
+    .await;
+
+    server.join("provider-metadata.json").unwrap()
+}
+
+#[tokio::test]
+async fn given_stored_advisories_then_only_missing_ones_are_retrieved() {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let metadata = start_server(requests.clone()).await;
+    let distribution = metadata.join("advisories/").unwrap();
+
+    let store = tempfile::tempdir().unwrap();
+    let base = distribution_base(store.path(), distribution.as_str());
+    std::fs::create_dir_all(&base).unwrap();
+    std::fs::write(base.join("a.json"), r#"{"local":true}"#).unwrap();
+    std::fs::write(base.join("c.json"), r#"{"local":true}"#).unwrap();
+
+    let source = OverlaySource::new(
+        FileSource::new(store.path(), FileOptions::new()).unwrap(),
+        HttpSource::new(
+            metadata,
+            Fetcher::new(FetcherOptions::new()).await.unwrap(),
+            HttpOptions::new(),
+        ),
+    );
+
+    let metadata = source.load_metadata().await.unwrap();
+    assert_eq!(
+        metadata.distributions[0].directory_url.as_ref(),
+        Some(&distribution)
+    );
+
+    let advisories = source
+        .load_index(DistributionContext::Directory(distribution.clone()))
+        .await
+        .unwrap();
+    let urls = advisories
+        .iter()
+        .map(|advisory| advisory.url.path())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            "/advisories/a.json",
+            "/advisories/b.json",
+            "/advisories/c.json"
+        ]
+    );
+
+    let mut data = vec![];
+    for advisory in advisories {
+        let retrieved = source.load_advisory(advisory.clone()).await.unwrap();
+        assert_eq!(retrieved.discovered.url, advisory.url);
+        data.push(String::from_utf8(retrieved.data.to_vec()).unwrap());
+    }
+    assert_eq!(
+        data,
+        [
+            r#"{"local":true}"#,
+            r#"{"remote":true}"#,
+            r#"{"local":true}"#
+        ]
+    );
+
+    let requests = requests.lock();
+    assert!(requests.iter().any(|path| path == "/advisories/b.json"));
+    assert!(!requests.iter().any(|path| path == "/advisories/a.json"));
+    assert!(!requests.iter().any(|path| path == "/advisories/c.json"));
+}
+
+#[tokio::test]
+async fn given_outdated_stored_advisory_then_it_is_retrieved() {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let metadata = start_server(requests.clone()).await;
+    let distribution = metadata.join("advisories/").unwrap();
+
+    let store = tempfile::tempdir().unwrap();
+    let base = distribution_base(store.path(), distribution.as_str());
+    std::fs::create_dir_all(&base).unwrap();
+    std::fs::write(base.join("a.json"), r#"{"local":true}"#).unwrap();
+    std::fs::write(base.join("b.json"), r#"{"local":true}"#).unwrap();
+    // older than the remote modification of 2024-01-01
+    std::fs::File::options()
+        .write(true)
+        .open(base.join("b.json"))
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_672_531_200))
+        .unwrap();
+
+    let source = OverlaySource::new(
+        FileSource::new(store.path(), FileOptions::new()).unwrap(),
+        HttpSource::new(
+            metadata,
+            Fetcher::new(FetcherOptions::new()).await.unwrap(),
+            HttpOptions::new(),
+        ),
+    );
+
+    let advisories = source
+        .load_index(DistributionContext::Directory(distribution))
+        .await
+        .unwrap();
+
+    let mut data = vec![];
+    for advisory in advisories {
+        let retrieved = source.load_advisory(advisory).await.unwrap();
+        data.push(String::from_utf8(retrieved.data.to_vec()).unwrap());
+    }
+    assert_eq!(data, [r#"{"local":true}"#, r#"{"remote":true}"#]);
+
+    let requests = requests.lock();
+    assert!(requests.iter().any(|path| path == "/advisories/b.json"));
+    assert!(!requests.iter().any(|path| path == "/advisories/a.json"));
+}
@@ -0,0 +1,59 @@
+use bytes::Bytes;
+use csaf_walker::{
+    discover::{DiscoveredAdvisory, DistributionContext},
+    retrieve::{ParseError, RetrievedAdvisory},
+};
+use std::{sync::Arc, time::SystemTime};
+use url::Url;
+use walker_common::{compression::Compression, retrieve::RetrievalMetadata};
+
+const ADVISORY: &[u8] = include_bytes!("../test-data/rhsa-2023_3408.json");
+
+fn retrieved(name: &str, data: impl Into<Bytes>) -> RetrievedAdvisory {
+    let base = Url::parse("https://example.com/advisories/").unwrap();
+    RetrievedAdvisory {
+        discovered: DiscoveredAdvisory {
+            context: Arc::new(DistributionContext::Directory(base.clone())),
+            url: base.join(name).unwrap(),
+            digest: None,
+            signature: None,
+            modified: SystemTime::now(),
+        },
+        data: data.into(),
+        signature: None,
+        sha256: None,
+        sha384: None,
+        sha512: None,
+        metadata: RetrievalMetadata {
+            last_modification: None,
+            etag: None,
+            not_modified: false,
+            transformed: false,
+        },
+    }
+}
+
+#[test]
+fn parse_plain() {
+    let csaf = retrieved("rhsa-2023_3408.json", ADVISORY).parse().unwrap();
+    assert_eq!(csaf.document.tracking.id, "RHSA-2023:3408");
+}
+
+#[test]
+fn parse_compressed() {
+    let data = Compression::Gzip.compress_opt(ADVISORY).unwrap().unwrap();
+    let csaf = retrieved("rhsa-2023_3408.json.gz", data).parse().unwrap();
+    assert_eq!(csaf.document.tracking.id, "RHSA-2023:3408");
+}
+
+#[test]
+fn parse_invalid() {
+    let result = retrieved("invalid.json", r#"{"document":{}}"#).parse();
+    assert!(matches!(result, Err(ParseError::Parse(_))));
+}
+
+#[test]
+fn decompress_invalid() {
+    let result = retrieved("invalid.json.gz", "not gzip").parse();
+    assert!(matches!(result, Err(ParseError::Decompress(_))));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{ok_or_not_found, start_mock_server};
+use csaf_walker::rolie::RolieSource;
+use url::Url;
+use walker_common::fetcher::{Fetcher, FetcherOptions};
+
+fn entry(id: &str) -> String {
+    format!(
+        r#"{{
+  "link": [{{"rel": "self", "href": "https://example.com/{id}.json"}}],
+  "format": {{"schema": "https://docs.oasis-open.org/csaf/csaf/v2.0/csaf_json_schema.json", "version": "2.0"}},
+  "id": "{id}",
+  "published": "2024-01-01T00:00:00Z",
+  "title": "{id}",
+  "updated": "2024-01-01T00:00:00Z",
+  "content": {{"src": "https://example.com/{id}.json", "type": "application/json"}}
+}}"#
+    )
+}
+
+fn page(links: &[(&str, &str)], entries: &[&str]) -> String {
+    let links = links
+        .iter()
+        .map(|(rel, href)| format!(r#"{{"rel": "{rel}", "href": "{href}"}}"#))
+        .collect::<Vec<_>>()
+        .join(",");
+    let entries = entries
+        .iter()
+        .map(|id| entry(id))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        r#"{{"feed": {{"id": "feed", "title": "Feed", "updated": "2024-01-01T00:00:00Z", "link": [{links}], "entry": [{entries}]}}}}"#
+    )
+}
+
+/// Start a server, serving a feed of three pages, linking back and forth
+async fn start_feed_server() -> Url {
+    let server = start_mock_server(|req| {
+        ok_or_not_found(match req.uri().path() {
+            "/feed.json" => Some(page(&[("next", "feed-2.json")], &["a", "b"])),
+            "/feed-2.json" => Some(page(
+                &[("previous", "feed.json"), ("next", "feed-3.json")],
+                &["b", "c"],
+            )),
+            "/feed-3.json" => Some(page(&[("previous", "feed-2.json")], &["d"])),
+            _ => None,
+        })
+    })
+    .await;
+
+    server.join("feed.json").unwrap()
+}
+
+async fn retrieve(max_pages: usize) -> Vec<String> {
+    let url = start_feed_server().await;
+    let fetcher = Fetcher::new(FetcherOptions::new()).await.unwrap();
+
+    RolieSource::retrieve(&fetcher, url, max_pages)
+        .await
+        .unwrap()
+        .files
+        .into_iter()
+        .map(|file| file.file)
+        .collect()
+}
+
+#[tokio::test]
+async fn all_pages() {
+    assert_eq!(
+        retrieve(10).await,
+        [
+            "https://example.com/a.json",
+            "https://example.com/b.json",
+            "https://example.com/c.json",
+            "https://example.com/d.json",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn max_pages() {
+    assert_eq!(
+        retrieve(2).await,
+        [
+            "https://example.com/a.json",
+            "https://example.com/b.json",
+            "https://example.com/c.json",
+        ]
+    );
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use common::{ok_or_not_found, start_mock_server};
+use csaf_walker::{
+    discover::{DiscoveredAdvisory, DiscoveredVisitor, DistributionContext},
+    model::store::distribution_base,
+    source::{HttpOptions, HttpSource},
+    visitors::skip::SkipExistingVisitor,
+};
+use flate2::write::GzEncoder;
+use sha2::{Digest, Sha256, Sha512};
+use std::{
+    convert::Infallible,
+    io::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::SystemTime,
+};
+use tempfile::TempDir;
+use url::Url;
+use walker_common::{
+    fetcher::{Fetcher, FetcherOptions},
+    utils::hex::Hex,
+};
+
+const REMOTE: &str = r#"{"document":{"title":"updated"}}"#;
+
+/// Start a server, serving the digest of the remote version of the advisory
+async fn start_server() -> Url {
+    let server = start_mock_server(|req| {
+        ok_or_not_found(match req.uri().path() {
+            "/advisories/2024/advisory.json.sha256" => Some(format!(
+                "{}  advisory.json",
+                Hex(&Sha256::digest(REMOTE)).to_lower()
+            )),
+            "/advisories/2024/advisory.json.sha512" => Some(format!(
+                "{}  advisory.json",
+                Hex(&Sha512::digest(REMOTE)).to_lower()
+            )),
+            _ => None,
+        })
+    })
+    .await;
+
+    server.join("advisories/").unwrap()
+}
+
+/// Run the visitor for an advisory, which is stored with the provided content.
+///
+/// Returns `true` if the advisory was passed on, `false` if it was skipped.
+async fn visit(stored: &str, compare_digests: bool) -> bool {
+    visit_stored(stored, compare_digests, false).await
+}
+
+/// Run the visitor for an advisory, which is stored with the provided content, optionally
+/// compressed using gzip.
+async fn visit_stored(stored: &str, compare_digests: bool, compressed: bool) -> bool {
+    visit_referenced(stored, compare_digests, compressed, None).await
+}
+
+/// Run the visitor for an advisory, which references a digest with the provided extension, like
+/// a ROLIE feed does.
+async fn visit_referenced(
+    stored: &str,
+    compare_digests: bool,
+    compressed: bool,
+    digest: Option<&str>,
+) -> bool {
+    let base = start_server().await;
+    let output = TempDir::new().unwrap();
+
+    let path = distribution_base(output.path(), base.as_str()).join("2024/advisory.json");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    match compressed {
+        true => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(stored.as_bytes()).unwrap();
+            std::fs::write(path.with_extension("json.gz"), encoder.finish().unwrap()).unwrap();
+        }
+        false => std::fs::write(&path, stored).unwrap(),
+    }
+
+    let source = HttpSource::new(
+        base.join("provider-metadata.json").unwrap(),
+        Fetcher::new(FetcherOptions::new()).await.unwrap(),
+        HttpOptions::new(),
+    );
+
+    let visited = Arc::new(AtomicBool::new(false));
+    let recorded = visited.clone();
+    let visitor = SkipExistingVisitor {
+        visitor: move |_: DiscoveredAdvisory| {
+            recorded.store(true, Ordering::Relaxed);
+            async { Ok::<_, Infallible>(()) }
+        },
+        output: output.path().to_path_buf(),
+        since: None,
+        partition_tlp: false,
+    }
+    .with_digests(compare_digests.then_some(source));
+
+    visitor
+        .visit_advisory(
+            &(),
+            DiscoveredAdvisory {
+                context: Arc::new(DistributionContext::Directory(base.clone())),
+                url: base.join("2024/advisory.json").unwrap(),
+                digest: digest.map(|extension| {
+                    base.join(&format!("2024/advisory.json.{extension}"))
+                        .unwrap()
+                }),
+                signature: None,
+                // older than the stored file
+                modified: SystemTime::UNIX_EPOCH,
+            },
+        )
+        .await
+        .unwrap();
+
+    visited.load(Ordering::Relaxed)
+}
+
+#[tokio::test]
+async fn skip_unchanged() {
+    assert!(!visit(REMOTE, true).await);
+}
+
+#[tokio::test]
+async fn fetch_changed_digest() {
+    assert!(visit(r#"{"document":{"title":"original"}}"#, true).await);
+}
+
+#[tokio::test]
+async fn skip_by_timestamp_only() {
+    assert!(!visit(r#"{"document":{"title":"original"}}"#, false).await);
+}
+
+#[tokio::test]
+async fn skip_unchanged_compressed() {
+    assert!(!visit_stored(REMOTE, true, true).await);
+}
+
+#[tokio::test]
+async fn fetch_changed_digest_compressed() {
+    assert!(visit_stored(r#"{"document":{"title":"original"}}"#, true, true).await);
+}
+
+#[tokio::test]
+async fn skip_by_timestamp_only_compressed() {
+    assert!(!visit_stored(r#"{"document":{"title":"original"}}"#, false, true).await);
+}
+
+#[tokio::test]
+async fn skip_unchanged_referenced_digest() {
+    assert!(!visit_referenced(REMOTE, true, false, Some("sha512")).await);
+}
+
+#[tokio::test]
+async fn fetch_changed_referenced_digest() {
+    assert!(
+        visit_referenced(
+            r#"{"document":{"title":"original"}}"#,
+            true,
+            true,
+            Some("sha512")
+        )
+        .await
+    );
+}
@@ -1,21 +1,34 @@
 use bytes::Bytes;
+use csaf_walker::visitors::{
+    diff::{Change, DiffReport, DiffSummary, DiffVisitor},
+    store::StoreRetrievedError,
+};
 use csaf_walker::{
-    discover::{DiscoveredAdvisory, DistributionContext},
-    model::metadata::{
-        Distribution, MetadataVersion, ProviderMetadata, Publisher, PublisherCategory, Role,
+    discover::{DiscoveredAdvisory, DiscoveredContext, DistributionContext},
+    model::{
+        metadata::{
+            Distribution, Feed, MetadataVersion, ProviderMetadata, Publisher, PublisherCategory,
+            Role, Rolie, TlpLabel,
+        },
+        store::StoreIndex,
     },
-    retrieve::{RetrievedAdvisory, RetrievedVisitor},
-    source::{FileSource, HttpSource, HttpSourceError},
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::{FileSource, HttpSource, HttpSourceError, Source},
     visitors::store::StoreVisitor,
 };
 use digest::Output;
 use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use std::{fs, rc::Rc, sync::Arc, time::Duration};
 use tempfile::TempDir;
 use time::OffsetDateTime;
 use url::Url;
-use walker_common::retrieve::{RetrievalError, RetrievalMetadata, RetrievedDigest};
+use walker_common::{
+    compression::Compression,
+    retrieve::{RetrievalError, RetrievalMetadata, RetrievedDigest},
+    store::StoreError,
+    utils::hex::Hex,
+};
 
 /// Helper function to create a basic ProviderMetadata for testing
 fn create_test_metadata() -> ProviderMetadata {
@@ -25,6 +38,7 @@ fn create_test_metadata() -> ProviderMetadata {
         distributions: vec![Distribution {
             directory_url: Some(Url::parse("https://example.com/advisories/").unwrap()),
             rolie: None,
+            publisher: None,
         }],
         last_updated: chrono::Utc::now(),
         list_on_csaf_aggregators: false,
@@ -98,10 +112,13 @@ fn create_test_retrieved_advisory(discovered: DiscoveredAdvisory) -> RetrievedAd
                 0x78, 0x52, 0xb8, 0x55,
             ]),
         }),
+        sha384: None,
         sha512: None,
         metadata: RetrievalMetadata {
             last_modification: Some(OffsetDateTime::now_utc()),
             etag: Some("test-etag".to_string()),
+            not_modified: false,
+            transformed: false,
         },
     }
 }
@@ -154,6 +171,229 @@ async fn given_advisory_could_be_retrieved_when_it_should_be_stored_it_is_saved_
         "Advisory file should be created at {:?}",
         expected_file
     );
+
+    // The temporary file must have been moved into place
+    let leftovers: Vec<_> = fs::read_dir(&distribution_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "No temporary files should remain: {leftovers:?}"
+    );
+}
+
+#[tokio::test]
+async fn given_concurrent_stores_of_same_advisory_when_visiting_then_file_is_complete() {
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path());
+
+    let context = Rc::new(create_test_metadata());
+    let visit = || {
+        let retrieved = create_test_retrieved_advisory(create_test_discovered_advisory());
+        let result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, FileSource>> =
+            Ok(retrieved);
+        cut.visit_advisory(&context, result)
+    };
+
+    let (first, second) = tokio::join!(visit(), visit());
+    first.unwrap();
+    second.unwrap();
+
+    let distribution_dir = temp_dir
+        .path()
+        .join(utf8_percent_encode("https://example.com/advisories/", NON_ALPHANUMERIC).to_string());
+    let expected = create_test_retrieved_advisory(create_test_discovered_advisory()).data;
+    assert_eq!(
+        fs::read(distribution_dir.join("test-advisory-2024-001.json")).unwrap(),
+        expected
+    );
+
+    let leftovers: Vec<_> = fs::read_dir(&distribution_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "No temporary files should remain: {leftovers:?}"
+    );
+}
+
+#[tokio::test]
+async fn given_write_index_when_advisory_was_stored_then_index_lists_it() {
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path()).enable_indexer(true);
+    let indexer = cut.indexer();
+
+    let context = Rc::new(create_test_metadata());
+    let retrieved = create_test_retrieved_advisory(create_test_discovered_advisory());
+    let expected_sha256 = Hex(&Sha256::digest(&retrieved.data)).to_lower();
+    let retrieved_advisory_result: Result<
+        RetrievedAdvisory,
+        RetrievalError<DiscoveredAdvisory, FileSource>,
+    > = Ok(retrieved);
+
+    cut.visit_advisory(&context, retrieved_advisory_result)
+        .await
+        .unwrap();
+
+    let file = indexer
+        .write()
+        .await
+        .unwrap()
+        .expect("index must be written");
+    assert_eq!(file, temp_dir.path().join("metadata").join("index.json"));
+
+    let index: StoreIndex = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+    let encoded_dir = utf8_percent_encode("https://example.com/advisories/", NON_ALPHANUMERIC);
+    assert_eq!(index.advisories.len(), 1);
+    assert_eq!(
+        index.advisories[0].path,
+        format!("{encoded_dir}/test-advisory-2024-001.json")
+    );
+    assert_eq!(index.advisories[0].sha256, expected_sha256);
+    assert!(index.advisories[0].signature);
+    assert!(index.advisories[0].last_modification.is_some());
+}
+
+#[tokio::test]
+async fn given_compression_when_advisory_is_stored_then_it_can_be_read_back() {
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path()).compress(Compression::Gzip);
+
+    let context = Rc::new(create_test_metadata());
+    let retrieved = create_test_retrieved_advisory(create_test_discovered_advisory());
+    let expected = retrieved.data.clone();
+    let retrieved_advisory_result: Result<
+        RetrievedAdvisory,
+        RetrievalError<DiscoveredAdvisory, FileSource>,
+    > = Ok(retrieved);
+
+    cut.visit_advisory(&context, retrieved_advisory_result)
+        .await
+        .unwrap();
+
+    let distribution_dir = temp_dir
+        .path()
+        .join(utf8_percent_encode("https://example.com/advisories/", NON_ALPHANUMERIC).to_string());
+    let file = distribution_dir.join("test-advisory-2024-001.json");
+    assert!(!file.exists());
+    assert!(file.with_added_extension("gz").exists());
+    assert!(file.with_added_extension("sha256").exists());
+    assert!(file.with_added_extension("asc").exists());
+
+    let source = FileSource::new(temp_dir.path(), None).unwrap();
+    let advisory = source
+        .load_advisory(DiscoveredAdvisory {
+            context: Arc::new(DistributionContext::Directory(
+                Url::from_directory_path(&distribution_dir).unwrap(),
+            )),
+            url: Url::from_file_path(&file).unwrap(),
+            digest: None,
+            signature: None,
+            modified: std::time::SystemTime::now(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(advisory.data, expected);
+    assert!(advisory.sha256.is_some());
+    assert_eq!(advisory.signature.as_deref(), Some("test-signature"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn given_dedup_when_identical_advisory_is_stored_twice_then_it_is_linked() {
+    use std::os::unix::fs::MetadataExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path()).dedup(true);
+    let context = Rc::new(create_test_metadata());
+
+    let feed = Url::parse("https://example.com/feed/").unwrap();
+    let mut files = vec![];
+    for base in [Url::parse("https://example.com/advisories/").unwrap(), feed] {
+        let mut discovered = create_test_discovered_advisory();
+        discovered.url = base.join("test-advisory-2024-001.json").unwrap();
+        discovered.context = Arc::new(DistributionContext::Directory(base.clone()));
+
+        let retrieved_advisory_result: Result<
+            RetrievedAdvisory,
+            RetrievalError<DiscoveredAdvisory, FileSource>,
+        > = Ok(create_test_retrieved_advisory(discovered));
+        cut.visit_advisory(&context, retrieved_advisory_result)
+            .await
+            .unwrap();
+
+        files.push(
+            temp_dir
+                .path()
+                .join(utf8_percent_encode(base.as_str(), NON_ALPHANUMERIC).to_string())
+                .join("test-advisory-2024-001.json"),
+        );
+    }
+
+    for ext in [None, Some("sha256"), Some("asc")] {
+        let [first, second] = [&files[0], &files[1]].map(|file| match ext {
+            Some(ext) => file.with_added_extension(ext),
+            None => file.clone(),
+        });
+        let first = fs::metadata(first).unwrap();
+        let second = fs::metadata(second).unwrap();
+        assert_eq!(first.ino(), second.ino(), "{ext:?} must be linked");
+        assert_eq!(first.nlink(), 2);
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn given_no_dedup_when_identical_advisory_is_stored_twice_then_it_is_copied() {
+    use std::os::unix::fs::MetadataExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path());
+    let context = Rc::new(create_test_metadata());
+
+    let mut files = vec![];
+    for base in [
+        "https://example.com/advisories/",
+        "https://example.com/feed/",
+    ] {
+        let base = Url::parse(base).unwrap();
+        let mut discovered = create_test_discovered_advisory();
+        discovered.url = base.join("test-advisory-2024-001.json").unwrap();
+        discovered.context = Arc::new(DistributionContext::Directory(base.clone()));
+
+        let retrieved_advisory_result: Result<
+            RetrievedAdvisory,
+            RetrievalError<DiscoveredAdvisory, FileSource>,
+        > = Ok(create_test_retrieved_advisory(discovered));
+        cut.visit_advisory(&context, retrieved_advisory_result)
+            .await
+            .unwrap();
+
+        files.push(
+            temp_dir
+                .path()
+                .join(utf8_percent_encode(base.as_str(), NON_ALPHANUMERIC).to_string())
+                .join("test-advisory-2024-001.json"),
+        );
+    }
+
+    let first = fs::metadata(&files[0]).unwrap();
+    let second = fs::metadata(&files[1]).unwrap();
+    assert_ne!(first.ino(), second.ino());
+}
+
+#[tokio::test]
+async fn given_write_index_is_disabled_then_no_index_is_written() {
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path());
+
+    assert_eq!(cut.indexer().write().await.unwrap(), None);
+    assert!(!temp_dir.path().join("metadata").join("index.json").exists());
 }
 
 #[tokio::test]
@@ -355,3 +595,283 @@ async fn given_advisory_retrieval_fails_with_non_client_error_in_http_source_whe
         Ok(_) => panic!("Expected error but got Ok"),
     }
 }
+
+#[tokio::test]
+async fn given_advisories_differing_only_by_case_when_checking_case_collisions_then_error_is_returned()
+ {
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path()).check_case_collisions(true);
+
+    let context = Rc::new(create_test_metadata());
+
+    let lower = create_test_discovered_advisory();
+    let mut upper = create_test_discovered_advisory();
+    upper.url = Url::parse("https://example.com/advisories/TEST-ADVISORY-2024-001.json").unwrap();
+
+    let result: Result<(), StoreRetrievedError<FileSource>> = cut
+        .visit_advisory(&context, Ok(create_test_retrieved_advisory(lower)))
+        .await;
+    assert!(
+        result.is_ok(),
+        "first advisory should be stored: {result:?}"
+    );
+
+    let result: Result<(), StoreRetrievedError<FileSource>> = cut
+        .visit_advisory(&context, Ok(create_test_retrieved_advisory(upper)))
+        .await;
+
+    match result {
+        Err(StoreRetrievedError::Store(StoreError::CaseCollision { .. })) => {}
+        other => panic!("expected case collision, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn given_prune_is_enabled_when_walk_is_complete_then_stale_files_are_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path()).prune(true);
+    let pruner = cut.pruner();
+
+    let metadata = create_test_metadata();
+    let keys = vec![];
+    let discovered_context = DiscoveredContext {
+        metadata: &metadata,
+        memory_budget: None,
+        transfer: None,
+    };
+    let retrieval_context = RetrievalContext {
+        discovered: &discovered_context,
+        keys: &keys,
+    };
+
+    let context = RetrievedVisitor::<FileSource>::visit_context(&cut, &retrieval_context)
+        .await
+        .unwrap();
+
+    let encoded_dir =
+        utf8_percent_encode("https://example.com/advisories/", NON_ALPHANUMERIC).to_string();
+    let distribution_dir = temp_dir.path().join(encoded_dir);
+    let stale = distribution_dir.join("2023").join("stale.json");
+    fs::create_dir_all(stale.parent().unwrap()).unwrap();
+    fs::write(&stale, "{}").unwrap();
+    fs::write(stale.with_added_extension("sha256"), "").unwrap();
+
+    let result: Result<(), StoreRetrievedError<FileSource>> = cut
+        .visit_advisory(
+            &context,
+            Ok(create_test_retrieved_advisory(
+                create_test_discovered_advisory(),
+            )),
+        )
+        .await;
+    assert!(result.is_ok(), "advisory should be stored: {result:?}");
+
+    let mut removed = pruner.prune().unwrap();
+    removed.sort();
+
+    assert_eq!(
+        removed,
+        vec![stale.clone(), stale.with_added_extension("sha256")]
+    );
+    assert!(
+        distribution_dir
+            .join("test-advisory-2024-001.json")
+            .exists()
+    );
+    assert!(
+        distribution_dir
+            .join("test-advisory-2024-001.json.sha256")
+            .exists()
+    );
+    assert!(
+        distribution_dir
+            .join("test-advisory-2024-001.json.asc")
+            .exists()
+    );
+    assert!(
+        temp_dir
+            .path()
+            .join("metadata")
+            .join("provider-metadata.json")
+            .exists()
+    );
+}
+
+#[tokio::test]
+async fn given_partition_tlp_when_storing_feeds_then_they_are_separated_by_label() {
+    let temp_dir = TempDir::new().unwrap();
+    let cut: StoreVisitor = StoreVisitor::new(temp_dir.path()).partition_tlp(true);
+
+    let feed = |name: &str, tlp_label| Feed {
+        summary: None,
+        tlp_label,
+        url: Url::parse(&format!("https://example.com/feeds/{name}.json")).unwrap(),
+    };
+    let mut metadata = create_test_metadata();
+    metadata.distributions.push(Distribution {
+        directory_url: None,
+        rolie: Some(Rolie {
+            categories: vec![],
+            feeds: vec![
+                feed("white", TlpLabel::White),
+                feed("amber", TlpLabel::Amber),
+            ],
+            services: vec![],
+        }),
+        publisher: None,
+    });
+
+    let keys = vec![];
+    let discovered_context = DiscoveredContext {
+        metadata: &metadata,
+        memory_budget: None,
+        transfer: None,
+    };
+    let retrieval_context = RetrievalContext {
+        discovered: &discovered_context,
+        keys: &keys,
+    };
+
+    let context = RetrievedVisitor::<FileSource>::visit_context(&cut, &retrieval_context)
+        .await
+        .unwrap();
+
+    let feed_url = Url::parse("https://example.com/feeds/amber.json").unwrap();
+    let discovered = DiscoveredAdvisory {
+        context: Arc::new(DistributionContext::Feed {
+            url: feed_url.clone(),
+            tlp_label: TlpLabel::Amber,
+        }),
+        url: Url::parse("https://example.com/feeds/test-advisory-2024-001.json").unwrap(),
+        digest: None,
+        signature: None,
+        modified: std::time::SystemTime::now(),
+    };
+    let result: Result<(), StoreRetrievedError<FileSource>> = cut
+        .visit_advisory(&context, Ok(create_test_retrieved_advisory(discovered)))
+        .await;
+    assert!(result.is_ok(), "advisory should be stored: {result:?}");
+
+    let encoded = |url: &str| utf8_percent_encode(url, NON_ALPHANUMERIC).to_string();
+    assert!(
+        temp_dir
+            .path()
+            .join("tlp-amber")
+            .join(encoded(feed_url.as_str()))
+            .join("test-advisory-2024-001.json")
+            .exists()
+    );
+    assert!(
+        temp_dir
+            .path()
+            .join("tlp-unlabeled")
+            .join(encoded("https://example.com/advisories/"))
+            .is_dir()
+    );
+    assert!(!temp_dir.path().join("metadata").exists());
+
+    let load = |partition: &str| -> ProviderMetadata {
+        let file = temp_dir
+            .path()
+            .join(partition)
+            .join("metadata")
+            .join("provider-metadata.json");
+        serde_json::from_slice(&fs::read(file).unwrap()).unwrap()
+    };
+    let feeds = |metadata: ProviderMetadata| {
+        metadata
+            .distributions
+            .into_iter()
+            .flat_map(|dist| dist.rolie.map(|rolie| rolie.feeds).unwrap_or_default())
+            .map(|feed| feed.tlp_label)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(feeds(load("tlp-white")), [TlpLabel::White]);
+    assert_eq!(feeds(load("tlp-amber")), [TlpLabel::Amber]);
+    let unlabeled = load("tlp-unlabeled");
+    assert_eq!(unlabeled.distributions.len(), 1);
+    assert!(unlabeled.distributions[0].directory_url.is_some());
+}
+
+#[tokio::test]
+async fn given_diff_visitor_when_storing_again_then_changes_are_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    let report = DiffReport::new();
+    let cut = DiffVisitor::new(
+        StoreVisitor::new(temp_dir.path()),
+        temp_dir.path(),
+        report.clone(),
+    );
+    let context = Rc::new(create_test_metadata());
+
+    // without a stored digest, the digest of the stored content is used
+    let advisory = |data: &'static str| RetrievedAdvisory {
+        data: Bytes::from(data),
+        sha256: None,
+        ..create_test_retrieved_advisory(create_test_discovered_advisory())
+    };
+
+    for data in [r#"{"a":1}"#, r#"{"a":1}"#, r#"{"a":2}"#] {
+        RetrievedVisitor::<FileSource>::visit_advisory(&cut, &context, Ok(advisory(data)))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        report.summary(),
+        DiffSummary {
+            added: 1,
+            modified: 1,
+            unchanged: 1,
+            removed: 0,
+        }
+    );
+
+    let file = temp_dir.path().join("diff.json");
+    report.write(&file).unwrap();
+    let diff: serde_json::Value = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+    let changes = diff["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| serde_json::from_value::<Change>(change["change"].clone()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(changes, [Change::Added, Change::Modified]);
+}
+
+#[tokio::test]
+async fn given_diff_visitor_when_stored_advisory_is_unreadable_then_it_is_stored_as_modified() {
+    let temp_dir = TempDir::new().unwrap();
+    let report = DiffReport::new();
+    let cut = DiffVisitor::new(
+        StoreVisitor::new(temp_dir.path()),
+        temp_dir.path(),
+        report.clone(),
+    );
+    let context = Rc::new(create_test_metadata());
+
+    // a stored copy, which fails to decompress
+    let dir = temp_dir
+        .path()
+        .join(utf8_percent_encode("https://example.com/advisories/", NON_ALPHANUMERIC).to_string());
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("test-advisory-2024-001.json.gz"), "not gzip").unwrap();
+
+    let advisory = RetrievedAdvisory {
+        sha256: None,
+        ..create_test_retrieved_advisory(create_test_discovered_advisory())
+    };
+    RetrievedVisitor::<FileSource>::visit_advisory(&cut, &context, Ok(advisory))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.summary(),
+        DiffSummary {
+            modified: 1,
+            ..Default::default()
+        }
+    );
+    assert!(dir.join("test-advisory-2024-001.json").exists());
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{base, ok_or_not_found, start_mock_server};
+use csaf_walker::{
+    model::store::distribution_base,
+    source::{HttpOptions, HttpSource},
+    visitors::streaming::StreamingStoreVisitor,
+    walker::{Error, Walker},
+};
+use sha2::{Digest, Sha256};
+use url::Url;
+use walker_common::{
+    fetcher::{Fetcher, FetcherOptions},
+    lock::Lock,
+    utils::hex::Hex,
+};
+
+const ADVISORY: &str = r#"{"document":{"title":"streamed"}}"#;
+
+/// Start a server, serving a provider with a good advisory, and one with a wrong digest.
+async fn start_server() -> Url {
+    let server = start_mock_server(|req| {
+        let base = base(&req);
+        ok_or_not_found(match req.uri().path() {
+            "/provider-metadata.json" => Some(
+                serde_json::json!({
+                    "canonical_url": format!("{base}/provider-metadata.json"),
+                    "distributions": [{ "directory_url": format!("{base}/advisories/") }],
+                    "last_updated": "2024-01-01T00:00:00Z",
+                    "metadata_version": "2.0",
+                    "publisher": {
+                        "category": "vendor",
+                        "name": "Example",
+                        "namespace": "https://example.com"
+                    }
+                })
+                .to_string(),
+            ),
+            "/advisories/changes.csv" => Some(
+                "\"good.json\",\"2024-01-01T00:00:00Z\"\n\"bad.json\",\"2024-01-01T00:00:00Z\"\n"
+                    .to_string(),
+            ),
+            "/advisories/good.json" | "/advisories/bad.json" => Some(ADVISORY.to_string()),
+            "/advisories/good.json.sha256" => Some(format!(
+                "{}  good.json",
+                Hex(&Sha256::digest(ADVISORY)).to_lower()
+            )),
+            "/advisories/bad.json.sha256" => Some(format!(
+                "{}  bad.json",
+                Hex(&Sha256::digest("other")).to_lower()
+            )),
+            _ => None,
+        })
+    })
+    .await;
+
+    server.join("provider-metadata.json").unwrap()
+}
+
+#[tokio::test]
+async fn given_streaming_store_then_valid_advisories_are_stored() {
+    let metadata = start_server().await;
+    let output = tempfile::tempdir().unwrap();
+
+    let source = HttpSource::new(
+        metadata.clone(),
+        Fetcher::new(FetcherOptions::new()).await.unwrap(),
+        HttpOptions::new(),
+    );
+    let visitor = StreamingStoreVisitor::new(source.clone(), output.path()).no_xattrs(true);
+
+    let result = Walker::new(source)
+        .continue_on_error(true)
+        .walk(visitor)
+        .await;
+
+    let Err(Error::Failed { stats, errors, .. }) = result else {
+        panic!("must fail the advisory with the wrong digest");
+    };
+    assert_eq!(stats.succeeded, 1);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].1.to_string().contains("Digest mismatch"));
+
+    let base = distribution_base(
+        output.path(),
+        metadata.join("advisories/").unwrap().as_str(),
+    );
+    let files = std::fs::read_dir(&base)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<std::collections::BTreeSet<_>>();
+    assert_eq!(
+        files,
+        ["good.json", "good.json.sha256"]
+            .map(ToString::to_string)
+            .into()
+    );
+    assert_eq!(
+        std::fs::read_to_string(base.join("good.json")).unwrap(),
+        ADVISORY
+    );
+    assert!(
+        output
+            .path()
+            .join("metadata/provider-metadata.json")
+            .exists()
+    );
+}
+
+#[tokio::test]
+async fn given_locked_store_then_streaming_fails() {
+    let metadata = start_server().await;
+    let output = tempfile::tempdir().unwrap();
+    let _lock = Lock::store(output.path()).unwrap();
+
+    let source = HttpSource::new(
+        metadata,
+        Fetcher::new(FetcherOptions::new()).await.unwrap(),
+        HttpOptions::new(),
+    );
+    let visitor = StreamingStoreVisitor::new(source.clone(), output.path()).no_xattrs(true);
+
+    let err = Walker::new(source).walk(visitor).await.unwrap_err();
+    assert!(err.to_string().contains("already in progress"), "{err}");
+}
@@ -0,0 +1,181 @@
+use csaf_walker::{
+    retrieve::RetrievingVisitor,
+    source::{FileOptions, FileSource},
+    validation::{ValidatedAdvisory, ValidationError, ValidationVisitor},
+    visitors::audit::{AuditFormat, AuditLog, AuditVisitor},
+    walker::{StreamError, Walker},
+};
+use futures::StreamExt;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use walker_common::validate::{MissingSignature, ValidationOptions};
+
+/// Validate the (unsigned) test data, returning the number of valid and missing signatures.
+async fn validate(missing_signature: MissingSignature) -> (usize, usize) {
+    let source = FileSource::new("test-data", FileOptions::new().rootless(true)).unwrap();
+
+    let valid = Arc::new(AtomicUsize::new(0));
+    let missing = Arc::new(AtomicUsize::new(0));
+
+    let visitor = {
+        let valid = valid.clone();
+        let missing = missing.clone();
+        move |result: Result<ValidatedAdvisory, ValidationError<FileSource>>| {
+            let valid = valid.clone();
+            let missing = missing.clone();
+            async move {
+                match result {
+                    Ok(_) => valid.fetch_add(1, Ordering::Relaxed),
+                    Err(ValidationError::SignatureMissing { .. }) => {
+                        missing.fetch_add(1, Ordering::Relaxed)
+                    }
+                    Err(err) => return Err(err.to_string()),
+                };
+                Ok::<_, String>(())
+            }
+        }
+    };
+
+    Walker::new(source.clone())
+        .walk(RetrievingVisitor::new(
+            source,
+            ValidationVisitor::new(visitor)
+                .with_options(ValidationOptions::new().missing_signature(missing_signature)),
+        ))
+        .await
+        .unwrap();
+
+    (
+        valid.load(Ordering::Relaxed),
+        missing.load(Ordering::Relaxed),
+    )
+}
+
+#[tokio::test]
+async fn missing_signature_allow() {
+    assert_eq!(validate(MissingSignature::Allow).await, (5, 0));
+}
+
+#[tokio::test]
+async fn missing_signature_warn() {
+    assert_eq!(validate(MissingSignature::Warn).await, (5, 0));
+}
+
+#[tokio::test]
+async fn missing_signature_reject() {
+    assert_eq!(validate(MissingSignature::Reject).await, (0, 5));
+}
+
+/// Validate the test data, recording it to an audit log of the format.
+async fn audit(format: AuditFormat) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.log");
+    let log = AuditLog::open(&path, format).unwrap();
+
+    let source = FileSource::new("test-data", FileOptions::new().rootless(true)).unwrap();
+    let visitor = AuditVisitor::new(
+        |_: Result<ValidatedAdvisory, ValidationError<FileSource>>| async { Ok::<_, String>(()) },
+        log,
+    );
+
+    Walker::new(source.clone())
+        .walk(RetrievingVisitor::new(
+            source,
+            ValidationVisitor::new(visitor)
+                .with_options(ValidationOptions::new().missing_signature(MissingSignature::Reject)),
+        ))
+        .await
+        .unwrap();
+
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[tokio::test]
+async fn audit_json_lines() {
+    let log = audit(AuditFormat::JsonLines).await;
+
+    let records = log
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(records.len(), 5);
+    for record in records {
+        assert_eq!(record["validated"], false);
+        assert_eq!(record["signer"], serde_json::Value::Null);
+        assert_eq!(record["error"], "Missing signature");
+        assert_eq!(record["sha256"].as_str().unwrap().len(), 64);
+    }
+}
+
+#[tokio::test]
+async fn audit_csv() {
+    let log = audit(AuditFormat::Csv).await;
+
+    let mut lines = log.lines();
+    assert_eq!(
+        lines.next(),
+        Some("url,sha256,validated,signer,error,timestamp")
+    );
+    let rows = lines.collect::<Vec<_>>();
+    assert_eq!(rows.len(), 5);
+    assert!(
+        rows.iter()
+            .all(|row| row.contains(",false,,Missing signature,"))
+    );
+}
+
+#[tokio::test]
+async fn into_stream() {
+    let source = FileSource::new("test-data", FileOptions::new().rootless(true)).unwrap();
+
+    let results = Walker::new(source)
+        .into_stream(ValidationOptions::new().missing_signature(MissingSignature::Reject))
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(results.len(), 5);
+    assert!(results.iter().all(|result| matches!(
+        result,
+        Err(StreamError::Advisory(
+            ValidationError::SignatureMissing { .. }
+        ))
+    )));
+}
+
+#[tokio::test]
+async fn into_stream_walk_error() {
+    // without the rootless option, the provider metadata is missing
+    let source = FileSource::new("test-data", FileOptions::new()).unwrap();
+
+    let results = Walker::new(source)
+        .into_stream(ValidationOptions::new())
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(results.len(), 1);
+    assert!(matches!(results[0], Err(StreamError::Walk(_))));
+}
+
+#[cfg(feature = "csaf")]
+#[tokio::test]
+async fn into_verification_stream() {
+    use csaf_walker::verification::check::init_verifying_visitor;
+
+    let source = FileSource::new("test-data", FileOptions::new().rootless(true)).unwrap();
+    let checks = init_verifying_visitor().len();
+
+    let results = Walker::new(source)
+        .into_verification_stream(ValidationOptions::new(), init_verifying_visitor())
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(results.len(), 5);
+    for result in results {
+        let advisory = result.unwrap();
+        // every check either failed or succeeded
+        assert_eq!(advisory.failures.len() + advisory.successes.len(), checks);
+        assert!(advisory.url.path().ends_with(".json"));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::visitors::SendVisitor;
+use crate::visitors::{DispatchSink, HttpSink, SendVisitor};
 use reqwest::Url;
 use std::path::PathBuf;
 use walker_common::sender::{
@@ -8,7 +8,10 @@ use walker_common::sender::{
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Sending")]
 pub struct SendArguments {
-    /// Target to send to
+    /// Target to send to.
+    ///
+    /// Besides HTTP(S) endpoints, this may be `nats://host:port/subject` or
+    /// `kafka://host:port/topic`, if the respective feature is enabled.
     pub target: Url,
 
     /// Sender connect timeout
@@ -42,7 +45,7 @@ pub struct SendArguments {
         id = "sender-retries",
         long,
         env = "SENDER_RETRIES",
-        default_value = "0"
+        default_value = "3"
     )]
     pub retries: usize,
 
@@ -64,6 +67,14 @@ pub struct SendArguments {
     )]
     pub max_delay: humantime::Duration,
 
+    /// Number of documents to send as a single batch
+    #[arg(id = "sender-batch-size", long, env = "SENDER_BATCH_SIZE")]
+    pub batch_size: Option<usize>,
+
+    /// Only log what would be sent, without sending anything
+    #[arg(id = "sender-dry-run", long, env = "SENDER_DRY_RUN")]
+    pub dry_run: bool,
+
     /// Custom query parameters
     #[arg(
         id = "sender-query-parameter",
@@ -78,7 +89,7 @@ pub struct SendArguments {
 }
 
 impl SendArguments {
-    pub async fn into_visitor(self) -> Result<SendVisitor, anyhow::Error> {
+    pub async fn into_visitor(self) -> Result<SendVisitor<DispatchSink>, anyhow::Error> {
         let SendArguments {
             target,
             connect_timeout,
@@ -88,28 +99,44 @@ impl SendArguments {
             retries,
             min_delay,
             max_delay,
+            batch_size,
+            dry_run,
             oidc,
             query,
         } = self;
 
-        let provider = oidc.into_provider().await?;
-        let sender = HttpSender::new(
-            provider,
-            HttpSenderOptions::default()
-                .connect_timeout(Some(connect_timeout.into()))
-                .timeout(Some(timeout.into()))
-                .tls_insecure(tls_insecure)
-                .additional_root_certificates(additional_root_certificates)
-                .query_parameters(query.into_iter().map(|entry| match entry.split_once('=') {
-                    Some((key, value)) => (key.to_string(), value.to_string()),
-                    None => (entry, "".to_string()),
-                })),
-        )
-        .await?;
+        let sink: DispatchSink = match target.scheme() {
+            "http" | "https" => {
+                let provider = oidc.into_provider().await?;
+                let sender = HttpSender::new(
+                    provider,
+                    HttpSenderOptions::default()
+                        .connect_timeout(Some(connect_timeout.into()))
+                        .timeout(Some(timeout.into()))
+                        .tls_insecure(tls_insecure)
+                        .additional_root_certificates(additional_root_certificates)
+                        .query_parameters(query.into_iter().map(
+                            |entry| match entry.split_once('=') {
+                                Some((key, value)) => (key.to_string(), value.to_string()),
+                                None => (entry, "".to_string()),
+                            },
+                        )),
+                )
+                .await?;
+                HttpSink::new(target, sender).into()
+            }
+            #[cfg(feature = "nats")]
+            "nats" => crate::visitors::NatsSink::connect(&target).await?.into(),
+            #[cfg(feature = "kafka")]
+            "kafka" => crate::visitors::KafkaSink::connect(&target).await?.into(),
+            scheme => anyhow::bail!("Unsupported send target scheme: {scheme}"),
+        };
 
-        Ok(SendVisitor::new(target, sender)
+        Ok(SendVisitor::from_sink(sink)
             .retries(retries)
             .min_delay(min_delay)
-            .max_delay(max_delay))
+            .max_delay(max_delay)
+            .batch_size(batch_size)
+            .dry_run(dry_run))
     }
 }
@@ -14,7 +14,7 @@ pub enum SendRetrievedAdvisoryError<S: Source> {
     Retrieval(#[from] RetrievalError<DiscoveredAdvisory, S>),
 }
 
-impl<S: Source> RetrievedVisitor<S> for SendVisitor {
+impl<S: Source, K: Sink> RetrievedVisitor<S> for SendVisitor<K> {
     type Error = SendRetrievedAdvisoryError<S>;
     type Context = ();
 
@@ -41,7 +41,7 @@ pub enum SendValidatedAdvisoryError<S: Source> {
     Validation(#[from] ValidationError<S>),
 }
 
-impl<S: Source> ValidatedVisitor<S> for SendVisitor {
+impl<S: Source, K: Sink> ValidatedVisitor<S> for SendVisitor<K> {
     type Error = SendValidatedAdvisoryError<S>;
     type Context = ();
 
@@ -59,7 +59,7 @@ impl<S: Source> ValidatedVisitor<S> for SendVisitor {
     }
 }
 
-impl SendVisitor {
+impl<K: Sink> SendVisitor<K> {
     async fn send_retrieved_advisory(&self, advisory: RetrievedAdvisory) -> Result<(), SendError> {
         log::debug!(
             "Sending: {} (modified: {:?})",
@@ -77,9 +77,6 @@ impl SendVisitor {
     }
 
     pub async fn send_json(&self, name: &str, data: Bytes) -> Result<(), SendError> {
-        self.send(name, data, |request| {
-            request.header(header::CONTENT_TYPE, "application/json")
-        })
-        .await
+        self.send(SinkDocument::json(name, data)).await
     }
 }
@@ -0,0 +1,194 @@
+use super::{BatchResult, SendError, SendOnceError, Sink, SinkDocument};
+use reqwest::{Body, Method, StatusCode, Url, header};
+use std::{sync::Arc, time::Duration};
+use walker_common::{
+    http::{calculate_retry_after_from_response_header, retry_after_from_response_header},
+    sender::HttpSender,
+};
+
+const CONTENT_TYPE_NDJSON: &str = "application/x-ndjson";
+
+/// Send documents to an HTTP endpoint, using `POST` requests.
+///
+/// Batches of JSON documents are sent as a single request, using newline delimited JSON. Documents
+/// with an ID are sent in a separate request, carrying one `id` query parameter per line.
+#[non_exhaustive]
+#[derive(Clone)]
+pub struct HttpSink {
+    /// The target endpoint
+    pub url: Url,
+
+    /// The HTTP client to use
+    pub sender: HttpSender,
+
+    /// The default retry-after duration when a 429 response doesn't include a Retry-After header
+    default_retry_after: Duration,
+}
+
+impl HttpSink {
+    pub fn new(url: impl Into<Url>, sender: HttpSender) -> Self {
+        Self {
+            url: url.into(),
+            sender,
+            default_retry_after: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HttpSink {
+    /// Post a request, `name` being used for logging.
+    async fn post(
+        &self,
+        name: &str,
+        body: Body,
+        content_type: &str,
+        content_encoding: Option<&str>,
+        ids: &[&str],
+    ) -> Result<(), SendOnceError> {
+        let mut request = self
+            .sender
+            .request(Method::POST, self.url.clone())
+            .await
+            .map_err(|err| SendOnceError::Temporary(err.into()))?
+            .header(header::CONTENT_TYPE, content_type)
+            .body(body);
+        for id in ids {
+            request = request.query(&[("id", id)]);
+        }
+        if let Some(encoding) = content_encoding {
+            request = request.header(header::CONTENT_ENCODING, encoding);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|err| SendOnceError::Temporary(err.into()))?;
+
+        if let Some(retry_after) =
+            calculate_retry_after_from_response_header(&response, self.default_retry_after)
+        {
+            log::info!(
+                "Rate limited (429) when uploading {name}, retry after: {:?}",
+                retry_after
+            );
+            return Err(SendOnceError::Temporary(SendError::RateLimited(
+                retry_after,
+            )));
+        }
+
+        let status = response.status();
+
+        if status.is_success() {
+            log::debug!("Uploaded {} -> {}", name, response.status());
+            Ok(())
+        } else if status.is_client_error() {
+            log::warn!("Failed to upload, payload rejected {name} -> {status}",);
+            Err(SendOnceError::Permanent(SendError::Client(status)))
+        } else if status == StatusCode::SERVICE_UNAVAILABLE
+            && let Some(retry_after) = retry_after_from_response_header(&response)
+        {
+            log::info!(
+                "Service unavailable (503) when uploading {name}, retry after: {:?}",
+                retry_after
+            );
+            Err(SendOnceError::Temporary(SendError::Unavailable(
+                retry_after,
+            )))
+        } else if status.is_server_error() {
+            log::warn!("Failed to upload, server error {name} -> {status}",);
+            Err(SendOnceError::Temporary(SendError::Server(status)))
+        } else {
+            Err(SendOnceError::Permanent(SendError::UnexpectedStatus(
+                status,
+            )))
+        }
+    }
+}
+
+impl Sink for HttpSink {
+    fn target(&self) -> String {
+        self.url.to_string()
+    }
+
+    async fn send_once(&self, document: &SinkDocument) -> Result<(), SendOnceError> {
+        self.post(
+            &document.name,
+            Body::from(document.data.clone()),
+            document.content_type,
+            document.content_encoding,
+            document.id.as_deref().as_slice(),
+        )
+        .await
+    }
+
+    /// Send plain JSON documents as NDJSON requests, all others individually.
+    async fn send_batch_once(&self, documents: &[SinkDocument]) -> Vec<BatchResult> {
+        let mut results = Vec::with_capacity(documents.len());
+        // documents without and with an ID
+        let mut batches = [NdJsonBatch::default(), NdJsonBatch::default()];
+
+        for (i, document) in documents.iter().enumerate() {
+            if document.content_type != "application/json" || document.content_encoding.is_some() {
+                results.push(self.send_once(document).await.map_err(Arc::new));
+                continue;
+            }
+
+            let batch = &mut batches[usize::from(document.id.is_some())];
+            match batch.push(i, document) {
+                Ok(()) => results.push(Ok(())),
+                Err(err) => results.push(Err(Arc::new(SendOnceError::Permanent(SendError::Sink(
+                    err.into(),
+                ))))),
+            }
+        }
+
+        for batch in batches {
+            if batch.documents.is_empty() {
+                continue;
+            }
+
+            let name = format!("batch of {} documents", batch.documents.len());
+            let ids = batch
+                .documents
+                .iter()
+                .filter_map(|i| documents[*i].id.as_deref())
+                .collect::<Vec<_>>();
+            if let Err(err) = self
+                .post(
+                    &name,
+                    Body::from(batch.body),
+                    CONTENT_TYPE_NDJSON,
+                    None,
+                    &ids,
+                )
+                .await
+            {
+                // all documents of the request share its error
+                let err = Arc::new(err);
+                for i in batch.documents {
+                    results[i] = Err(err.clone());
+                }
+            }
+        }
+
+        results
+    }
+}
+
+/// The body of an NDJSON request, along with the indexes of the documents it contains.
+#[derive(Default)]
+struct NdJsonBatch {
+    body: Vec<u8>,
+    documents: Vec<usize>,
+}
+
+impl NdJsonBatch {
+    fn push(&mut self, index: usize, document: &SinkDocument) -> Result<(), serde_json::Error> {
+        // NDJSON requires each document to be on a single line
+        let value = serde_json::from_slice::<serde_json::Value>(&document.data)?;
+        serde_json::to_writer(&mut self.body, &value)?;
+        self.body.push(b'\n');
+        self.documents.push(index);
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use super::{SendError, SendOnceError, Sink, SinkDocument};
+use reqwest::{Url, header};
+use rskafka::{
+    client::{
+        ClientBuilder,
+        partition::{Compression, PartitionClient, UnknownTopicHandling},
+    },
+    record::Record,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Produce documents to a Kafka topic.
+///
+/// All documents are written to a single partition, using the document name as the key.
+#[derive(Clone)]
+pub struct KafkaSink {
+    client: Arc<PartitionClient>,
+}
+
+impl KafkaSink {
+    pub fn new(client: PartitionClient) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+
+    /// Connect to a Kafka broker, using a URL in the form of `kafka://host:port/topic`.
+    pub async fn connect(url: &Url) -> anyhow::Result<Self> {
+        let topic = url.path().trim_start_matches('/');
+        if topic.is_empty() {
+            anyhow::bail!("Missing topic in Kafka URL: {url}");
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing host in Kafka URL: {url}"))?;
+        let port = url.port().unwrap_or(9092);
+
+        let client = ClientBuilder::new(vec![format!("{host}:{port}")])
+            .build()
+            .await?
+            .partition_client(topic, 0, UnknownTopicHandling::Retry)
+            .await?;
+
+        Ok(Self::new(client))
+    }
+}
+
+impl Sink for KafkaSink {
+    fn target(&self) -> String {
+        format!("Kafka topic {}", self.client.topic())
+    }
+
+    async fn send_once(&self, document: &SinkDocument) -> Result<(), SendOnceError> {
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            header::CONTENT_TYPE.to_string(),
+            document.content_type.as_bytes().to_vec(),
+        );
+        if let Some(encoding) = document.content_encoding {
+            headers.insert(
+                header::CONTENT_ENCODING.to_string(),
+                encoding.as_bytes().to_vec(),
+            );
+        }
+        headers.insert(
+            super::HEADER_NAME.to_string(),
+            document.name.as_bytes().to_vec(),
+        );
+        if let Some(id) = &document.id {
+            headers.insert(super::HEADER_ID.to_string(), id.as_bytes().to_vec());
+        }
+
+        let record = Record {
+            key: Some(document.name.as_bytes().to_vec()),
+            value: Some(document.data.to_vec()),
+            headers,
+            timestamp: chrono::Utc::now(),
+        };
+
+        self.client
+            .produce(vec![record], Compression::NoCompression)
+            .await
+            .map_err(|err| SendOnceError::Temporary(SendError::Sink(err.into())))?;
+
+        log::debug!("Produced {}", document.name);
+
+        Ok(())
+    }
+}
@@ -1,11 +1,27 @@
-use backon::{ExponentialBuilder, Retryable};
+use backon::Retryable;
 use bytes::Bytes;
-use reqwest::{Body, Method, StatusCode, Url, header};
-use std::time::Duration;
-use walker_common::{
-    http::calculate_retry_after_from_response_header,
-    sender::{self, HttpSender},
+use reqwest::{StatusCode, Url};
+use std::{
+    fmt::{Display, Formatter},
+    future::Future,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
+use walker_common::sender::{self, HttpSender};
+
+mod http;
+pub use http::*;
+
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(feature = "nats")]
+pub use nats::*;
+
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "kafka")]
+pub use kafka::*;
 
 #[cfg(feature = "sbom-walker")]
 mod sbom;
@@ -22,6 +38,11 @@ mod clap;
 #[cfg(feature = "clap")]
 pub use self::clap::*;
 
+/// Header carrying the name of the document, for sinks which support headers.
+pub const HEADER_NAME: &str = "Walker-Name";
+/// Header carrying the ID of the document, for sinks which support headers.
+pub const HEADER_ID: &str = "Walker-Id";
+
 #[derive(Debug, thiserror::Error)]
 pub enum SendError {
     #[error(transparent)]
@@ -36,17 +57,230 @@ pub enum SendError {
     UnexpectedStatus(StatusCode),
     #[error("Rate limited (HTTP 429), retry after {0:?}")]
     RateLimited(Duration),
+    #[error("Service unavailable (HTTP 503), retry after {0:?}")]
+    Unavailable(Duration),
+    #[error("sink error: {0}")]
+    Sink(Box<dyn std::error::Error + Send + Sync>),
+    #[error("failed to send documents of batch: {}", BatchFailure::display(.0))]
+    Batch(Vec<BatchFailure>),
+}
+
+/// A document of a batch which could not be sent.
+#[derive(Debug)]
+pub struct BatchFailure {
+    /// The name of the document
+    pub name: String,
+    /// The reason for the failure, might be shared by documents sent in the same request
+    pub error: Arc<SendOnceError>,
+}
+
+impl BatchFailure {
+    fn display(failures: &[Self]) -> impl Display + '_ {
+        struct Failures<'a>(&'a [BatchFailure]);
+
+        impl Display for Failures<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                for (i, failure) in self.0.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{} ({})", failure.name, failure.error)?;
+                }
+                Ok(())
+            }
+        }
+
+        Failures(failures)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SendOnceError {
+    #[error(transparent)]
+    Temporary(SendError),
+    #[error(transparent)]
+    Permanent(SendError),
+}
+
+impl SendError {
+    /// The delay requested by the target, if any.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited(retry_after) | Self::Unavailable(retry_after) => Some(*retry_after),
+            _ => None,
+        }
+    }
+}
+
+impl SendOnceError {
+    /// Check if the failure is temporary, and sending should be retried.
+    pub fn is_temporary(&self) -> bool {
+        matches!(self, Self::Temporary(_))
+    }
+
+    /// The delay requested by the target, if any.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Temporary(err) | Self::Permanent(err) => err.retry_after(),
+        }
+    }
+}
+
+impl From<SendOnceError> for SendError {
+    fn from(value: SendOnceError) -> Self {
+        match value {
+            SendOnceError::Temporary(e) => e,
+            SendOnceError::Permanent(e) => e,
+        }
+    }
+}
+
+/// A document to send.
+#[derive(Clone, Debug)]
+pub struct SinkDocument {
+    /// The name of the document, used for logging and as metadata
+    pub name: String,
+    /// An optional ID of the document
+    pub id: Option<String>,
+    /// The content
+    pub data: Bytes,
+    /// The content type
+    pub content_type: &'static str,
+    /// The encoding of the content, if any
+    pub content_encoding: Option<&'static str>,
+}
+
+impl SinkDocument {
+    /// Create a new JSON document.
+    pub fn json(name: impl Into<String>, data: Bytes) -> Self {
+        Self {
+            name: name.into(),
+            id: None,
+            data,
+            content_type: "application/json",
+            content_encoding: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<Option<String>>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    pub fn content_encoding(mut self, content_encoding: impl Into<Option<&'static str>>) -> Self {
+        self.content_encoding = content_encoding.into();
+        self
+    }
+}
+
+/// A target documents can be sent to.
+///
+/// A sink only makes a single attempt. Retries are handled by the [`SendVisitor`], so that they
+/// apply uniformly to all sinks.
+pub trait Sink {
+    /// A description of the target, used for logging.
+    ///
+    /// By default, this is the name of the sink's type.
+    fn target(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Send a document once, reporting if a failure is temporary or permanent.
+    fn send_once(&self, document: &SinkDocument)
+    -> impl Future<Output = Result<(), SendOnceError>>;
+
+    /// Send a batch of documents once.
+    ///
+    /// Returns one result per document, in the order of the documents. Documents sent using the
+    /// same request share the error of that request.
+    ///
+    /// By default, this sends each document individually.
+    fn send_batch_once(
+        &self,
+        documents: &[SinkDocument],
+    ) -> impl Future<Output = Vec<BatchResult>> {
+        async move {
+            let mut results = Vec::with_capacity(documents.len());
+            for document in documents {
+                results.push(self.send_once(document).await.map_err(Arc::new));
+            }
+            results
+        }
+    }
+}
+
+/// The outcome of sending a single document of a batch.
+pub type BatchResult = Result<(), Arc<SendOnceError>>;
+
+/// A sink, chosen at runtime.
+#[derive(Clone)]
+pub enum DispatchSink {
+    Http(HttpSink),
+    #[cfg(feature = "nats")]
+    Nats(NatsSink),
+    #[cfg(feature = "kafka")]
+    Kafka(KafkaSink),
+}
+
+impl Sink for DispatchSink {
+    fn target(&self) -> String {
+        match self {
+            Self::Http(sink) => sink.target(),
+            #[cfg(feature = "nats")]
+            Self::Nats(sink) => sink.target(),
+            #[cfg(feature = "kafka")]
+            Self::Kafka(sink) => sink.target(),
+        }
+    }
+
+    async fn send_once(&self, document: &SinkDocument) -> Result<(), SendOnceError> {
+        match self {
+            Self::Http(sink) => sink.send_once(document).await,
+            // boxed, as the futures of the clients are deeply nested
+            #[cfg(feature = "nats")]
+            Self::Nats(sink) => Box::pin(sink.send_once(document)).await,
+            #[cfg(feature = "kafka")]
+            Self::Kafka(sink) => Box::pin(sink.send_once(document)).await,
+        }
+    }
+
+    async fn send_batch_once(&self, documents: &[SinkDocument]) -> Vec<BatchResult> {
+        match self {
+            Self::Http(sink) => sink.send_batch_once(documents).await,
+            #[cfg(feature = "nats")]
+            Self::Nats(sink) => Box::pin(sink.send_batch_once(documents)).await,
+            #[cfg(feature = "kafka")]
+            Self::Kafka(sink) => Box::pin(sink.send_batch_once(documents)).await,
+        }
+    }
+}
+
+impl From<HttpSink> for DispatchSink {
+    fn from(value: HttpSink) -> Self {
+        Self::Http(value)
+    }
+}
+
+#[cfg(feature = "nats")]
+impl From<NatsSink> for DispatchSink {
+    fn from(value: NatsSink) -> Self {
+        Self::Nats(value)
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl From<KafkaSink> for DispatchSink {
+    fn from(value: KafkaSink) -> Self {
+        Self::Kafka(value)
+    }
 }
 
 /// Send data to a remote sink.
 #[non_exhaustive]
 #[derive(Clone)]
-pub struct SendVisitor {
-    /// The target endpoint
-    pub url: Url,
-
-    /// The HTTP client to use
-    pub sender: HttpSender,
+pub struct SendVisitor<K = HttpSink> {
+    /// The sink to send to
+    pub sink: K,
 
     /// The number of retries in case of a server or transmission failure
     retries: usize,
@@ -57,19 +291,38 @@ pub struct SendVisitor {
     /// The maximum delay between retries, will be overruled by the retry-after header if present.
     max_delay: Option<Duration>,
 
-    /// The default retry-after duration when a 429 response doesn't include a Retry-After header
-    default_retry_after: Duration,
+    /// The number of documents to buffer and send as a single batch, if any
+    batch_size: Option<usize>,
+
+    /// Documents buffered for the next batch, shared between clones
+    batch: Arc<Mutex<Vec<SinkDocument>>>,
+
+    /// Documents of earlier batches which failed to be sent, shared between clones
+    failures: Arc<Mutex<Vec<BatchFailure>>>,
+
+    /// Only log what would be sent, without actually sending anything
+    dry_run: bool,
 }
 
 impl SendVisitor {
+    /// Create a new visitor, sending to an HTTP endpoint.
     pub fn new(url: impl Into<Url>, sender: HttpSender) -> Self {
+        Self::from_sink(HttpSink::new(url, sender))
+    }
+}
+
+impl<K: Sink> SendVisitor<K> {
+    /// Create a new visitor, sending to the provided sink.
+    pub fn from_sink(sink: K) -> Self {
         Self {
-            url: url.into(),
-            sender,
-            retries: 0,
+            sink,
+            retries: DEFAULT_RETRIES,
             min_delay: None,
             max_delay: None,
-            default_retry_after: Duration::from_secs(10),
+            batch_size: None,
+            batch: Default::default(),
+            failures: Default::default(),
+            dry_run: false,
         }
     }
 
@@ -87,111 +340,473 @@ impl SendVisitor {
         self.max_delay = Some(retry_delay.into());
         self
     }
+
+    /// Send documents in batches of the provided size.
+    ///
+    /// Documents are buffered until the batch is full, so [`Self::flush`] must be called once
+    /// all documents have been sent. Sending the document completing a batch only fails if that
+    /// document could not be sent, the failures of other documents are reported by
+    /// [`Self::flush`].
+    pub fn batch_size(mut self, batch_size: impl Into<Option<usize>>) -> Self {
+        self.batch_size = batch_size.into().filter(|size| *size > 1);
+        self
+    }
+
+    /// Only log the documents which would be sent, without sending them.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Send a document, retry in case of temporary errors
+    ///
+    /// Retries use an exponential backoff with full jitter, between the minimum and maximum
+    /// delay. A delay requested by the target (e.g. using `Retry-After`) takes precedence if it
+    /// is longer.
+    ///
+    /// When sending in batches, the document might only be buffered for now.
+    pub async fn send(&self, document: SinkDocument) -> Result<(), SendError> {
+        if self.dry_run {
+            log::info!(
+                "Dry run, would send {} to {} ({} bytes, {}{})",
+                document.name,
+                self.sink.target(),
+                document.data.len(),
+                document.content_type,
+                document
+                    .content_encoding
+                    .map(|encoding| format!(", encoding: {encoding}"))
+                    .unwrap_or_default(),
+            );
+            return Ok(());
+        }
+
+        let Some(batch_size) = self.batch_size else {
+            return self
+                .retry(&document.name, || self.sink.send_once(&document))
+                .await;
+        };
+
+        let name = document.name.clone();
+        let batch = {
+            let mut batch = self.batch.lock().unwrap_or_else(|err| err.into_inner());
+            batch.push(document);
+            if batch.len() < batch_size {
+                return Ok(());
+            }
+            mem::take(&mut *batch)
+        };
+
+        // only report the failure of this document, keep the others for the flush
+        let (own, others): (Vec<_>, Vec<_>) = self
+            .send_batch(batch)
+            .await
+            .into_iter()
+            .partition(|failure| failure.name == name);
+        self.failures
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .extend(others);
+
+        match own.is_empty() {
+            true => Ok(()),
+            false => Err(SendError::Batch(own)),
+        }
+    }
+
+    /// Send all documents still buffered for a batch.
+    ///
+    /// This fails if any document sent as part of a batch failed, and its failure was not yet
+    /// reported.
+    pub async fn flush(&self) -> Result<(), SendError> {
+        let batch = mem::take(&mut *self.batch.lock().unwrap_or_else(|err| err.into_inner()));
+        let mut failures = match batch.is_empty() {
+            true => vec![],
+            false => self.send_batch(batch).await,
+        };
+
+        failures.splice(
+            0..0,
+            mem::take(&mut *self.failures.lock().unwrap_or_else(|err| err.into_inner())),
+        );
+
+        match failures.is_empty() {
+            true => Ok(()),
+            false => Err(SendError::Batch(failures)),
+        }
+    }
+
+    /// Send a batch, only retrying the documents which failed temporarily.
+    ///
+    /// Returns the documents which could not be sent.
+    async fn send_batch(&self, mut pending: Vec<SinkDocument>) -> Vec<BatchFailure> {
+        log::debug!("Sending batch of {} documents", pending.len());
+
+        let mut backoff = self.backoff();
+        let mut failures = Vec::new();
+
+        loop {
+            let mut results = self.sink.send_batch_once(&pending).await;
+            if results.len() != pending.len() {
+                log::warn!(
+                    "Sink returned {} results for a batch of {} documents",
+                    results.len(),
+                    pending.len()
+                );
+                // fail the documents without a result
+                let missing = Arc::new(SendOnceError::Permanent(SendError::Sink(
+                    format!(
+                        "missing result of a batch ({} of {} documents)",
+                        results.len(),
+                        pending.len()
+                    )
+                    .into(),
+                )));
+                results.resize_with(pending.len(), || Err(missing.clone()));
+            }
+
+            let mut retry = Vec::new();
+            let mut retry_after = None;
+            for (document, result) in pending.into_iter().zip(results) {
+                match result {
+                    Ok(()) => {}
+                    Err(error) if error.is_temporary() => {
+                        retry_after = retry_after.max(error.retry_after());
+                        retry.push((document, error));
+                    }
+                    Err(error) => failures.push(BatchFailure {
+                        name: document.name,
+                        error,
+                    }),
+                }
+            }
+
+            if retry.is_empty() {
+                break;
+            }
+
+            let Some(delay) = backoff.next() else {
+                // no more retries left
+                failures.extend(retry.into_iter().map(|(document, error)| BatchFailure {
+                    name: document.name,
+                    error,
+                }));
+                break;
+            };
+            // only use server-provided delay if it's longer
+            let delay = retry_after.map_or(delay, |retry_after| retry_after.max(delay));
+
+            log::debug!(
+                "Retrying to send {} documents of batch in {delay:?}",
+                retry.len()
+            );
+            tokio::time::sleep(delay).await;
+
+            pending = retry.into_iter().map(|(document, _)| document).collect();
+        }
+
+        for failure in &failures {
+            log::warn!("Failed to send {}: {}", failure.name, failure.error);
+        }
+
+        failures
+    }
+
+    fn backoff(&self) -> JitterBackoff {
+        JitterBackoff::new(
+            self.min_delay.unwrap_or(DEFAULT_MIN_DELAY),
+            self.max_delay.unwrap_or(DEFAULT_MAX_DELAY),
+            self.retries,
+        )
+    }
+
+    async fn retry<F, Fut>(&self, name: &str, f: F) -> Result<(), SendError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<(), SendOnceError>>,
+    {
+        Ok(f.retry(self.backoff())
+            .when(|e| matches!(e, SendOnceError::Temporary(_)))
+            .adjust(|e, dur| {
+                // no more retries left
+                let dur = dur?;
+                match e {
+                    // only use server-provided delay if it's longer
+                    SendOnceError::Temporary(e) => match e.retry_after() {
+                        Some(retry_after) if retry_after > dur => Some(retry_after),
+                        _ => Some(dur),
+                    },
+                    SendOnceError::Permanent(_) => Some(dur),
+                }
+            })
+            .notify(|e, dur| {
+                log::debug!("Retrying to send {name} in {dur:?}: {e}");
+            })
+            .await?)
+    }
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum SendOnceError {
-    #[error(transparent)]
-    Temporary(SendError),
-    #[error(transparent)]
-    Permanent(SendError),
+/// The default number of retries in case of a server or transmission failure.
+pub const DEFAULT_RETRIES: usize = 3;
+const DEFAULT_MIN_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// An exponential backoff, using "full jitter".
+///
+/// The upper bound starts with the minimum delay and doubles with every attempt, limited by the
+/// maximum delay. The actual delay is chosen randomly between the minimum delay and that bound.
+#[derive(Debug)]
+struct JitterBackoff {
+    min: Duration,
+    max: Duration,
+    remaining: usize,
+    attempt: u32,
+    rng: fastrand::Rng,
 }
 
-impl From<SendOnceError> for SendError {
-    fn from(value: SendOnceError) -> Self {
-        match value {
-            SendOnceError::Temporary(e) => e,
-            SendOnceError::Permanent(e) => e,
+impl JitterBackoff {
+    fn new(min: Duration, max: Duration, retries: usize) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            remaining: retries,
+            attempt: 0,
+            rng: fastrand::Rng::new(),
         }
     }
 }
 
-impl SendVisitor {
-    /// Send request once
-    async fn send_once<F>(
-        &self,
-        name: &str,
-        data: Bytes,
-        customizer: F,
-    ) -> Result<(), SendOnceError>
-    where
-        F: FnOnce(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
-    {
-        let request = self
-            .sender
-            .request(Method::POST, self.url.clone())
-            .await
-            .map_err(|err| SendOnceError::Temporary(err.into()))?
-            .body(Body::from(data));
-        let request = customizer(request);
-        let response = request
-            .send()
-            .await
-            .map_err(|err| SendOnceError::Temporary(err.into()))?;
+impl Iterator for JitterBackoff {
+    type Item = Duration;
 
-        if let Some(retry_after) =
-            calculate_retry_after_from_response_header(&response, self.default_retry_after)
-        {
-            log::info!(
-                "Rate limited (429) when uploading {name}, retry after: {:?}",
-                retry_after
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+
+        let upper = self
+            .min
+            .checked_mul(2u32.saturating_pow(self.attempt))
+            .map_or(self.max, |upper| upper.min(self.max));
+        self.attempt = self.attempt.saturating_add(1);
+
+        Some(self.min + (upper - self.min).mul_f64(self.rng.f64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_backoff_is_bounded() {
+        let min = Duration::from_millis(100);
+        let max = Duration::from_secs(1);
+
+        let delays: Vec<_> = JitterBackoff::new(min, max, 10).collect();
+        assert_eq!(delays.len(), 10);
+
+        for (attempt, delay) in delays.into_iter().enumerate() {
+            let upper = (min * 2u32.pow(attempt as u32)).min(max);
+            assert!(
+                (min..=upper).contains(&delay),
+                "{delay:?} must be between {min:?} and {upper:?}"
             );
-            return Err(SendOnceError::Temporary(SendError::RateLimited(
-                retry_after,
-            )));
         }
+    }
+
+    /// A sink recording the names of the documents sent, failing for names starting with `fail`.
+    #[derive(Clone, Default)]
+    struct MockSink {
+        sent: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Sink for MockSink {
+        async fn send_once(&self, document: &SinkDocument) -> Result<(), SendOnceError> {
+            if document.name.starts_with("fail") {
+                return Err(SendOnceError::Permanent(SendError::Client(
+                    StatusCode::BAD_REQUEST,
+                )));
+            }
+            self.sent.lock().unwrap().push(document.name.clone());
+            Ok(())
+        }
+    }
+
+    fn document(name: &str) -> SinkDocument {
+        SinkDocument::json(name, Bytes::from_static(b"{}"))
+    }
+
+    #[tokio::test]
+    async fn batch_is_sent_when_full_or_flushed() {
+        let sink = MockSink::default();
+        let visitor = SendVisitor::from_sink(sink.clone()).batch_size(2);
+
+        visitor.send(document("a")).await.unwrap();
+        assert!(sink.sent.lock().unwrap().is_empty());
+        visitor.send(document("b")).await.unwrap();
+        assert_eq!(*sink.sent.lock().unwrap(), ["a", "b"]);
+
+        visitor.send(document("c")).await.unwrap();
+        assert_eq!(sink.sent.lock().unwrap().len(), 2);
+        visitor.flush().await.unwrap();
+        assert_eq!(*sink.sent.lock().unwrap(), ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn batch_reports_failed_documents() {
+        let sink = MockSink::default();
+        let visitor = SendVisitor::from_sink(sink.clone()).batch_size(3);
+
+        visitor.send(document("a")).await.unwrap();
+        visitor.send(document("fail-1")).await.unwrap();
+        // the failure belongs to another document of the batch
+        visitor.send(document("b")).await.unwrap();
+
+        assert_eq!(*sink.sent.lock().unwrap(), ["a", "b"]);
+        match visitor.flush().await.unwrap_err() {
+            SendError::Batch(failures) => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].name, "fail-1");
+                assert!(!failures[0].error.is_temporary());
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_reports_failure_of_completing_document() {
+        let sink = MockSink::default();
+        let visitor = SendVisitor::from_sink(sink.clone()).batch_size(2);
+
+        visitor.send(document("a")).await.unwrap();
+        match visitor.send(document("fail-1")).await.unwrap_err() {
+            SendError::Batch(failures) => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].name, "fail-1");
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+
+        // already reported
+        visitor.flush().await.unwrap();
+    }
 
-        let status = response.status();
+    /// A sink dropping the result of the last document of a batch.
+    #[derive(Clone, Default)]
+    struct TruncatingSink;
 
-        if status.is_success() {
-            log::debug!("Uploaded {} -> {}", name, response.status());
+    impl Sink for TruncatingSink {
+        async fn send_once(&self, _document: &SinkDocument) -> Result<(), SendOnceError> {
             Ok(())
-        } else if status.is_client_error() {
-            log::warn!("Failed to upload, payload rejected {name} -> {status}",);
-            Err(SendOnceError::Permanent(SendError::Client(status)))
-        } else if status.is_server_error() {
-            log::warn!("Failed to upload, server error {name} -> {status}",);
-            Err(SendOnceError::Temporary(SendError::Server(status)))
-        } else {
-            Err(SendOnceError::Permanent(SendError::UnexpectedStatus(
-                status,
-            )))
+        }
+
+        async fn send_batch_once(&self, documents: &[SinkDocument]) -> Vec<BatchResult> {
+            vec![Ok(()); documents.len() - 1]
         }
     }
 
-    /// Send request, retry in case of temporary errors
-    async fn send<F>(&self, name: &str, data: Bytes, customizer: F) -> Result<(), SendError>
-    where
-        F: Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
-    {
-        let mut retry = ExponentialBuilder::default();
-        if self.retries > 0 {
-            retry = retry.with_max_times(self.retries);
-        }
-        if let Some(min_delay) = self.min_delay {
-            retry = retry.with_min_delay(min_delay);
-        }
-        if let Some(max_delay) = self.max_delay {
-            retry = retry.with_max_delay(max_delay);
-        }
-
-        Ok(
-            (|| async { self.send_once(name, data.clone(), &customizer).await })
-                .retry(retry)
-                .when(|e| matches!(e, SendOnceError::Temporary(_)))
-                .adjust(|e, dur| {
-                    if let SendOnceError::Temporary(SendError::RateLimited(retry_after)) = e {
-                        if let Some(dur_value) = dur
-                            && dur_value > *retry_after
-                        {
-                            return dur;
-                        }
-                        Some(*retry_after) // only use server-provided delay if it's longer
-                    } else {
-                        dur // minimum delay as per backoff strategy
-                    }
-                })
-                .await?,
-        )
+    #[tokio::test]
+    async fn batch_fails_documents_without_result() {
+        let visitor = SendVisitor::from_sink(TruncatingSink).batch_size(3);
+
+        visitor.send(document("a")).await.unwrap();
+        visitor.send(document("b")).await.unwrap();
+        visitor.send(document("c")).await.unwrap_err();
+        visitor.flush().await.unwrap();
+
+        visitor.send(document("d")).await.unwrap();
+        visitor.send(document("e")).await.unwrap();
+        match visitor.flush().await.unwrap_err() {
+            SendError::Batch(failures) => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].name, "e");
+                assert!(!failures[0].error.is_temporary());
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+
+    /// A sink sending batches, failing temporarily for documents starting with `flaky` once.
+    #[derive(Clone, Default)]
+    struct FlakyBatchSink {
+        sent: Arc<Mutex<Vec<String>>>,
+        failed: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Sink for FlakyBatchSink {
+        async fn send_once(&self, document: &SinkDocument) -> Result<(), SendOnceError> {
+            let mut failed = self.failed.lock().unwrap();
+            if document.name.starts_with("flaky") && !failed.contains(&document.name) {
+                failed.push(document.name.clone());
+                return Err(SendOnceError::Temporary(SendError::Server(
+                    StatusCode::BAD_GATEWAY,
+                )));
+            }
+            self.sent.lock().unwrap().push(document.name.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_only_retries_failed_documents() {
+        let sink = FlakyBatchSink::default();
+        let visitor = SendVisitor::from_sink(sink.clone())
+            .batch_size(3)
+            .min_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(1));
+
+        visitor.send(document("a")).await.unwrap();
+        visitor.send(document("flaky-1")).await.unwrap();
+        visitor.send(document("b")).await.unwrap();
+
+        assert_eq!(*sink.sent.lock().unwrap(), ["a", "b", "flaky-1"]);
+    }
+
+    #[tokio::test]
+    async fn dry_run_sends_nothing() {
+        let sink = MockSink::default();
+        let visitor = SendVisitor::from_sink(sink.clone()).dry_run(true);
+
+        visitor.send(document("a")).await.unwrap();
+        visitor.send(document("fail-1")).await.unwrap();
+
+        assert!(sink.sent.lock().unwrap().is_empty());
+    }
+
+    /// A sink failing temporarily for the first attempts.
+    #[derive(Clone, Default)]
+    struct FlakySink {
+        attempts: Arc<Mutex<usize>>,
+    }
+
+    impl Sink for FlakySink {
+        async fn send_once(&self, _document: &SinkDocument) -> Result<(), SendOnceError> {
+            let mut attempts = self.attempts.lock().unwrap();
+            *attempts += 1;
+            if *attempts <= DEFAULT_RETRIES {
+                return Err(SendOnceError::Temporary(SendError::Server(
+                    StatusCode::BAD_GATEWAY,
+                )));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn temporary_failures_are_retried_by_default() {
+        let sink = FlakySink::default();
+        let visitor = SendVisitor::from_sink(sink.clone())
+            .min_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(1));
+
+        visitor.send(document("a")).await.unwrap();
+        assert_eq!(*sink.attempts.lock().unwrap(), DEFAULT_RETRIES + 1);
+    }
+
+    #[test]
+    fn jitter_backoff_without_retries() {
+        assert_eq!(
+            JitterBackoff::new(Duration::from_secs(1), Duration::from_secs(2), 0).next(),
+            None
+        );
     }
 }
@@ -0,0 +1,68 @@
+use super::{SendError, SendOnceError, Sink, SinkDocument};
+use async_nats::{Client, HeaderMap};
+use reqwest::{Url, header};
+
+/// Publish documents to a NATS subject.
+#[derive(Clone, Debug)]
+pub struct NatsSink {
+    client: Client,
+    subject: String,
+}
+
+impl NatsSink {
+    pub fn new(client: Client, subject: impl Into<String>) -> Self {
+        Self {
+            client,
+            subject: subject.into(),
+        }
+    }
+
+    /// Connect to a NATS server, using a URL in the form of `nats://host:port/subject`.
+    pub async fn connect(url: &Url) -> anyhow::Result<Self> {
+        let subject = url.path().trim_start_matches('/');
+        if subject.is_empty() {
+            anyhow::bail!("Missing subject in NATS URL: {url}");
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing host in NATS URL: {url}"))?;
+        let port = url.port().unwrap_or(4222);
+
+        let client = async_nats::connect(format!("{host}:{port}")).await?;
+
+        Ok(Self::new(client, subject))
+    }
+}
+
+impl Sink for NatsSink {
+    fn target(&self) -> String {
+        format!("NATS subject {}", self.subject)
+    }
+
+    async fn send_once(&self, document: &SinkDocument) -> Result<(), SendOnceError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE.as_str(), document.content_type);
+        if let Some(encoding) = document.content_encoding {
+            headers.insert(header::CONTENT_ENCODING.as_str(), encoding);
+        }
+        headers.insert(super::HEADER_NAME, document.name.as_str());
+        if let Some(id) = &document.id {
+            headers.insert(super::HEADER_ID, id.as_str());
+        }
+
+        self.client
+            .publish_with_headers(self.subject.clone(), headers, document.data.clone())
+            .await
+            .map_err(|err| SendOnceError::Temporary(SendError::Sink(err.into())))?;
+        // publishing only queues the message, flushing makes sure it got written
+        self.client
+            .flush()
+            .await
+            .map_err(|err| SendOnceError::Temporary(SendError::Sink(err.into())))?;
+
+        log::debug!("Published {} -> {}", document.name, self.subject);
+
+        Ok(())
+    }
+}
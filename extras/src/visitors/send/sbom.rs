@@ -4,7 +4,6 @@ use crate::sbom::{
     retrieve::{RetrievalContext, RetrievedSbom, RetrievedVisitor},
     validation::{ValidatedSbom, ValidatedVisitor, ValidationContext},
 };
-use reqwest::header;
 use sbom_walker::source::Source;
 use walker_common::{retrieve::RetrievalError, validate::ValidationError};
 
@@ -16,7 +15,7 @@ pub enum SendRetrievedSbomError<S: Source> {
     Retrieval(#[from] RetrievalError<DiscoveredSbom, S>),
 }
 
-impl<S: Source> RetrievedVisitor<S> for SendVisitor {
+impl<S: Source, K: Sink> RetrievedVisitor<S> for SendVisitor<K> {
     type Error = SendRetrievedSbomError<S>;
     type Context = ();
 
@@ -42,7 +41,7 @@ pub enum SendValidatedSbomError<S: Source> {
     Validation(#[from] ValidationError<S>),
 }
 
-impl<S: Source> ValidatedVisitor<S> for SendVisitor {
+impl<S: Source, K: Sink> ValidatedVisitor<S> for SendVisitor<K> {
     type Error = SendValidatedSbomError<S>;
     type Context = ();
 
@@ -60,7 +59,7 @@ impl<S: Source> ValidatedVisitor<S> for SendVisitor {
     }
 }
 
-impl SendVisitor {
+impl<K: Sink> SendVisitor<K> {
     async fn send_sbom(&self, sbom: RetrievedSbom) -> Result<(), SendError> {
         log::debug!(
             "Sending: {} (modified: {:?})",
@@ -86,16 +85,10 @@ impl SendVisitor {
 
         let bzip2 = name.ends_with(".bz2");
 
-        self.send(url.as_str(), data, |mut request| {
-            request = request
-                .query(&[("id", name)])
-                .header(header::CONTENT_TYPE, "application/json");
-            if bzip2 {
-                request.header(header::CONTENT_ENCODING, "bzip2")
-            } else {
-                request
-            }
-        })
-        .await
+        let document = SinkDocument::json(url.as_str(), data)
+            .id(name.to_string())
+            .content_encoding(bzip2.then_some("bzip2"));
+
+        self.send(document).await
     }
 }
@@ -8,6 +8,7 @@ use sbom_walker::{
 };
 use walker_common::{
     cli::{CommandDefaults, client::ClientArguments, runner::RunnerArguments},
+    lock::Lock,
     progress::Progress,
     since::Since,
 };
@@ -37,6 +38,7 @@ impl Download {
     pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
         let store: StoreVisitor = self.store.try_into()?;
         let base = store.base.clone();
+        let _lock = Lock::store(&base)?;
 
         let since = Since::new(
             self.skip.since,
@@ -1,4 +1,4 @@
-use crate::inspect::inspect_validated;
+use crate::inspect::{LenientReport, inspect_validated};
 use anyhow::anyhow;
 use bytes::Bytes;
 use parking_lot::Mutex;
@@ -18,6 +18,10 @@ pub struct Inspect {
     #[arg()]
     sources: Vec<String>,
 
+    /// Log non-critical issues, like dangling references, as warnings instead of failing.
+    #[arg(long)]
+    lenient: bool,
+
     #[command(flatten)]
     client: ClientArguments,
 
@@ -35,13 +39,18 @@ impl Inspect {
 
         let mut progress = progress.start(self.sources.len());
 
+        let mut skipped = 0;
         for source in self.sources {
             log::info!("Inspecting: {source}");
-            Self::inspect(&fetcher, messages.clone(), &source).await?;
+            skipped += Self::inspect(&fetcher, messages.clone(), &source, self.lenient).await?;
             progress.tick().await;
         }
         progress.finish().await;
 
+        if self.lenient {
+            println!("Skipped {skipped} non-critical issues");
+        }
+
         let messages = messages.lock();
         for (source, messages) in &*messages {
             println!("{source}:");
@@ -64,7 +73,8 @@ impl Inspect {
         fetcher: &Fetcher,
         messages: Arc<Mutex<BTreeMap<String, Vec<String>>>>,
         source: &str,
-    ) -> anyhow::Result<()> {
+        lenient: bool,
+    ) -> anyhow::Result<usize> {
         let (data, url) = if source.starts_with("http://") || source.starts_with("https://") {
             log::debug!("Fetching remote");
             let url = Url::parse(source)?;
@@ -80,8 +90,14 @@ impl Inspect {
 
         log::info!("{} bytes of data", data.len());
 
+        let report = (source, messages);
+        let lenient = lenient.then(|| LenientReport::new(&report));
+
         inspect_validated(
-            &(source, messages),
+            match &lenient {
+                Some(lenient) => lenient,
+                None => &report,
+            },
             ValidatedSbom {
                 retrieved: RetrievedSbom {
                     discovered: DiscoveredSbom {
@@ -97,6 +113,6 @@ impl Inspect {
             },
         );
 
-        Ok(())
+        Ok(lenient.map(|lenient| lenient.skipped()).unwrap_or_default())
     }
 }
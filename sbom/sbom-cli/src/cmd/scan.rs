@@ -1,8 +1,17 @@
-use crate::{cmd::DiscoverArguments, common::walk_standard};
+use crate::{cmd::DiscoverArguments, common::walk_standard, inspect::LenientReport};
+use parking_lot::Mutex;
 use sbom_walker::{
-    Sbom, discover::DiscoveredSbom, retrieve::RetrievedSbom, source::DispatchSource,
+    Sbom, discover::DiscoveredSbom, report::check, retrieve::RetrievedSbom, source::DispatchSource,
     validation::ValidatedSbom,
 };
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 use tokio::task;
 use walker_common::{
     cli::{
@@ -11,9 +20,42 @@ use walker_common::{
     },
     compression::decompress,
     progress::Progress,
+    utils::{hex::Hex, url::Urlify},
     validate::ValidationError,
 };
 
+/// The output format of the scan command
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON object per SBOM, as newline delimited JSON
+    Json,
+}
+
+/// The outcome of scanning a single SBOM, as emitted by the JSON output format
+#[derive(Clone, Debug, Default, serde::Serialize)]
+struct ScanEntry {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// The SHA-256 digest of the retrieved data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ScanEntry {
+    fn emit(&self) -> anyhow::Result<()> {
+        println!("{}", serde_json::to_string(self)?);
+        Ok(())
+    }
+}
+
 /// Scan SBOMs
 #[derive(clap::Args, Debug)]
 pub struct Scan {
@@ -28,12 +70,26 @@ pub struct Scan {
 
     #[command(flatten)]
     validation: ValidationArguments,
+
+    /// Check the content of documents, logging non-critical issues (like dangling references) as
+    /// warnings and continuing with the rest of the document. Documents which fail to decode or
+    /// parse are logged as warnings and skipped.
+    #[arg(long)]
+    lenient: bool,
+
+    /// The output format
+    #[arg(short, long, value_enum, default_value_t)]
+    output: OutputFormat,
 }
 
 impl CommandDefaults for Scan {}
 
 impl Scan {
     pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+        let lenient = self.lenient;
+        let json = self.output == OutputFormat::Json;
+        let skipped = AtomicUsize::new(0);
+
         walk_standard(
             progress,
             self.client,
@@ -41,13 +97,25 @@ impl Scan {
             self.discover,
             self.validation,
             async |advisory: Result<ValidatedSbom, ValidationError<DispatchSource>>| {
+                let skip = |msg: String| {
+                    log::warn!("{msg}");
+                    skipped.fetch_add(1, Ordering::Relaxed);
+                };
+
                 match advisory {
                     Ok(sbom) => {
-                        println!("Advisory: {}", sbom.url);
+                        if !json {
+                            println!("Advisory: {}", sbom.url);
+                        }
                         log::debug!("  Metadata: {:?}", sbom.sha256);
                         log::debug!("    SHA256: {:?}", sbom.sha256);
                         log::debug!("    SHA512: {:?}", sbom.sha512);
 
+                        let mut entry = ScanEntry {
+                            url: sbom.url.to_string(),
+                            ..Default::default()
+                        };
+
                         let ValidatedSbom {
                             retrieved:
                                 RetrievedSbom {
@@ -57,15 +125,86 @@ impl Scan {
                                 },
                         } = sbom;
 
-                        let data =
-                            task::spawn_blocking(move || decompress(data, url.path())).await??;
+                        let name = url.to_string();
+                        let (sha256, data) = task::spawn_blocking(move || {
+                            // the digest of the retrieved data, even if no digest file was provided
+                            let sha256 = json.then(|| Hex(&Sha256::digest(&data)).to_lower());
+                            (sha256, decompress(data, url.path()))
+                        })
+                        .await?;
+                        entry.sha256 = sha256;
+
+                        let data = match data {
+                            Ok(data) => data,
+                            Err(err) => {
+                                if json {
+                                    entry.error = Some(format!("Failed to decode: {err}"));
+                                    entry.emit()?;
+                                }
+                                if lenient {
+                                    skip(format!("Failed to decode {name}: {err}"));
+                                    return Ok(());
+                                }
+                                return Err(err);
+                            }
+                        };
+
+                        let format = Sbom::detect_format(&data);
+                        if json {
+                            entry.format = format.map(|format| format.to_string());
+                        } else {
+                            match format {
+                                Some(format) => println!("  Format: {format}"),
+                                None => println!("  Format: unknown"),
+                            }
+                        }
 
                         match Sbom::try_parse_any(&data) {
-                            Ok(sbom) => process_sbom(sbom),
+                            Ok(sbom) => {
+                                let errors = match lenient {
+                                    true => {
+                                        let (errors, issues) = check_lenient(&name, &sbom);
+                                        skipped.fetch_add(issues, Ordering::Relaxed);
+                                        errors
+                                    }
+                                    false => vec![],
+                                };
+
+                                if json {
+                                    entry.name = document_name(&sbom);
+                                    if !errors.is_empty() {
+                                        entry.error = Some(errors.join("; "));
+                                    }
+                                } else {
+                                    process_sbom(sbom);
+                                    for error in errors {
+                                        eprintln!("  Error: {error}");
+                                    }
+                                }
+                            }
                             Err(err) => {
-                                eprintln!("  Format error: {err}");
+                                if json {
+                                    entry.error = Some(err.to_string());
+                                }
+                                if lenient {
+                                    skip(format!("Failed to parse {name}: {err}"));
+                                } else if !json {
+                                    eprintln!("  Format error: {err}");
+                                }
                             }
                         }
+
+                        if json {
+                            entry.emit()?;
+                        }
+                    }
+                    Err(err) if json => {
+                        ScanEntry {
+                            url: err.url().to_string(),
+                            error: Some(err.to_string()),
+                            ..Default::default()
+                        }
+                        .emit()?;
                     }
                     Err(err) => {
                         eprintln!("SBOM(ERR): {err}");
@@ -77,10 +216,47 @@ impl Scan {
         )
         .await?;
 
+        if lenient && json {
+            log::info!(
+                "Skipped {} non-critical issues",
+                skipped.load(Ordering::Relaxed)
+            );
+        } else if lenient {
+            println!(
+                "Skipped {} non-critical issues",
+                skipped.load(Ordering::Relaxed)
+            );
+        }
+
         Ok(())
     }
 }
 
+/// Check the content of a document, skipping non-critical issues one by one.
+///
+/// Returns the critical errors, and the number of skipped issues.
+fn check_lenient(name: &str, sbom: &Sbom) -> (Vec<String>, usize) {
+    let errors = (name.to_string(), Arc::new(Mutex::new(BTreeMap::new())));
+
+    let report = LenientReport::new(&errors);
+    check::all(&report, sbom);
+    let skipped = report.skipped();
+
+    let errors = errors.1.lock().remove(name).unwrap_or_default();
+    (errors, skipped)
+}
+
+/// Get the name of an SBOM document.
+fn document_name(sbom: &Sbom) -> Option<String> {
+    match sbom {
+        Sbom::Spdx(sbom) => Some(sbom.document_creation_information.document_name.clone()),
+        Sbom::SerdeCycloneDx(sbom) => sbom
+            .metadata()
+            .and_then(|metadata| metadata.component())
+            .map(|component| component.name().to_string()),
+    }
+}
+
 fn process_sbom(sbom: Sbom) {
     match sbom {
         Sbom::Spdx(sbom) => {
@@ -90,8 +266,40 @@ fn process_sbom(sbom: Sbom) {
             );
         }
 
-        Sbom::SerdeCycloneDx(_sbom) => {
-            println!("  CycloneDX");
+        Sbom::SerdeCycloneDx(sbom) => {
+            let graph = sbom.dependency_graph();
+            println!(
+                "  CycloneDX: {} dependencies, {} unresolved references",
+                graph.edges.len(),
+                graph.unresolved.len()
+            );
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lenient_skips_dangling_references() {
+        let sbom = Sbom::try_parse_any(
+            br#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.5",
+  "version": 1,
+  "components": [{"type": "library", "name": "a", "bom-ref": "a"}],
+  "dependencies": [
+    {"ref": "a", "dependsOn": ["missing-1", "missing-2"]},
+    {"ref": "missing-3"}
+  ]
+}"#,
+        )
+        .expect("must parse");
+
+        let (errors, skipped) = check_lenient("test", &sbom);
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(skipped, 3);
+    }
+}
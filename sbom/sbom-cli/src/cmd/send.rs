@@ -15,7 +15,7 @@ use walker_common::{
     since::Since,
     validate::ValidationOptions,
 };
-use walker_extras::visitors::{SendArguments, SendVisitor};
+use walker_extras::visitors::SendArguments;
 
 /// Walk a source and send validated/retrieved documents to a sink.
 #[derive(clap::Args, Debug)]
@@ -48,7 +48,11 @@ impl CommandDefaults for Send {}
 impl Send {
     pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
-        let send: SendVisitor = self.send.into_visitor().await?;
+        // nothing gets sent, so nothing must be recorded as processed
+        let dry_run = self.send.dry_run;
+        let skip_failures = self.skip_failures;
+        let send = self.send.into_visitor().await?;
+        let flush = send.clone();
 
         let since = Since::new(
             self.skip.since,
@@ -82,7 +86,15 @@ impl Send {
         )
         .await?;
 
-        since.store()?;
+        // send what's left of the last batch, reporting the documents of batches which failed
+        match flush.flush().await {
+            Err(err) if skip_failures => log::warn!("Skipping failed documents: {err}"),
+            flushed => flushed?,
+        }
+
+        if !dry_run {
+            since.store()?;
+        }
 
         Ok(())
     }
@@ -13,6 +13,7 @@ use walker_common::{
         CommandDefaults, client::ClientArguments, runner::RunnerArguments,
         validation::ValidationArguments,
     },
+    lock::Lock,
     progress::Progress,
     since::Since,
     validate::ValidationOptions,
@@ -47,6 +48,7 @@ impl Sync {
         let options: ValidationOptions = self.validation.into();
         let store: StoreVisitor = self.store.try_into()?;
         let base = store.base.clone();
+        let _lock = Lock::store(&base)?;
 
         let since = Since::new(
             self.skip.since,
@@ -9,7 +9,7 @@ pub fn fix_license(report: &dyn ReportSink, mut json: Value) -> (Value, bool) {
             if let Some(declared) = package["licenseDeclared"].as_str()
                 && let Err(err) = spdx_expression::SpdxExpression::parse(declared)
             {
-                report.error(format!("Faulty SPDX license expression: {err}"));
+                report.issue(format!("Faulty SPDX license expression: {err}"));
                 package["licenseDeclared"] = "NOASSERTION".into();
                 changed = true;
             }
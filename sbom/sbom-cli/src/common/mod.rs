@@ -13,8 +13,12 @@ use sbom_walker::{
     walker::Walker,
 };
 use std::future::Future;
+use tokio_util::sync::CancellationToken;
 use walker_common::{
-    cli::{client::ClientArguments, runner::RunnerArguments, validation::ValidationArguments},
+    cli::{
+        client::ClientArguments, runner::RunnerArguments, shutdown::cancel_on_signal,
+        validation::ValidationArguments,
+    },
     progress::Progress,
     validate::ValidationOptions,
 };
@@ -90,16 +94,24 @@ where
     P: Progress,
 {
     let visitor = f(source.clone()).await?;
-    let walker = Walker::new(source).with_progress(progress);
 
-    match runner.workers {
-        1 => {
-            walker.walk(visitor).await?;
-        }
-        n => {
-            walker.walk_parallel(n, visitor).await?;
-        }
-    }
+    let cancellation = CancellationToken::new();
+    let signals = cancel_on_signal(cancellation.clone());
+
+    let walker = Walker::new(source)
+        .with_progress(progress)
+        .with_memory_budget(runner.memory_budget())
+        .with_cancellation(cancellation)
+        .continue_on_error(runner.continue_on_error);
+
+    let result = match runner.workers {
+        1 => walker.walk(visitor).await,
+        n => walker.walk_parallel(n, visitor).await,
+    };
+    signals.abort();
+
+    let stats = result?;
+    log::info!("Walk finished: {stats}");
 
     Ok(())
 }
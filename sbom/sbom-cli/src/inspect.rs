@@ -2,13 +2,46 @@ use crate::common::fix_license;
 use sbom_walker::{
     Sbom,
     discover::DiscoveredSbom,
+    model::sbom::Format,
     report::{ReportSink, check},
     retrieve::RetrievedSbom,
     source::Source,
     validation::ValidatedSbom,
 };
+use std::sync::atomic::{AtomicUsize, Ordering};
 use walker_common::{compression::decompress, validate::ValidationError};
 
+/// A report sink, logging non-critical issues as warnings instead of reporting them as errors.
+pub struct LenientReport<'r> {
+    report: &'r dyn ReportSink,
+    skipped: AtomicUsize,
+}
+
+impl<'r> LenientReport<'r> {
+    pub fn new(report: &'r dyn ReportSink) -> Self {
+        Self {
+            report,
+            skipped: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of non-critical issues which got skipped.
+    pub fn skipped(&self) -> usize {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+impl ReportSink for LenientReport<'_> {
+    fn error(&self, msg: String) {
+        self.report.error(msg);
+    }
+
+    fn issue(&self, msg: String) {
+        log::warn!("{msg}");
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 pub fn inspect_validated(report: &dyn ReportSink, sbom: ValidatedSbom) {
     let ValidatedSbom {
         retrieved:
@@ -29,11 +62,19 @@ pub fn inspect_validated(report: &dyn ReportSink, sbom: ValidatedSbom) {
         }
     };
 
+    if Sbom::detect_format(&data) == Some(Format::SpdxTag) {
+        match Sbom::try_parse_any(&data) {
+            Ok(sbom) => check::all(report, &sbom),
+            Err(err) => report.error(format!("Failed to parse file: {err}")),
+        }
+        return;
+    }
+
     let mut value = match serde_json::from_slice(&data) {
         Ok(value) => value,
         Err(err) => {
             report.error(format!(
-                "Failed to parse file as JSON: {err} (currently only JSON files are supported)"
+                "Failed to parse file as JSON: {err} (currently only JSON and SPDX tag-value files are supported)"
             ));
             return;
         }
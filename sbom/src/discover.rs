@@ -3,7 +3,7 @@
 use crate::{model::metadata, model::metadata::SourceMetadata};
 use std::{fmt::Debug, future::Future, ops::Deref, time::SystemTime};
 use url::Url;
-use walker_common::utils::url::Urlify;
+use walker_common::utils::{budget::MemoryBudget, url::Urlify};
 
 /// Discovery configuration
 pub struct DiscoverConfig {
@@ -42,6 +42,11 @@ impl Urlify for DiscoveredSbom {
 #[derive(Debug)]
 pub struct DiscoveredContext<'c> {
     pub metadata: &'c SourceMetadata,
+    /// The memory budget of the walk, if any.
+    ///
+    /// Visitors retrieving documents should reserve the size of the retrieved data, until they
+    /// are done processing it.
+    pub memory_budget: Option<&'c MemoryBudget>,
 }
 
 impl Deref for DiscoveredContext<'_> {
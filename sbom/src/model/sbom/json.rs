@@ -1,5 +1,6 @@
 use serde::Deserialize;
 use serde_json::Value;
+use walker_common::utils::json::{self, JsonParseError};
 
 pub enum JsonPayload<'a> {
     Value(Value),
@@ -7,13 +8,14 @@ pub enum JsonPayload<'a> {
 }
 
 impl JsonPayload<'_> {
-    pub fn parse<T>(self) -> Result<T, serde_json::Error>
+    /// Parse the payload, reporting the location of a failure.
+    pub fn parse<T>(self) -> Result<T, JsonParseError>
     where
         for<'de> T: Deserialize<'de>,
     {
         match self {
-            Self::Value(data) => serde_json::from_value(data),
-            Self::Bytes(data) => serde_json::from_slice(data),
+            Self::Value(data) => json::from_value(data),
+            Self::Bytes(data) => json::from_slice(data),
         }
     }
 }
@@ -10,6 +10,7 @@ pub use json::JsonPayload;
 use anyhow::{anyhow, bail};
 use serde_json::Value;
 use std::fmt::{Debug, Display, Formatter};
+use walker_common::utils::json::JsonParseError;
 
 pub enum Parser {
     CycloneDxJson,
@@ -91,7 +92,50 @@ impl Display for ParserKind {
     }
 }
 
+/// The format of an SBOM document, as detected from its content.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    CycloneDxJson,
+    CycloneDxXml,
+    SpdxJson,
+    SpdxTag,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CycloneDxJson => write!(f, "CycloneDX JSON"),
+            Self::CycloneDxXml => write!(f, "CycloneDX XML"),
+            Self::SpdxJson => write!(f, "SPDX JSON"),
+            Self::SpdxTag => write!(f, "SPDX tag-value"),
+        }
+    }
+}
+
+/// The number of leading bytes inspected when detecting the format of a JSON document.
+const SNIFF_LEN: usize = 16 * 1024;
+
 impl Sbom {
+    /// Detect the format of a document by inspecting its leading bytes, ignoring any file name.
+    ///
+    /// For JSON documents, this looks for the `bomFormat` or `spdxVersion` keys. Returns `None`
+    /// if the format could not be detected.
+    pub fn detect_format(data: &[u8]) -> Option<Format> {
+        let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+        let data = data.trim_ascii_start();
+        let head = &data[..data.len().min(SNIFF_LEN)];
+
+        let contains = |needle: &[u8]| head.windows(needle.len()).any(|w| w == needle);
+
+        match head.first()? {
+            b'{' if contains(br#""bomFormat""#) => Some(Format::CycloneDxJson),
+            b'{' if contains(br#""spdxVersion""#) => Some(Format::SpdxJson),
+            b'<' if contains(b"cyclonedx.org/schema/bom") => Some(Format::CycloneDxXml),
+            _ if head.starts_with(b"SPDXVersion:") => Some(Format::SpdxTag),
+            _ => None,
+        }
+    }
+
     /// test if the file is a CycloneDX document, returning the file version
     pub fn is_cyclondx_json(json: &Value) -> anyhow::Result<&str> {
         let format = json["bomFormat"]
@@ -119,12 +163,26 @@ impl Sbom {
     }
 
     pub fn try_parse_any_json(json: Value) -> Result<Self, ParseAnyError> {
+        Self::try_parse_any_json_with(json, None)
+    }
+
+    /// Parse a detected JSON document.
+    ///
+    /// If the original data is present, it will be used for parsing the actual format. This takes
+    /// a bit longer, but allows reporting the line and column of a failure.
+    fn try_parse_any_json_with(json: Value, data: Option<&[u8]>) -> Result<Self, ParseAnyError> {
+        #[allow(unused)]
+        let payload = |json| match data {
+            Some(data) => JsonPayload::Bytes(data),
+            None => JsonPayload::Value(json),
+        };
+
         let err = ParseAnyError::new();
 
         #[cfg(feature = "serde-cyclonedx")]
         let err = match Self::is_cyclondx_json(&json) {
             Ok("1.4" | "1.5" | "1.6") => {
-                return Self::try_serde_cyclonedx_json(JsonPayload::Value(json)).map_err(|e| {
+                return Self::try_serde_cyclonedx_json(payload(json)).map_err(|e| {
                     // drop any previous error, as we know what format and version it is
                     ParseAnyError::from((ParserKind::Cyclone13DxJson, e.into()))
                 });
@@ -144,7 +202,7 @@ impl Sbom {
         #[cfg(feature = "cyclonedx-bom")]
         let err = match Self::is_cyclondx_json(&json) {
             Ok("1.2" | "1.3" | "1.4") => {
-                return Self::try_cyclonedx_json(JsonPayload::Value(json)).map_err(|e| {
+                return Self::try_cyclonedx_json(payload(json)).map_err(|e| {
                     // drop any previous error, as we know what format and version it is
                     ParseAnyError::from((ParserKind::Cyclone13DxJson, e.into()))
                 });
@@ -164,7 +222,7 @@ impl Sbom {
         #[cfg(feature = "spdx-rs")]
         let err = match Self::is_spdx_json(&json) {
             Ok("SPDX-2.2" | "SPDX-2.3") => {
-                return Self::try_spdx_json(JsonPayload::Value(json)).map_err(|e| {
+                return Self::try_spdx_json(payload(json)).map_err(|e| {
                     // drop any previous error, as we know what format and version it is
                     ParseAnyError::from((ParserKind::Spdx23Json, e.into()))
                 });
@@ -186,12 +244,38 @@ impl Sbom {
     }
 
     /// try parsing with all possible kinds that make sense.
+    ///
+    /// If the format can be detected from the content (see [`Self::detect_format`]), only the
+    /// matching parser is used. Otherwise, this falls back to trying each parser.
     pub fn try_parse_any(data: &[u8]) -> Result<Self, ParseAnyError> {
+        match Self::detect_format(data) {
+            Some(format @ (Format::CycloneDxJson | Format::SpdxJson)) => {
+                let kind = match format {
+                    Format::SpdxJson => ParserKind::Spdx23Json,
+                    _ => ParserKind::Cyclone13DxJson,
+                };
+                let json = serde_json::from_slice(data)
+                    .map_err(|err| ParseAnyError::from((kind, err.into())))?;
+                return Self::try_parse_any_json_with(json, Some(data));
+            }
+            #[cfg(feature = "cyclonedx-bom")]
+            Some(Format::CycloneDxXml) => {
+                return Self::try_cyclonedx_xml(data)
+                    .map_err(|err| ParseAnyError::from((ParserKind::Cyclone13DxXml, err.into())));
+            }
+            #[cfg(feature = "spdx-rs")]
+            Some(Format::SpdxTag) => {
+                return Self::try_spdx_tag_bytes(data)
+                    .map_err(|err| ParseAnyError::from((ParserKind::Spdx23Tag, err)));
+            }
+            _ => {}
+        }
+
         #[allow(unused)]
         if let Ok(json) = serde_json::from_slice(data) {
             // try to parse this as JSON, which eliminates e.g. the "tag" format, which seems to just parse anything
 
-            Self::try_parse_any_json(json)
+            Self::try_parse_any_json_with(json, Some(data))
         } else {
             // it is not JSON, it could be XML or "tagged"
             let err = ParseAnyError::new();
@@ -203,13 +287,7 @@ impl Sbom {
             };
 
             #[cfg(feature = "spdx-rs")]
-            use anyhow::Context;
-
-            #[cfg(feature = "spdx-rs")]
-            let err = match std::str::from_utf8(data)
-                .context("unable to interpret bytes as string")
-                .and_then(|data| Self::try_spdx_tag(data).map_err(|err| err.into()))
-            {
+            let err = match Self::try_spdx_tag_bytes(data) {
                 Ok(doc) => return Ok(doc),
                 Err(e) => err.add(ParserKind::Spdx23Tag, e),
             };
@@ -219,7 +297,7 @@ impl Sbom {
     }
 
     #[cfg(feature = "spdx-rs")]
-    pub fn try_spdx_json(data: JsonPayload) -> Result<Self, serde_json::Error> {
+    pub fn try_spdx_json(data: JsonPayload) -> Result<Self, JsonParseError> {
         Ok(Self::Spdx(data.parse()?))
     }
 
@@ -228,6 +306,14 @@ impl Sbom {
         Ok(Self::Spdx(spdx_rs::parsers::spdx_from_tag_value(data)?))
     }
 
+    #[cfg(feature = "spdx-rs")]
+    fn try_spdx_tag_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        use anyhow::Context;
+
+        let data = std::str::from_utf8(data).context("unable to interpret bytes as string")?;
+        Ok(Self::try_spdx_tag(data)?)
+    }
+
     #[cfg(feature = "cyclonedx-bom")]
     #[allow(deprecated)]
     pub fn try_cyclonedx_json<'a>(
@@ -252,10 +338,7 @@ impl Sbom {
     #[cfg(feature = "serde-cyclonedx")]
     pub fn try_serde_cyclonedx_json<'a>(
         data: impl Into<JsonPayload<'a>>,
-    ) -> Result<Self, serde_json::Error> {
-        match data.into() {
-            JsonPayload::Value(json) => Ok(Self::SerdeCycloneDx(serde_json::from_value(json)?)),
-            JsonPayload::Bytes(data) => Ok(Self::SerdeCycloneDx(serde_json::from_slice(data)?)),
-        }
+    ) -> Result<Self, JsonParseError> {
+        Ok(Self::SerdeCycloneDx(data.into().parse()?))
     }
 }
@@ -88,6 +88,24 @@ macro_rules! r#type {
     };
 }
 
+/// A node of the [`DependencyGraph`], resolved to its component or service.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DependencyNode<'a> {
+    pub bom_ref: &'a str,
+    pub name: &'a str,
+    /// The package URL, only present for components.
+    pub purl: Option<&'a str>,
+}
+
+/// The resolved dependencies of an SBOM.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DependencyGraph<'a> {
+    /// The edges, from a node to the nodes it depends on.
+    pub edges: Vec<(DependencyNode<'a>, Vec<DependencyNode<'a>>)>,
+    /// References which could not be resolved to a component or service, in order of occurrence.
+    pub unresolved: Vec<&'a str>,
+}
+
 /// Collect `bom-ref`s, recursing into sub-element
 pub trait BomRefCollection<'a> {
     fn bom_refs_to(&self, refs: &mut HashMap<&'a str, usize>);
@@ -152,6 +170,53 @@ impl<'a> Sbom<'a> {
 
         refs
     }
+
+    /// Resolve the dependencies of the SBOM to their components and services.
+    ///
+    /// References which can't be resolved are not part of any edge, but collected separately.
+    /// A dependency whose left-side reference can't be resolved is dropped entirely.
+    pub fn dependency_graph(&'a self) -> DependencyGraph<'a> {
+        let mut nodes = HashMap::new();
+
+        if let Some(component) = self.metadata().and_then(|metadata| metadata.component()) {
+            component.nodes_to(&mut nodes);
+        }
+        for component in self.components().into_iter().flatten() {
+            component.nodes_to(&mut nodes);
+        }
+        for service in self.services().into_iter().flatten() {
+            service.nodes_to(&mut nodes);
+        }
+
+        let mut graph = DependencyGraph::default();
+        let mut resolve = |r#ref: &'a str| match nodes.get(r#ref) {
+            Some(node) => Some(*node),
+            None => {
+                if !graph.unresolved.contains(&r#ref) {
+                    graph.unresolved.push(r#ref);
+                }
+                None
+            }
+        };
+
+        let mut edges = vec![];
+        for dep in self.dependencies().into_iter().flatten() {
+            let left = resolve(dep.r#ref());
+            let right = dep
+                .dependencies()
+                .into_iter()
+                .flatten()
+                .filter_map(&mut resolve)
+                .collect();
+
+            if let Some(left) = left {
+                edges.push((left, right));
+            }
+        }
+        graph.edges = edges;
+
+        graph
+    }
 }
 
 impl From<serde_cyclonedx::cyclonedx::v_1_4::CycloneDx> for Sbom<'static> {
@@ -215,6 +280,8 @@ bom_refs!(Component -> |c| c.components());
 
 impl<'a> Component<'a> {
     attribute!(bom_ref => |c -> Option<&'a str> | c.bom_ref.as_deref());
+    attribute!(name => |c -> &'a str | c.name.as_str());
+    attribute!(purl => |c -> Option<&'a str> | c.purl.as_deref());
     attribute!(components => |c -> Option<Vec<Component<'a>>> | c.components.as_ref().map(|c| c.iter().map(Into::into).collect()));
 }
 
@@ -226,16 +293,53 @@ bom_refs!(Service -> |c| c.services());
 
 impl<'a> Service<'a> {
     attribute!(bom_ref => |s -> Option<&'a str> | s.bom_ref.as_deref());
+    attribute!(name => |s -> &'a str | s.name.as_str());
     attribute!(services => |s -> Option<Vec<Service<'a>>> | s.services.as_ref().map(|s| s.iter().map(Into::into).collect()));
 }
 
 // dependency
 
+impl<'a> Component<'a> {
+    fn nodes_to(&self, nodes: &mut HashMap<&'a str, DependencyNode<'a>>) {
+        if let Some(bom_ref) = self.bom_ref() {
+            nodes.insert(
+                bom_ref,
+                DependencyNode {
+                    bom_ref,
+                    name: self.name(),
+                    purl: self.purl(),
+                },
+            );
+        }
+        for child in self.components().into_iter().flatten() {
+            child.nodes_to(nodes);
+        }
+    }
+}
+
+impl<'a> Service<'a> {
+    fn nodes_to(&self, nodes: &mut HashMap<&'a str, DependencyNode<'a>>) {
+        if let Some(bom_ref) = self.bom_ref() {
+            nodes.insert(
+                bom_ref,
+                DependencyNode {
+                    bom_ref,
+                    name: self.name(),
+                    purl: None,
+                },
+            );
+        }
+        for child in self.services().into_iter().flatten() {
+            child.nodes_to(nodes);
+        }
+    }
+}
+
 r#type!(Dependency);
 from!('a, Dependency,  Dependency<'a>);
 
-impl Dependency<'_> {
-    pub fn r#ref(&self) -> &str {
+impl<'a> Dependency<'a> {
+    pub fn r#ref(&self) -> &'a str {
         match self {
             Self::V1_4(dep) => &dep.ref_,
             Self::V1_5(dep) => dep.ref_.as_str().unwrap_or_default(),
@@ -243,7 +347,7 @@ impl Dependency<'_> {
         }
     }
 
-    pub fn dependencies(&self) -> Option<Vec<&str>> {
+    pub fn dependencies(&self) -> Option<Vec<&'a str>> {
         match self {
             Self::V1_4(dep) => dep
                 .depends_on
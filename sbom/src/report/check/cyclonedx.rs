@@ -74,7 +74,7 @@ impl CycloneDxChecks<'_> {
 
         for deps in self.sbom.dependencies.iter().flat_map(|d| &d.0) {
             if !bom_refs.contains_key(&*deps.dependency_ref) {
-                self.report.error(format!(
+                self.report.issue(format!(
                     "Missing left-side dependency reference: {}",
                     deps.dependency_ref
                 ));
@@ -82,7 +82,7 @@ impl CycloneDxChecks<'_> {
             for right in &deps.dependencies {
                 if !bom_refs.contains_key(right.as_str()) {
                     self.report
-                        .error(format!("Missing right-side dependency reference: {right}",));
+                        .issue(format!("Missing right-side dependency reference: {right}",));
                 }
             }
         }
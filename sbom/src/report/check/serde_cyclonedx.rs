@@ -44,7 +44,7 @@ impl CycloneDxChecks<'_> {
 
         for deps in self.sbom.dependencies().iter().flatten() {
             if !bom_refs.contains_key(deps.r#ref()) {
-                self.report.error(format!(
+                self.report.issue(format!(
                     "Missing left-side dependency reference: {}",
                     deps.r#ref()
                 ));
@@ -52,7 +52,7 @@ impl CycloneDxChecks<'_> {
             for right in deps.dependencies().iter().flatten() {
                 if !bom_refs.contains_key(right) {
                     self.report
-                        .error(format!("Missing right-side dependency reference: {right}",));
+                        .issue(format!("Missing right-side dependency reference: {right}",));
                 }
             }
         }
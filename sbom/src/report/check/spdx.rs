@@ -121,7 +121,7 @@ impl SpdxChecks<'_> {
             }
             (_, Some((doc_ref, _id))) if doc_ref.starts_with("DocumentRef-") => {
                 if !doc_refs.contains(doc_ref) {
-                    self.report.error(format!(
+                    self.report.issue(format!(
                         "Invalid document reference '{doc_ref}' of relationship '{left}' -[{rel:?}]-> '{right}'",
                     ));
                 }
@@ -129,7 +129,7 @@ impl SpdxChecks<'_> {
             }
             _ => {
                 if !ids.contains(id) {
-                    self.report.error(format!(
+                    self.report.issue(format!(
                         "Invalid reference '{id}' of relationship '{left}' -[{rel:?}]-> '{right}'",
                     ));
                 }
@@ -137,7 +137,7 @@ impl SpdxChecks<'_> {
         }
 
         if !ids.contains(id) {
-            self.report.error(format!(
+            self.report.issue(format!(
                 "Invalid reference '{id}' of relationship '{left}' -[{rel:?}]-> '{right}'",
             ));
         }
@@ -11,6 +11,14 @@ pub struct ReportResult<'d> {
 
 pub trait ReportSink {
     fn error(&self, msg: String);
+
+    /// Report a non-critical issue, like a dangling reference, which doesn't prevent processing
+    /// the rest of the document.
+    ///
+    /// By default, this is reported as an error.
+    fn issue(&self, msg: String) {
+        self.error(msg);
+    }
 }
 
 /// A no-op report sink
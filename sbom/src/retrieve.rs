@@ -14,7 +14,7 @@ use std::{
 use url::Url;
 use walker_common::{
     retrieve::{RetrievalError, RetrievalMetadata, RetrievedDigest, RetrievedDocument},
-    utils::{openpgp::PublicKey, url::Urlify},
+    utils::{budget::MemoryBudget, openpgp::PublicKey, url::Urlify},
     validate::source::{KeySource, KeySourceError},
 };
 
@@ -151,6 +151,12 @@ where
     Visitor(VE),
 }
 
+/// The context of the [`RetrievingVisitor`].
+pub struct RetrievingContext<C> {
+    context: C,
+    memory_budget: Option<MemoryBudget>,
+}
+
 impl<V, S> DiscoveredVisitor for RetrievingVisitor<V, S>
 where
     V: RetrievedVisitor<S>,
@@ -158,7 +164,7 @@ where
 {
     type Error =
         Error<V::Error, <S as walker_common::source::Source>::Error, <S as KeySource>::Error>;
-    type Context = V::Context;
+    type Context = RetrievingContext<V::Context>;
 
     async fn visit_context(
         &self,
@@ -193,13 +199,19 @@ where
             }
         }
 
-        self.visitor
+        let inner = self
+            .visitor
             .visit_context(&RetrievalContext {
                 keys: &keys,
                 discovered: context,
             })
             .await
-            .map_err(Error::Visitor)
+            .map_err(Error::Visitor)?;
+
+        Ok(RetrievingContext {
+            context: inner,
+            memory_budget: context.memory_budget.cloned(),
+        })
     }
 
     async fn visit_sbom(
@@ -213,8 +225,14 @@ where
             .await
             .map_err(|err| RetrievalError::Source { err, discovered });
 
+        // hold the reservation until the document was processed
+        let _reservation = match (&context.memory_budget, &sbom) {
+            (Some(memory_budget), Ok(sbom)) => Some(memory_budget.reserve(sbom.data.len())),
+            _ => None,
+        };
+
         self.visitor
-            .visit_sbom(context, sbom)
+            .visit_sbom(&context.context, sbom)
             .await
             .map_err(Error::Visitor)?;
 
@@ -14,6 +14,7 @@ use walker_common::{
 /// which prevents us from using `dyn` ("cannot be made into an object").
 ///
 /// There may be a better way around this, feel free to send a PR ;-)
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug)]
 pub enum DispatchSource {
     Http(HttpSource),
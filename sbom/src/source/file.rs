@@ -188,6 +188,8 @@ impl Source for FileSource {
             metadata: RetrievalMetadata {
                 last_modification,
                 etag: None,
+                not_modified: false,
+                transformed: false,
             },
         })
     }
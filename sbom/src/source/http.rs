@@ -15,7 +15,9 @@ use url::{ParseError, Url};
 use walker_common::{
     changes::{self, ChangeEntry, ChangeSource},
     fetcher::{self, DataProcessor, Fetcher},
-    retrieve::{RetrievalMetadata, RetrievedDigest, RetrievingDigest},
+    retrieve::{
+        DigestFormatError, RetrievalMetadata, RetrievedDigest, RetrievingDigest, parse_digest,
+    },
     utils::openpgp::PublicKey,
     validate::source::{Key, KeySource, KeySourceError},
 };
@@ -84,6 +86,8 @@ pub enum HttpSourceError {
     Url(#[from] ParseError),
     #[error("CSV error: {0}")]
     Csv(#[from] csv::Error),
+    #[error("Invalid digest file of {url}: {err}")]
+    DigestFormat { url: Url, err: DigestFormatError },
 }
 
 impl From<changes::Error> for HttpSourceError {
@@ -143,20 +147,8 @@ impl Source for HttpSource {
                 .fetch::<Option<String>>(format!("{url}.sha512", url = discovered.url)),
         )?;
 
-        let sha256 = sha256
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
-                current: Sha256::new(),
-            });
-        let sha512 = sha512
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
-                current: Sha512::new(),
-            });
+        let sha256 = retrieving_digest::<Sha256>(&discovered, sha256)?;
+        let sha512 = retrieving_digest::<Sha512>(&discovered, sha512)?;
 
         let advisory = self
             .fetcher
@@ -170,6 +162,26 @@ impl Source for HttpSource {
     }
 }
 
+/// Prepare a digest for being calculated while retrieving the SBOM.
+fn retrieving_digest<D: Digest>(
+    discovered: &DiscoveredSbom,
+    expected: Option<String>,
+) -> Result<Option<RetrievingDigest<D>>, HttpSourceError> {
+    expected
+        .map(|expected| {
+            Ok(RetrievingDigest {
+                expected: parse_digest::<D>(&expected).map_err(|err| {
+                    HttpSourceError::DigestFormat {
+                        url: discovered.url.clone(),
+                        err,
+                    }
+                })?,
+                current: D::new(),
+            })
+        })
+        .transpose()
+}
+
 pub struct FetchedRetrievedSbom {
     data: Bytes,
     sha256: Option<RetrievedDigest<Sha256>>,
@@ -238,6 +250,8 @@ impl DataProcessor for FetchingRetrievedSbom {
             metadata: RetrievalMetadata {
                 last_modification,
                 etag,
+                not_modified: false,
+                transformed: false,
             },
         })
     }
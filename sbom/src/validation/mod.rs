@@ -15,7 +15,9 @@ use url::Url;
 use walker_common::{
     retrieve::RetrievalError,
     utils::{openpgp::PublicKey, url::Urlify},
-    validate::{ValidationError, ValidationOptions, digest::validate_digest, openpgp},
+    validate::{
+        MissingSignature, ValidationError, ValidationOptions, digest::validate_digest, openpgp,
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -185,11 +187,23 @@ where
             ) {
                 Ok(()) => Ok(ValidatedSbom { retrieved }),
                 Err(error) => Err(ValidationProcessError::Proceed(
-                    ValidationError::Signature { error, retrieved },
+                    ValidationError::SignatureInvalid { error, retrieved },
                 )),
             }
         } else {
-            Ok(ValidatedSbom { retrieved })
+            match self.options.missing_signature {
+                MissingSignature::Allow => {
+                    log::debug!("Missing signature: {}", retrieved.url);
+                    Ok(ValidatedSbom { retrieved })
+                }
+                MissingSignature::Warn => {
+                    log::warn!("Missing signature: {}", retrieved.url);
+                    Ok(ValidatedSbom { retrieved })
+                }
+                MissingSignature::Reject => Err(ValidationProcessError::Proceed(
+                    ValidationError::SignatureMissing { retrieved },
+                )),
+            }
         }
     }
 }
@@ -13,6 +13,7 @@ use std::{
 };
 use tokio::fs;
 use walker_common::{
+    compression::Compression,
     retrieve::RetrievalError,
     store::{Document, StoreError, store_document},
     utils::openpgp::PublicKey,
@@ -208,10 +209,12 @@ impl StoreVisitor {
                 changed: sbom.modified,
                 metadata: &sbom.metadata,
                 sha256: &sbom.sha256,
+                sha384: &None,
                 sha512: &sbom.sha512,
                 signature: &sbom.signature,
                 no_timestamps: self.no_timestamps,
                 no_xattrs: self.no_xattrs,
+                compression: Compression::None,
             },
         )
         .await?;
@@ -4,10 +4,17 @@ use crate::{
     discover::{DiscoveredContext, DiscoveredVisitor},
     source::Source,
 };
-use futures::{StreamExt, TryFutureExt, TryStreamExt, stream};
-use std::{fmt::Debug, sync::Arc};
-use url::ParseError;
-use walker_common::progress::{Progress, ProgressBar};
+use futures::{StreamExt, TryStreamExt, future, stream};
+use parking_lot::Mutex;
+use std::{fmt::Debug, sync::Arc, time::Instant};
+use tokio_util::sync::CancellationToken;
+use url::{ParseError, Url};
+use walker_common::{
+    progress::{Progress, ProgressBar},
+    utils::budget::MemoryBudget,
+};
+
+pub use walker_common::walker::WalkStats;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<VE, SE>
@@ -21,11 +28,22 @@ where
     Url(#[from] ParseError),
     #[error("Visitor error: {0}")]
     Visitor(VE),
+    #[error("Walk was cancelled")]
+    Cancelled,
+    #[error("{} of {} SBOMs failed", .stats.failed, .stats.visited())]
+    Failed {
+        stats: WalkStats,
+        /// The URLs of the failed SBOMs, with their errors.
+        errors: Vec<(Url, VE)>,
+    },
 }
 
 pub struct Walker<S: Source, P: Progress> {
     source: S,
     progress: P,
+    memory_budget: Option<MemoryBudget>,
+    continue_on_error: bool,
+    cancellation: CancellationToken,
 }
 
 impl<S: Source> Walker<S, ()> {
@@ -33,6 +51,9 @@ impl<S: Source> Walker<S, ()> {
         Self {
             source,
             progress: (),
+            memory_budget: None,
+            continue_on_error: false,
+            cancellation: CancellationToken::new(),
         }
     }
 }
@@ -42,10 +63,40 @@ impl<S: Source, P: Progress> Walker<S, P> {
         Walker {
             source: self.source,
             progress,
+            memory_budget: self.memory_budget,
+            continue_on_error: self.continue_on_error,
+            cancellation: self.cancellation,
         }
     }
 
-    pub async fn walk<V>(self, visitor: V) -> Result<(), Error<V::Error, S::Error>>
+    /// Set a memory budget for [`Self::walk_parallel`].
+    ///
+    /// Once the retrieved, but not yet processed, data exceeds the budget, no new documents will
+    /// be dispatched until enough data was processed.
+    pub fn with_memory_budget(mut self, memory_budget: impl Into<Option<MemoryBudget>>) -> Self {
+        self.memory_budget = memory_budget.into();
+        self
+    }
+
+    /// Continue with the remaining SBOMs when visiting an SBOM fails.
+    ///
+    /// The errors will be collected and returned as [`Error::Failed`] at the end of the walk.
+    /// Errors loading the metadata or the index still abort the walk.
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// Set a token for cancelling the walk.
+    ///
+    /// Once cancelled, no new SBOMs will be visited, but the in-flight ones are finished. The walk
+    /// then fails with [`Error::Cancelled`].
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    pub async fn walk<V>(self, visitor: V) -> Result<WalkStats, Error<V::Error, S::Error>>
     where
         V: DiscoveredVisitor,
     {
@@ -54,14 +105,23 @@ impl<S: Source, P: Progress> Walker<S, P> {
         let context = visitor
             .visit_context(&DiscoveredContext {
                 metadata: &metadata,
+                memory_budget: self.memory_budget.as_ref(),
             })
             .await
             .map_err(Error::Visitor)?;
 
         let index = self.source.load_index().await.map_err(Error::Source)?;
         let mut progress = self.progress.start(index.len());
+        let mut outcome = Outcome::default();
+        outcome.stats.discovered = index.len();
 
         for sbom in index {
+            if self.cancellation.is_cancelled() {
+                log::info!("Walk cancelled");
+                progress.finish().await;
+                return Err(Error::Cancelled);
+            }
+
             log::debug!("  Discovered SBOM: {sbom:?}");
             progress
                 .set_message(
@@ -73,23 +133,24 @@ impl<S: Source, P: Progress> Walker<S, P> {
                         .to_string(),
                 )
                 .await;
-            visitor
-                .visit_sbom(&context, sbom)
-                .await
+            let url = sbom.url.clone();
+            let result = visitor.visit_sbom(&context, sbom).await;
+            outcome
+                .add(&url, result, self.continue_on_error)
                 .map_err(Error::Visitor)?;
             progress.tick().await;
         }
 
         progress.finish().await;
 
-        Ok(())
+        outcome.finish()
     }
 
     pub async fn walk_parallel<V>(
         self,
         limit: usize,
         visitor: V,
-    ) -> Result<(), Error<V::Error, S::Error>>
+    ) -> Result<WalkStats, Error<V::Error, S::Error>>
     where
         V: DiscoveredVisitor,
     {
@@ -99,25 +160,111 @@ impl<S: Source, P: Progress> Walker<S, P> {
         let context = visitor
             .visit_context(&DiscoveredContext {
                 metadata: &metadata,
+                memory_budget: self.memory_budget.as_ref(),
             })
             .await
             .map_err(Error::Visitor)?;
 
         let visitor = Arc::new(visitor);
         let context = Arc::new(context);
+        let memory_budget = &self.memory_budget;
+        let index = self.source.load_index().await.map_err(Error::Source)?;
+        let mut outcome = Outcome::default();
+        outcome.stats.discovered = index.len();
+        let outcome = Mutex::new(outcome);
+
+        let mut cancelled = false;
 
-        stream::iter(self.source.load_index().await.map_err(Error::Source)?)
+        // stop dispatching new SBOMs once cancelled, but let the in-flight ones finish
+        stream::iter(index)
+            .take_while(|_| {
+                cancelled = self.cancellation.is_cancelled();
+                future::ready(!cancelled)
+            })
             .map(Ok)
             .try_for_each_concurrent(limit, async |sbom| {
+                if let Some(memory_budget) = memory_budget {
+                    memory_budget.available().await;
+                }
+
                 log::debug!("Discovered advisory: {}", sbom.url);
 
-                visitor
-                    .visit_sbom(&context, sbom)
+                let url = sbom.url.clone();
+                let result = visitor.visit_sbom(&context, sbom).await;
+                outcome
+                    .lock()
+                    .add(&url, result, self.continue_on_error)
                     .map_err(Error::Visitor)
-                    .await
             })
             .await?;
 
-        Ok(())
+        if cancelled {
+            log::info!("Walk cancelled");
+            return Err(Error::Cancelled);
+        }
+
+        outcome.into_inner().finish()
+    }
+}
+
+/// Collects the results of visiting SBOMs.
+struct Outcome<VE> {
+    stats: WalkStats,
+    errors: Vec<(Url, VE)>,
+    start: Instant,
+}
+
+impl<VE> Default for Outcome<VE> {
+    fn default() -> Self {
+        Self {
+            stats: Default::default(),
+            start: Instant::now(),
+            errors: vec![],
+        }
+    }
+}
+
+impl<VE> Outcome<VE>
+where
+    VE: std::fmt::Display + Debug,
+{
+    /// Add the result of visiting an SBOM.
+    ///
+    /// Fails with the error of the visitor, unless continuing on errors.
+    fn add(
+        &mut self,
+        url: &Url,
+        result: Result<(), VE>,
+        continue_on_error: bool,
+    ) -> Result<(), VE> {
+        match result {
+            Ok(()) => {
+                self.stats.succeeded += 1;
+                Ok(())
+            }
+            Err(err) if continue_on_error => {
+                log::warn!("Failed to visit SBOM {url}: {err}");
+                self.stats.failed += 1;
+                self.errors.push((url.clone(), err));
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn finish<SE>(mut self) -> Result<WalkStats, Error<VE, SE>>
+    where
+        SE: std::fmt::Display + Debug,
+    {
+        self.stats.duration = self.start.elapsed();
+
+        if self.errors.is_empty() {
+            Ok(self.stats)
+        } else {
+            Err(Error::Failed {
+                stats: self.stats,
+                errors: self.errors,
+            })
+        }
     }
 }
@@ -1,5 +1,5 @@
 use parking_lot::Mutex;
-use sbom_walker::report;
+use sbom_walker::{model::sbom::Format, report};
 use std::{collections::BTreeMap, sync::Arc};
 
 #[cfg(feature = "cyclonedx-bom")]
@@ -24,3 +24,87 @@ fn issue_57_inspect() {
 
     assert_eq!(result.len(), 0);
 }
+
+#[cfg(feature = "serde-cyclonedx")]
+#[test]
+fn issue_57_dependency_graph() {
+    use sbom_walker::Sbom;
+
+    let sbom = Sbom::try_parse_any(include_bytes!("data/issue_57/sbom.json")).expect("must parse");
+    let Sbom::SerdeCycloneDx(sbom) = sbom else {
+        panic!("must be CycloneDX");
+    };
+
+    let graph = sbom.dependency_graph();
+    assert_eq!(graph.edges.len(), 171);
+    assert!(graph.unresolved.is_empty());
+
+    let (root, deps) = graph
+        .edges
+        .iter()
+        .find(|(node, _)| node.bom_ref == "ffbd72ab-1bb9-4a0f-835a-ad44af349d64")
+        .expect("must have the root component");
+    assert_eq!(root.purl, None);
+    assert_eq!(deps.len(), 1);
+
+    let (_, deps) = graph
+        .edges
+        .iter()
+        .find(|(node, _)| node.bom_ref == deps[0].bom_ref)
+        .expect("must have the application component");
+    let express = deps
+        .iter()
+        .find(|node| node.bom_ref == "pkg:npm/express@4.21.2")
+        .expect("must depend on express");
+    assert_eq!(express.name, "express");
+    assert_eq!(express.purl, Some("pkg:npm/express@4.21.2"));
+}
+
+#[test]
+fn detect_format() {
+    use sbom_walker::Sbom;
+
+    assert_eq!(
+        Sbom::detect_format(include_bytes!("data/issue_57/sbom.json")),
+        Some(Format::CycloneDxJson)
+    );
+    assert_eq!(
+        Sbom::detect_format(
+            b"\n  {\"SPDXID\": \"SPDXRef-DOCUMENT\", \"spdxVersion\": \"SPDX-2.3\"}"
+        ),
+        Some(Format::SpdxJson)
+    );
+    assert_eq!(
+        Sbom::detect_format(b"SPDXVersion: SPDX-2.3\nDataLicense: CC0-1.0\n"),
+        Some(Format::SpdxTag)
+    );
+    assert_eq!(Sbom::detect_format(b"{\"foo\": \"bar\"}"), None);
+    assert_eq!(Sbom::detect_format(b""), None);
+}
+
+#[cfg(feature = "spdx-rs")]
+#[test]
+fn spdx_tag_value_round_trip() {
+    use sbom_walker::Sbom;
+
+    let data = include_bytes!("data/spdx/example.spdx");
+    assert_eq!(Sbom::detect_format(data), Some(Format::SpdxTag));
+
+    let sbom = Sbom::try_parse_any(data).expect("must parse");
+    let Sbom::Spdx(spdx) = &sbom else {
+        panic!("must be SPDX");
+    };
+    assert_eq!(spdx.document_creation_information.document_name, "example");
+    assert_eq!(spdx.package_information.len(), 2);
+    assert_eq!(spdx.relationships.len(), 2);
+
+    // the same document, serialized as JSON, must parse into the same model
+    let json = serde_json::to_vec(spdx).expect("must serialize");
+    assert_eq!(Sbom::detect_format(&json), Some(Format::SpdxJson));
+    let parsed = Sbom::try_parse_any(&json).expect("must parse");
+    assert_eq!(parsed, sbom);
+
+    let result: Arc<Mutex<BTreeMap<String, Vec<String>>>> = Default::default();
+    report::check::all(&("", result.clone()), &sbom);
+    assert_eq!(result.lock().len(), 0);
+}